@@ -104,6 +104,7 @@ impl From<KpalError> for GPIOPluginError {
         let error_code = match error {
             KpalError::PluginUninitialized => PLUGIN_UNINIT_ERR,
             KpalError::ValueConversionError(_) => CONVERSION_ERR,
+            KpalError::NullPointer => NULL_PTR_ERR,
         };
 
         GPIOPluginError {
@@ -1,7 +1,7 @@
 //! KPAL plugin to control the output of a single GPIO pin using the GPIO char device.
 mod errors;
 
-use std::{cell::RefCell, convert::TryInto, ffi::CString};
+use std::{cell::RefCell, ffi::CString};
 
 use gpio_cdev::{Chip, LineHandle, LineRequestFlags};
 use libc::c_int;
@@ -39,18 +39,24 @@ impl PluginAPI<GPIOPluginError> for GPIOPlugin {
                     value: Value::String(CString::new(DEFAULT_DEVICE_FILE).unwrap()),
                     callbacks_init: Callbacks::Update,
                     callbacks_run: Callbacks::Constant,
+                    choices: vec![],
+                    metadata: Metadata::default(),
             },
             1, "offset" => Attribute {
                     name: CString::new("Offset").unwrap(),
                     value: Value::Uint(DEFAULT_OFFSET),
                     callbacks_init: Callbacks::Update,
                     callbacks_run: Callbacks::Constant,
+                    choices: vec![],
+                    metadata: Metadata::default(),
             },
             2, "pin state" => Attribute {
                     name: CString::new("Pin state").unwrap(),
-                    value: Value::Int(0),
+                    value: Value::Bool(false),
                     callbacks_init: Callbacks::Constant,
                     callbacks_run: Callbacks::GetAndSet(on_get_pin_state, on_set_pin_state),
+                    choices: vec![],
+                    metadata: Metadata::default(),
             },
         });
 
@@ -98,6 +104,14 @@ impl PluginAPI<GPIOPluginError> for GPIOPlugin {
         Ok(())
     }
 
+    /// Releases the GPIO line and the chip so that they may be requested again.
+    fn deinit(&mut self) -> Result<(), GPIOPluginError> {
+        self.line_handle = None;
+        self.chip = None;
+
+        Ok(())
+    }
+
     fn attributes(&self) -> &Attributes<GPIOPlugin, GPIOPluginError> {
         &self.attributes
     }
@@ -115,7 +129,7 @@ fn on_get_pin_state(plugin: &GPIOPlugin, _cached: &Value) -> Result<Value, GPIOP
         .as_ref()
         .ok_or_else(|| Error::PluginUninitialized)?
         .get_value()?;
-    let value = Value::Int(pin_value.try_into()?);
+    let value = Value::Bool(pin_value != 0);
 
     Ok(value)
 }
@@ -132,8 +146,8 @@ fn on_set_pin_state(
     _cached: &Value,
     val: &Val,
 ) -> Result<(), GPIOPluginError> {
-    let pin_value = if let Val::Int(pin_value) = val {
-        pin_value.to_owned().try_into()?
+    let pin_value = if let Val::Bool(pin_value) = val {
+        u8::from(*pin_value)
     } else {
         return Err(GPIOPluginError::new(ATTRIBUTE_TYPE_MISMATCH));
     };
@@ -147,4 +161,9 @@ fn on_set_pin_state(
     Ok(())
 }
 
-declare_plugin!(GPIOPlugin, GPIOPluginError);
+declare_plugin!(
+    GPIOPlugin,
+    GPIOPluginError,
+    "gpio-cdev",
+    env!("CARGO_PKG_VERSION")
+);
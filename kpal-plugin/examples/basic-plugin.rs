@@ -40,6 +40,12 @@ struct Basic {
     /// We wrap the attributes in a RefCell so that we can mutate their values inside methods where
     /// instances of this struct are immutable.
     attributes: Attributes<Self, BasicError>,
+
+    /// The operations that the peripheral can perform, such as homing the stage.
+    actions: Actions<Self, BasicError>,
+
+    /// The handle that tells the daemon when an attribute changes without its request.
+    notifier: Option<Notifier>,
 }
 
 // Plugins implement the PluginAPI trait. They take a custom error type as a type parameter that is
@@ -59,6 +65,17 @@ impl PluginAPI<BasicError> for Basic {
 
                     // Settable attributes should use the GetAndSet Callback variant.
                     callbacks_run: Callbacks::GetAndSet(on_get_x, on_set_x),
+                    choices: vec![],
+
+                    // Metadata helps clients to display the attribute. The daemon rejects values
+                    // that lie outside of the range given by min and max.
+                    metadata: Metadata {
+                        description: Some(CString::new("The position of the stage").unwrap()),
+                        units: Some(CString::new("mm").unwrap()),
+                        min: Some(-1000.0),
+                        max: Some(1000.0),
+                        step: Some(0.01),
+                    },
                 },
                 1, "y" => Attribute {
                     name: CString::new("y").unwrap(),
@@ -72,6 +89,8 @@ impl PluginAPI<BasicError> for Basic {
                     // Not all attributes can be set. For example, the value of a sensor may only
                     // be readable. For these attributes, use the Get variant.
                     callbacks_run: Callbacks::Get(on_get_y),
+                    choices: vec![],
+                    metadata: Metadata::default(),
                 },
                 2, "z" => Attribute {
                     name: CString::new("z").unwrap(),
@@ -81,6 +100,8 @@ impl PluginAPI<BasicError> for Basic {
                     // Constant variant of the Callbacks enum. They are not settable and will
                     // always return the same value.
                     callbacks_run: Callbacks::Constant,
+                    choices: vec![],
+                    metadata: Metadata::default(),
                 },
                 3, "msg" => Attribute {
                     name: CString::new("msg").unwrap(),
@@ -89,8 +110,94 @@ impl PluginAPI<BasicError> for Basic {
                     value: Value::String(CString::new("foobar").unwrap()),
                     callbacks_init: Callbacks::Constant,
                     callbacks_run: Callbacks::GetAndSet(on_get_msg, on_set_msg),
+                    choices: vec![],
+                    metadata: Metadata::default(),
+                },
+                4, "enabled" => Attribute {
+                    name: CString::new("enabled").unwrap(),
+                    // Boolean values are useful for attributes that are either on or off.
+                    value: Value::Bool(false),
+                    callbacks_init: Callbacks::Constant,
+                    callbacks_run: Callbacks::GetAndSet(on_get_enabled, on_set_enabled),
+                    choices: vec![],
+                    metadata: Metadata::default(),
+                },
+                5, "waveform" => Attribute {
+                    name: CString::new("waveform").unwrap(),
+                    // Arrays of numbers and raw bytes are useful for attributes such as waveforms,
+                    // spectra, and sensor frames.
+                    value: Value::DoubleArray(vec![0.0, 0.5, 1.0, 0.5, 0.0]),
+                    callbacks_init: Callbacks::Constant,
+                    callbacks_run: Callbacks::GetAndSet(on_get_buffer, on_set_buffer),
+                    choices: vec![],
+                    metadata: Metadata::default(),
+                },
+                6, "histogram" => Attribute {
+                    name: CString::new("histogram").unwrap(),
+                    value: Value::IntArray(vec![1, 2, 3]),
+                    callbacks_init: Callbacks::Constant,
+                    callbacks_run: Callbacks::Get(on_get_buffer),
+                    choices: vec![],
+                    metadata: Metadata::default(),
+                },
+                7, "frame" => Attribute {
+                    name: CString::new("frame").unwrap(),
+                    value: Value::Bytes(vec![0, 1, 2, 3]),
+                    callbacks_init: Callbacks::Constant,
+                    callbacks_run: Callbacks::GetAndSet(on_get_buffer, on_set_buffer),
+                    choices: vec![],
+                    metadata: Metadata::default(),
+                },
+                8, "position" => Attribute {
+                    name: CString::new("position").unwrap(),
+                    // 64-bit integers are useful for values that may overflow a c_int, such as
+                    // encoder counts and timestamps.
+                    value: Value::Int64(0),
+                    callbacks_init: Callbacks::Constant,
+                    callbacks_run: Callbacks::GetAndSet(on_get_position, on_set_position),
+                    choices: vec![],
+                    metadata: Metadata::default(),
+                },
+                9, "gain" => Attribute {
+                    name: CString::new("gain").unwrap(),
+                    value: Value::String(CString::new("low").unwrap()),
+                    callbacks_init: Callbacks::Update,
+                    callbacks_run: Callbacks::GetAndSet(on_get_gain, on_set_gain),
+                    // String attributes that represent modes may declare the set of values that
+                    // they are allowed to take. The daemon rejects any other value before the
+                    // callbacks are fired.
+                    choices: vec![
+                        CString::new("low").unwrap(),
+                        CString::new("medium").unwrap(),
+                        CString::new("high").unwrap(),
+                    ],
+                    metadata: Metadata {
+                        description: Some(CString::new("The gain of the amplifier").unwrap()),
+                        ..Metadata::default()
+                    },
+                },
+            }),
+            // Actions are operations that do not map onto reading or setting an attribute.
+            actions: RefCell::new(multimap! {
+                0, "home" => Action {
+                    name: CString::new("home").unwrap(),
+                    arguments: vec![],
+                    result: Value::Double(0.0),
+                    callback: on_home,
+                },
+                1, "move_by" => Action {
+                    name: CString::new("move_by").unwrap(),
+                    // The value of each argument is only an example. Its variant determines the
+                    // type of value that must be provided when the action is invoked.
+                    arguments: vec![Argument {
+                        name: CString::new("distance").unwrap(),
+                        value: Value::Double(0.0),
+                    }],
+                    result: Value::Double(0.0),
+                    callback: on_move_by,
                 },
             }),
+            notifier: None,
         })
     }
 
@@ -103,6 +210,27 @@ impl PluginAPI<BasicError> for Basic {
         Ok(())
     }
 
+    /// Deinitializes the plugin by releasing any resources acquired during initialization.
+    ///
+    /// Implementing this method is optional. It is called when the daemon returns the plugin to
+    /// its init phase, e.g. so that its pre-init attributes can be modified.
+    fn deinit(&mut self) -> Result<(), BasicError> {
+        println!("Deinitializing the BasicPlugin... Done!");
+
+        Ok(())
+    }
+
+    /// Stores the notifier that is provided by the daemon.
+    ///
+    /// Implementing this method is optional. Plugins use the notifier to tell the daemon that an
+    /// attribute has changed, e.g. when a limit switch is hit or when an action moves the stage.
+    /// The notifier may be copied into any threads that are started by the plugin.
+    fn set_notifier(&mut self, notifier: Notifier) -> Result<(), BasicError> {
+        self.notifier = Some(notifier);
+
+        Ok(())
+    }
+
     /// Returns the attributes of the plugin.
     ///
     /// This method must be defined by a plugin library because the PluginAPI trait cannot specify
@@ -110,6 +238,11 @@ impl PluginAPI<BasicError> for Basic {
     fn attributes(&self) -> &Attributes<Basic, BasicError> {
         &self.attributes
     }
+
+    /// Returns the actions of the plugin.
+    fn actions(&self) -> Option<&Actions<Basic, BasicError>> {
+        Some(&self.actions)
+    }
 }
 
 // Callbacks are used to acutally communicate with the hardware whenever an attribute is read or
@@ -204,6 +337,175 @@ fn on_set_msg(_plugin: &Basic, _cached: &Value, _val: &Val) -> Result<(), BasicE
     Ok(())
 }
 
+/// Callback function that is fired when the 'enabled' attribute is read during the run phase.
+///
+/// # Arguments
+///
+/// * `_plugin` - A reference to the plugin struct. This provides the callback with the plugin's
+/// state.
+/// * `cached` - The most recently read or modified value of the attribute.
+fn on_get_enabled(_plugin: &Basic, cached: &Value) -> Result<Value, BasicError> {
+    println!("Getting the value of attribute enabled");
+
+    Ok(cached.clone())
+}
+
+/// Callback function that is fired when the 'enabled' attribute is set during the run phase.
+///
+/// # Arguments
+///
+/// * `_plugin` - A reference to the plugin struct. This provides the callback with the plugin's
+/// state.
+/// * `_cached` - The most recently read or modified value of the attribute.
+/// * `_val` - The new value of the attribute.
+fn on_set_enabled(_plugin: &Basic, _cached: &Value, _val: &Val) -> Result<(), BasicError> {
+    println!("Setting the value of attribute enabled");
+
+    Ok(())
+}
+
+/// Callback function that is fired when an array or byte buffer attribute is read.
+///
+/// A single callback may be shared by several attributes when they require the same routine.
+///
+/// # Arguments
+///
+/// * `_plugin` - A reference to the plugin struct. This provides the callback with the plugin's
+/// state.
+/// * `cached` - The most recently read or modified value of the attribute.
+fn on_get_buffer(_plugin: &Basic, cached: &Value) -> Result<Value, BasicError> {
+    println!("Getting the value of a buffer attribute");
+
+    Ok(cached.clone())
+}
+
+/// Callback function that is fired when an array or byte buffer attribute is set.
+///
+/// # Arguments
+///
+/// * `_plugin` - A reference to the plugin struct. This provides the callback with the plugin's
+/// state.
+/// * `_cached` - The most recently read or modified value of the attribute.
+/// * `_val` - The new value of the attribute.
+fn on_set_buffer(_plugin: &Basic, _cached: &Value, _val: &Val) -> Result<(), BasicError> {
+    println!("Setting the value of a buffer attribute");
+
+    Ok(())
+}
+
+/// Callback function that is fired when the 'position' attribute is read during the run phase.
+///
+/// # Arguments
+///
+/// * `_plugin` - A reference to the plugin struct. This provides the callback with the plugin's
+/// state.
+/// * `cached` - The most recently read or modified value of the attribute.
+fn on_get_position(_plugin: &Basic, cached: &Value) -> Result<Value, BasicError> {
+    println!("Getting the value of attribute position");
+
+    Ok(cached.clone())
+}
+
+/// Callback function that is fired when the 'position' attribute is set during the run phase.
+///
+/// # Arguments
+///
+/// * `_plugin` - A reference to the plugin struct. This provides the callback with the plugin's
+/// state.
+/// * `_cached` - The most recently read or modified value of the attribute.
+/// * `_val` - The new value of the attribute.
+fn on_set_position(_plugin: &Basic, _cached: &Value, _val: &Val) -> Result<(), BasicError> {
+    println!("Setting the value of attribute position");
+
+    Ok(())
+}
+
+/// Callback function that is fired when the 'gain' attribute is read during the run phase.
+///
+/// # Arguments
+///
+/// * `_plugin` - A reference to the plugin struct. This provides the callback with the plugin's
+/// state.
+/// * `cached` - The most recently read or modified value of the attribute.
+fn on_get_gain(_plugin: &Basic, cached: &Value) -> Result<Value, BasicError> {
+    println!("Getting the value of attribute gain");
+
+    Ok(cached.clone())
+}
+
+/// Callback function that is fired when the 'gain' attribute is set during the run phase.
+///
+/// The new value is guaranteed by the daemon to be one of the attribute's choices.
+///
+/// # Arguments
+///
+/// * `_plugin` - A reference to the plugin struct. This provides the callback with the plugin's
+/// state.
+/// * `_cached` - The most recently read or modified value of the attribute.
+/// * `_val` - The new value of the attribute.
+fn on_set_gain(_plugin: &Basic, _cached: &Value, _val: &Val) -> Result<(), BasicError> {
+    println!("Setting the value of attribute gain");
+
+    Ok(())
+}
+
+/// Callback function that is fired when the 'home' action is invoked.
+///
+/// Actions may modify the plugin's attributes. Here, homing the stage resets the position 'x'.
+///
+/// # Arguments
+///
+/// * `plugin` - A reference to the plugin struct. This provides the callback with the plugin's
+/// state.
+/// * `_arguments` - The values of the action's arguments.
+fn on_home(plugin: &Basic, _arguments: &[Value]) -> Result<Value, BasicError> {
+    println!("Homing the stage");
+    let mut attributes = plugin.attributes.borrow_mut();
+    let x = attributes
+        .get_mut(&0)
+        .ok_or_else(|| BasicError::new(ATTRIBUTE_DOES_NOT_EXIST))?;
+    x.value = Value::Double(0.0);
+
+    // The daemon is told that 'x' has changed so that it can inform its clients.
+    if let Some(notifier) = plugin.notifier {
+        notifier.notify(0);
+    }
+
+    Ok(x.value.clone())
+}
+
+/// Callback function that is fired when the 'move_by' action is invoked.
+///
+/// The arguments are guaranteed to match the action's declared arguments.
+///
+/// # Arguments
+///
+/// * `plugin` - A reference to the plugin struct. This provides the callback with the plugin's
+/// state.
+/// * `arguments` - The values of the action's arguments.
+fn on_move_by(plugin: &Basic, arguments: &[Value]) -> Result<Value, BasicError> {
+    println!("Moving the stage");
+    let distance = match arguments {
+        [Value::Double(distance)] => *distance,
+        _ => return Err(BasicError::new(ACTION_ARGUMENTS_MISMATCH)),
+    };
+
+    let mut attributes = plugin.attributes.borrow_mut();
+    let x = attributes
+        .get_mut(&0)
+        .ok_or_else(|| BasicError::new(ATTRIBUTE_DOES_NOT_EXIST))?;
+    x.value = match x.value {
+        Value::Double(position) => Value::Double(position + distance),
+        _ => return Err(BasicError::new(ATTRIBUTE_TYPE_MISMATCH)),
+    };
+
+    if let Some(notifier) = plugin.notifier {
+        notifier.notify(0);
+    }
+
+    Ok(x.value.clone())
+}
+
 /// The plugin's error type.
 ///
 /// Plugin methods all return the same, custom error type provided by the plugin author(s). This
@@ -243,16 +545,22 @@ impl PluginError for BasicError {
 // Now that everything has been setup, we call the `declare_plugin` macro to automatically generate
 // the functions and structures that will be used by the daemon to communicate with the
 // plugin. This macro allows plugin developers to entirely avoid writing unsafe, foreign function
-// interface code.
-declare_plugin!(Basic, BasicError);
+// interface code. The name and version are reported to the daemon so that peripherals can refer
+// to the library without knowing the order in which the libraries were loaded.
+declare_plugin!(Basic, BasicError, "basic-plugin", env!("CARGO_PKG_VERSION"));
 
 // Unit tests for the plugin lie within a module called `tests` that is preceded by a #[cfg(test)]
 // attribute.
 #[cfg(test)]
 mod tests {
-    use libc::c_uchar;
+    use std::{
+        ptr,
+        sync::atomic::{AtomicUsize, Ordering},
+    };
+
+    use libc::{c_uchar, c_void, size_t};
 
-    use crate::RUN_PHASE;
+    use crate::{INIT_PHASE, RUN_PHASE};
 
     use super::*;
 
@@ -311,6 +619,114 @@ mod tests {
         )
     }
 
+    #[test]
+    fn set_bool_attribute_value() {
+        let plugin = Basic::new().unwrap();
+        let new_val = Val::Bool(true);
+
+        plugin.attribute_set_value(4, &new_val, RUN_PHASE).unwrap();
+        let attributes = plugin.attributes.borrow();
+        let actual = &attributes.get(&4).unwrap().value.as_val();
+        assert_eq!(
+            new_val, *actual,
+            "Expected attribute value to be {:?} but it was {:?}",
+            new_val, *actual
+        )
+    }
+
+    #[test]
+    fn set_array_attribute_value() {
+        let plugin = Basic::new().unwrap();
+        let new_value = Value::DoubleArray(vec![1.0, 2.0]);
+
+        // Vals of arrays contain pointers, so the owned values are compared instead.
+        plugin
+            .attribute_set_value(5, &new_value.as_val(), RUN_PHASE)
+            .unwrap();
+        let attributes = plugin.attributes.borrow();
+        let actual = &attributes.get(&5).unwrap().value;
+        assert_eq!(
+            new_value, *actual,
+            "Expected attribute value to be {:?} but it was {:?}",
+            new_value, *actual
+        )
+    }
+
+    #[test]
+    fn attribute_choices() {
+        let plugin = Basic::new().unwrap();
+
+        let choices = plugin.attribute_choices(9).unwrap();
+        assert_eq!(3, choices.len());
+        assert_eq!(CString::new("low").unwrap(), choices[0]);
+        assert!(plugin.attribute_choices(0).unwrap().is_empty());
+    }
+
+    #[test]
+    fn attribute_metadata() {
+        let plugin = Basic::new().unwrap();
+
+        let metadata = plugin.attribute_metadata(0).unwrap();
+        assert_eq!(1000.0, metadata.max);
+        assert!(!metadata.units.is_null());
+
+        let metadata = plugin.attribute_metadata(1).unwrap();
+        assert!(metadata.max.is_nan());
+        assert!(metadata.units.is_null());
+    }
+
+    #[test]
+    fn attribute_settable() {
+        let plugin = Basic::new().unwrap();
+
+        assert!(plugin.attribute_settable(0, RUN_PHASE).unwrap());
+        assert!(!plugin.attribute_settable(1, RUN_PHASE).unwrap());
+        assert!(!plugin.attribute_settable(2, INIT_PHASE).unwrap());
+    }
+
+    #[test]
+    fn invoke_action() {
+        let plugin = Basic::new().unwrap();
+
+        let result = plugin.invoke_action(1, &[Val::Double(2.5)]).unwrap();
+        assert_eq!(Val::Double(2.5), result);
+
+        let result = plugin.invoke_action(0, &[]).unwrap();
+        assert_eq!(Val::Double(0.0), result);
+
+        assert_eq!(
+            ACTION_ARGUMENTS_MISMATCH,
+            plugin
+                .invoke_action(1, &[Val::Int(1)])
+                .unwrap_err()
+                .error_code
+        );
+        assert_eq!(
+            ACTION_DOES_NOT_EXIST,
+            plugin.invoke_action(2, &[]).unwrap_err().error_code
+        );
+    }
+
+    #[test]
+    fn notify() {
+        static NOTIFIED: AtomicUsize = AtomicUsize::new(usize::MAX);
+
+        extern "C" fn callback(_: *mut c_void, id: size_t) {
+            NOTIFIED.store(id, Ordering::SeqCst);
+        }
+
+        let mut plugin = Basic::new().unwrap();
+        plugin
+            .set_notifier(Notifier {
+                callback,
+                context: ptr::null_mut(),
+            })
+            .unwrap();
+
+        plugin.invoke_action(1, &[Val::Double(2.5)]).unwrap();
+        assert_eq!(0, NOTIFIED.load(Ordering::SeqCst));
+    }
+
     #[test]
     fn set_attribute_wrong_variant() {
         let plugin = Basic::new().unwrap();
@@ -1,13 +1,26 @@
-use libc::c_char;
+use libc::{c_char, c_uint};
 
 use crate::ffi::Phase;
 
+/// The version of the binary interface between the daemon and plugin libraries.
+///
+/// This must be incremented whenever the layout of the VTable or of any other type that is passed
+/// across the FFI boundary changes. The daemon refuses to load libraries that were built for a
+/// different version.
+pub const ABI_VERSION: c_uint = 1;
+
 /// Indicates that an attribute may not be set before plugin initialization.
 pub const ATTRIBUTE_PRE_INIT_FALSE: c_char = 0;
 
 /// Indicates that an attribute may be set before plugin initialization.
 pub const ATTRIBUTE_PRE_INIT_TRUE: c_char = 1;
 
+/// Indicates that an attribute may not be set during a lifecycle phase.
+pub const ATTRIBUTE_SETTABLE_FALSE: c_char = 0;
+
+/// Indicates that an attribute may be set during a lifecycle phase.
+pub const ATTRIBUTE_SETTABLE_TRUE: c_char = 1;
+
 /// Indicates that the init phase callbacks should be used when interacting with a plugin.
 pub const INIT_PHASE: Phase = 0;
 
@@ -15,7 +28,7 @@ pub const INIT_PHASE: Phase = 0;
 pub const RUN_PHASE: Phase = 1;
 
 /// Error messages associated with each error code.
-pub static ERRORS: [&[u8]; 13] = [
+pub static ERRORS: [&[u8]; 17] = [
     // 0 PLUGIN_OK
     b"Plugin OK\0",
     // 1 UNDEFINED_ERR
@@ -42,6 +55,14 @@ pub static ERRORS: [&[u8]; 13] = [
     b"Could not update plugin attribute's cached value\0",
     // 12 LIFECYCLE_PHASE_ERR
     b"Unrecognized lifecycle phase\0",
+    // 13 PLUGIN_DEINIT_ERR
+    b"Plugin failed to deinitialize\0",
+    // 14 ACTION_DOES_NOT_EXIST
+    b"Action does not exist\0",
+    // 15 ACTION_ARGUMENTS_MISMATCH
+    b"Action arguments do not match the action's declared arguments\0",
+    // 16 PLUGIN_HOST_ERR
+    b"The process that hosts the plugin failed\0",
 ];
 
 pub mod error_codes {
@@ -61,4 +82,8 @@ pub mod error_codes {
     pub const CALLBACK_ERR: c_int = 10;
     pub const UPDATE_CACHED_VALUE_ERR: c_int = 11;
     pub const LIFECYCLE_PHASE_ERR: c_int = 12;
+    pub const PLUGIN_DEINIT_ERR: c_int = 13;
+    pub const ACTION_DOES_NOT_EXIST: c_int = 14;
+    pub const ACTION_ARGUMENTS_MISMATCH: c_int = 15;
+    pub const PLUGIN_HOST_ERR: c_int = 16;
 }
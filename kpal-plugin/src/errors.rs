@@ -10,6 +10,9 @@ pub enum Error {
 
     /// Raised when a Val cannot be converted to a Value.
     ValueConversionError(FromBytesWithNulError),
+
+    /// Raised when a non-empty Val refers to its data with a null pointer.
+    NullPointer,
 }
 
 impl std::error::Error for Error {
@@ -17,6 +20,7 @@ impl std::error::Error for Error {
         match self {
             Error::PluginUninitialized => None,
             Error::ValueConversionError(e) => Some(e),
+            Error::NullPointer => None,
         }
     }
 }
@@ -26,6 +30,7 @@ impl fmt::Display for Error {
         match self {
             Error::PluginUninitialized => write!(f, "Plugin is not yet initialized"),
             Error::ValueConversionError(e) => write!(f, "Value conversion error\nCaused by: {}", e),
+            Error::NullPointer => write!(f, "Value refers to its data with a null pointer"),
         }
     }
 }
@@ -1,14 +1,16 @@
 //! Functions and types used by the foreign function interface to communicate with a plugin.
 use std::boxed::Box;
 use std::convert::TryInto;
+use std::ffi::CString;
 use std::ptr::null;
 
 use libc::{c_char, c_int, c_uchar, size_t};
 
 use crate::error_codes::*;
 use crate::{
-    copy_string, PluginAPI, PluginData, PluginError, Val, ATTRIBUTE_PRE_INIT_FALSE,
-    ATTRIBUTE_PRE_INIT_TRUE, ERRORS,
+    copy_string, MetadataVal, Notifier, PluginAPI, PluginData, PluginError, Val,
+    ATTRIBUTE_PRE_INIT_FALSE, ATTRIBUTE_PRE_INIT_TRUE, ATTRIBUTE_SETTABLE_FALSE,
+    ATTRIBUTE_SETTABLE_TRUE, ERRORS,
 };
 
 /// Determines which callbacks to use by indicating the current lifecycle phase of the plugin when
@@ -63,6 +65,68 @@ pub unsafe extern "C" fn plugin_init<T: PluginAPI<E>, E: PluginError + 'static>(
     }
 }
 
+/// Deinitializes a plugin.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences a raw pointer.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+pub unsafe extern "C" fn plugin_deinit<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *mut PluginData,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    };
+
+    let plugin_data = plugin_data as *mut T;
+    match (*plugin_data).deinit() {
+        Ok(_) => {
+            log::debug!("Successfully deinitialized plugin");
+            PLUGIN_OK
+        }
+        Err(e) => {
+            log::error!("Plugin failed to deinitialize: {}", e);
+            e.error_code()
+        }
+    }
+}
+
+/// Passes the notifier that the plugin uses to report changes to its attributes.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences a raw pointer.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `notifier` - The handle that forwards notifications to the daemon
+pub unsafe extern "C" fn set_notifier<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *mut PluginData,
+    notifier: Notifier,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    };
+
+    let plugin_data = plugin_data as *mut T;
+    match (*plugin_data).set_notifier(notifier) {
+        Ok(_) => {
+            log::debug!("Successfully set the plugin's notifier");
+            PLUGIN_OK
+        }
+        Err(e) => {
+            log::error!("Plugin failed to set its notifier: {}", e);
+            e.error_code()
+        }
+    }
+}
+
 /// Returns an error message to the daemon given an error code.
 ///
 /// If an undefined error code is provided, then this function will return a null pointer.
@@ -173,6 +237,45 @@ pub unsafe extern "C" fn attribute_name<T: PluginAPI<E>, E: PluginError + 'stati
     }
 }
 
+/// Writes the alias of an attribute to a buffer that is provided by the caller.
+///
+/// This function returns a status code that indicates whether the operation succeeded and the
+/// cause of any possible errors.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences a raw pointer.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `id` - The id of the attribute
+/// * `buffer` - A buffer of bytes into which the attribute's alias will be written
+/// * `length` - The length of the buffer
+pub unsafe extern "C" fn attribute_alias<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *const PluginData,
+    id: size_t,
+    buffer: *mut c_uchar,
+    length: size_t,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    }
+    let plugin_data = plugin_data as *const T;
+
+    match (*plugin_data).attribute_alias(id) {
+        Ok(alias) => CString::new(alias)
+            .map_err(|_| ())
+            .and_then(|alias| {
+                copy_string(alias.as_bytes_with_nul(), buffer, length).map_err(|_| ())
+            })
+            .map(|_| PLUGIN_OK)
+            .unwrap_or_else(|_| UNDEFINED_ERR),
+        Err(e) => e.error_code(),
+    }
+}
+
 /// Indicates whether an attribute may be set before initialization.
 ///
 /// This function accepts a pointer to a c_char. If the char is ATTRIBUTE_PRE_INIT_FALSE after the
@@ -226,6 +329,139 @@ pub unsafe extern "C" fn attribute_pre_init<T: PluginAPI<E>, E: PluginError + 's
     }
 }
 
+/// Indicates whether an attribute may be set during a lifecycle phase.
+///
+/// This function accepts a pointer to a c_char. If the function returns PLUGIN_OK, then the char
+/// is ATTRIBUTE_SETTABLE_TRUE if the attribute may be set during the given phase and
+/// ATTRIBUTE_SETTABLE_FALSE if it is read-only.
+///
+/// If the function does not return PLUGIN_OK, then the value stored at settable will not be
+/// modified.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `id` - The id of the attribute
+/// * `phase` - The phase of the plugin lifecycle
+/// * `settable` - A value that determines whether the attribute may be set during the phase
+pub unsafe extern "C" fn attribute_settable<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *const PluginData,
+    id: size_t,
+    phase: Phase,
+    settable: *mut c_char,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    }
+    if settable.is_null() {
+        log::error!("settable pointer is null");
+        return NULL_PTR_ERR;
+    }
+    let plugin_data = plugin_data as *const T;
+
+    match (*plugin_data).attribute_settable(id, phase) {
+        Ok(settable_resp) => {
+            if settable_resp {
+                *settable = ATTRIBUTE_SETTABLE_TRUE;
+            } else {
+                *settable = ATTRIBUTE_SETTABLE_FALSE;
+            };
+            PLUGIN_OK
+        }
+        Err(e) => e.error_code(),
+    }
+}
+
+/// Writes the choices of an attribute to a buffer that is provided by the caller.
+///
+/// The choices are written as consecutive null-terminated strings followed by an additional null
+/// byte. If the attribute may take any value, then only the final null byte is written.
+///
+/// This function returns a status code that indicates whether the operation succeeded and the
+/// cause of any possible errors.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences a raw pointer.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `id` - The id of the attribute
+/// * `buffer` - A buffer of bytes into which the attribute's choices will be written
+/// * `length` - The length of the buffer
+pub unsafe extern "C" fn attribute_choices<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *const PluginData,
+    id: size_t,
+    buffer: *mut c_uchar,
+    length: size_t,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    }
+    let plugin_data = plugin_data as *const T;
+
+    match (*plugin_data).attribute_choices(id) {
+        Ok(choices) => {
+            let mut bytes: Vec<u8> = Vec::new();
+            for choice in &choices {
+                bytes.extend_from_slice(choice.to_bytes_with_nul());
+            }
+            bytes.push(0);
+
+            copy_string(&bytes, buffer, length)
+                .map(|_| PLUGIN_OK)
+                .unwrap_or_else(|_| UNDEFINED_ERR)
+        }
+        Err(e) => e.error_code(),
+    }
+}
+
+/// Writes the metadata of an attribute to a MetadataVal instance that is provided by the caller.
+///
+/// This function returns a status code that indicates whether the operation succeeded and the
+/// cause of any possible errors. The strings in the metadata point to data that is owned by the
+/// plugin and should be copied by the caller.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `id` - The id of the attribute
+/// * `metadata` - A pointer to a MetadataVal struct that is provided by this function's caller
+pub unsafe extern "C" fn attribute_metadata<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *const PluginData,
+    id: size_t,
+    metadata: *mut MetadataVal,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    }
+    if metadata.is_null() {
+        log::error!("metadata pointer is null");
+        return NULL_PTR_ERR;
+    }
+    let plugin_data = plugin_data as *const T;
+
+    match (*plugin_data).attribute_metadata(id) {
+        Ok(new_metadata) => {
+            *metadata = new_metadata;
+            PLUGIN_OK
+        }
+        Err(e) => e.error_code(),
+    }
+}
+
 /// Writes the value of an attribute to a Value instance that is provided by the caller.
 ///
 /// This function returns a status code that indicates whether the operation succeeded and the
@@ -310,3 +546,226 @@ pub unsafe extern "C" fn set_attribute_value<T: PluginAPI<E>, E: PluginError + '
         Err(e) => e.error_code(),
     }
 }
+
+/// Returns the number of actions of the plugin.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences a raw pointer.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `count` - A pointer to a size_t that will contain the number of actions
+pub unsafe extern "C" fn action_count<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *const PluginData,
+    count: *mut size_t,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    };
+    if count.is_null() {
+        log::error!("count pointer is null");
+        return NULL_PTR_ERR;
+    }
+
+    let plugin_data = plugin_data as *const T;
+    *count = (*plugin_data).action_count();
+
+    PLUGIN_OK
+}
+
+/// Writes the plugin's action IDs to a buffer that is provided by the caller.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences a raw pointer.
+///
+/// # Arguments
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `buffer` - A pointer to a string of size_t's into which the action IDs will be written
+/// * `length` - The length of the buffer
+pub unsafe extern "C" fn action_ids<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *const PluginData,
+    buffer: *mut size_t,
+    length: size_t,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    }
+    let plugin_data = plugin_data as *const T;
+    let ids = (*plugin_data).action_ids();
+
+    match copy_string(&ids, buffer, length) {
+        Ok(_) => PLUGIN_OK,
+        Err(_) => UNDEFINED_ERR,
+    }
+}
+
+/// Writes the name of an action to a buffer that is provided by the caller.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences a raw pointer.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `id` - The id of the action
+/// * `buffer` - A buffer of bytes into which the action's name will be written
+/// * `length` - The length of the buffer
+pub unsafe extern "C" fn action_name<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *const PluginData,
+    id: size_t,
+    buffer: *mut c_uchar,
+    length: size_t,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    }
+    let plugin_data = plugin_data as *const T;
+
+    match (*plugin_data).action_name(id) {
+        Ok(name) => copy_string(name.to_bytes_with_nul(), buffer, length)
+            .map(|_| PLUGIN_OK)
+            .unwrap_or_else(|_| UNDEFINED_ERR),
+        Err(e) => e.error_code(),
+    }
+}
+
+/// Returns the number of arguments of an action.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `id` - The id of the action
+/// * `count` - A pointer to a size_t that will contain the number of arguments
+pub unsafe extern "C" fn action_argument_count<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *const PluginData,
+    id: size_t,
+    count: *mut size_t,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    }
+    if count.is_null() {
+        log::error!("count pointer is null");
+        return NULL_PTR_ERR;
+    }
+    let plugin_data = plugin_data as *const T;
+
+    match (*plugin_data).action_argument_count(id) {
+        Ok(argument_count) => {
+            *count = argument_count;
+            PLUGIN_OK
+        }
+        Err(e) => e.error_code(),
+    }
+}
+
+/// Writes the name and example value of an action's argument.
+///
+/// The name is written to a buffer and the value to a Val instance, both of which are provided by
+/// the caller. The variant of the value determines the argument's type.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `id` - The id of the action
+/// * `index` - The position of the argument in the action's list of arguments
+/// * `buffer` - A buffer of bytes into which the argument's name will be written
+/// * `length` - The length of the buffer
+/// * `value` - A pointer to a Val enum into which the argument's example value will be written
+pub unsafe extern "C" fn action_argument<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *const PluginData,
+    id: size_t,
+    index: size_t,
+    buffer: *mut c_uchar,
+    length: size_t,
+    value: *mut Val,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    }
+    if value.is_null() {
+        log::error!("value pointer is null");
+        return NULL_PTR_ERR;
+    }
+    let plugin_data = plugin_data as *const T;
+
+    match (*plugin_data).action_argument(id, index) {
+        Ok(argument) => {
+            if copy_string(argument.name.to_bytes_with_nul(), buffer, length).is_err() {
+                return UNDEFINED_ERR;
+            }
+            *value = argument.value.as_val();
+            PLUGIN_OK
+        }
+        Err(e) => e.error_code(),
+    }
+}
+
+/// Invokes an action.
+///
+/// This function returns a status code that indicates whether the operation succeeded and the
+/// cause of any possible errors. If it returns PLUGIN_OK, then the action's result is written to
+/// the Val instance that is provided by the caller.
+///
+/// # Safety
+///
+/// This function is unsafe because it dereferences raw pointers.
+///
+/// # Arguments
+///
+/// * `plugin_data` - A pointer to a PluginData struct
+/// * `id` - The id of the action
+/// * `args` - A pointer to an array of Val enums that contain the action's arguments
+/// * `args_length` - The number of arguments in the array
+/// * `result` - A pointer to a Val enum into which the action's result will be written
+pub unsafe extern "C" fn invoke_action<T: PluginAPI<E>, E: PluginError + 'static>(
+    plugin_data: *mut PluginData,
+    id: size_t,
+    args: *const Val,
+    args_length: size_t,
+    result: *mut Val,
+) -> c_int {
+    if plugin_data.is_null() {
+        log::error!("plugin_data pointer is null");
+        return NULL_PTR_ERR;
+    }
+    if result.is_null() {
+        log::error!("result pointer is null");
+        return NULL_PTR_ERR;
+    }
+    let args = if args_length == 0 {
+        &[]
+    } else if args.is_null() {
+        log::error!("args pointer is null");
+        return NULL_PTR_ERR;
+    } else {
+        std::slice::from_raw_parts(args, args_length)
+    };
+    let plugin_data = plugin_data as *mut T;
+
+    match (*plugin_data).invoke_action(id, args) {
+        Ok(value) => {
+            log::debug!("Response for the invocation of action {}: {:?}", id, value);
+            *result = value;
+            PLUGIN_OK
+        }
+        Err(e) => e.error_code(),
+    }
+}
@@ -5,7 +5,8 @@
 //! 1. Define two structs, one that will hold your plugin's data, including its attributes, and
 //! another that will hold your plugin's error information.
 //! 2. Implement the PluginAPI and PluginError traits for the data and error structs, respectively.
-//! 3. Implement any callbacks that will be used to read and update your plugin's attribute values.
+//! 3. Implement any callbacks that will be used to read and update your plugin's attribute values
+//! and to perform its actions.
 //!
 //! Plugins have a lifecycle defined by distinct phases. The attribute callbacks are different for
 //! each phase. Currently there are two phases:
@@ -13,6 +14,9 @@
 //! 1. `Init` - The phase during which a plugin is being initialized
 //! 2. `Run` - A plugin's normal operating phase
 //!
+//! A plugin may be returned from the run phase to the init phase by deinitializing it. This allows
+//! its pre-init attributes to be modified before it is initialized again.
+//!
 //! See the examples folder for ideas on how to implement the datatypes and methods defined in this
 //! library.
 mod constants;
@@ -24,16 +28,18 @@ use std::{
     cell::{Ref, RefCell},
     cmp::PartialEq,
     ffi::{CStr, CString},
-    fmt, slice,
+    fmt,
+    mem::discriminant,
+    ptr, slice,
 };
 
-use libc::{c_char, c_double, c_int, c_uchar, c_uint, size_t};
+use libc::{c_char, c_double, c_float, c_int, c_uchar, c_uint, c_void, size_t};
 pub use multi_map::{multimap, MultiMap};
 
 pub use {
     constants::{
-        error_codes, ATTRIBUTE_PRE_INIT_FALSE, ATTRIBUTE_PRE_INIT_TRUE, ERRORS, INIT_PHASE,
-        RUN_PHASE,
+        error_codes, ABI_VERSION, ATTRIBUTE_PRE_INIT_FALSE, ATTRIBUTE_PRE_INIT_TRUE,
+        ATTRIBUTE_SETTABLE_FALSE, ATTRIBUTE_SETTABLE_TRUE, ERRORS, INIT_PHASE, RUN_PHASE,
     },
     errors::Error,
     ffi::*,
@@ -51,6 +57,27 @@ where
     /// Initialzes the plugin by performing any hardware initialization.
     fn init(&mut self) -> Result<(), E>;
 
+    /// Deinitializes the plugin by releasing any hardware resources acquired by `init`.
+    ///
+    /// After this method returns, the plugin should be ready to be initialized again. The default
+    /// implementation does nothing.
+    fn deinit(&mut self) -> Result<(), E> {
+        Ok(())
+    }
+
+    /// Receives the notifier that the plugin may use to report changes to its attributes.
+    ///
+    /// The daemon calls this method once before the plugin is initialized. Plugins whose attributes
+    /// change without a request from the daemon, e.g. in response to a hardware interrupt, should
+    /// keep a copy of the notifier. The default implementation discards it.
+    ///
+    /// # Arguments
+    ///
+    /// * `notifier` - the handle that forwards notifications to the daemon
+    fn set_notifier(&mut self, _notifier: Notifier) -> Result<(), E> {
+        Ok(())
+    }
+
     /// Returns the attributes of the plugin.
     fn attributes(&self) -> &Attributes<Self, E>;
 
@@ -89,6 +116,25 @@ where
         }
     }
 
+    /// Returns the alias of an attribute.
+    ///
+    /// The alias is the alternative key of the attribute in the plugin's `Attributes`, e.g.
+    /// `offset`. Unlike the name, which is meant to be read by people, the alias is meant to be used
+    /// by programs to look up the attribute.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the numeric ID of the attribute
+    fn attribute_alias(&self, id: usize) -> Result<&'static str, E> {
+        log::debug!("Received request for the alias of attribute: {}", id);
+        self.attributes()
+            .borrow()
+            .iter()
+            .find(|(attr_id, _)| **attr_id == id)
+            .map(|(_, (alias, _))| *alias)
+            .ok_or_else(|| E::new(error_codes::ATTRIBUTE_DOES_NOT_EXIST))
+    }
+
     /// Indicates whether an attribute may be set before initialization.
     ///
     /// # Arguments
@@ -111,6 +157,76 @@ where
         }
     }
 
+    /// Indicates whether an attribute may be set during a lifecycle phase.
+    ///
+    /// Attributes with `Update` or `GetAndSet` callbacks are settable; attributes with `Constant`
+    /// or `Get` callbacks are read-only.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the numeric ID of the attribute
+    /// * `phase` - the lifecycle phase of the plugin
+    fn attribute_settable(&self, id: usize, phase: Phase) -> Result<bool, E> {
+        log::debug!(
+            "Received request for the settable status of attribute {} in phase {}",
+            id,
+            phase
+        );
+        let attributes = self.attributes().borrow();
+        let attribute = attributes
+            .get(&id)
+            .ok_or_else(|| E::new(error_codes::ATTRIBUTE_DOES_NOT_EXIST))?;
+
+        let callbacks = if phase == constants::INIT_PHASE {
+            &attribute.callbacks_init
+        } else if phase == constants::RUN_PHASE {
+            &attribute.callbacks_run
+        } else {
+            return Err(E::new(error_codes::LIFECYCLE_PHASE_ERR));
+        };
+
+        match callbacks {
+            Callbacks::Update | Callbacks::GetAndSet(_, _) => Ok(true),
+            Callbacks::Constant | Callbacks::Get(_) => Ok(false),
+        }
+    }
+
+    /// Returns the choices of an attribute.
+    ///
+    /// If the attribute that corresponds to the `id` does not exist, then an error is returned.
+    /// An empty Vec is returned if the attribute may take any value.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the numeric ID of the attribute
+    fn attribute_choices(&self, id: usize) -> Result<Vec<CString>, E> {
+        log::debug!("Received request for the choices of attribute: {}", id);
+        let attributes = self.attributes().borrow();
+        let attribute = attributes
+            .get(&id)
+            .ok_or_else(|| E::new(error_codes::ATTRIBUTE_DOES_NOT_EXIST))?;
+
+        Ok(attribute.choices.clone())
+    }
+
+    /// Returns the metadata of an attribute.
+    ///
+    /// If the attribute that corresponds to the `id` does not exist, then an error is returned.
+    /// Otherwise, the metadata is returned as a C-compatible struct.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the numeric ID of the attribute
+    fn attribute_metadata(&self, id: usize) -> Result<MetadataVal, E> {
+        log::debug!("Received request for the metadata of attribute: {}", id);
+        let attributes = self.attributes().borrow();
+        let attribute = attributes
+            .get(&id)
+            .ok_or_else(|| E::new(error_codes::ATTRIBUTE_DOES_NOT_EXIST))?;
+
+        Ok(attribute.metadata.as_val())
+    }
+
     /// Returns the value of an attribute.
     ///
     /// If the attribute that corresponds to the `id` does not exist, then an error is
@@ -213,6 +329,151 @@ where
 
         Ok(())
     }
+
+    /// Returns the actions of the plugin.
+    ///
+    /// Plugins without actions may rely on the default implementation, which returns `None`.
+    fn actions(&self) -> Option<&Actions<Self, E>> {
+        None
+    }
+
+    /// Returns the number of actions of the plugin.
+    fn action_count(&self) -> usize {
+        self.actions()
+            .map_or(0, |actions| actions.borrow().iter().count())
+    }
+
+    /// Returns the action IDs.
+    fn action_ids(&self) -> Vec<usize> {
+        self.actions().map_or_else(Vec::new, |actions| {
+            actions.borrow().iter().map(|(id, _)| *id).collect()
+        })
+    }
+
+    /// Returns the name of an action.
+    ///
+    /// If the action that corresponds to the `id` does not exist, then an error is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the numeric ID of the action
+    fn action_name(&self, id: usize) -> Result<Ref<'_, CString>, E> {
+        log::debug!("Received request for the name of action: {}", id);
+        let actions = self
+            .actions()
+            .ok_or_else(|| E::new(error_codes::ACTION_DOES_NOT_EXIST))?
+            .borrow();
+        match actions.get(&id) {
+            Some(_) => Ok(Ref::map(actions, |a| {
+                &a.get(&id)
+                    .expect("Action does not exist. This should never happen.")
+                    .name
+            })),
+            None => Err(E::new(error_codes::ACTION_DOES_NOT_EXIST)),
+        }
+    }
+
+    /// Returns the number of arguments of an action.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the numeric ID of the action
+    fn action_argument_count(&self, id: usize) -> Result<usize, E> {
+        log::debug!("Received request for the argument count of action: {}", id);
+        let actions = self
+            .actions()
+            .ok_or_else(|| E::new(error_codes::ACTION_DOES_NOT_EXIST))?
+            .borrow();
+        let action = actions
+            .get(&id)
+            .ok_or_else(|| E::new(error_codes::ACTION_DOES_NOT_EXIST))?;
+
+        Ok(action.arguments.len())
+    }
+
+    /// Returns an argument of an action.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the numeric ID of the action
+    /// * `index` - the position of the argument in the action's list of arguments
+    fn action_argument(&self, id: usize, index: usize) -> Result<Ref<'_, Argument>, E> {
+        log::debug!("Received request for argument {} of action: {}", index, id);
+        let actions = self
+            .actions()
+            .ok_or_else(|| E::new(error_codes::ACTION_DOES_NOT_EXIST))?
+            .borrow();
+        match actions.get(&id) {
+            Some(action) if index < action.arguments.len() => Ok(Ref::map(actions, |a| {
+                &a.get(&id)
+                    .expect("Action does not exist. This should never happen.")
+                    .arguments[index]
+            })),
+            Some(_) => Err(E::new(error_codes::ACTION_ARGUMENTS_MISMATCH)),
+            None => Err(E::new(error_codes::ACTION_DOES_NOT_EXIST)),
+        }
+    }
+
+    /// Invokes an action and returns its result.
+    ///
+    /// The arguments must match the number and types of the action's declared arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the numeric ID of the action
+    /// * `args` - the values of the action's arguments
+    fn invoke_action(&self, id: usize, args: &[Val]) -> Result<Val, E> {
+        log::debug!("Received request to invoke action: {}", id);
+        let (callback, values) = {
+            let actions = self
+                .actions()
+                .ok_or_else(|| E::new(error_codes::ACTION_DOES_NOT_EXIST))?
+                .borrow();
+            let action = actions
+                .get(&id)
+                .ok_or_else(|| E::new(error_codes::ACTION_DOES_NOT_EXIST))?;
+
+            if args.len() != action.arguments.len() {
+                return Err(E::new(error_codes::ACTION_ARGUMENTS_MISMATCH));
+            }
+
+            let mut values = Vec::with_capacity(args.len());
+            for (arg, declared) in args.iter().zip(action.arguments.iter()) {
+                let value = arg.to_value().map_err(|err| {
+                    log::error!(
+                        "Could not convert action argument: {{ id: {:?}, error: {:?} }}",
+                        id,
+                        err
+                    );
+                    E::new(error_codes::CONVERSION_ERR)
+                })?;
+                if discriminant(&value) != discriminant(&declared.value) {
+                    return Err(E::new(error_codes::ACTION_ARGUMENTS_MISMATCH));
+                }
+                values.push(value);
+            }
+
+            (action.callback, values)
+        };
+
+        // The actions are not borrowed while the callback runs so that it may use the plugin.
+        let result = callback(self, &values).map_err(|err| {
+            log::error!("Callback error {{ id: {:?}, error: {:?} }}", id, err);
+            E::new(error_codes::CALLBACK_ERR)
+        })?;
+
+        // Cache the result so that the memory it refers to outlives this call.
+        let mut actions = self
+            .actions()
+            .ok_or_else(|| E::new(error_codes::ACTION_DOES_NOT_EXIST))?
+            .borrow_mut();
+        let action = actions
+            .get_mut(&id)
+            .ok_or_else(|| E::new(error_codes::ACTION_DOES_NOT_EXIST))?;
+        action.result = result;
+
+        Ok(action.result.as_val())
+    }
 }
 
 /// Convenience function that calls a set callback only for valid (Value, Val) pairs.
@@ -229,29 +490,12 @@ fn set_helper<T, E: std::error::Error + PluginError + 'static>(
     val: &Val,
     set: fn(&T, &Value, &Val) -> Result<(), E>,
 ) -> Result<(), E> {
-    let err = Err(E::new(error_codes::ATTRIBUTE_TYPE_MISMATCH));
-
-    // The full range is listed so the compiler will remind you to update this function when a new
-    // variant is added.
-    match (value, val) {
-        (Value::Int(_), Val::Int(_))
-        | (Value::Double(_), Val::Double(_))
-        | (Value::String(_), Val::String(_, _))
-        | (Value::Uint(_), Val::Uint(_)) => set(plugin, value, val),
-        // Invalid inputs
-        (Value::Int(_), Val::Double(_)) => err,
-        (Value::Int(_), Val::String(_, _)) => err,
-        (Value::Int(_), Val::Uint(_)) => err,
-        (Value::Double(_), Val::Int(_)) => err,
-        (Value::Double(_), Val::String(_, _)) => err,
-        (Value::Double(_), Val::Uint(_)) => err,
-        (Value::String(_), Val::Int(_)) => err,
-        (Value::String(_), Val::Double(_)) => err,
-        (Value::String(_), Val::Uint(_)) => err,
-        (Value::Uint(_), Val::Int(_)) => err,
-        (Value::Uint(_), Val::Double(_)) => err,
-        (Value::Uint(_), Val::String(_, _)) => err,
+    // The current value is converted so that both discriminants belong to the same enum.
+    if discriminant(&value.as_val()) != discriminant(val) {
+        return Err(E::new(error_codes::ATTRIBUTE_TYPE_MISMATCH));
     }
+
+    set(plugin, value, val)
 }
 
 /// The set of functions that must be implemented by a plugin library's main error type.
@@ -314,6 +558,41 @@ pub struct PluginData {
     _private: [u8; 0],
 }
 
+/// A handle that a plugin uses to tell the daemon that the value of one of its attributes changed.
+///
+/// The daemon passes a notifier to the plugin before it is initialized. After receiving a
+/// notification, the daemon reads the attribute's new value through the plugin API and forwards it
+/// to its clients. Notifications may be sent from any thread, including threads that are owned by
+/// the plugin.
+///
+/// # Safety
+///
+/// The notifier implements the `Send` trait because the daemon guarantees that its context may be
+/// used from any thread. The context is only valid for as long as the plugin exists, so a plugin
+/// must stop sending notifications before its data is freed.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct Notifier {
+    /// The function that forwards a notification to the daemon.
+    pub callback: extern "C" fn(context: *mut c_void, id: size_t),
+
+    /// An opaque pointer to the daemon's state that is passed to the callback.
+    pub context: *mut c_void,
+}
+
+impl Notifier {
+    /// Tells the daemon that the value of an attribute has changed.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - the numeric ID of the attribute
+    pub fn notify(&self, id: usize) {
+        (self.callback)(self.context, id)
+    }
+}
+
+unsafe impl Send for Notifier {}
+
 /// A table of function pointers that comprise the plugin API for the foreign function interface.
 ///
 /// By default, functions in the VTable return a number that represents a status code that maps
@@ -335,6 +614,13 @@ pub struct VTable {
     /// plugin data structures.
     pub plugin_init: unsafe extern "C" fn(*mut PluginData) -> c_int,
 
+    /// Deinitializes a plugin, releasing the hardware resources acquired by `plugin_init`.
+    pub plugin_deinit: unsafe extern "C" fn(*mut PluginData) -> c_int,
+
+    /// Passes the notifier that the plugin uses to report changes to its attributes.
+    pub set_notifier:
+        unsafe extern "C" fn(plugin_data: *mut PluginData, notifier: Notifier) -> c_int,
+
     /// Returns an error message associated with a Plugin error code.
     pub error_message_ns: extern "C" fn(c_int) -> *const c_uchar,
 
@@ -354,6 +640,14 @@ pub struct VTable {
         length: size_t,
     ) -> c_int,
 
+    /// Writes the alias of an attribute to a buffer that is provided by the caller.
+    pub attribute_alias: unsafe extern "C" fn(
+        plugin_data: *const PluginData,
+        id: size_t,
+        buffer: *mut c_uchar,
+        length: size_t,
+    ) -> c_int,
+
     /// Indicates whether an attribute may be set before initialization.
     pub attribute_pre_init: unsafe extern "C" fn(
         plugin_data: *const PluginData,
@@ -361,6 +655,29 @@ pub struct VTable {
         pre_init: *mut c_char,
     ) -> c_int,
 
+    /// Indicates whether an attribute may be set during a lifecycle phase.
+    pub attribute_settable: unsafe extern "C" fn(
+        plugin_data: *const PluginData,
+        id: size_t,
+        phase: Phase,
+        settable: *mut c_char,
+    ) -> c_int,
+
+    /// Writes the choices of an attribute to a buffer that is provided by the caller.
+    pub attribute_choices: unsafe extern "C" fn(
+        plugin_data: *const PluginData,
+        id: size_t,
+        buffer: *mut c_uchar,
+        length: size_t,
+    ) -> c_int,
+
+    /// Writes the metadata of an attribute to a MetadataVal instance provided by the caller.
+    pub attribute_metadata: unsafe extern "C" fn(
+        plugin_data: *const PluginData,
+        id: size_t,
+        metadata: *mut MetadataVal,
+    ) -> c_int,
+
     /// Writes the value of an attribute to a Value instance that is provided by the caller.
     pub attribute_value: unsafe extern "C" fn(
         plugin_data: *const PluginData,
@@ -376,6 +693,49 @@ pub struct VTable {
         value: *const Val,
         phase: Phase,
     ) -> c_int,
+
+    /// Returns the number of actions of the plugin.
+    pub action_count:
+        unsafe extern "C" fn(plugin_data: *const PluginData, count: *mut size_t) -> c_int,
+
+    /// Returns the action IDs in a buffer provided by the caller.
+    pub action_ids:
+        unsafe extern "C" fn(plugin_data: *const PluginData, ids: *mut size_t, size_t) -> c_int,
+
+    /// Writes the name of an action to a buffer that is provided by the caller.
+    pub action_name: unsafe extern "C" fn(
+        plugin_data: *const PluginData,
+        id: size_t,
+        buffer: *mut c_uchar,
+        length: size_t,
+    ) -> c_int,
+
+    /// Returns the number of arguments of an action.
+    pub action_argument_count: unsafe extern "C" fn(
+        plugin_data: *const PluginData,
+        id: size_t,
+        count: *mut size_t,
+    ) -> c_int,
+
+    /// Writes the name and example value of an action's argument to a buffer and a Val instance
+    /// that are provided by the caller.
+    pub action_argument: unsafe extern "C" fn(
+        plugin_data: *const PluginData,
+        id: size_t,
+        index: size_t,
+        buffer: *mut c_uchar,
+        length: size_t,
+        value: *mut Val,
+    ) -> c_int,
+
+    /// Invokes an action and writes its result to a Val instance that is provided by the caller.
+    pub invoke_action: unsafe extern "C" fn(
+        plugin_data: *mut PluginData,
+        id: size_t,
+        args: *const Val,
+        args_length: size_t,
+        result: *mut Val,
+    ) -> c_int,
 }
 
 /// The type signature of the function that returns a new plugin instance.
@@ -384,6 +744,22 @@ pub type KpalPluginInit = unsafe extern "C" fn(*mut Plugin) -> c_int;
 /// The type signature of the function that initializes a library.
 pub type KpalLibraryInit = unsafe extern "C" fn() -> c_int;
 
+/// The type signature of the function that returns the name and version of a library.
+pub type KpalLibraryInfo = unsafe extern "C" fn(*mut LibraryInfo) -> c_int;
+
+/// The name and version of a plugin library.
+///
+/// Both fields point to null-terminated strings that live as long as the library is loaded.
+#[derive(Clone, Copy, Debug)]
+#[repr(C)]
+pub struct LibraryInfo {
+    /// The name of the library, e.g. `gpio-cdev`.
+    pub name: *const c_char,
+
+    /// The semantic version of the library, e.g. `0.3.0`.
+    pub version: *const c_char,
+}
+
 /// The type signature of the collection of attributes that is owned by the plugin.
 pub type Attributes<T, E> = RefCell<MultiMap<usize, &'static str, Attribute<T, E>>>;
 
@@ -407,6 +783,61 @@ pub struct Attribute<T, E: std::error::Error + PluginError> {
     /// The callback functions that are fired when the attribute is either read or set during the
     /// run phase of the plugin.
     pub callbacks_run: Callbacks<T, E>,
+
+    /// The set of values that a string attribute may take, e.g. the modes of a device setting.
+    ///
+    /// Choices may not be empty strings. If no choices are provided, then the attribute may take
+    /// any value.
+    pub choices: Vec<CString>,
+
+    /// The attribute's units, range, step and description.
+    pub metadata: Metadata,
+}
+
+/// The type signature of the collection of actions that is owned by the plugin.
+pub type Actions<T, E> = RefCell<MultiMap<usize, &'static str, Action<T, E>>>;
+
+/// An operation performed by a plugin that does not map onto reading or setting an attribute.
+///
+/// Examples include homing a stage, zeroing a scale, or capturing a frame.
+pub struct Action<T, E: std::error::Error + PluginError> {
+    /// The name of the action.
+    pub name: CString,
+
+    /// The arguments that must be provided when the action is invoked, in order.
+    pub arguments: Vec<Argument>,
+
+    /// The result of the most recent invocation of the action.
+    ///
+    /// This is the value that is returned through the FFI. Its initial value is not used.
+    pub result: Value,
+
+    /// The callback function that is fired when the action is invoked.
+    ///
+    /// The arguments are guaranteed to match the number and variants of the action's declared
+    /// arguments.
+    pub callback: fn(plugin: &T, arguments: &[Value]) -> Result<Value, E>,
+}
+
+impl<T, E: std::error::Error + PluginError> fmt::Debug for Action<T, E> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("Action")
+            .field("name", &self.name)
+            .field("arguments", &self.arguments)
+            .field("result", &self.result)
+            .field("callback", &format_args!("{:x}", self.callback as usize))
+            .finish()
+    }
+}
+
+/// A named argument of an action.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Argument {
+    /// The name of the argument.
+    pub name: CString,
+
+    /// An example value of the argument. Its variant determines the argument's type.
+    pub value: Value,
 }
 
 /// An owned value of an attribute.
@@ -420,6 +851,13 @@ pub enum Value {
     Double(c_double),
     String(CString),
     Uint(c_uint),
+    Bool(bool),
+    DoubleArray(Vec<c_double>),
+    IntArray(Vec<c_int>),
+    Bytes(Vec<c_uchar>),
+    Int64(i64),
+    Uint64(u64),
+    Float(c_float),
 }
 
 impl Value {
@@ -440,6 +878,13 @@ impl Value {
                 Val::String(slice.as_ptr(), slice.len())
             }
             Value::Uint(value) => Val::Uint(*value),
+            Value::Bool(value) => Val::Bool(*value),
+            Value::DoubleArray(value) => Val::DoubleArray(value.as_ptr(), value.len()),
+            Value::IntArray(value) => Val::IntArray(value.as_ptr(), value.len()),
+            Value::Bytes(value) => Val::Bytes(value.as_ptr(), value.len()),
+            Value::Int64(value) => Val::Int64(*value),
+            Value::Uint64(value) => Val::Uint64(*value),
+            Value::Float(value) => Val::Float(*value),
         }
     }
 }
@@ -455,6 +900,13 @@ pub enum Val {
     Double(c_double),
     String(*const c_uchar, size_t),
     Uint(c_uint),
+    Bool(bool),
+    DoubleArray(*const c_double, size_t),
+    IntArray(*const c_int, size_t),
+    Bytes(*const c_uchar, size_t),
+    Int64(i64),
+    Uint64(u64),
+    Float(c_float),
 }
 
 impl Val {
@@ -468,15 +920,117 @@ impl Val {
             Val::Int(value) => Ok(Value::Int(*value)),
             Val::Double(value) => Ok(Value::Double(*value)),
             Val::String(p_value, length) => {
-                let slice = unsafe { slice::from_raw_parts(*p_value, *length) };
+                let slice = unsafe { ffi_slice(*p_value, *length)? };
                 let c_string = CStr::from_bytes_with_nul(slice)?.to_owned();
                 Ok(Value::String(c_string))
             }
             Val::Uint(value) => Ok(Value::Uint(*value)),
+            Val::Bool(value) => Ok(Value::Bool(*value)),
+            Val::DoubleArray(p_value, length) => {
+                let slice = unsafe { ffi_slice(*p_value, *length)? };
+                Ok(Value::DoubleArray(slice.to_vec()))
+            }
+            Val::IntArray(p_value, length) => {
+                let slice = unsafe { ffi_slice(*p_value, *length)? };
+                Ok(Value::IntArray(slice.to_vec()))
+            }
+            Val::Bytes(p_value, length) => {
+                let slice = unsafe { ffi_slice(*p_value, *length)? };
+                Ok(Value::Bytes(slice.to_vec()))
+            }
+            Val::Int64(value) => Ok(Value::Int64(*value)),
+            Val::Uint64(value) => Ok(Value::Uint64(*value)),
+            Val::Float(value) => Ok(Value::Float(*value)),
         }
     }
 }
 
+/// Returns the data that a Val refers to as a slice.
+///
+/// Empty slices are returned without reading the pointer, which may be null in this case.
+///
+/// # Safety
+///
+/// The pointer must be valid for reads of `length` elements if `length` is not zero.
+///
+/// # Arguments
+///
+/// * `p_value` - A pointer to the first element of the data
+/// * `length` - The number of elements of the data
+unsafe fn ffi_slice<'a, T>(p_value: *const T, length: size_t) -> Result<&'a [T], Error> {
+    if length == 0 {
+        Ok(&[])
+    } else if p_value.is_null() {
+        Err(Error::NullPointer)
+    } else {
+        Ok(slice::from_raw_parts(p_value, length))
+    }
+}
+
+/// Information about an attribute that helps clients to display and validate its value.
+///
+/// All fields are optional. The range given by `min` and `max` is inclusive and applies to numeric
+/// values and to each element of numeric arrays.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    /// A human-readable description of the attribute.
+    pub description: Option<CString>,
+
+    /// The physical units of the attribute's value, e.g. "mm" or "Hz".
+    pub units: Option<CString>,
+
+    /// The smallest value that the attribute may take.
+    pub min: Option<c_double>,
+
+    /// The largest value that the attribute may take.
+    pub max: Option<c_double>,
+
+    /// The increment between successive values of the attribute.
+    pub step: Option<c_double>,
+}
+
+impl Metadata {
+    /// Returns a reference type to the Metadata that may pass through the FFI.
+    ///
+    /// Strings are embedded as pointers to the data owned by the Metadata instance. Unset strings
+    /// are null pointers and unset numbers are NaN.
+    pub fn as_val(&self) -> MetadataVal {
+        let as_ptr = |string: &Option<CString>| {
+            string
+                .as_ref()
+                .map_or(ptr::null(), |string| string.as_ptr() as *const c_uchar)
+        };
+
+        MetadataVal {
+            description: as_ptr(&self.description),
+            units: as_ptr(&self.units),
+            min: self.min.unwrap_or(c_double::NAN),
+            max: self.max.unwrap_or(c_double::NAN),
+            step: self.step.unwrap_or(c_double::NAN),
+        }
+    }
+}
+
+/// A wrapper type for transporting Metadata through the plugin API.
+///
+/// Strings are null-terminated and are null pointers if they are not set. Numbers are NaN if they
+/// are not set.
+#[derive(Clone, Debug)]
+#[repr(C)]
+pub struct MetadataVal {
+    pub description: *const c_uchar,
+    pub units: *const c_uchar,
+    pub min: c_double,
+    pub max: c_double,
+    pub step: c_double,
+}
+
+impl Default for MetadataVal {
+    fn default() -> Self {
+        Metadata::default().as_val()
+    }
+}
+
 /// Callback functions that communicate with the hardware when an attribute is read or set.
 ///
 /// The purpose of a callback is two-fold: it performs the actual communication with the hardware
@@ -519,17 +1073,54 @@ impl<T, E: std::error::Error + PluginError> fmt::Debug for Callbacks<T, E> {
 /// Creates the required symbols for a plugin library.
 ///
 /// Any plugin library must call this macro exactly once to generate the symbols that are required
-/// by the daemon.
+/// by the daemon. The name and version that the library reports to the daemon are those of the
+/// crate that calls the macro unless they are provided as the third and fourth arguments.
 #[macro_export]
 macro_rules! declare_plugin {
     ($plugin_type:ty, $plugin_err_type:ty) => {
+        declare_plugin!(
+            $plugin_type,
+            $plugin_err_type,
+            env!("CARGO_PKG_NAME"),
+            env!("CARGO_PKG_VERSION")
+        );
+    };
+    ($plugin_type:ty, $plugin_err_type:ty, $name:expr, $version:expr) => {
+        /// The version of the binary interface that the library was built for.
+        ///
+        /// The daemon reads this value before calling any function of the library.
+        #[no_mangle]
+        pub static KPAL_ABI_VERSION: std::os::raw::c_uint = ABI_VERSION;
+
         /// Initializes the library.                                                                                                                                                                                      
         ///                                                                                                                                                                                                               
         /// This function is called only once by the daemon. It is called when a library is first
         /// loaded into memory.
         #[no_mangle]
         pub extern "C" fn kpal_library_init() -> c_int {
-            env_logger::init();
+            // The logger already exists if the library was loaded before and was not unloaded
+            // from memory when the daemon released it.
+            let _ = env_logger::try_init();
+            PLUGIN_OK
+        }
+
+        /// Writes the name and version of the library to a LibraryInfo instance that is provided
+        /// by the caller.
+        ///
+        /// # Safety
+        ///
+        /// This function is unsafe because it dereferences a raw pointer.
+        #[no_mangle]
+        pub unsafe extern "C" fn kpal_library_info(info: *mut LibraryInfo) -> c_int {
+            if info.is_null() {
+                log::error!("info pointer is null");
+                return NULL_PTR_ERR;
+            }
+
+            info.write(LibraryInfo {
+                name: concat!($name, "\0").as_ptr() as *const std::os::raw::c_char,
+                version: concat!($version, "\0").as_ptr() as *const std::os::raw::c_char,
+            });
             PLUGIN_OK
         }
 
@@ -559,13 +1150,25 @@ macro_rules! declare_plugin {
             let vtable = VTable {
                 plugin_free,
                 plugin_init: plugin_init::<$plugin_type, $plugin_err_type>,
+                plugin_deinit: plugin_deinit::<$plugin_type, $plugin_err_type>,
+                set_notifier: set_notifier::<$plugin_type, $plugin_err_type>,
                 error_message_ns,
                 attribute_count: attribute_count::<$plugin_type, $plugin_err_type>,
                 attribute_ids: attribute_ids::<$plugin_type, $plugin_err_type>,
                 attribute_name: attribute_name::<$plugin_type, $plugin_err_type>,
+                attribute_alias: attribute_alias::<$plugin_type, $plugin_err_type>,
                 attribute_pre_init: attribute_pre_init::<$plugin_type, $plugin_err_type>,
+                attribute_settable: attribute_settable::<$plugin_type, $plugin_err_type>,
+                attribute_choices: attribute_choices::<$plugin_type, $plugin_err_type>,
+                attribute_metadata: attribute_metadata::<$plugin_type, $plugin_err_type>,
                 attribute_value: attribute_value::<$plugin_type, $plugin_err_type>,
                 set_attribute_value: set_attribute_value::<$plugin_type, $plugin_err_type>,
+                action_count: action_count::<$plugin_type, $plugin_err_type>,
+                action_ids: action_ids::<$plugin_type, $plugin_err_type>,
+                action_name: action_name::<$plugin_type, $plugin_err_type>,
+                action_argument_count: action_argument_count::<$plugin_type, $plugin_err_type>,
+                action_argument: action_argument::<$plugin_type, $plugin_err_type>,
+                invoke_action: invoke_action::<$plugin_type, $plugin_err_type>,
             };
 
             plugin.write(Plugin {
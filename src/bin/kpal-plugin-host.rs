@@ -0,0 +1,28 @@
+use std::path::PathBuf;
+use std::process::exit;
+
+use structopt::StructOpt;
+
+use kpal::plugins::host;
+
+/// Hosts a single plugin on behalf of the KPAL daemon.
+///
+/// The daemon starts this process, sends it requests through its standard input, and reads the
+/// replies from its standard output. It is not meant to be started by users.
+#[derive(Debug, StructOpt)]
+#[structopt(name = "kpal-plugin-host")]
+struct Cli {
+    /// The path to the plugin library
+    #[structopt(parse(from_os_str))]
+    library: PathBuf,
+}
+
+fn main() {
+    let args = Cli::from_args();
+    env_logger::init();
+
+    if let Err(e) = host::serve(&args.library) {
+        log::error!("{}", e);
+        exit(1);
+    }
+}
@@ -1,21 +1,37 @@
+use std::env;
 use std::process::exit;
 use std::sync::Arc;
 
 use env_logger;
-use log;
+use log::{self, LevelFilter};
 use structopt::StructOpt;
 
-use kpal::init::{init, Cli, Init};
-use kpal::integrations::rest::routes;
+use kpal::init::{init, Cli, Config, Init};
+use kpal::integrations::{rest::routes, websocket};
 
 fn main() {
-    env_logger::init();
     let args = Cli::from_args();
+    let config = Config::new(&args);
+
+    init_logger(config.as_ref().ok().and_then(|config| config.log_level));
+
+    let config = match config {
+        Ok(config) => config,
+        Err(e) => {
+            log::error!("{}", e);
+            exit(1);
+        }
+    };
 
     let Init {
+        groups,
         libraries,
+        rules,
+        samplers,
+        sequences,
+        store,
         transmitters,
-    } = match init(&args) {
+    } = match init(&config) {
         Ok(init) => init,
         Err(e) => {
             log::error!("{}", e);
@@ -23,12 +39,57 @@ fn main() {
         }
     };
 
-    let transmitters = Arc::new(transmitters);
+    let timeout = config.request_timeout;
+    let event_chunk_size = config.event_chunk_size;
+    let libraries = Arc::new(libraries);
+    let store = Arc::new(store);
+
+    log::info!(
+        "Launching the websocket server at {}...",
+        &config.websocket_addr
+    );
+    if let Err(e) = websocket::start(
+        config.websocket_addr,
+        libraries.clone(),
+        store.clone(),
+        timeout,
+        transmitters.clone(),
+    ) {
+        log::error!("{}", e);
+        exit(1);
+    }
 
-    log::info!("Launching the server at {}...", &args.server_addr);
-    rouille::start_server(&args.server_addr, move |request| {
+    log::info!("Launching the server at {}...", &config.server_addr);
+    rouille::start_server(config.server_addr, move |request| {
         let transmitters = transmitters.clone();
 
-        routes(&request, &libraries, transmitters)
+        routes(
+            &request,
+            &groups,
+            &libraries,
+            &rules,
+            &samplers,
+            &sequences,
+            &store,
+            timeout,
+            event_chunk_size,
+            transmitters,
+        )
     });
 }
+
+/// Initializes the logger.
+///
+/// The `RUST_LOG` environment variable takes precedence over the log level from the
+/// configuration.
+///
+/// # Arguments
+///
+/// * `level` - The log level from the daemon's configuration, if any
+fn init_logger(level: Option<LevelFilter>) {
+    let mut builder = env_logger::Builder::from_default_env();
+    if let (None, Some(level)) = (env::var_os("RUST_LOG"), level) {
+        builder.filter_level(level);
+    }
+    builder.init();
+}
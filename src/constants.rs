@@ -1,14 +1,81 @@
 //! Constant values that affect the operation of the daemon.
 use std::time::Duration;
 
+/// The maximum length of a buffer that holds the C-string representing an action or argument name.
+pub const ACTION_NAME_BUFFER_LENGTH: usize = 512;
+
+/// The maximum length of a buffer that holds the C-strings representing an attribute's choices.
+pub const ATTRIBUTE_CHOICES_BUFFER_LENGTH: usize = 4096;
+
 /// The maximum length of a buffer that holds the C-string representing an attribute name.
 pub const ATTRIBUTE_NAME_BUFFER_LENGTH: usize = 512;
 
+/// The address that the daemon uses to listen for requests if none is provided.
+pub const DEFAULT_SERVER_ADDRESS: &str = "0.0.0.0:8000";
+
+/// The address that the daemon uses to listen for websocket connections if none is provided.
+pub const DEFAULT_WEBSOCKET_ADDRESS: &str = "0.0.0.0:8001";
+
+/// The number of past events of each peripheral that are kept for subscribers that resume.
+pub const EVENT_HISTORY_LENGTH: usize = 100;
+
+/// The number of low bits of an event ID that count the events of a peripheral. The remaining bits
+/// hold the time at which the peripheral's event log was created.
+pub const EVENT_ID_COUNTER_BITS: u32 = 20;
+
+/// The size of the chunks into which the HTTP server divides a streamed response body. Event streams
+/// are padded to chunk boundaries so that each message is sent immediately unless a smaller chunk
+/// size is configured.
+pub const EVENT_STREAM_CHUNK_SIZE: usize = 8192;
+
+/// The amount of time after which an idle event stream sends a comment to keep its connection open.
+pub const EVENT_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(15);
+
 /// The directory (relative to the user's HOME) that KPAL uses to store configuration files.
 pub const KPAL_DIR: &str = ".kpal";
 
 /// The directory (relative to the KPAL_DIR) that KPAL searches for plugin library files.
 pub const LIBRARY_DIR: &str = "libraries";
 
-/// The maximum amount of time that a request will wait before timing out in error.
+/// The name of the binary that hosts plugins in their own processes.
+///
+/// It is expected to be in the same directory as the daemon's binary.
+pub const PLUGIN_HOST_BIN: &str = "kpal-plugin-host";
+
+/// The amount of time that a plugin host process is given to reply to a call before it is killed.
+pub const PLUGIN_HOST_CALL_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The amount of time that a plugin host process is given to exit before it is killed.
+pub const PLUGIN_HOST_SHUTDOWN_TIMEOUT: Duration = Duration::from_secs(5);
+
+/// The directory (relative to the KPAL_DIR) in which KPAL records the samples of attributes.
+pub const SAMPLES_DIR: &str = "samples";
+
+/// The file (relative to the KPAL_DIR) in which KPAL stores the configuration of peripherals.
+pub const STORE_FILE: &str = "peripherals.json";
+
+/// The file (relative to the KPAL_DIR) in which KPAL stores the automation rules.
+pub const RULES_FILE: &str = "rules.json";
+
+/// The shortest amount of time between two successive readings of an attribute by a rule.
+pub const RULE_MIN_POLL_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The amount of time that a rule waits before it tries again to subscribe to the events of a
+/// peripheral that is not available.
+pub const RULE_RETRY_INTERVAL: Duration = Duration::from_secs(1);
+
+/// The longest amount of time that a rule waits for an event before it checks whether it was
+/// removed.
+pub const RULE_WAKE_INTERVAL: Duration = Duration::from_millis(50);
+
+/// The number of past samples of each sampled attribute that are kept in memory.
+pub const SAMPLE_HISTORY_LENGTH: usize = 10_000;
+
+/// The shortest amount of time between two successive samples of an attribute.
+pub const SAMPLER_MIN_INTERVAL: Duration = Duration::from_millis(10);
+
+/// The number of values read by each sequence that are kept in memory.
+pub const SEQUENCE_READINGS_LENGTH: usize = 10_000;
+
+/// The default maximum amount of time that a request will wait before timing out in error.
 pub const REQUEST_TIMEOUT: Duration = Duration::from_millis(5000);
@@ -0,0 +1,32 @@
+use std::{boxed::Box, error::Error, fmt, sync::PoisonError};
+
+/// An error that is raised while managing the groups of peripherals.
+#[derive(Debug)]
+pub struct GroupError {
+    side: Option<Box<dyn Error + 'static>>,
+}
+
+impl GroupError {
+    pub fn new(error: Option<Box<dyn Error + 'static>>) -> GroupError {
+        GroupError { side: error }
+    }
+}
+
+impl Error for GroupError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.side.as_ref().map(|e| e.as_ref())
+    }
+}
+
+impl fmt::Display for GroupError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "GroupError {{ Cause: {:?} }}", self.side)
+    }
+}
+
+impl<T> From<PoisonError<T>> for GroupError {
+    fn from(_: PoisonError<T>) -> GroupError {
+        // The PoisonError contains an item with a non-static lifetime.
+        GroupError::new(None)
+    }
+}
@@ -0,0 +1,128 @@
+//! Named groups of peripherals that are operated on together.
+//!
+//! A group is a list of peripheral IDs, such as a bank of identical devices. Reading or setting an
+//! attribute of a group sends one message to every member at once so that the members handle it
+//! concurrently inside their own executor threads. Groups are only kept in memory.
+mod errors;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use log;
+
+pub use errors::GroupError;
+
+/// The Result type returned by the methods of the groups.
+type Result<T> = std::result::Result<T, GroupError>;
+
+/// A named group of peripherals.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Group {
+    /// The ID of the group.
+    pub id: usize,
+
+    /// The name of the group.
+    pub name: String,
+
+    /// The IDs of the peripherals that belong to the group.
+    pub peripheral_ids: BTreeSet<usize>,
+}
+
+/// The collection of all groups, indexed by their IDs.
+#[derive(Default)]
+pub struct Groups {
+    /// The ID of the next group that is created.
+    next_id: AtomicUsize,
+
+    /// The groups that currently exist.
+    groups: Mutex<BTreeMap<usize, Group>>,
+}
+
+impl Groups {
+    /// Creates a new group and returns its ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the group
+    /// * `peripheral_ids` - The IDs of the peripherals that belong to the group
+    pub fn insert(&self, name: String, peripheral_ids: BTreeSet<usize>) -> Result<usize> {
+        let mut groups = self.groups.lock()?;
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+
+        groups.insert(
+            id,
+            Group {
+                id,
+                name,
+                peripheral_ids,
+            },
+        );
+
+        log::info!("Created group {}", id);
+        Ok(id)
+    }
+
+    /// Returns a group, or None if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the group
+    pub fn group(&self, id: usize) -> Result<Option<Group>> {
+        Ok(self.groups.lock()?.get(&id).cloned())
+    }
+
+    /// Returns all the groups, ordered by ID.
+    pub fn groups(&self) -> Result<Vec<Group>> {
+        Ok(self.groups.lock()?.values().cloned().collect())
+    }
+
+    /// Removes a group. Its peripherals are not affected.
+    ///
+    /// Returns false if the group does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the group
+    pub fn remove(&self, id: usize) -> Result<bool> {
+        let removed = self.groups.lock()?.remove(&id).is_some();
+        if removed {
+            log::info!("Removed group {}", id);
+        }
+
+        Ok(removed)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_insert_and_remove_groups() {
+        let groups = Groups::default();
+        let ids: BTreeSet<usize> = vec![3, 1, 2].into_iter().collect();
+
+        assert_eq!(0, groups.insert("foo".to_string(), ids.clone()).unwrap());
+        assert_eq!(
+            1,
+            groups.insert("bar".to_string(), BTreeSet::new()).unwrap()
+        );
+
+        let group = groups.group(0).unwrap().unwrap();
+        assert_eq!("foo", group.name);
+        assert_eq!(
+            vec![1, 2, 3],
+            group.peripheral_ids.into_iter().collect::<Vec<_>>()
+        );
+
+        assert!(groups.remove(0).unwrap());
+        assert!(!groups.remove(0).unwrap());
+        assert!(groups.group(0).unwrap().is_none());
+        assert_eq!(1, groups.groups().unwrap().len());
+    }
+}
@@ -0,0 +1,33 @@
+use std::{boxed::Box, error::Error, fmt};
+
+/// An error that is raised when the daemon's configuration is invalid.
+#[derive(Debug)]
+pub struct ConfigError {
+    /// A message that describes the problem with the configuration.
+    message: String,
+
+    /// The lower-level error that was raised, if any.
+    side: Option<Box<dyn Error + 'static>>,
+}
+
+impl ConfigError {
+    pub fn new(message: String, side: Option<Box<dyn Error + 'static>>) -> ConfigError {
+        ConfigError { message, side }
+    }
+}
+
+impl Error for ConfigError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.side.as_ref().map(|e| e.as_ref())
+    }
+}
+
+impl fmt::Display for ConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "ConfigError {{ Message: {}, Cause: {:?} }}",
+            self.message, self.side
+        )
+    }
+}
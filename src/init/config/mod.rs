@@ -0,0 +1,431 @@
+//! The daemon's configuration.
+//!
+//! The configuration is assembled from the command line arguments and an optional configuration
+//! file in either TOML or JSON format. Arguments that are passed on the command line take
+//! precedence over the values in the file. The configuration is validated in full before the
+//! daemon starts so that mistakes are reported immediately.
+//!
+//! An example configuration file in TOML format:
+//!
+//! ```toml
+//! server_address = "0.0.0.0:8000"
+//! websocket_address = "0.0.0.0:8001"
+//! library_dirs = ["/home/pi/.kpal/libraries", "/opt/kpal/libraries"]
+//! log_level = "info"
+//! request_timeout_ms = 5000
+//! event_chunk_size = 8192
+//!
+//! [[peripherals]]
+//! library_name = "libbasic-plugin.so"
+//! name = "foo"
+//! attributes = [{ id = 0, type = "double", value = 1.0 }]
+//! isolated = true
+//! ```
+mod errors;
+
+use std::{
+    collections::BTreeSet,
+    convert::TryFrom,
+    ffi::OsStr,
+    fs,
+    net::SocketAddr,
+    path::{Path, PathBuf},
+    str::FromStr,
+    time::Duration,
+};
+
+use log::LevelFilter;
+use serde::Deserialize;
+
+use crate::{
+    constants::{
+        DEFAULT_SERVER_ADDRESS, DEFAULT_WEBSOCKET_ADDRESS, EVENT_STREAM_CHUNK_SIZE, REQUEST_TIMEOUT,
+    },
+    models::AttributeBuilder,
+};
+
+use super::{store::AttributeRecord, Cli, DEFAULT_LIBRARY_DIR};
+
+pub use errors::ConfigError;
+
+/// The daemon's configuration after it has been assembled from all its sources and validated.
+#[derive(Debug)]
+pub struct Config {
+    /// The size in bytes to which each message of an event stream is padded.
+    pub event_chunk_size: usize,
+
+    /// The directories to search for plugin libraries.
+    pub library_dirs: Vec<PathBuf>,
+
+    /// The log level to use if none is set in the environment.
+    pub log_level: Option<LevelFilter>,
+
+    /// Whether to skip recreating the peripherals from the store at startup.
+    pub no_restore: bool,
+
+    /// The peripherals to create when the daemon starts.
+    pub peripherals: Vec<PeripheralConfig>,
+
+    /// The maximum amount of time that a request will wait for a response from a peripheral.
+    pub request_timeout: Duration,
+
+    /// The file in which the automation rules are stored.
+    pub rules_file: PathBuf,
+
+    /// The directory in which the samples of attributes are recorded.
+    pub samples_dir: PathBuf,
+
+    /// The address that the daemon will use to listen for requests.
+    pub server_addr: SocketAddr,
+
+    /// The file in which the configuration of peripherals is stored.
+    pub store_file: PathBuf,
+
+    /// The address that the daemon will use to listen for websocket connections.
+    pub websocket_addr: SocketAddr,
+}
+
+impl Config {
+    /// Assembles and validates the configuration from the command line arguments.
+    ///
+    /// # Arguments
+    ///
+    /// * `args` - The command line arguments that were passed to the daemon at startup.
+    pub fn new(args: &Cli) -> Result<Config, ConfigError> {
+        let file = match &args.config {
+            Some(path) => ConfigFile::read(path)?,
+            None => ConfigFile::default(),
+        };
+
+        let server_addr = match args.server_addr.or(file.server_address) {
+            Some(addr) => addr,
+            None => DEFAULT_SERVER_ADDRESS
+                .parse()
+                .expect("Could not parse the default server address"),
+        };
+
+        let websocket_addr = match args.websocket_addr.or(file.websocket_address) {
+            Some(addr) => addr,
+            None => DEFAULT_WEBSOCKET_ADDRESS
+                .parse()
+                .expect("Could not parse the default websocket address"),
+        };
+        if websocket_addr == server_addr {
+            return Err(ConfigError::new(
+                "The server and websocket addresses must be different".to_string(),
+                None,
+            ));
+        }
+
+        let library_dirs = match &args.library_dir {
+            Some(dir) => vec![dir.clone()],
+            None => file
+                .library_dirs
+                .unwrap_or_else(|| vec![PathBuf::from(&*DEFAULT_LIBRARY_DIR)]),
+        };
+        if library_dirs.is_empty() {
+            return Err(ConfigError::new(
+                "At least one library directory is required".to_string(),
+                None,
+            ));
+        }
+        for dir in &library_dirs {
+            if !dir.is_dir() {
+                return Err(ConfigError::new(
+                    format!("Library directory does not exist: {:?}", dir),
+                    None,
+                ));
+            }
+        }
+
+        let log_level = match file.log_level {
+            Some(level) => Some(LevelFilter::from_str(&level).map_err(|e| {
+                ConfigError::new(format!("Invalid log level: {}", level), Some(Box::new(e)))
+            })?),
+            None => None,
+        };
+
+        let request_timeout = match file.request_timeout_ms {
+            Some(0) => {
+                return Err(ConfigError::new(
+                    "The request timeout must be greater than zero".to_string(),
+                    None,
+                ))
+            }
+            Some(ms) => Duration::from_millis(ms),
+            None => REQUEST_TIMEOUT,
+        };
+
+        // Messages are only sent once they fill a chunk of the HTTP server, so a padding size that
+        // does not divide the server's chunk size would hold messages back indefinitely.
+        let event_chunk_size = file.event_chunk_size.unwrap_or(EVENT_STREAM_CHUNK_SIZE);
+        if event_chunk_size == 0 || !EVENT_STREAM_CHUNK_SIZE.is_multiple_of(event_chunk_size) {
+            return Err(ConfigError::new(
+                format!(
+                    "The event chunk size must divide {} bytes",
+                    EVENT_STREAM_CHUNK_SIZE
+                ),
+                None,
+            ));
+        }
+
+        let mut names = BTreeSet::new();
+        for periph in &file.peripherals {
+            if !names.insert(&periph.name) {
+                return Err(ConfigError::new(
+                    format!("Peripheral name {} is used more than once", periph.name),
+                    None,
+                ));
+            }
+            for attr in &periph.attributes {
+                AttributeBuilder::try_from(attr).map_err(|e| {
+                    ConfigError::new(
+                        format!(
+                            "Invalid value for attribute {} of peripheral {}",
+                            attr.id, periph.name
+                        ),
+                        Some(Box::new(e)),
+                    )
+                })?;
+            }
+        }
+
+        Ok(Config {
+            event_chunk_size,
+            library_dirs,
+            log_level,
+            no_restore: args.no_restore,
+            peripherals: file.peripherals,
+            request_timeout,
+            rules_file: args.rules_file.clone(),
+            samples_dir: args.samples_dir.clone(),
+            server_addr,
+            store_file: args.store_file.clone(),
+            websocket_addr,
+        })
+    }
+}
+
+/// A peripheral that is created when the daemon starts.
+#[derive(Clone, Debug, Deserialize, PartialEq)]
+#[serde(deny_unknown_fields)]
+pub struct PeripheralConfig {
+    /// The initial values of the peripheral's attributes.
+    #[serde(default)]
+    pub attributes: Vec<AttributeRecord>,
+
+    /// Whether the plugin of the peripheral runs in its own process.
+    #[serde(default)]
+    pub isolated: bool,
+
+    /// The name of the plugin library that controls the peripheral.
+    pub library_name: String,
+
+    /// The name of the peripheral.
+    pub name: String,
+}
+
+/// The contents of a configuration file.
+#[derive(Debug, Default, Deserialize)]
+#[serde(deny_unknown_fields)]
+struct ConfigFile {
+    event_chunk_size: Option<usize>,
+    library_dirs: Option<Vec<PathBuf>>,
+    log_level: Option<String>,
+    #[serde(default)]
+    peripherals: Vec<PeripheralConfig>,
+    request_timeout_ms: Option<u64>,
+    server_address: Option<SocketAddr>,
+    websocket_address: Option<SocketAddr>,
+}
+
+impl ConfigFile {
+    /// Reads a configuration file.
+    ///
+    /// Files with a `.json` extension are parsed as JSON; all others are parsed as TOML. Relative
+    /// library directories are interpreted relative to the directory that contains the file.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The location of the configuration file
+    fn read(path: &Path) -> Result<ConfigFile, ConfigError> {
+        let contents = fs::read_to_string(path).map_err(|e| {
+            ConfigError::new(
+                format!("Could not read the configuration file {:?}", path),
+                Some(Box::new(e)),
+            )
+        })?;
+
+        let mut file: ConfigFile = if path.extension() == Some(OsStr::new("json")) {
+            serde_json::from_str(&contents).map_err(|e| {
+                ConfigError::new(
+                    format!("Could not parse the configuration file {:?}", path),
+                    Some(Box::new(e)),
+                )
+            })?
+        } else {
+            toml::from_str(&contents).map_err(|e| {
+                ConfigError::new(
+                    format!("Could not parse the configuration file {:?}", path),
+                    Some(Box::new(e)),
+                )
+            })?
+        };
+
+        if let (Some(dirs), Some(parent)) = (&mut file.library_dirs, path.parent()) {
+            for dir in dirs.iter_mut() {
+                if dir.is_relative() {
+                    *dir = parent.join(&dir);
+                }
+            }
+        }
+
+        Ok(file)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::fs::File;
+    use std::io::Write;
+
+    use tempfile::{tempdir, TempDir};
+
+    use crate::init::store::ValueRecord;
+
+    fn args(config: Option<PathBuf>) -> Cli {
+        Cli {
+            config,
+            library_dir: None,
+            no_restore: false,
+            rules_file: PathBuf::from("rules.json"),
+            samples_dir: PathBuf::from("samples"),
+            server_addr: None,
+            store_file: PathBuf::from("peripherals.json"),
+            websocket_addr: None,
+        }
+    }
+
+    fn write_config(dir: &TempDir, name: &str, contents: &str) -> PathBuf {
+        let path = dir.path().join(name);
+        let mut file = File::create(&path).unwrap();
+        file.write_all(contents.as_bytes()).unwrap();
+        path
+    }
+
+    #[test]
+    fn test_config_from_toml() {
+        let dir = tempdir().unwrap();
+        fs::create_dir(dir.path().join("libraries")).unwrap();
+        let path = write_config(
+            &dir,
+            "kpald.toml",
+            r#"
+            server_address = "127.0.0.1:9000"
+            websocket_address = "127.0.0.1:9001"
+            library_dirs = ["libraries"]
+            log_level = "debug"
+            request_timeout_ms = 100
+            event_chunk_size = 1024
+
+            [[peripherals]]
+            library_name = "libbasic-plugin.so"
+            name = "foo"
+            attributes = [{ id = 0, type = "double", value = 1.0 }]
+            isolated = true
+            "#,
+        );
+
+        let config = Config::new(&args(Some(path))).unwrap();
+
+        assert_eq!(config.server_addr, "127.0.0.1:9000".parse().unwrap());
+        assert_eq!(config.websocket_addr, "127.0.0.1:9001".parse().unwrap());
+        assert_eq!(config.library_dirs, vec![dir.path().join("libraries")]);
+        assert_eq!(config.log_level, Some(LevelFilter::Debug));
+        assert_eq!(config.request_timeout, Duration::from_millis(100));
+        assert_eq!(config.event_chunk_size, 1024);
+        assert_eq!(config.peripherals.len(), 1);
+        assert!(config.peripherals[0].isolated);
+        assert_eq!(
+            config.peripherals[0].attributes[0],
+            AttributeRecord {
+                id: 0,
+                value: ValueRecord::Double(1.0),
+            }
+        );
+    }
+
+    #[test]
+    fn test_config_from_json() {
+        let dir = tempdir().unwrap();
+        let path = write_config(
+            &dir,
+            "kpald.json",
+            &format!(r#"{{"library_dirs": [{:?}]}}"#, dir.path()),
+        );
+
+        let config = Config::new(&args(Some(path))).unwrap();
+
+        assert_eq!(config.library_dirs, vec![dir.path().to_owned()]);
+        assert_eq!(config.request_timeout, REQUEST_TIMEOUT);
+        assert_eq!(config.event_chunk_size, EVENT_STREAM_CHUNK_SIZE);
+        assert!(config.peripherals.is_empty());
+    }
+
+    #[test]
+    fn test_cli_overrides_config() {
+        let dir = tempdir().unwrap();
+        let path = write_config(&dir, "kpald.toml", r#"server_address = "127.0.0.1:9000""#);
+        let mut args = args(Some(path));
+        args.library_dir = Some(dir.path().to_owned());
+        args.server_addr = Some("127.0.0.1:9001".parse().unwrap());
+
+        let config = Config::new(&args).unwrap();
+
+        assert_eq!(config.server_addr, "127.0.0.1:9001".parse().unwrap());
+    }
+
+    #[test]
+    fn test_invalid_configs() {
+        let dir = tempdir().unwrap();
+        let cases = vec![
+            r#"library_dirs = ["does-not-exist"]"#,
+            r#"log_level = "loud""#,
+            r#"request_timeout_ms = 0"#,
+            r#"event_chunk_size = 0"#,
+            r#"event_chunk_size = 1000"#,
+            r#"unknown_key = 1"#,
+            r#"
+            server_address = "127.0.0.1:9000"
+            websocket_address = "127.0.0.1:9000"
+            "#,
+            r#"
+            [[peripherals]]
+            library_name = "libbasic-plugin.so"
+            name = "foo"
+            attributes = [{ id = 3, type = "string", value = "a\u0000b" }]
+            "#,
+            r#"
+            [[peripherals]]
+            library_name = "libbasic-plugin.so"
+            name = "foo"
+
+            [[peripherals]]
+            library_name = "libbasic-plugin.so"
+            name = "foo"
+            "#,
+        ];
+
+        for case in cases {
+            let path = write_config(&dir, "kpald.toml", case);
+            let mut args = args(Some(path));
+            if !case.contains("library_dirs") {
+                args.library_dir = Some(dir.path().to_owned());
+            }
+
+            assert!(Config::new(&args).is_err(), "Config was valid: {}", case);
+        }
+    }
+}
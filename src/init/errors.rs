@@ -1,6 +1,15 @@
-use std::{boxed::Box, error::Error, fmt};
+use std::{
+    boxed::Box,
+    error::Error,
+    fmt,
+    sync::{PoisonError, RwLockReadGuard},
+};
 
-use crate::init::libraries::LibraryInitError;
+use crate::{
+    init::{config::ConfigError, libraries::LibraryInitError, store::StoreError, Transmitters},
+    plugins::PluginError,
+    rules::RuleError,
+};
 
 /// Raised when an error occurs during the daemon's initialization.
 #[derive(Debug)]
@@ -26,8 +35,38 @@ impl fmt::Display for InitError {
     }
 }
 
+impl From<ConfigError> for InitError {
+    fn from(error: ConfigError) -> InitError {
+        InitError::new(Some(Box::new(error)))
+    }
+}
+
 impl From<LibraryInitError> for InitError {
     fn from(error: LibraryInitError) -> InitError {
         InitError::new(Some(Box::new(error)))
     }
 }
+
+impl From<RuleError> for InitError {
+    fn from(error: RuleError) -> InitError {
+        InitError::new(Some(Box::new(error)))
+    }
+}
+
+impl From<StoreError> for InitError {
+    fn from(error: StoreError) -> InitError {
+        InitError::new(Some(Box::new(error)))
+    }
+}
+
+impl From<PluginError> for InitError {
+    fn from(error: PluginError) -> InitError {
+        InitError::new(Some(Box::new(error)))
+    }
+}
+
+impl<'a> From<PoisonError<RwLockReadGuard<'a, Transmitters>>> for InitError {
+    fn from(_: PoisonError<RwLockReadGuard<Transmitters>>) -> InitError {
+        InitError::new(None) // The PoisonError contains an item with a non-static lifetime.
+    }
+}
@@ -1,4 +1,6 @@
-use std::{boxed::Box, error::Error, fmt, io};
+use std::{boxed::Box, error::Error, fmt, io, sync::PoisonError};
+
+use libc::c_uint;
 
 use crate::plugins::PluginError;
 
@@ -32,6 +34,12 @@ impl From<io::Error> for LibraryInitError {
     }
 }
 
+impl From<IncompatibleLibraryError> for LibraryInitError {
+    fn from(error: IncompatibleLibraryError) -> LibraryInitError {
+        LibraryInitError::new(Some(Box::new(error)))
+    }
+}
+
 impl From<NoLibrariesFoundError> for LibraryInitError {
     fn from(error: NoLibrariesFoundError) -> LibraryInitError {
         LibraryInitError::new(Some(Box::new(error)))
@@ -44,12 +52,52 @@ impl From<NoLibrariesLoadedError> for LibraryInitError {
     }
 }
 
+impl<T> From<PoisonError<T>> for LibraryInitError {
+    fn from(_: PoisonError<T>) -> LibraryInitError {
+        // The PoisonError contains an item with a non-static lifetime.
+        LibraryInitError::new(None)
+    }
+}
+
 impl From<PluginError> for LibraryInitError {
     fn from(error: PluginError) -> LibraryInitError {
         LibraryInitError::new(Some(Box::new(error)))
     }
 }
 
+/// An error that is raised when a library was built for a different ABI version than the daemon.
+#[derive(Debug)]
+pub struct IncompatibleLibraryError {
+    /// The path to the library file.
+    pub path: String,
+
+    /// The ABI version that the library was built for, if it reports one.
+    pub found: Option<c_uint>,
+
+    /// The ABI version that the daemon requires.
+    pub expected: c_uint,
+}
+
+impl Error for IncompatibleLibraryError {}
+
+impl fmt::Display for IncompatibleLibraryError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self.found {
+            Some(found) => write!(
+                f,
+                "library {} was built for ABI version {}, but the daemon requires version {}",
+                self.path, found, self.expected
+            ),
+            None => write!(
+                f,
+                "library {} does not report its ABI version and was probably built with an older \
+                 version of kpal-plugin; the daemon requires version {}",
+                self.path, self.expected
+            ),
+        }
+    }
+}
+
 /// An error that is raised when no libraries could found.
 #[derive(Debug)]
 pub struct NoLibrariesFoundError {}
@@ -3,18 +3,26 @@ mod errors;
 
 use std::{
     boxed::Box,
-    ffi::OsStr,
+    collections::BTreeMap,
+    ffi::{CStr, OsStr},
     fs::read_dir,
     io,
+    mem::MaybeUninit,
     path::{Path, PathBuf},
-    sync::{Arc, Mutex},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
 };
 
-use libc::c_int;
+use libc::{c_int, c_uint};
 use libloading::{Library as Dll, Symbol};
 use log;
+use semver::Version;
 
-use kpal_plugin::{error_codes::*, KpalLibraryInit, Plugin};
+use kpal_plugin::{
+    error_codes::*, KpalLibraryInfo, KpalLibraryInit, LibraryInfo, Plugin, ABI_VERSION,
+};
 
 use crate::{
     models::Library,
@@ -22,7 +30,7 @@ use crate::{
 };
 
 pub use errors::LibraryInitError;
-use errors::{NoLibrariesFoundError, NoLibrariesLoadedError};
+use errors::{IncompatibleLibraryError, NoLibrariesFoundError, NoLibrariesLoadedError};
 
 /// A thread safe version of a [Library](../models/struct.Library.html) instance.
 ///
@@ -31,24 +39,184 @@ use errors::{NoLibrariesFoundError, NoLibrariesLoadedError};
 /// make function calls from the library in a deterministic order.
 pub type TSLibrary = Arc<Mutex<Library>>;
 
-/// Returns a list of loaded plugin libraries.
+/// The outcome of a request to unload a library.
+#[derive(Debug, PartialEq)]
+pub enum Unload {
+    /// The library was unloaded.
+    Unloaded,
+
+    /// The library was not unloaded because it is still used by a peripheral.
+    InUse,
+
+    /// The library does not exist.
+    NotFound,
+}
+
+/// The collection of plugin libraries that are loaded into memory, indexed by their IDs.
+///
+/// Libraries may be loaded and unloaded while the daemon is running. IDs are never reused so that
+/// peripherals keep referring to the right library.
+pub struct Libraries {
+    /// The directories that are searched for plugin library files.
+    dirs: Vec<PathBuf>,
+
+    /// The libraries that are currently loaded.
+    libraries: RwLock<BTreeMap<usize, TSLibrary>>,
+
+    /// The ID of the next library that is loaded.
+    next_id: AtomicUsize,
+}
+
+impl Libraries {
+    /// Creates a new collection from a list of libraries that are already loaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `dirs` - The directories that are searched for plugin library files
+    /// * `libs` - The libraries that are already loaded, in the order of their IDs
+    pub fn new(dirs: Vec<PathBuf>, libs: Vec<TSLibrary>) -> Libraries {
+        let next_id = libs.len();
+        let libraries = libs.into_iter().enumerate().collect();
+
+        Libraries {
+            dirs,
+            libraries: RwLock::new(libraries),
+            next_id: AtomicUsize::new(next_id),
+        }
+    }
+
+    /// Finds a library by name and returns it together with its ID.
+    ///
+    /// The name is either the name of the library file or the name of the library that is
+    /// optionally followed by a version requirement, e.g. `gpio-cdev@0.3`. If several libraries
+    /// match, then the one with the highest version is returned.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the library to find
+    pub fn find(&self, name: &str) -> Result<Option<(TSLibrary, usize)>, LibraryInitError> {
+        let mut found: Option<(TSLibrary, usize, Option<Version>)> = None;
+        for (id, lib) in self.libraries.read()?.iter() {
+            let guard = match lib.lock() {
+                Ok(guard) => guard,
+                Err(_) => continue,
+            };
+            if !guard.matches(name) {
+                continue;
+            }
+
+            let newer = match &found {
+                Some((_, _, version)) => guard.version() > version.as_ref(),
+                None => true,
+            };
+            if newer {
+                found = Some((lib.clone(), *id, guard.version().cloned()));
+            }
+        }
+
+        Ok(found.map(|(lib, id, _)| (lib, id)))
+    }
+
+    /// Returns a library, or None if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the library
+    pub fn get(&self, id: usize) -> Result<Option<TSLibrary>, LibraryInitError> {
+        Ok(self.libraries.read()?.get(&id).cloned())
+    }
+
+    /// Returns all the libraries, ordered by ID.
+    pub fn libraries(&self) -> Result<Vec<TSLibrary>, LibraryInitError> {
+        Ok(self.libraries.read()?.values().cloned().collect())
+    }
+
+    /// Loads the library files that were added to the library directories since they were last
+    /// searched and returns the new libraries.
+    ///
+    /// Files that are already loaded are skipped. Files that cannot be loaded are logged and
+    /// skipped as well.
+    pub fn rescan(&self) -> Result<Vec<TSLibrary>, LibraryInitError> {
+        let mut libraries = self.libraries.write()?;
+
+        let mut loaded = Vec::new();
+        for lib in libraries.values() {
+            loaded.push(lib.lock()?.path().to_owned());
+        }
+
+        let mut lib_paths = Vec::new();
+        for dir in &self.dirs {
+            if let Some(found) = find_libraries(dir)? {
+                lib_paths.extend(found.into_iter().filter(|path| !loaded.contains(path)));
+            }
+        }
+
+        let first_id = self.next_id.load(Ordering::SeqCst);
+        let new_libs = load_libraries(lib_paths, first_id).unwrap_or_default();
+        self.next_id
+            .store(first_id + new_libs.len(), Ordering::SeqCst);
+
+        for (id, lib) in (first_id..).zip(&new_libs) {
+            log::info!("Loaded library {}", id);
+            libraries.insert(id, lib.clone());
+        }
+
+        Ok(new_libs)
+    }
+
+    /// Unloads a library unless it is still used by a peripheral.
+    ///
+    /// Every peripheral is counted as a user of its library so that the library is not unloaded
+    /// while the functions of the plugin are still in use. Other references to the library, e.g.
+    /// by requests that are being served, do not prevent it from being unloaded.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the library
+    pub fn remove(&self, id: usize) -> Result<Unload, LibraryInitError> {
+        let mut libraries = self.libraries.write()?;
+
+        match libraries.get(&id) {
+            Some(lib) if lib.lock()?.peripherals() > 0 => return Ok(Unload::InUse),
+            Some(_) => (),
+            None => return Ok(Unload::NotFound),
+        };
+
+        libraries.remove(&id);
+        log::info!("Unloaded library {}", id);
+
+        Ok(Unload::Unloaded)
+    }
+}
+
+/// Returns the collection of loaded plugin libraries.
 ///
 /// # Arguments
 ///
-/// * `dir` - A path to a directory to search for plugin library files
-pub fn init(dir: &Path) -> Result<Vec<TSLibrary>, LibraryInitError> {
-    log::info!(
-        "Searching for peripheral library files inside the following directory: {:?}",
-        dir
-    );
-
-    let libraries = find_libraries(&dir)?.ok_or_else(|| {
-        log::error!("Could not find any libraries from {:?}", dir);
-        NoLibrariesFoundError {}
-    })?;
+/// * `dirs` - Paths to the directories to search for plugin library files
+pub fn init(dirs: &[PathBuf]) -> Result<Libraries, LibraryInitError> {
+    let mut libraries = Vec::new();
+    for dir in dirs {
+        log::info!(
+            "Searching for peripheral library files inside the following directory: {:?}",
+            dir
+        );
 
-    load_libraries(libraries)
-        .ok_or_else(|| LibraryInitError::new(Some(Box::new(NoLibrariesLoadedError {}))))
+        match find_libraries(dir)? {
+            Some(mut found) => libraries.append(&mut found),
+            None => log::warn!("Could not find any libraries in {:?}", dir),
+        }
+    }
+
+    if libraries.is_empty() {
+        log::error!("Could not find any libraries from {:?}", dirs);
+        return Err(NoLibrariesFoundError {}.into());
+    }
+
+    let libraries = load_libraries(libraries, 0)
+        .ok_or_else(|| LibraryInitError::new(Some(Box::new(NoLibrariesLoadedError {}))))?;
+
+    Ok(Libraries::new(dirs.to_vec(), libraries))
 }
 
 /// Finds all plugin library files inside a directory.
@@ -85,24 +253,27 @@ fn find_libraries(dir: &Path) -> Result<Option<Vec<PathBuf>>, io::Error> {
 
 /// Loads a list of plugin library files.
 ///
+/// The libraries that are loaded are assigned consecutive IDs.
+///
 /// # Arguments
 ///
 /// * `lib_paths` - A vector of `PathBuf`s pointing to library files to load
-fn load_libraries(lib_paths: Vec<PathBuf>) -> Option<Vec<TSLibrary>> {
+/// * `first_id` - The ID of the first library that is loaded
+fn load_libraries(lib_paths: Vec<PathBuf>, first_id: usize) -> Option<Vec<TSLibrary>> {
     log::debug!("Loading peripherals...");
-    let (mut libraries, mut counter) = (Vec::new(), 0usize);
+    let (mut libraries, mut counter) = (Vec::new(), first_id);
 
-    for lib in lib_paths {
-        let path = lib.to_str().unwrap_or("Unknown library path");
+    for lib_path in lib_paths {
+        let path = lib_path.to_str().unwrap_or("Unknown library path");
 
-        let file_name = lib
+        let file_name = lib_path
             .file_name()
             .unwrap_or_else(|| OsStr::new("Unknown"))
             .to_string_lossy()
             .into_owned();
 
         log::info!("Attempting to load library from file: {}", path);
-        let lib = match Dll::new(&lib) {
+        let lib = match Dll::new(&lib_path) {
             Ok(lib) => {
                 log::info!("Loading of library {} succeeded", path);
                 lib
@@ -113,6 +284,14 @@ fn load_libraries(lib_paths: Vec<PathBuf>) -> Option<Vec<TSLibrary>> {
             }
         };
 
+        let abi_version = match check_abi_version(&lib, path) {
+            Ok(abi_version) => abi_version,
+            Err(e) => {
+                log::error!("Refusing to load library {}: {}", path, e);
+                continue;
+            }
+        };
+
         log::info!("Calling initialization routine for {}", path);
         let result = match init_library(&lib) {
             Ok(result) => result,
@@ -127,7 +306,26 @@ fn load_libraries(lib_paths: Vec<PathBuf>) -> Option<Vec<TSLibrary>> {
             continue;
         }
 
-        let mut new_lib = Library::new(counter, file_name, Some(lib));
+        let (name, version) = match library_info(&lib) {
+            Ok(Some((name, version))) => (name, Some(version)),
+            Ok(None) => {
+                log::warn!("Library {} does not report its name and version", path);
+                (file_name, None)
+            }
+            Err(e) => {
+                log::error!("Failed to read the name and version of {}: {}", path, e);
+                continue;
+            }
+        };
+
+        let mut new_lib = Library::new(
+            counter,
+            name,
+            version,
+            abi_version,
+            lib_path.clone(),
+            Some(lib),
+        );
         if init_library_attributes(&mut new_lib).is_err() {
             log::error!("Failed to initialize library attributes: {:?}", new_lib);
             continue;
@@ -145,6 +343,34 @@ fn load_libraries(lib_paths: Vec<PathBuf>) -> Option<Vec<TSLibrary>> {
     }
 }
 
+/// Returns the ABI version of a library if it is the one that the daemon requires.
+///
+/// This is checked before any function of the library is called because the layout of the types
+/// that are passed between the daemon and a library built for a different version is unknown.
+///
+/// # Arguments
+///
+/// * `lib` - The library to check
+/// * `path` - The path to the library file
+fn check_abi_version(lib: &Dll, path: &str) -> Result<c_uint, LibraryInitError> {
+    let found = unsafe {
+        lib.get::<*const c_uint>(b"KPAL_ABI_VERSION\0")
+            .ok()
+            .filter(|version| !version.is_null())
+            .map(|version| **version)
+    };
+
+    match found {
+        Some(found) if found == ABI_VERSION => Ok(found),
+        _ => Err(IncompatibleLibraryError {
+            path: path.to_string(),
+            found,
+            expected: ABI_VERSION,
+        }
+        .into()),
+    }
+}
+
 /// Calls the initialization callback function of the library.
 ///
 /// The integer return code of the callback is returned in the Ok variant of the result.
@@ -159,6 +385,44 @@ fn init_library(lib: &Dll) -> Result<c_int, io::Error> {
     }
 }
 
+/// Returns the name and version that are reported by a library.
+///
+/// `None` is returned if the library does not report them, e.g. because it was built with an older
+/// version of `kpal-plugin`.
+///
+/// # Arguments
+///
+/// * `lib` - The library whose name and version are returned
+fn library_info(lib: &Dll) -> Result<Option<(String, Version)>, String> {
+    let info = unsafe {
+        let library_info: Symbol<KpalLibraryInfo> = match lib.get(b"kpal_library_info\0") {
+            Ok(library_info) => library_info,
+            Err(_) => return Ok(None),
+        };
+
+        let mut info = MaybeUninit::<LibraryInfo>::uninit();
+        let result = library_info(info.as_mut_ptr());
+        if result != PLUGIN_OK {
+            return Err(format!("received error code {}", result));
+        }
+
+        let info = info.assume_init();
+        if info.name.is_null() || info.version.is_null() {
+            return Err("received a null pointer".to_string());
+        }
+        (
+            CStr::from_ptr(info.name).to_string_lossy().into_owned(),
+            CStr::from_ptr(info.version).to_string_lossy().into_owned(),
+        )
+    };
+
+    let (name, version) = info;
+    let version =
+        Version::parse(&version).map_err(|e| format!("invalid version {}: {}", version, e))?;
+
+    Ok(Some((name, version)))
+}
+
 fn init_library_attributes(lib: &mut Library) -> Result<(), LibraryInitError> {
     let plugin: Plugin = unsafe { kpal_plugin_new(lib)? };
     let mut executor = Executor::new(plugin);
@@ -167,6 +431,7 @@ fn init_library_attributes(lib: &mut Library) -> Result<(), LibraryInitError> {
         LibraryInitError::new(None)
     })?;
     lib.set_attributes(attrs);
+    lib.set_actions(executor.discover_actions());
 
     Ok(())
 }
@@ -274,7 +539,7 @@ mod tests {
         let mut libs: Vec<PathBuf> = Vec::new();
         libs.push(lib);
 
-        assert!(load_libraries(libs).is_some());
+        assert!(load_libraries(libs, 0).is_some());
     }
 
     /// load_libraries does not return library files that do not exist.
@@ -288,6 +553,6 @@ mod tests {
         let mut libs: Vec<PathBuf> = Vec::new();
         libs.push(lib);
 
-        assert!(load_libraries(libs).is_none());
+        assert!(load_libraries(libs, 0).is_none());
     }
 }
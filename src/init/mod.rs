@@ -1,18 +1,35 @@
 //! Routines for initializing the daemon.
+mod config;
 mod errors;
 mod libraries;
+mod store;
 mod transmitters;
 
-use std::{net::SocketAddr, path::PathBuf, sync::RwLock};
+use std::{
+    collections::BTreeSet,
+    net::SocketAddr,
+    path::PathBuf,
+    sync::{Arc, RwLock},
+};
 
 use dirs::home_dir;
 use lazy_static::lazy_static;
+use log;
 use structopt::StructOpt;
 
-use crate::constants::{KPAL_DIR, LIBRARY_DIR};
+use crate::{
+    constants::{KPAL_DIR, LIBRARY_DIR, RULES_FILE, SAMPLES_DIR, STORE_FILE},
+    groups::Groups,
+    plugins::init as init_plugin,
+    rules::Rules,
+    sampler::Samplers,
+    sequences::Sequences,
+};
 
+pub use config::{Config, ConfigError, PeripheralConfig};
 pub use errors::InitError;
-pub use libraries::TSLibrary;
+pub use libraries::{Libraries, LibraryInitError, TSLibrary, Unload};
+pub use store::{PeripheralRecord, Store, StoreError, ValueRecord};
 pub use transmitters::Transmitters;
 
 lazy_static! {
@@ -23,6 +40,27 @@ lazy_static! {
         default_dir.push(LIBRARY_DIR);
         default_dir.to_string_lossy().to_string()
     };
+    static ref DEFAULT_RULES_FILE: String = {
+        let mut default_file = PathBuf::new();
+        default_file.push(home_dir().expect("Could not determine user's home directory"));
+        default_file.push(KPAL_DIR);
+        default_file.push(RULES_FILE);
+        default_file.to_string_lossy().to_string()
+    };
+    static ref DEFAULT_SAMPLES_DIR: String = {
+        let mut default_dir = PathBuf::new();
+        default_dir.push(home_dir().expect("Could not determine user's home directory"));
+        default_dir.push(KPAL_DIR);
+        default_dir.push(SAMPLES_DIR);
+        default_dir.to_string_lossy().to_string()
+    };
+    static ref DEFAULT_STORE_FILE: String = {
+        let mut default_file = PathBuf::new();
+        default_file.push(home_dir().expect("Could not determine user's home directory"));
+        default_file.push(KPAL_DIR);
+        default_file.push(STORE_FILE);
+        default_file.to_string_lossy().to_string()
+    };
 }
 
 /// The set of command line arguments for the daemon.
@@ -32,47 +70,248 @@ lazy_static! {
     about = "An extensible control system for physical computing"
 )]
 pub struct Cli {
-    /// The address that the daemon will use to listen for requests.
-    #[structopt(short = "s", long = "server-address", default_value = "0.0.0.0:8000")]
-    pub server_addr: SocketAddr,
+    /// A TOML or JSON file that configures the daemon. Command line arguments take precedence over
+    /// the values in this file.
+    #[structopt(short = "c", long = "config", parse(from_os_str))]
+    pub config: Option<PathBuf>,
+
+    /// The address that the daemon will use to listen for requests. [default: 0.0.0.0:8000]
+    #[structopt(short = "s", long = "server-address")]
+    pub server_addr: Option<SocketAddr>,
+
+    /// The directory to search for plugin libraries. [default: ~/.kpal/libraries]
+    #[structopt(short = "l", long = "library-dir", parse(from_os_str))]
+    pub library_dir: Option<PathBuf>,
+
+    /// The file in which the automation rules are stored.
+    #[structopt(
+        long = "rules-file",
+        default_value = &DEFAULT_RULES_FILE,
+        parse(from_os_str)
+    )]
+    pub rules_file: PathBuf,
 
-    /// The directory to search for plugin libraries.
+    /// The directory in which the samples of attributes are recorded.
     #[structopt(
-        short = "l",
-        long = "library-dir",
-        default_value = &DEFAULT_LIBRARY_DIR,
+        long = "samples-dir",
+        default_value = &DEFAULT_SAMPLES_DIR,
         parse(from_os_str)
     )]
-    pub library_dir: PathBuf,
+    pub samples_dir: PathBuf,
+
+    /// The file in which the configuration of peripherals is stored.
+    #[structopt(
+        long = "store-file",
+        default_value = &DEFAULT_STORE_FILE,
+        parse(from_os_str)
+    )]
+    pub store_file: PathBuf,
+
+    /// The address that the daemon will use to listen for websocket connections. [default:
+    /// 0.0.0.0:8001]
+    #[structopt(short = "w", long = "websocket-address")]
+    pub websocket_addr: Option<SocketAddr>,
+
+    /// Do not recreate the peripherals from the store at startup. The stored configuration is
+    /// kept, and the peripherals are recreated the next time that the daemon starts without this
+    /// flag.
+    #[structopt(long = "no-restore")]
+    pub no_restore: bool,
 }
 
 /// The datatypes generated by the initialization steps.
 pub struct Init {
-    /// The set of plugin libraries found during the initialization process.
-    pub libraries: Vec<TSLibrary>,
+    /// The named groups of peripherals.
+    pub groups: Groups,
+
+    /// The collection of plugin libraries that are loaded into memory.
+    pub libraries: Libraries,
+
+    /// The automation rules that set attributes in response to the values of others.
+    pub rules: Rules,
+
+    /// The collection of samplers that periodically read the values of attributes.
+    pub samplers: Samplers,
+
+    /// The collection of sequences that are running or that have finished.
+    pub sequences: Sequences,
+
+    /// The store that records the configuration of peripherals.
+    pub store: Store,
 
     /// The set of transmitters that are used to send messages into executor threads.
-    pub transmitters: RwLock<transmitters::Transmitters>,
+    pub transmitters: Arc<RwLock<transmitters::Transmitters>>,
 }
 
 /// Initializes the daemon.
 ///
 /// This method returns the data structures that are required by the daemon to operate, including a
-/// vector of thread-safe libraries that have been loaded into memory and a collection of
-/// transmitters for communicating with peripherals.
+/// collection of the libraries that have been loaded into memory and a collection of
+/// transmitters for communicating with peripherals. The peripherals listed in the configuration
+/// are created and, unless disabled, the peripherals recorded in the store are recreated. The
+/// automation rules are started once all the peripherals exist.
 ///
 /// # Arguments
 ///
-/// * `args` - The command line arguments that were passed to the daemon at startup.
-pub fn init(args: &Cli) -> Result<Init> {
-    let libraries = libraries::init(&args.library_dir)?;
-    let transmitters = RwLock::new(transmitters::init());
+/// * `config` - The daemon's configuration.
+pub fn init(config: &Config) -> Result<Init> {
+    let libraries = libraries::init(&config.library_dirs)?;
+    let transmitters = Arc::new(RwLock::new(transmitters::init()));
+
+    let store = Store::open(&config.store_file)?;
+
+    create_peripherals(
+        &config.peripherals,
+        &libraries,
+        &store,
+        transmitters.clone(),
+    )?;
+    if !config.no_restore {
+        restore(&store, &libraries, transmitters.clone())?;
+    }
+
+    let rules = Rules::open(
+        &config.rules_file,
+        config.request_timeout,
+        transmitters.clone(),
+    )?;
 
     Ok(Init {
+        groups: Groups::default(),
         libraries,
+        rules,
+        samplers: Samplers::new(&config.samples_dir),
+        sequences: Sequences::default(),
+        store,
         transmitters,
     })
 }
 
+/// Creates the peripherals that are listed in the daemon's configuration.
+///
+/// The ID of each peripheral is recorded in the store so that it does not change when the daemon
+/// restarts. A peripheral that is new to the configuration is assigned an ID that is larger than
+/// those of all stored records. Any failure is an error so that a misconfigured daemon does not
+/// start, including a configured peripheral whose name belongs to a stored peripheral.
+///
+/// # Arguments
+///
+/// * `periphs` - The peripherals listed in the configuration
+/// * `libs` - The set of plugin libraries found during the initialization process
+/// * `store` - The store that records the configuration of peripherals
+/// * `txs` - The set of transmitters that are used to send messages into executor threads
+fn create_peripherals(
+    periphs: &[PeripheralConfig],
+    libs: &Libraries,
+    store: &Store,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<()> {
+    let records = store.records()?;
+
+    // Peripherals that were removed from the configuration no longer reserve their IDs.
+    for record in &records {
+        if record.configured && !periphs.iter().any(|p| p.name == record.name) {
+            store.remove(record.id)?;
+        }
+    }
+
+    for periph in periphs {
+        let id = match records.iter().find(|r| r.name == periph.name) {
+            Some(record) if record.configured => record.id,
+            Some(record) => {
+                return Err(ConfigError::new(
+                    format!(
+                        "Peripheral {} has the same name as stored peripheral {}",
+                        periph.name, record.id
+                    ),
+                    None,
+                )
+                .into())
+            }
+            None => store.next_id(),
+        };
+
+        let (lib, library_id) = libs.find(&periph.library_name)?.ok_or_else(|| {
+            ConfigError::new(
+                format!(
+                    "Library {} of peripheral {} not found",
+                    periph.library_name, periph.name
+                ),
+                None,
+            )
+        })?;
+
+        let record = PeripheralRecord {
+            attributes: periph.attributes.clone(),
+            configured: true,
+            id,
+            isolated: periph.isolated,
+            library_name: periph.library_name.clone(),
+            name: periph.name.clone(),
+        };
+        let builder = record.to_builder(library_id)?;
+
+        init_plugin(builder, lib, txs.clone())?;
+        store.insert(record)?;
+        log::info!("Created peripheral {} from the configuration", id);
+    }
+
+    Ok(())
+}
+
+/// Recreates the peripherals that are recorded in the store, except for those of the configuration.
+///
+/// Peripherals that cannot be recreated, e.g. because their library is no longer available, are
+/// skipped so that they do not prevent the daemon from starting. Their records are kept and their
+/// IDs are not given to new peripherals, so they are restored once the problem is fixed. A record whose ID or name is
+/// already in use is an error because the rules refer to peripherals by their IDs.
+///
+/// # Arguments
+///
+/// * `store` - The store that records the configuration of peripherals
+/// * `libs` - The set of plugin libraries found during the initialization process
+/// * `txs` - The set of transmitters that are used to send messages into executor threads
+fn restore(store: &Store, libs: &Libraries, txs: Arc<RwLock<Transmitters>>) -> Result<()> {
+    let records = store.records()?;
+    let mut names: BTreeSet<&str> = records
+        .iter()
+        .filter(|r| r.configured)
+        .map(|r| r.name.as_str())
+        .collect();
+
+    for record in records.iter().filter(|r| !r.configured) {
+        if txs.read()?.contains_key(&record.id) || !names.insert(&record.name) {
+            return Err(ConfigError::new(
+                format!(
+                    "Could not restore peripheral {}: its ID or its name {} is already in use",
+                    record.id, record.name
+                ),
+                None,
+            )
+            .into());
+        }
+
+        let (lib, library_id) = match libs.find(&record.library_name)? {
+            Some(found) => found,
+            None => {
+                log::error!(
+                    "Could not restore peripheral {}: library {} not found",
+                    record.id,
+                    record.library_name
+                );
+                continue;
+            }
+        };
+        let builder = record.to_builder(library_id)?;
+
+        match init_plugin(builder, lib, txs.clone()) {
+            Ok(_) => log::info!("Restored peripheral {}", record.id),
+            Err(e) => log::error!("Could not restore peripheral {}: {}", record.id, e),
+        }
+    }
+
+    Ok(())
+}
+
 /// The Result type that is returned by the Init module.
 pub type Result<T> = std::result::Result<T, InitError>;
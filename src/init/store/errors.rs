@@ -0,0 +1,72 @@
+use std::{
+    boxed::Box,
+    error::Error,
+    ffi::NulError,
+    fmt, io,
+    str::Utf8Error,
+    sync::{MutexGuard, PoisonError},
+};
+
+use base64::DecodeError;
+
+use super::Records;
+
+/// An error that is raised while reading or writing the peripheral store.
+#[derive(Debug)]
+pub struct StoreError {
+    side: Option<Box<dyn Error + 'static>>,
+}
+
+impl StoreError {
+    pub fn new(error: Option<Box<dyn Error + 'static>>) -> StoreError {
+        StoreError { side: error }
+    }
+}
+
+impl Error for StoreError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.side.as_ref().map(|e| e.as_ref())
+    }
+}
+
+impl fmt::Display for StoreError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "StoreError {{ Cause: {:?} }}", self.side)
+    }
+}
+
+impl From<DecodeError> for StoreError {
+    fn from(error: DecodeError) -> StoreError {
+        StoreError::new(Some(Box::new(error)))
+    }
+}
+
+impl From<io::Error> for StoreError {
+    fn from(error: io::Error) -> StoreError {
+        StoreError::new(Some(Box::new(error)))
+    }
+}
+
+impl From<NulError> for StoreError {
+    fn from(error: NulError) -> StoreError {
+        StoreError::new(Some(Box::new(error)))
+    }
+}
+
+impl From<serde_json::Error> for StoreError {
+    fn from(error: serde_json::Error) -> StoreError {
+        StoreError::new(Some(Box::new(error)))
+    }
+}
+
+impl From<Utf8Error> for StoreError {
+    fn from(error: Utf8Error) -> StoreError {
+        StoreError::new(Some(Box::new(error)))
+    }
+}
+
+impl<'a> From<PoisonError<MutexGuard<'a, Records>>> for StoreError {
+    fn from(_: PoisonError<MutexGuard<Records>>) -> StoreError {
+        StoreError::new(None) // The PoisonError contains an item with a non-static lifetime.
+    }
+}
@@ -0,0 +1,509 @@
+//! A persistent store for the configuration of the peripherals created by users.
+//!
+//! The store records the information that is needed to recreate each peripheral when the daemon
+//! restarts, i.e. the name of its plugin library, its name, and the values of the attributes that
+//! were set by the user. Records are written to a JSON file every time that they change.
+mod errors;
+
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    ffi::CString,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Mutex,
+    },
+};
+
+use log;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    models::{AttributeBuilder, PeripheralBuilder, Value},
+    util::write_json,
+};
+
+pub use errors::StoreError;
+
+/// The collection of peripheral records, indexed by peripheral ID.
+pub type Records = BTreeMap<usize, PeripheralRecord>;
+
+/// A thread-safe store of peripheral records that is backed by a file.
+#[derive(Debug)]
+pub struct Store {
+    /// The file in which the records are saved.
+    path: PathBuf,
+
+    /// The records currently held in the store.
+    records: Mutex<Records>,
+
+    /// The ID that is given to the next new peripheral.
+    next_id: AtomicUsize,
+}
+
+impl Store {
+    /// Opens the store that is saved in the given file.
+    ///
+    /// An empty store is returned if the file does not yet exist. The file is not created until
+    /// the first record is inserted.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file in which the records are saved
+    pub fn open(path: &Path) -> Result<Store, StoreError> {
+        let records = if path.exists() {
+            let contents = fs::read_to_string(path)?;
+            let records: Vec<PeripheralRecord> = serde_json::from_str(&contents)?;
+            records
+                .into_iter()
+                .map(|record| (record.id, record))
+                .collect()
+        } else {
+            log::debug!("No peripheral store found at {:?}", path);
+            BTreeMap::new()
+        };
+
+        let next_id = records.keys().next_back().map_or(0, |id| id + 1);
+
+        Ok(Store {
+            path: path.to_owned(),
+            records: Mutex::new(records),
+            next_id: AtomicUsize::new(next_id),
+        })
+    }
+
+    /// Inserts a record into the store, replacing any record with the same peripheral ID.
+    ///
+    /// The store is left unchanged if the record cannot be saved.
+    ///
+    /// # Arguments
+    ///
+    /// * `record` - The record to insert
+    pub fn insert(&self, record: PeripheralRecord) -> Result<(), StoreError> {
+        let mut records = self.records.lock()?;
+        let id = record.id;
+        self.next_id.fetch_max(id + 1, Ordering::SeqCst);
+
+        let previous = records.insert(id, record);
+        if let Err(e) = self.save(&records) {
+            match previous {
+                Some(previous) => records.insert(id, previous),
+                None => records.remove(&id),
+            };
+            return Err(e);
+        }
+
+        Ok(())
+    }
+
+    /// Returns a new peripheral ID.
+    ///
+    /// IDs are never given out twice, even after the records of their peripherals are removed, so
+    /// that clients, groups, samplers and rules that still refer to a removed peripheral do not
+    /// reach a new one. When the store is opened, the IDs continue after the largest recorded ID.
+    pub fn next_id(&self) -> usize {
+        self.next_id.fetch_add(1, Ordering::SeqCst)
+    }
+
+    /// Gives back a peripheral ID that was not used by any peripheral.
+    ///
+    /// The ID is only given out again if no other ID was given out since.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The unused ID
+    pub fn release_id(&self, id: usize) {
+        let _ = self
+            .next_id
+            .compare_exchange(id + 1, id, Ordering::SeqCst, Ordering::SeqCst);
+    }
+
+    /// Returns a copy of all records in the store.
+    pub fn records(&self) -> Result<Vec<PeripheralRecord>, StoreError> {
+        let records = self.records.lock()?;
+        Ok(records.values().cloned().collect())
+    }
+
+    /// Removes the record with the given peripheral ID from the store, if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the peripheral whose record will be removed
+    pub fn remove(&self, id: usize) -> Result<(), StoreError> {
+        let mut records = self.records.lock()?;
+        if records.remove(&id).is_some() {
+            self.save(&records)?;
+        }
+        Ok(())
+    }
+
+    /// Sets new values for attributes of the record with the given peripheral ID.
+    ///
+    /// Values for attributes that are not provided are left unchanged.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the peripheral whose record will be updated
+    /// * `attrs` - Builders containing the new attribute values
+    pub fn update_attributes(
+        &self,
+        id: usize,
+        attrs: &[AttributeBuilder],
+    ) -> Result<(), StoreError> {
+        let mut records = self.records.lock()?;
+        if let Some(record) = records.get_mut(&id) {
+            for attr in attrs {
+                let attr = AttributeRecord::try_from(attr)?;
+                record.attributes.retain(|a| a.id != attr.id);
+                record.attributes.push(attr);
+            }
+            record.attributes.sort_by_key(|a| a.id);
+            self.save(&records)?;
+        }
+        Ok(())
+    }
+
+    /// Writes the records to the store's file.
+    fn save(&self, records: &Records) -> Result<(), StoreError> {
+        let records: Vec<&PeripheralRecord> = records.values().collect();
+        write_json(&self.path, &records)?;
+
+        Ok(())
+    }
+}
+
+/// The information required to recreate a peripheral.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct PeripheralRecord {
+    /// The values of the attributes that were set by the user.
+    pub attributes: Vec<AttributeRecord>,
+
+    /// Whether the peripheral is listed in the daemon's configuration.
+    ///
+    /// The record of a configured peripheral only reserves its ID. The peripheral itself is
+    /// recreated from the configuration.
+    #[serde(default)]
+    pub configured: bool,
+
+    /// The ID of the peripheral.
+    pub id: usize,
+
+    /// Whether the plugin of the peripheral runs in its own process.
+    #[serde(default)]
+    pub isolated: bool,
+
+    /// The name of the plugin library that controls the peripheral.
+    ///
+    /// The name is recorded instead of the library ID because library IDs may change when the
+    /// daemon restarts.
+    pub library_name: String,
+
+    /// The name of the peripheral.
+    pub name: String,
+}
+
+impl PeripheralRecord {
+    /// Creates a new record from a peripheral builder.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the peripheral
+    /// * `library_name` - The name of the plugin library that controls the peripheral
+    /// * `builder` - The builder containing the user-set attributes of the peripheral
+    pub fn new(
+        id: usize,
+        library_name: String,
+        builder: &PeripheralBuilder,
+    ) -> Result<PeripheralRecord, StoreError> {
+        let attributes = builder
+            .attribute_builders()
+            .values()
+            .map(AttributeRecord::try_from)
+            .collect::<Result<Vec<AttributeRecord>, StoreError>>()?;
+
+        Ok(PeripheralRecord {
+            attributes,
+            configured: false,
+            id,
+            isolated: builder.isolated(),
+            library_name,
+            name: builder.name().to_owned(),
+        })
+    }
+
+    /// Returns a peripheral builder that recreates the recorded peripheral.
+    ///
+    /// # Arguments
+    ///
+    /// * `library_id` - The current ID of the plugin library that controls the peripheral
+    pub fn to_builder(&self, library_id: usize) -> Result<PeripheralBuilder, StoreError> {
+        let mut builder = PeripheralBuilder::new(library_id, self.name.clone())
+            .set_id(self.id)
+            .set_isolated(self.isolated);
+        for attr in &self.attributes {
+            builder = builder.set_attribute_builder(AttributeBuilder::try_from(attr)?);
+        }
+
+        Ok(builder)
+    }
+}
+
+/// The recorded value of a single attribute.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+pub struct AttributeRecord {
+    pub id: usize,
+
+    #[serde(flatten)]
+    pub value: ValueRecord,
+}
+
+impl TryFrom<&AttributeBuilder> for AttributeRecord {
+    type Error = StoreError;
+
+    fn try_from(builder: &AttributeBuilder) -> Result<AttributeRecord, Self::Error> {
+        Ok(AttributeRecord {
+            id: *builder.id(),
+            value: ValueRecord::try_from(builder.value())?,
+        })
+    }
+}
+
+impl TryFrom<&AttributeRecord> for AttributeBuilder {
+    type Error = StoreError;
+
+    fn try_from(record: &AttributeRecord) -> Result<AttributeBuilder, Self::Error> {
+        Ok(AttributeBuilder::new(
+            record.id,
+            Value::try_from(&record.value)?,
+        ))
+    }
+}
+
+/// The recorded value of an attribute.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", content = "value")]
+pub enum ValueRecord {
+    #[serde(rename(deserialize = "double", serialize = "double"))]
+    Double(f64),
+
+    #[serde(rename(deserialize = "integer", serialize = "integer"))]
+    Int(i32),
+
+    #[serde(rename(deserialize = "string", serialize = "string"))]
+    String(String),
+
+    #[serde(rename(deserialize = "unsigned_integer", serialize = "unsigned_integer"))]
+    Uint(u32),
+
+    #[serde(rename(deserialize = "boolean", serialize = "boolean"))]
+    Bool(bool),
+
+    #[serde(rename(deserialize = "double_array", serialize = "double_array"))]
+    DoubleArray(Vec<f64>),
+
+    #[serde(rename(deserialize = "integer_array", serialize = "integer_array"))]
+    IntArray(Vec<i32>),
+
+    /// Raw bytes are recorded as a base64 string.
+    #[serde(rename(deserialize = "bytes", serialize = "bytes"))]
+    Bytes(String),
+
+    #[serde(rename(deserialize = "integer64", serialize = "integer64"))]
+    Int64(i64),
+
+    #[serde(rename(deserialize = "unsigned_integer64", serialize = "unsigned_integer64"))]
+    Uint64(u64),
+
+    #[serde(rename(deserialize = "float", serialize = "float"))]
+    Float(f32),
+}
+
+impl TryFrom<&Value> for ValueRecord {
+    type Error = StoreError;
+
+    fn try_from(value: &Value) -> Result<ValueRecord, Self::Error> {
+        Ok(match value {
+            Value::Double { value } => ValueRecord::Double(*value),
+            Value::Int { value } => ValueRecord::Int(*value),
+            Value::String { value } => ValueRecord::String(value.to_str()?.to_owned()),
+            Value::Uint { value } => ValueRecord::Uint(*value),
+            Value::Bool { value } => ValueRecord::Bool(*value),
+            Value::DoubleArray { value } => ValueRecord::DoubleArray(value.clone()),
+            Value::IntArray { value } => ValueRecord::IntArray(value.clone()),
+            Value::Bytes { value } => ValueRecord::Bytes(base64::encode(value)),
+            Value::Int64 { value } => ValueRecord::Int64(*value),
+            Value::Uint64 { value } => ValueRecord::Uint64(*value),
+            Value::Float { value } => ValueRecord::Float(*value),
+        })
+    }
+}
+
+impl TryFrom<&ValueRecord> for Value {
+    type Error = StoreError;
+
+    fn try_from(record: &ValueRecord) -> Result<Value, Self::Error> {
+        Ok(match record {
+            ValueRecord::Double(value) => Value::Double { value: *value },
+            ValueRecord::Int(value) => Value::Int { value: *value },
+            ValueRecord::String(value) => Value::String {
+                value: CString::new(value.as_str())?,
+            },
+            ValueRecord::Uint(value) => Value::Uint { value: *value },
+            ValueRecord::Bool(value) => Value::Bool { value: *value },
+            ValueRecord::DoubleArray(value) => Value::DoubleArray {
+                value: value.clone(),
+            },
+            ValueRecord::IntArray(value) => Value::IntArray {
+                value: value.clone(),
+            },
+            ValueRecord::Bytes(value) => Value::Bytes {
+                value: base64::decode(value)?,
+            },
+            ValueRecord::Int64(value) => Value::Int64 { value: *value },
+            ValueRecord::Uint64(value) => Value::Uint64 { value: *value },
+            ValueRecord::Float(value) => Value::Float { value: *value },
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_open_missing_file() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("peripherals.json");
+
+        let store = Store::open(&path).unwrap();
+
+        assert!(store.records().unwrap().is_empty());
+        assert!(!path.exists());
+    }
+
+    #[test]
+    fn test_insert_update_and_remove() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("peripherals.json");
+        let builder = PeripheralBuilder::new(0, "foo".to_string())
+            .set_attribute_builder(AttributeBuilder::new(0, Value::Double { value: 1.0 }));
+
+        let store = Store::open(&path).unwrap();
+        let record = PeripheralRecord::new(3, "libfoo.so".to_string(), &builder).unwrap();
+        store.insert(record.clone()).unwrap();
+        assert_eq!(Store::open(&path).unwrap().records().unwrap(), vec![record]);
+
+        let attrs = vec![
+            AttributeBuilder::new(0, Value::Double { value: 2.0 }),
+            AttributeBuilder::new(
+                1,
+                Value::String {
+                    value: CString::new("bar").unwrap(),
+                },
+            ),
+        ];
+        store.update_attributes(3, &attrs).unwrap();
+        let records = Store::open(&path).unwrap().records().unwrap();
+        assert_eq!(
+            records[0].attributes,
+            vec![
+                AttributeRecord {
+                    id: 0,
+                    value: ValueRecord::Double(2.0)
+                },
+                AttributeRecord {
+                    id: 1,
+                    value: ValueRecord::String("bar".to_string())
+                },
+            ]
+        );
+
+        store.remove(3).unwrap();
+        assert!(Store::open(&path).unwrap().records().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_failed_insert_leaves_the_store_unchanged() {
+        let dir = tempdir().unwrap();
+        let builder = PeripheralBuilder::new(0, "foo".to_string());
+
+        // The store cannot create its directory where a file already exists.
+        fs::write(dir.path().join("kpal"), "").unwrap();
+        let path = dir.path().join("kpal").join("peripherals.json");
+        let store = Store::open(&path).unwrap();
+        let record = PeripheralRecord::new(0, "libfoo.so".to_string(), &builder).unwrap();
+
+        assert!(store.insert(record).is_err());
+        assert!(store.records().unwrap().is_empty());
+    }
+
+    #[test]
+    fn test_ids_are_not_reused() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("peripherals.json");
+        let builder = PeripheralBuilder::new(0, "foo".to_string());
+
+        let store = Store::open(&path).unwrap();
+        let id = store.next_id();
+        assert_eq!(id, 0);
+        let record = PeripheralRecord::new(id, "libfoo.so".to_string(), &builder).unwrap();
+        store.insert(record).unwrap();
+
+        store.remove(id).unwrap();
+        assert_eq!(store.next_id(), 1);
+        store.release_id(1);
+        assert_eq!(store.next_id(), 1);
+
+        let record = PeripheralRecord::new(5, "libfoo.so".to_string(), &builder).unwrap();
+        store.insert(record).unwrap();
+        assert_eq!(store.next_id(), 6);
+        assert_eq!(Store::open(&path).unwrap().next_id(), 6);
+    }
+
+    #[test]
+    fn test_to_builder() {
+        let record = PeripheralRecord {
+            attributes: vec![AttributeRecord {
+                id: 0,
+                value: ValueRecord::Int(7),
+            }],
+            configured: false,
+            id: 2,
+            isolated: true,
+            library_name: "libfoo.so".to_string(),
+            name: "foo".to_string(),
+        };
+
+        let mut builder = record.to_builder(5).unwrap();
+
+        assert_eq!(*builder.library_id(), 5);
+        assert!(builder.isolated());
+        assert_eq!(builder.name(), "foo");
+        match builder.attribute_builder(0).unwrap().value() {
+            Value::Int { value } => assert_eq!(*value, 7),
+            _ => panic!("Unexpected attribute value"),
+        }
+    }
+
+    #[test]
+    fn test_bytes_record() {
+        let builder = AttributeBuilder::new(
+            0,
+            Value::Bytes {
+                value: vec![0, 255],
+            },
+        );
+
+        let record = AttributeRecord::try_from(&builder).unwrap();
+        assert_eq!(record.value, ValueRecord::Bytes("AP8=".to_string()));
+
+        match AttributeBuilder::try_from(&record).unwrap().value() {
+            Value::Bytes { value } => assert_eq!(*value, vec![0, 255]),
+            _ => panic!("Unexpected attribute value"),
+        }
+    }
+}
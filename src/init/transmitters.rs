@@ -1,12 +1,133 @@
-use std::collections::HashMap;
+use std::collections::{hash_map, HashMap};
 use std::sync::Mutex;
 
 use crate::plugins::Transmitter;
 
 /// A set of distinct transmitters for sending messages into executor threads.
-pub type Transmitters = HashMap<usize, Mutex<Transmitter>>;
+///
+/// The transmitters are indexed by the IDs of their peripherals, and the IDs are indexed by the
+/// names of the peripherals. Both indexes are only modified together so that a name is in use
+/// exactly as long as its peripheral's transmitter is in the set.
+#[derive(Debug, Default)]
+pub struct Transmitters {
+    /// The transmitters, indexed by peripheral ID.
+    txs: HashMap<usize, Mutex<Transmitter>>,
+
+    /// The peripheral IDs, indexed by peripheral name.
+    ids: HashMap<String, usize>,
+}
+
+impl Transmitters {
+    /// Returns true if the set contains the transmitter of a peripheral.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the peripheral
+    pub fn contains_key(&self, id: &usize) -> bool {
+        self.txs.contains_key(id)
+    }
+
+    /// Returns the ID of the peripheral with the given name, if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name of the peripheral
+    pub fn find(&self, name: &str) -> Option<usize> {
+        self.ids.get(name).copied()
+    }
+
+    /// Returns the transmitter of a peripheral, if it exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the peripheral
+    pub fn get(&self, id: &usize) -> Option<&Mutex<Transmitter>> {
+        self.txs.get(id)
+    }
+
+    /// Adds the transmitter of a new peripheral to the set.
+    ///
+    /// The transmitter is not added if the ID or the name of the peripheral is already in use, in
+    /// which case it is returned in the error.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the peripheral
+    /// * `name` - The name of the peripheral
+    /// * `tx` - The transmitter of the peripheral
+    pub fn insert(
+        &mut self,
+        id: usize,
+        name: &str,
+        tx: Mutex<Transmitter>,
+    ) -> Result<(), Mutex<Transmitter>> {
+        if self.txs.contains_key(&id) || self.ids.contains_key(name) {
+            return Err(tx);
+        }
+
+        self.txs.insert(id, tx);
+        self.ids.insert(name.to_owned(), id);
+
+        Ok(())
+    }
+
+    /// Returns an iterator over the peripheral IDs and their transmitters.
+    pub fn iter(&self) -> hash_map::Iter<'_, usize, Mutex<Transmitter>> {
+        self.txs.iter()
+    }
+
+    /// Returns true if the set contains no transmitters.
+    pub fn is_empty(&self) -> bool {
+        self.txs.is_empty()
+    }
+
+    /// Returns an iterator over the IDs of the peripherals.
+    pub fn keys(&self) -> hash_map::Keys<'_, usize, Mutex<Transmitter>> {
+        self.txs.keys()
+    }
+
+    /// Returns the number of transmitters in the set.
+    pub fn len(&self) -> usize {
+        self.txs.len()
+    }
+
+    /// Removes the transmitter of a peripheral from the set and frees its name.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the peripheral
+    pub fn remove(&mut self, id: &usize) -> Option<Mutex<Transmitter>> {
+        let tx = self.txs.remove(id)?;
+        self.ids.retain(|_, other| other != id);
+
+        Some(tx)
+    }
+}
 
 /// Returns an empty collection of thread transmitters.
 pub fn init() -> Transmitters {
-    HashMap::new()
+    Transmitters::default()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::sync::mpsc::channel;
+
+    #[test]
+    fn test_names_are_freed_with_their_transmitters() {
+        let mut txs = Transmitters::default();
+        let (tx, _rx) = channel();
+
+        txs.insert(0, "foo", Mutex::new(tx.clone())).unwrap();
+        assert_eq!(Some(0), txs.find("foo"));
+        assert!(txs.insert(1, "foo", Mutex::new(tx.clone())).is_err());
+        assert!(txs.insert(0, "bar", Mutex::new(tx.clone())).is_err());
+
+        txs.remove(&0).unwrap();
+        assert_eq!(None, txs.find("foo"));
+        txs.insert(1, "foo", Mutex::new(tx)).unwrap();
+        assert_eq!(Some(1), txs.find("foo"));
+    }
 }
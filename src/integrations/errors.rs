@@ -4,14 +4,18 @@ use std::{
     fmt,
     sync::{
         mpsc::{RecvTimeoutError, SendError},
-        MutexGuard, PoisonError, RwLockReadGuard,
+        MutexGuard, PoisonError, RwLockReadGuard, RwLockWriteGuard,
     },
 };
 
 use crate::{
-    init::Transmitters,
+    groups::GroupError,
+    init::{LibraryInitError, StoreError, Transmitters},
     models::Library,
     plugins::{Message, PluginError, Transmitter},
+    rules::RuleError,
+    sampler::SamplerError,
+    sequences::SequenceError,
 };
 
 /// A reason for why an error occurred in a KPAL module.
@@ -84,6 +88,66 @@ impl From<PluginError> for IntegrationsError {
     }
 }
 
+impl From<SamplerError> for IntegrationsError {
+    fn from(error: SamplerError) -> IntegrationsError {
+        IntegrationsError::new(
+            "Unable to sample the attribute".to_string(),
+            ErrorReason::InternalError,
+            Some(Box::new(error)),
+        )
+    }
+}
+
+impl From<GroupError> for IntegrationsError {
+    fn from(error: GroupError) -> IntegrationsError {
+        IntegrationsError::new(
+            "Unable to update the groups".to_string(),
+            ErrorReason::InternalError,
+            Some(Box::new(error)),
+        )
+    }
+}
+
+impl From<LibraryInitError> for IntegrationsError {
+    fn from(error: LibraryInitError) -> IntegrationsError {
+        IntegrationsError::new(
+            "Unable to load or unload the libraries".to_string(),
+            ErrorReason::InternalError,
+            Some(Box::new(error)),
+        )
+    }
+}
+
+impl From<RuleError> for IntegrationsError {
+    fn from(error: RuleError) -> IntegrationsError {
+        IntegrationsError::new(
+            "Unable to update the rules".to_string(),
+            ErrorReason::InternalError,
+            Some(Box::new(error)),
+        )
+    }
+}
+
+impl From<SequenceError> for IntegrationsError {
+    fn from(error: SequenceError) -> IntegrationsError {
+        IntegrationsError::new(
+            "Unable to run the sequence".to_string(),
+            ErrorReason::InternalError,
+            Some(Box::new(error)),
+        )
+    }
+}
+
+impl From<StoreError> for IntegrationsError {
+    fn from(error: StoreError) -> IntegrationsError {
+        IntegrationsError::new(
+            "Unable to update the peripheral store".to_string(),
+            ErrorReason::InternalError,
+            Some(Box::new(error)),
+        )
+    }
+}
+
 impl<'a> From<PoisonError<MutexGuard<'a, Library>>> for IntegrationsError {
     fn from(_: PoisonError<MutexGuard<Library>>) -> IntegrationsError {
         let message = "Unable to retrieve the library because its thread is poisoned".to_string();
@@ -120,6 +184,19 @@ impl<'a> From<PoisonError<RwLockReadGuard<'a, Transmitters>>> for IntegrationsEr
     }
 }
 
+impl<'a> From<PoisonError<RwLockWriteGuard<'a, Transmitters>>> for IntegrationsError {
+    fn from(_: PoisonError<RwLockWriteGuard<Transmitters>>) -> IntegrationsError {
+        let message =
+            "Unable to modify the collection of transmitters because its lock is poisoned"
+                .to_string();
+        IntegrationsError {
+            message,
+            reason: ErrorReason::InternalError,
+            side: None, // The PoisonError contains an item with a non-static lifetime.
+        }
+    }
+}
+
 impl From<RecvTimeoutError> for IntegrationsError {
     fn from(error: RecvTimeoutError) -> Self {
         IntegrationsError {
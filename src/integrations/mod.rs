@@ -7,6 +7,7 @@
 //! Examples of possible integrations include
 //!
 //! - a JSON REST API
+//! - a JSON websocket API
 //! - gRPC
 //! - a C static library
 //!
@@ -14,55 +15,565 @@
 //! KPAL crate. Submodules contain implementations of specific integrations.
 
 pub mod rest;
+pub mod websocket;
 
 mod errors;
 
-use std::sync::{mpsc::channel, Arc, RwLock};
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    sync::{
+        mpsc::{channel, Sender},
+        Arc, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use semver::VersionReq;
 
 use crate::{
-    constants::REQUEST_TIMEOUT,
-    init::{TSLibrary, Transmitters},
-    models::{Attribute, Library, Peripheral, PeripheralBuilder, Value},
-    plugins::{init as init_plugin, Message},
+    constants::{RULE_MIN_POLL_INTERVAL, SAMPLER_MIN_INTERVAL},
+    groups::Groups,
+    init::{Libraries, PeripheralRecord, Store, Transmitters, Unload},
+    models::{Attribute, AttributeBuilder, Library, Peripheral, PeripheralBuilder, Value},
+    plugins::{init as init_plugin, Message, PluginError},
+    rules::Rules,
+    sampler::Samplers,
+    sequences::{peripheral_ids, Sequences},
 };
 
+pub use crate::groups::Group;
+pub use crate::plugins::{AttributeUpdate, Event, Subscription, UpdateMode};
+pub use crate::rules::{Comparison, Condition, Rule, RuleStatus, SetAttribute};
+pub use crate::sampler::{Sample, SamplerConfig};
+pub use crate::sequences::{Reading, SequenceStatus, State, Step};
 pub use errors::{ErrorReason, IntegrationsError};
 
 /// The Result type that is returned by public functions in the `integrations` module.
 type Result<T> = std::result::Result<T, IntegrationsError>;
 
+/// Cancels a running Sequence and returns its status.
+///
+/// A Sequence that has already finished is left untouched.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Sequence.
+/// * `sequences` - The collection of sequences.
+pub fn cancel_sequence(id: usize, sequences: &Sequences) -> Result<SequenceStatus> {
+    sequences.cancel(id)?.ok_or_else(sequence_not_found)
+}
+
+/// Creates a new Group of Peripherals and returns its ID.
+///
+/// Groups that list Peripherals that do not exist are rejected with
+/// `ErrorReason::UnprocessableRequest`.
+///
+/// # Arguments
+///
+/// * `name` - The name of the Group.
+/// * `peripheral_ids` - The IDs of the Peripherals that belong to the Group.
+/// * `groups` - The collection of groups.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn create_group(
+    name: String,
+    peripheral_ids: BTreeSet<usize>,
+    groups: &Groups,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<usize> {
+    {
+        let txs = txs.read()?;
+        if let Some(id) = peripheral_ids.iter().find(|id| !txs.contains_key(id)) {
+            return Err(IntegrationsError::new(
+                format!("Peripheral not found: peripheral id {}", id),
+                ErrorReason::UnprocessableRequest,
+                None,
+            ));
+        }
+    }
+
+    let id = groups.insert(name, peripheral_ids)?;
+
+    Ok(id)
+}
+
 /// Creates a new peripheral from a peripheral builder and a plugin library.
 ///
-/// The ID of the new peripheral is returned.
+/// The ID of the new peripheral is returned. Peripheral names are unique, so a peripheral whose
+/// name is already in use is rejected with `ErrorReason::UnprocessableRequest`.
 ///
 /// # Arguments
 ///
 /// * `builder` - A PeripheralBuilder instance. This method assumes that none of the builder fields
 /// are initialized.
 /// * `libs` - The collection of plugin libraries known to KPAL.
+/// * `store` - The store that records the configuration of peripherals.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
 pub fn create_peripheral(
     mut builder: PeripheralBuilder,
-    libs: &[TSLibrary],
+    libs: &Libraries,
+    store: &Store,
     txs: Arc<RwLock<Transmitters>>,
 ) -> Result<usize> {
-    let lib = match libs.get(*builder.library_id()) {
-        Some(lib) => lib.clone(),
-        None => {
-            return Err(IntegrationsError::new(
-                "Library not found".to_string(),
+    if find_peripheral(builder.name(), txs.clone())?.is_some() {
+        return Err(IntegrationsError::new(
+            format!("A peripheral named {} already exists", builder.name()),
+            ErrorReason::UnprocessableRequest,
+            None,
+        ));
+    }
+
+    let lib = libs
+        .get(*builder.library_id())?
+        .ok_or_else(library_not_found)?;
+
+    let library_name = lib.lock()?.qualified_name();
+    let id = store.next_id();
+    builder = builder.set_id(id);
+
+    // The peripheral is recorded before it is started so that a peripheral that could not be
+    // recorded never runs. The ID is given back if the peripheral is rejected so that failed
+    // requests do not use it up.
+    if let Err(e) =
+        PeripheralRecord::new(id, library_name, &builder).and_then(|record| store.insert(record))
+    {
+        store.release_id(id);
+        return Err(e.into());
+    }
+
+    if let Err(e) = init_plugin(builder, lib, txs) {
+        if let Err(e) = store.remove(id) {
+            log::error!("Could not remove the record of peripheral {}: {}", id, e);
+        }
+        store.release_id(id);
+        return Err(e.into());
+    }
+
+    Ok(id)
+}
+
+/// Starts sampling a Peripheral Attribute at a fixed interval.
+///
+/// Any sampler of the same Attribute is replaced, but its history is kept. Intervals that are
+/// shorter than the minimum interval are rejected with `ErrorReason::UnprocessableRequest`.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral that owns the Attribute.
+/// * `attr_id` - The ID of the Attribute to sample.
+/// * `config` - The settings of the sampler.
+/// * `samplers` - The collection of samplers.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn create_sampler(
+    id: usize,
+    attr_id: usize,
+    config: SamplerConfig,
+    samplers: &Samplers,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<SamplerConfig> {
+    if config.interval < SAMPLER_MIN_INTERVAL {
+        return Err(IntegrationsError::new(
+            format!(
+                "The sampling interval must be at least {} ms",
+                SAMPLER_MIN_INTERVAL.as_millis()
+            ),
+            ErrorReason::UnprocessableRequest,
+            None,
+        ));
+    }
+
+    // Reading the attribute verifies that it exists before the sampler is started.
+    read_peripheral_attribute(id, attr_id, timeout, txs.clone())?;
+
+    samplers.start(id, attr_id, config.clone(), timeout, txs)?;
+
+    Ok(config)
+}
+
+/// Starts evaluating a new automation Rule and returns its ID.
+///
+/// Rules that use Peripherals that do not exist, that have a negative hysteresis, or that poll
+/// their Attribute too often are rejected with `ErrorReason::UnprocessableRequest`.
+///
+/// # Arguments
+///
+/// * `rule` - The Rule to create.
+/// * `rules` - The collection of rules.
+/// * `timeout` - The maximum amount of time to wait for a response from a peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn create_rule(
+    rule: Rule,
+    rules: &Rules,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<usize> {
+    let unprocessable =
+        |message: String| IntegrationsError::new(message, ErrorReason::UnprocessableRequest, None);
+
+    match rule.condition.comparison {
+        Comparison::Above { hysteresis, .. } | Comparison::Below { hysteresis, .. }
+            if hysteresis < 0.0 =>
+        {
+            return Err(unprocessable(
+                "The hysteresis must not be negative".to_string(),
+            ));
+        }
+        _ => (),
+    }
+
+    if let Some(interval) = rule.poll_interval {
+        if interval < RULE_MIN_POLL_INTERVAL {
+            return Err(unprocessable(format!(
+                "The polling interval must be at least {} ms",
+                RULE_MIN_POLL_INTERVAL.as_millis()
+            )));
+        }
+    }
+
+    {
+        let txs = txs.read()?;
+        for id in rule.peripheral_ids() {
+            if !txs.contains_key(&id) {
+                return Err(unprocessable(format!(
+                    "Peripheral not found: peripheral id {}",
+                    id
+                )));
+            }
+        }
+    }
+
+    let id = rules.insert(rule, timeout, txs)?;
+
+    Ok(id)
+}
+
+/// Starts running a new Sequence and returns its ID.
+///
+/// Sequences that use Peripherals that do not exist are rejected with
+/// `ErrorReason::UnprocessableRequest`.
+///
+/// # Arguments
+///
+/// * `steps` - The steps of the Sequence.
+/// * `sequences` - The collection of sequences.
+/// * `timeout` - The maximum amount of time to wait for a response from a peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn create_sequence(
+    steps: Vec<Step>,
+    sequences: &Sequences,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<usize> {
+    {
+        let txs = txs.read()?;
+        for id in peripheral_ids(&steps) {
+            if !txs.contains_key(&id) {
+                return Err(IntegrationsError::new(
+                    format!("Peripheral not found: peripheral id {}", id),
+                    ErrorReason::UnprocessableRequest,
+                    None,
+                ));
+            }
+        }
+    }
+
+    let id = sequences.start(steps, timeout, txs)?;
+
+    Ok(id)
+}
+
+/// Deinitializes a peripheral, returning its plugin to the init phase.
+///
+/// Pre-init attributes of a deinitialized peripheral may be modified before it is initialized
+/// again.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn deinit_peripheral(
+    id: usize,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Peripheral> {
+    let txs = txs.read()?;
+    let ptx = txs
+        .get(&id)
+        .ok_or_else(|| {
+            IntegrationsError::new(
+                "Peripheral not found".to_string(),
                 ErrorReason::ResourceNotFound,
                 None,
-            ))
+            )
+        })?
+        .lock()?;
+
+    let (tx, rx) = channel();
+    let msg = Message::DeinitPeripheral(tx);
+    ptx.send(msg)?;
+
+    rx.recv_timeout(timeout)?.map_err(IntegrationsError::from)
+}
+
+/// Deletes a Group. The Peripherals that belong to it are not affected.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Group.
+/// * `groups` - The collection of groups.
+pub fn delete_group(id: usize, groups: &Groups) -> Result<()> {
+    if groups.remove(id)? {
+        Ok(())
+    } else {
+        Err(group_not_found())
+    }
+}
+
+/// Unloads a Library.
+///
+/// Libraries that are still used by Peripherals are not unloaded and are rejected with
+/// `ErrorReason::UnprocessableRequest`.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Library to unload.
+/// * `libs` - The collection of plugin libraries known to KPAL.
+pub fn delete_library(id: usize, libs: &Libraries) -> Result<()> {
+    match libs.remove(id)? {
+        Unload::Unloaded => Ok(()),
+        Unload::InUse => Err(IntegrationsError::new(
+            format!("Library {} is in use by a peripheral", id),
+            ErrorReason::UnprocessableRequest,
+            None,
+        )),
+        Unload::NotFound => Err(library_not_found()),
+    }
+}
+
+/// Removes a peripheral and shuts down its executor.
+///
+/// The peripheral's transmitter is removed from the collection of transmitters before the
+/// shutdown message is sent so that no new requests are routed to the peripheral while its
+/// executor is stopping. The samplers of the peripheral's attributes are removed as well.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral to remove.
+/// * `samplers` - The collection of samplers.
+/// * `store` - The store that records the configuration of peripherals.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn delete_peripheral(
+    id: usize,
+    samplers: &Samplers,
+    store: &Store,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<()> {
+    let ptx = txs.write()?.remove(&id).ok_or_else(|| {
+        IntegrationsError::new(
+            "Peripheral not found".to_string(),
+            ErrorReason::ResourceNotFound,
+            None,
+        )
+    })?;
+    let ptx = ptx.lock()?;
+
+    store.remove(id)?;
+    samplers.stop_peripheral(id)?;
+
+    let (tx, rx) = channel();
+    let msg = Message::Shutdown(tx);
+    ptx.send(msg)?;
+
+    rx.recv_timeout(timeout)?.map_err(IntegrationsError::from)
+}
+
+/// Stops sampling a Peripheral Attribute and discards its history.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral that owns the Attribute.
+/// * `attr_id` - The ID of the sampled Attribute.
+/// * `samplers` - The collection of samplers.
+pub fn delete_sampler(id: usize, attr_id: usize, samplers: &Samplers) -> Result<()> {
+    if samplers.stop(id, attr_id)? {
+        Ok(())
+    } else {
+        Err(sampler_not_found())
+    }
+}
+
+/// Stops evaluating a Rule and deletes it.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Rule.
+/// * `rules` - The collection of rules.
+pub fn delete_rule(id: usize, rules: &Rules) -> Result<()> {
+    if rules.remove(id)? {
+        Ok(())
+    } else {
+        Err(rule_not_found())
+    }
+}
+
+/// Cancels a Sequence if it is running and discards its status.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Sequence.
+/// * `sequences` - The collection of sequences.
+pub fn delete_sequence(id: usize, sequences: &Sequences) -> Result<()> {
+    if sequences.remove(id)? {
+        Ok(())
+    } else {
+        Err(sequence_not_found())
+    }
+}
+
+/// Returns the ID of the Library that is referred to by a name.
+///
+/// The name is either the name of the library file or the name of the Library that is optionally
+/// followed by a version requirement, e.g. `gpio-cdev@0.3`. If several Libraries match, then the
+/// ID of the one with the highest version is returned. Version requirements that cannot be parsed
+/// are rejected with `ErrorReason::UnprocessableRequest`.
+///
+/// # Arguments
+///
+/// * `name` - The name that refers to the Library.
+/// * `libs` - The collection of plugin libraries known to KPAL.
+pub fn find_library_id(name: &str, libs: &Libraries) -> Result<usize> {
+    if let Some((_, req)) = name.split_once('@') {
+        if VersionReq::parse(req).is_err() {
+            return Err(IntegrationsError::new(
+                format!("Invalid library version requirement: {}", req),
+                ErrorReason::UnprocessableRequest,
+                None,
+            ));
         }
-    };
+    }
 
-    let id: usize = count_and_incr(txs.clone())?;
-    builder = builder.set_id(id);
+    libs.find(name)?
+        .map(|(_, id)| id)
+        .ok_or_else(library_not_found)
+}
 
-    init_plugin(builder, lib, txs)?;
+/// Initializes a peripheral that was previously deinitialized.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn init_peripheral(
+    id: usize,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Peripheral> {
+    let txs = txs.read()?;
+    let ptx = txs
+        .get(&id)
+        .ok_or_else(|| {
+            IntegrationsError::new(
+                "Peripheral not found".to_string(),
+                ErrorReason::ResourceNotFound,
+                None,
+            )
+        })?
+        .lock()?;
 
-    Ok(id)
+    let (tx, rx) = channel();
+    let msg = Message::InitPeripheral(tx);
+    ptx.send(msg)?;
+
+    rx.recv_timeout(timeout)?.map_err(IntegrationsError::from)
+}
+
+/// Invokes an action of a Peripheral and returns its result.
+///
+/// Arguments that do not match the action's declared arguments are rejected with
+/// `ErrorReason::UnprocessableRequest`.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral that performs the Action.
+/// * `action_id` - The ID of the Action to invoke.
+/// * `args` - The values of the Action's arguments.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn invoke_peripheral_action(
+    id: usize,
+    action_id: usize,
+    args: Vec<Value>,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Value> {
+    let txs = txs.read()?;
+    let ptx = txs
+        .get(&id)
+        .ok_or_else(|| {
+            IntegrationsError::new(
+                "Peripheral not found".to_string(),
+                ErrorReason::ResourceNotFound,
+                None,
+            )
+        })?
+        .lock()?;
+
+    let (tx, rx) = channel();
+    let msg = Message::InvokeAction(action_id, args, tx);
+    ptx.send(msg)?;
+
+    rx.recv_timeout(timeout)?.map_err(IntegrationsError::from)
+}
+
+/// Returns a Group.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Group.
+/// * `groups` - The collection of groups.
+pub fn read_group(id: usize, groups: &Groups) -> Result<Group> {
+    groups.group(id)?.ok_or_else(group_not_found)
+}
+
+/// Reads the same Attribute of every Peripheral in a Group.
+///
+/// The Peripherals are read concurrently. The result of each read is returned separately,
+/// indexed by Peripheral ID, so that one failing member does not hide the values of the others.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Group.
+/// * `attr_id` - The ID of the Attribute to read.
+/// * `groups` - The collection of groups.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripherals.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn read_group_attribute(
+    id: usize,
+    attr_id: usize,
+    groups: &Groups,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<BTreeMap<usize, Result<Attribute>>> {
+    let group = read_group(id, groups)?;
+    let txs = txs.read()?;
+
+    fan_out(&group.peripheral_ids, timeout, &txs, |tx| {
+        Message::GetPeripheralAttribute(attr_id, tx)
+    })
+}
+
+/// Returns all Groups.
+///
+/// # Arguments
+///
+/// * `groups` - The collection of groups.
+pub fn read_groups(groups: &Groups) -> Result<Vec<Group>> {
+    Ok(groups.groups()?)
 }
 
 /// Returns the list of plugin libraries currently known to KPAL.
@@ -73,9 +584,9 @@ pub fn create_peripheral(
 /// # Arguments
 ///
 /// * `libs` - The collection of plugin libraries known to KPAL.
-pub fn read_libraries(libs: &[TSLibrary]) -> Result<Vec<Library>> {
+pub fn read_libraries(libs: &Libraries) -> Result<Vec<Library>> {
     let mut result = Vec::new();
-    for lib in libs {
+    for lib in libs.libraries()? {
         result.push(lib.lock()?.clone());
     }
 
@@ -88,18 +599,8 @@ pub fn read_libraries(libs: &[TSLibrary]) -> Result<Vec<Library>> {
 ///
 /// * `id` - The ID of the Library to return.
 /// * `libs` - The collection of plugin libraries known to KPAL.
-pub fn read_library(id: usize, libs: &[TSLibrary]) -> Result<Library> {
-    let lib = libs
-        .get(id)
-        .ok_or_else(|| {
-            IntegrationsError::new(
-                "Library not found".to_string(),
-                ErrorReason::ResourceNotFound,
-                None,
-            )
-        })?
-        .lock()?
-        .clone();
+pub fn read_library(id: usize, libs: &Libraries) -> Result<Library> {
+    let lib = libs.get(id)?.ok_or_else(library_not_found)?.lock()?.clone();
 
     Ok(lib)
 }
@@ -109,8 +610,13 @@ pub fn read_library(id: usize, libs: &[TSLibrary]) -> Result<Library> {
 /// # Arguments
 ///
 /// * `id` - The ID of the Peripheral to return.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
-pub fn read_peripheral(id: usize, txs: Arc<RwLock<Transmitters>>) -> Result<Peripheral> {
+pub fn read_peripheral(
+    id: usize,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Peripheral> {
     let txs = txs.read()?;
     let ptx = txs
         .get(&id)
@@ -127,33 +633,27 @@ pub fn read_peripheral(id: usize, txs: Arc<RwLock<Transmitters>>) -> Result<Peri
     let msg = Message::GetPeripheral(tx);
     ptx.send(msg)?;
 
-    rx.recv_timeout(REQUEST_TIMEOUT)?
-        .map_err(IntegrationsError::from)
+    rx.recv_timeout(timeout)?.map_err(IntegrationsError::from)
 }
 
-/// Returns the current set of peripherals.
+/// Returns the current set of peripherals, ordered by ID.
+///
+/// The peripherals are read concurrently.
 ///
 /// # Arguments
 ///
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
-pub fn read_peripherals(txs: Arc<RwLock<Transmitters>>) -> Result<Vec<Peripheral>> {
-    let mut msg: Message;
-    let mut p: Peripheral;
-
+pub fn read_peripherals(
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Vec<Peripheral>> {
     let txs = txs.read()?;
-    let mut peripherals = Vec::new();
-    for (_, mutex) in txs.iter() {
-        let ptx = mutex.lock()?;
-
-        let (tx, rx) = channel();
-        msg = Message::GetPeripheral(tx);
-        ptx.send(msg)?;
-
-        p = rx.recv_timeout(REQUEST_TIMEOUT)??;
-        peripherals.push(p);
-    }
+    let ids: BTreeSet<usize> = txs.keys().cloned().collect();
 
-    Ok(peripherals)
+    fan_out(&ids, timeout, &txs, Message::GetPeripheral)?
+        .into_values()
+        .collect()
 }
 
 /// Returns the peripheral attribute with the given IDs.
@@ -162,10 +662,12 @@ pub fn read_peripherals(txs: Arc<RwLock<Transmitters>>) -> Result<Vec<Peripheral
 ///
 /// * `id` - The ID of the Peripheral that owns the Attribute to return.
 /// * `attr_id` - The ID of the Attribute to return.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
 pub fn read_peripheral_attribute(
     id: usize,
     attr_id: usize,
+    timeout: Duration,
     txs: Arc<RwLock<Transmitters>>,
 ) -> Result<Attribute> {
     let txs = txs.read()?;
@@ -184,8 +686,45 @@ pub fn read_peripheral_attribute(
     let msg = Message::GetPeripheralAttribute(attr_id, tx);
     ptx.send(msg)?;
 
-    rx.recv_timeout(REQUEST_TIMEOUT)?
-        .map_err(IntegrationsError::from)
+    rx.recv_timeout(timeout)?.map_err(IntegrationsError::from)
+}
+
+/// Returns the peripheral attribute with the given names.
+///
+/// # Arguments
+///
+/// * `name` - The name of the Peripheral that owns the Attribute to return.
+/// * `attr_name` - The alias or the name of the Attribute to return.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn read_peripheral_attribute_by_name(
+    name: &str,
+    attr_name: &str,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Attribute> {
+    let (id, attr_id) = find_attribute(name, attr_name, timeout, txs.clone())?;
+    read_peripheral_attribute(id, attr_id, timeout, txs)
+}
+
+/// Returns the samples of a Peripheral Attribute that were recorded by its sampler.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral that owns the Attribute.
+/// * `attr_id` - The ID of the sampled Attribute.
+/// * `since` - If provided, only the samples taken after this time in milliseconds since the Unix
+///   epoch are returned.
+/// * `samplers` - The collection of samplers.
+pub fn read_peripheral_attribute_history(
+    id: usize,
+    attr_id: usize,
+    since: Option<u64>,
+    samplers: &Samplers,
+) -> Result<Vec<Sample>> {
+    samplers
+        .history(id, attr_id, since)?
+        .ok_or_else(sampler_not_found)
 }
 
 /// Returns all attributes of the peripheral with the given ID.
@@ -193,9 +732,11 @@ pub fn read_peripheral_attribute(
 /// # Arguments
 ///
 /// * `id` - The ID of the Peripheral that owns the attributes to return.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
 pub fn read_peripheral_attributes(
     id: usize,
+    timeout: Duration,
     txs: Arc<RwLock<Transmitters>>,
 ) -> Result<Vec<Attribute>> {
     let txs = txs.read()?;
@@ -214,22 +755,198 @@ pub fn read_peripheral_attributes(
     let msg = Message::GetPeripheralAttributes(tx);
     ptx.send(msg)?;
 
-    rx.recv_timeout(REQUEST_TIMEOUT)?
-        .map_err(IntegrationsError::from)
+    rx.recv_timeout(timeout)?.map_err(IntegrationsError::from)
+}
+
+/// Loads the library files that were added to the library directories and returns the new
+/// Libraries.
+///
+/// # Arguments
+///
+/// * `libs` - The collection of plugin libraries known to KPAL.
+pub fn rescan_libraries(libs: &Libraries) -> Result<Vec<Library>> {
+    let mut result = Vec::new();
+    for lib in libs.rescan()? {
+        result.push(lib.lock()?.clone());
+    }
+
+    Ok(result)
+}
+
+/// Restarts a peripheral, optionally with new values for its pre-init attributes.
+///
+/// A running peripheral is deinitialized first. The new attribute values are validated before
+/// the plugin is touched.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral.
+/// * `attrs` - Builders containing the new values of pre-init attributes.
+/// * `store` - The store that records the configuration of peripherals.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn restart_peripheral(
+    id: usize,
+    attrs: Vec<AttributeBuilder>,
+    store: &Store,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Peripheral> {
+    let txs = txs.read()?;
+    let ptx = txs
+        .get(&id)
+        .ok_or_else(|| {
+            IntegrationsError::new(
+                "Peripheral not found".to_string(),
+                ErrorReason::ResourceNotFound,
+                None,
+            )
+        })?
+        .lock()?;
+
+    let (tx, rx) = channel();
+    let msg = Message::RestartPeripheral(attrs.clone(), tx);
+    ptx.send(msg)?;
+
+    let periph = rx.recv_timeout(timeout)??;
+    store.update_attributes(id, &attrs)?;
+
+    Ok(periph)
+}
+
+/// Subscribes to the events of a Peripheral.
+///
+/// An event is sent to the subscription every time the cached value of one of the Peripheral's
+/// Attributes changes. The subscription ends when the Peripheral is removed.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral.
+/// * `last_event_id` - The ID of the last event received by a client that is resuming a
+///   subscription. Events that occurred after this one are replayed if they are still recorded.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn subscribe_peripheral_events(
+    id: usize,
+    last_event_id: Option<u64>,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Subscription> {
+    let txs = txs.read()?;
+    let ptx = txs
+        .get(&id)
+        .ok_or_else(|| {
+            IntegrationsError::new(
+                "Peripheral not found".to_string(),
+                ErrorReason::ResourceNotFound,
+                None,
+            )
+        })?
+        .lock()?;
+
+    let (tx, rx) = channel();
+    let msg = Message::SubscribeEvents(last_event_id, tx);
+    ptx.send(msg)?;
+
+    rx.recv_timeout(timeout)?.map_err(IntegrationsError::from)
+}
+
+/// Returns the status of a Rule.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Rule.
+/// * `rules` - The collection of rules.
+pub fn read_rule(id: usize, rules: &Rules) -> Result<RuleStatus> {
+    rules.status(id)?.ok_or_else(rule_not_found)
+}
+
+/// Returns the statuses of all Rules.
+///
+/// # Arguments
+///
+/// * `rules` - The collection of rules.
+pub fn read_rules(rules: &Rules) -> Result<Vec<RuleStatus>> {
+    Ok(rules.statuses()?)
+}
+
+/// Returns the settings of the sampler of a Peripheral Attribute.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral that owns the Attribute.
+/// * `attr_id` - The ID of the sampled Attribute.
+/// * `samplers` - The collection of samplers.
+pub fn read_sampler(id: usize, attr_id: usize, samplers: &Samplers) -> Result<SamplerConfig> {
+    samplers.config(id, attr_id)?.ok_or_else(sampler_not_found)
+}
+
+/// Returns the status of a Sequence.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Sequence.
+/// * `sequences` - The collection of sequences.
+pub fn read_sequence(id: usize, sequences: &Sequences) -> Result<SequenceStatus> {
+    sequences.status(id)?.ok_or_else(sequence_not_found)
+}
+
+/// Returns the statuses of all Sequences.
+///
+/// # Arguments
+///
+/// * `sequences` - The collection of sequences.
+pub fn read_sequences(sequences: &Sequences) -> Result<Vec<SequenceStatus>> {
+    Ok(sequences.statuses()?)
+}
+
+/// Sets the same value of an Attribute of every Peripheral in a Group.
+///
+/// The Peripherals are updated concurrently. The result of each update is returned separately,
+/// indexed by Peripheral ID; a failure does not prevent the value from being set on the other
+/// members.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Group.
+/// * `attr_id` - The ID of the Attribute to update.
+/// * `value` - The new value of the Attribute.
+/// * `groups` - The collection of groups.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripherals.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn update_group_attribute(
+    id: usize,
+    attr_id: usize,
+    value: Value,
+    groups: &Groups,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<BTreeMap<usize, Result<Attribute>>> {
+    let group = read_group(id, groups)?;
+    let txs = txs.read()?;
+
+    fan_out(&group.peripheral_ids, timeout, &txs, |tx| {
+        Message::PatchPeripheralAttribute(attr_id, value.clone(), tx)
+    })
 }
 
 /// Updates the value of a Peripheral Attribute.
 ///
+/// Values that are not one of the Attribute's choices or that lie outside of its range are
+/// rejected with `ErrorReason::UnprocessableRequest`.
+///
 /// # Arguments
 ///
 /// * `id` - The ID of the Peripheral that owns the Attribute to return.
 /// * `attr_id` - The ID of the Attribute to return.
 /// * `value` - The new Value of the Attribute.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
 pub fn update_peripheral_attribute(
     id: usize,
     attr_id: usize,
     value: Value,
+    timeout: Duration,
     txs: Arc<RwLock<Transmitters>>,
 ) -> Result<Attribute> {
     let txs = txs.read()?;
@@ -248,30 +965,224 @@ pub fn update_peripheral_attribute(
     let msg = Message::PatchPeripheralAttribute(attr_id, value, tx);
     ptx.send(msg)?;
 
-    rx.recv_timeout(REQUEST_TIMEOUT)?
-        .map_err(IntegrationsError::from)
+    rx.recv_timeout(timeout)?.map_err(IntegrationsError::from)
 }
 
-/// Finds and returns the next largest integer to serve as a new peripheral ID.
+/// Updates the value of a Peripheral Attribute that is identified by names instead of IDs.
 ///
-/// This function loops over all the transmitters and finds the largest value for the peripheral
-/// ID. It then returns a value that is one greater than this.
+/// # Arguments
+///
+/// * `name` - The name of the Peripheral that owns the Attribute to update.
+/// * `attr_name` - The alias or the name of the Attribute to update.
+/// * `value` - The new Value of the Attribute.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn update_peripheral_attribute_by_name(
+    name: &str,
+    attr_name: &str,
+    value: Value,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Attribute> {
+    let (id, attr_id) = find_attribute(name, attr_name, timeout, txs.clone())?;
+    update_peripheral_attribute(id, attr_id, value, timeout, txs)
+}
+
+/// Updates the values of several attributes of a Peripheral.
+///
+/// The values are set in order by the Peripheral's executor while it handles a single message.
+/// The outcome of each value is returned in the same order. The mode determines whether the
+/// values that follow a failure are set and whether the values that precede it are restored.
 ///
 /// # Arguments
 ///
-/// * `txs` - The collection of transmitters for communicating with peripherals
-fn count_and_incr(txs: Arc<RwLock<Transmitters>>) -> Result<usize> {
+/// * `id` - The ID of the Peripheral that owns the Attributes.
+/// * `values` - The IDs of the Attributes to update and their new values.
+/// * `mode` - Determines what happens after a value cannot be set.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for communicating with peripherals.
+pub fn update_peripheral_attributes(
+    id: usize,
+    values: Vec<(usize, Value)>,
+    mode: UpdateMode,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Vec<AttributeUpdate<IntegrationsError>>> {
     let txs = txs.read()?;
-    if txs.len() == 0 {
-        return Ok(0);
-    }
+    let ptx = txs
+        .get(&id)
+        .ok_or_else(|| {
+            IntegrationsError::new(
+                "Peripheral not found".to_string(),
+                ErrorReason::ResourceNotFound,
+                None,
+            )
+        })?
+        .lock()?;
 
-    let mut largest_id: usize = 0;
-    for (id, _) in txs.iter() {
-        if *id > largest_id {
-            largest_id = *id
+    let (tx, rx) = channel();
+    let msg = Message::PatchPeripheralAttributes(values, mode, tx);
+    ptx.send(msg)?;
+
+    let results = rx.recv_timeout(timeout)??;
+
+    Ok(results
+        .into_iter()
+        .map(|result| result.map_err(IntegrationsError::from))
+        .collect())
+}
+
+/// Finds the IDs of a Peripheral and one of its Attributes from their names.
+///
+/// The Attribute is matched by its alias first and by its name otherwise.
+///
+/// # Arguments
+///
+/// * `name` - The name of the Peripheral
+/// * `attr_name` - The alias or the name of the Attribute
+/// * `timeout` - The maximum amount of time to wait for a response from the peripherals
+/// * `txs` - The collection of transmitters for sending messages into executor threads
+fn find_attribute(
+    name: &str,
+    attr_name: &str,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<(usize, usize)> {
+    let id = find_peripheral(name, txs.clone())?.ok_or_else(peripheral_not_found)?;
+    let periph = read_peripheral(id, timeout, txs)?;
+    let attrs = periph.attributes();
+
+    attrs
+        .iter()
+        .find(|(_, attr)| attr.alias() == attr_name)
+        .or_else(|| attrs.iter().find(|(_, attr)| attr.name() == attr_name))
+        .map(|(attr_id, _)| (id, *attr_id))
+        .ok_or_else(attribute_not_found)
+}
+
+/// Returns the ID of the Peripheral with the given name, if it exists.
+///
+/// # Arguments
+///
+/// * `name` - The name of the Peripheral
+/// * `txs` - The collection of transmitters for sending messages into executor threads
+fn find_peripheral(name: &str, txs: Arc<RwLock<Transmitters>>) -> Result<Option<usize>> {
+    Ok(txs.read()?.find(name))
+}
+
+/// Sends a message to each of several Peripherals and collects their responses.
+///
+/// Every message is sent before any response is awaited so that the Peripherals handle them
+/// concurrently, and all responses share the same deadline. A Peripheral that does not exist or
+/// does not respond in time only affects its own result.
+///
+/// # Arguments
+///
+/// * `ids` - The IDs of the Peripherals
+/// * `timeout` - The maximum amount of time to wait for all the responses
+/// * `txs` - The collection of transmitters for sending messages into executor threads
+/// * `message` - Creates the message for each Peripheral from the transmitter for its response
+fn fan_out<T, F>(
+    ids: &BTreeSet<usize>,
+    timeout: Duration,
+    txs: &Transmitters,
+    message: F,
+) -> Result<BTreeMap<usize, Result<T>>>
+where
+    F: Fn(Sender<std::result::Result<T, PluginError>>) -> Message,
+{
+    let mut results = BTreeMap::new();
+    let mut pending = Vec::new();
+    for id in ids {
+        let ptx = match txs.get(id) {
+            Some(ptx) => ptx.lock()?,
+            None => {
+                results.insert(*id, Err(peripheral_not_found()));
+                continue;
+            }
+        };
+
+        let (tx, rx) = channel();
+        match ptx.send(message(tx)) {
+            Ok(_) => pending.push((*id, rx)),
+            Err(e) => {
+                results.insert(*id, Err(e.into()));
+            }
         }
     }
 
-    Ok(largest_id + 1)
+    let deadline = Instant::now() + timeout;
+    for (id, rx) in pending {
+        let remaining = deadline.saturating_duration_since(Instant::now());
+        let result = match rx.recv_timeout(remaining) {
+            Ok(result) => result.map_err(IntegrationsError::from),
+            Err(e) => Err(e.into()),
+        };
+        results.insert(id, result);
+    }
+
+    Ok(results)
+}
+
+/// Returns the error that is raised when an Attribute does not exist.
+fn attribute_not_found() -> IntegrationsError {
+    IntegrationsError::new(
+        "Attribute not found".to_string(),
+        ErrorReason::ResourceNotFound,
+        None,
+    )
+}
+
+/// Returns the error that is raised when a Group does not exist.
+fn group_not_found() -> IntegrationsError {
+    IntegrationsError::new(
+        "Group not found".to_string(),
+        ErrorReason::ResourceNotFound,
+        None,
+    )
+}
+
+/// Returns the error that is raised when a Library does not exist.
+fn library_not_found() -> IntegrationsError {
+    IntegrationsError::new(
+        "Library not found".to_string(),
+        ErrorReason::ResourceNotFound,
+        None,
+    )
+}
+
+/// Returns the error that is raised when a Peripheral does not exist.
+fn peripheral_not_found() -> IntegrationsError {
+    IntegrationsError::new(
+        "Peripheral not found".to_string(),
+        ErrorReason::ResourceNotFound,
+        None,
+    )
+}
+
+/// Returns the error that is raised when a Rule does not exist.
+fn rule_not_found() -> IntegrationsError {
+    IntegrationsError::new(
+        "Rule not found".to_string(),
+        ErrorReason::ResourceNotFound,
+        None,
+    )
+}
+
+/// Returns the error that is raised when an Attribute is not being sampled.
+fn sampler_not_found() -> IntegrationsError {
+    IntegrationsError::new(
+        "Sampler not found".to_string(),
+        ErrorReason::ResourceNotFound,
+        None,
+    )
+}
+
+/// Returns the error that is raised when a Sequence does not exist.
+fn sequence_not_found() -> IntegrationsError {
+    IntegrationsError::new(
+        "Sequence not found".to_string(),
+        ErrorReason::ResourceNotFound,
+        None,
+    )
 }
@@ -0,0 +1,191 @@
+//! Server-sent event streams.
+use std::{
+    cmp,
+    convert::TryFrom,
+    io::{Read, Result as IoResult, Write},
+    sync::mpsc::RecvTimeoutError,
+    vec,
+};
+
+use log;
+
+use crate::{
+    constants::{EVENT_KEEP_ALIVE_INTERVAL, EVENT_STREAM_CHUNK_SIZE},
+    integrations::{Event, Subscription},
+};
+
+use super::super::schemas::AttributeRead;
+
+/// Streams the events of a subscription to a client in the server-sent events format.
+///
+/// The stream is the body of the response, so it is read by the HTTP server as the events occur.
+/// The server divides the body into chunks and only sends a full chunk once the next one has
+/// started. Every message therefore ends with a comment that pads it to a multiple of the
+/// configured chunk size, and the stream starts one byte past the end of a server chunk. The
+/// comment is left unterminated, and the next message starts by terminating it. If the configured
+/// chunk size is smaller than the server's, the messages are sent in batches that fill a server
+/// chunk. The stream ends when the peripheral is removed, and it is dropped when the client
+/// disconnects.
+pub struct EventStream {
+    /// The events that the client missed, which are sent before any new event.
+    missed: vec::IntoIter<Event>,
+
+    /// The receiver of the new events.
+    subscription: Subscription,
+
+    /// The message that is being read by the server.
+    buffer: Vec<u8>,
+
+    /// The number of bytes of the message that were already read.
+    position: usize,
+
+    /// The size to which each message is padded.
+    chunk_size: usize,
+}
+
+impl EventStream {
+    /// Returns a new event stream.
+    ///
+    /// # Arguments
+    ///
+    /// * `subscription` - The subscription that provides the events to send to the client
+    /// * `chunk_size` - The size to which each message is padded, which divides the server's
+    ///   chunk size
+    pub fn new(mut subscription: Subscription, chunk_size: usize) -> EventStream {
+        let missed = std::mem::take(&mut subscription.missed).into_iter();
+
+        // The server holds back the response headers until the first chunk is sent, so the stream
+        // starts with a comment that fills it before any event occurs. The extra byte starts the
+        // next chunk.
+        let mut buffer = b": open\n\n".to_vec();
+        pad(&mut buffer, EVENT_STREAM_CHUNK_SIZE);
+        buffer.push(b' ');
+
+        EventStream {
+            missed,
+            subscription,
+            buffer,
+            position: 0,
+            chunk_size,
+        }
+    }
+
+    /// Writes the next message into the buffer.
+    ///
+    /// Returns false if there are no more messages because the peripheral was removed.
+    fn next_message(&mut self) -> IoResult<bool> {
+        self.buffer.clear();
+        self.buffer.push(b'\n');
+        self.position = 0;
+
+        let event = match self.missed.next() {
+            Some(event) => Some(event),
+            None => match self.subscription.rx.recv_timeout(EVENT_KEEP_ALIVE_INTERVAL) {
+                Ok(event) => Some(event),
+                Err(RecvTimeoutError::Timeout) => None,
+                Err(RecvTimeoutError::Disconnected) => {
+                    log::debug!("Event stream closed because its peripheral was removed");
+                    return Ok(false);
+                }
+            },
+        };
+
+        match event {
+            Some(event) => write_event(&mut self.buffer, &event)?,
+            None => write_keep_alive(&mut self.buffer)?,
+        }
+        pad(&mut self.buffer, self.chunk_size);
+
+        Ok(true)
+    }
+}
+
+impl Read for EventStream {
+    fn read(&mut self, buf: &mut [u8]) -> IoResult<usize> {
+        // Padded messages are never empty, so the buffer is not empty after the next message.
+        if self.position == self.buffer.len() && !self.next_message()? {
+            return Ok(0);
+        }
+
+        let count = cmp::min(buf.len(), self.buffer.len() - self.position);
+        buf[..count].copy_from_slice(&self.buffer[self.position..self.position + count]);
+        self.position += count;
+
+        Ok(count)
+    }
+}
+
+/// Writes a single event to a message.
+///
+/// Events whose attributes cannot be represented by the REST API are skipped.
+///
+/// # Arguments
+///
+/// * `message` - The message to write to
+/// * `event` - The event to write
+fn write_event<W: Write>(message: &mut W, event: &Event) -> IoResult<()> {
+    let data = match AttributeRead::try_from(event.attribute().clone())
+        .map_err(|e| e.to_string())
+        .and_then(|attr| serde_json::to_string(&attr).map_err(|e| e.to_string()))
+    {
+        Ok(data) => data,
+        Err(e) => {
+            log::error!("Could not serialize event {}: {}", event.id(), e);
+            return Ok(());
+        }
+    };
+
+    write!(
+        message,
+        "id: {}\nevent: attribute\ndata: {}\n\n",
+        event.id(),
+        data
+    )
+}
+
+/// Writes a comment to a message to keep an idle connection open.
+///
+/// # Arguments
+///
+/// * `message` - The message to write to
+fn write_keep_alive<W: Write>(message: &mut W) -> IoResult<()> {
+    message.write_all(b": keep-alive\n\n")
+}
+
+/// Pads a message with an unterminated comment so that its length is a multiple of the chunk size.
+///
+/// # Arguments
+///
+/// * `message` - The message to pad
+/// * `chunk_size` - The size of the chunks
+fn pad(message: &mut Vec<u8>, chunk_size: usize) {
+    // The comment consists of at least a colon.
+    let padded = (message.len() + 1).div_ceil(chunk_size) * chunk_size;
+
+    message.push(b':');
+    message.resize(padded, b' ');
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_pad() {
+        for chunk_size in &[1024, EVENT_STREAM_CHUNK_SIZE] {
+            for length in &[0, 1, chunk_size - 1, *chunk_size] {
+                let mut message = vec![b'a'; *length];
+                pad(&mut message, *chunk_size);
+
+                let expected = if length < chunk_size {
+                    *chunk_size
+                } else {
+                    2 * chunk_size
+                };
+                assert_eq!(message.len(), expected);
+                assert_eq!(message[*length], b':');
+                assert!(message[*length + 1..].iter().all(|byte| *byte == b' '));
+            }
+        }
+    }
+}
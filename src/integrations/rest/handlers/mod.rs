@@ -1,40 +1,198 @@
 //! Functions for processing requests to the REST integration.
 mod errors;
+mod events;
 
 use std::{
+    collections::BTreeMap,
     convert::{TryFrom, TryInto},
     sync::{Arc, RwLock},
+    time::Duration,
 };
 
 use rouille::input::json::json_input;
-use rouille::{Request, Response};
+use rouille::{Request, Response, ResponseBody};
 
 use crate::{
-    init::{TSLibrary, Transmitters},
+    groups::Groups,
+    init::{Libraries, Store, Transmitters},
     integrations::{
-        create_peripheral, read_libraries, read_library, read_peripheral,
-        read_peripheral_attribute, read_peripheral_attributes, read_peripherals,
-        update_peripheral_attribute,
+        self, cancel_sequence, create_group, create_peripheral, create_rule, create_sampler,
+        create_sequence, deinit_peripheral, delete_sampler, init_peripheral,
+        invoke_peripheral_action, read_group, read_group_attribute, read_groups, read_libraries,
+        read_library, read_peripheral, read_peripheral_attribute,
+        read_peripheral_attribute_by_name, read_peripheral_attribute_history,
+        read_peripheral_attributes, read_peripherals, read_rule, read_rules, read_sampler,
+        read_sequence, read_sequences, rescan_libraries, rest::status_from_reason,
+        restart_peripheral, subscribe_peripheral_events, update_group_attribute,
+        update_peripheral_attribute, update_peripheral_attribute_by_name,
+        update_peripheral_attributes, AttributeUpdate, IntegrationsError, Rule,
     },
-    models::{PeripheralBuilder, Value},
+    models::{Attribute, AttributeBuilder, PeripheralBuilder, Value},
+    rules::Rules,
+    sampler::Samplers,
+    sequences::Sequences,
 };
 
 use super::schemas::{
-    AttributeRead, LibraryRead, PeripheralCreate, PeripheralCreateResponse, PeripheralRead,
-    SchemaError, ValueReadUpdate,
+    AttributeRead, AttributeSampleRead, AttributeSamplerCreate, AttributeSamplerRead,
+    GroupAttributeReadUpdateResponse, GroupCreate, GroupCreateResponse, GroupRead, LibraryRead,
+    PeripheralActionCreate, PeripheralActionCreateResponse, PeripheralAttributeUpdateResponse,
+    PeripheralAttributesUpdate, PeripheralCreate, PeripheralCreateResponse, PeripheralRead,
+    PeripheralRestartCreate, RuleCreate, RuleCreateResponse, RuleRead, SchemaError, SequenceCreate,
+    SequenceCreateResponse, SequenceRead, ValueReadUpdate,
 };
 
+use events::EventStream;
+
 pub use errors::RestHandlerError;
 
 /// The Result type returned by the REST handlers.
 type Result<T> = std::result::Result<T, RestHandlerError>;
 
+/// Handles the DELETE /api/v0/groups/{id} endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Group to delete.
+/// * `groups` - The collection of groups.
+pub fn delete_group(id: usize, groups: &Groups) -> Result<Response> {
+    integrations::delete_group(id, groups)?;
+
+    Ok(Response::empty_204())
+}
+
+/// Handles the DELETE /api/v0/libraries/{id} endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Library to unload.
+/// * `libs` - The collection of plugin libraries known to KPAL.
+pub fn delete_library(id: usize, libs: &Libraries) -> Result<Response> {
+    integrations::delete_library(id, libs)?;
+
+    Ok(Response::empty_204())
+}
+
+/// Handles the DELETE /api/v0/peripherals/{id} endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral to delete.
+/// * `samplers` - The collection of samplers.
+/// * `store` - The store that records the configuration of peripherals.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn delete_peripheral(
+    id: usize,
+    samplers: &Samplers,
+    store: &Store,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    integrations::delete_peripheral(id, samplers, store, timeout, txs)?;
+
+    Ok(Response::empty_204())
+}
+
+/// Handles the DELETE /api/v0/peripherals/{id}/attributes/{attr_id}/sampler endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral that owns the Attribute.
+/// * `attr_id` - The ID of the sampled Attribute.
+/// * `samplers` - The collection of samplers.
+pub fn delete_peripheral_attribute_sampler(
+    id: usize,
+    attr_id: usize,
+    samplers: &Samplers,
+) -> Result<Response> {
+    delete_sampler(id, attr_id, samplers)?;
+
+    Ok(Response::empty_204())
+}
+
+/// Handles the DELETE /api/v0/rules/{id} endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Rule to delete.
+/// * `rules` - The collection of rules.
+pub fn delete_rule(id: usize, rules: &Rules) -> Result<Response> {
+    integrations::delete_rule(id, rules)?;
+
+    Ok(Response::empty_204())
+}
+
+/// Handles the DELETE /api/v0/sequences/{id} endpoint.
+///
+/// A running Sequence is cancelled before it is deleted.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Sequence to delete.
+/// * `sequences` - The collection of sequences.
+pub fn delete_sequence(id: usize, sequences: &Sequences) -> Result<Response> {
+    integrations::delete_sequence(id, sequences)?;
+
+    Ok(Response::empty_204())
+}
+
+/// Handles the GET /api/v0/groups/{id} endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Group to return.
+/// * `groups` - The collection of groups.
+pub fn get_group(id: usize, groups: &Groups) -> Result<Response> {
+    let group = read_group(id, groups)?;
+
+    let response = GroupRead::from(group);
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the GET /api/v0/groups/{id}/attributes/{attr_id} endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Group.
+/// * `attr_id` - The ID of the Attribute to read from every member of the Group.
+/// * `groups` - The collection of groups.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripherals.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn get_group_attribute(
+    id: usize,
+    attr_id: usize,
+    groups: &Groups,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let results = read_group_attribute(id, attr_id, groups, timeout, txs)?;
+
+    let response = group_attribute_response(results)?;
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the GET /api/v0/groups endpoint.
+///
+/// # Arguments
+///
+/// * `groups` - The collection of groups.
+pub fn get_groups(groups: &Groups) -> Result<Response> {
+    let groups = read_groups(groups)?;
+
+    let response: Vec<GroupRead> = groups.into_iter().map(GroupRead::from).collect();
+
+    Ok(Response::json(&response))
+}
+
 /// Handles the GET /api/v0/libraries endpoint.
 ///
 /// # Arguments
 ///
 /// * `libs` - The collection of plugin libraries known to KPAL.
-pub fn get_libraries(libs: &[TSLibrary]) -> Result<Response> {
+pub fn get_libraries(libs: &Libraries) -> Result<Response> {
     let libs = read_libraries(libs)?;
 
     let response: Vec<LibraryRead> =
@@ -51,7 +209,7 @@ pub fn get_libraries(libs: &[TSLibrary]) -> Result<Response> {
 ///
 /// * `id` - The ID of the Library to return.
 /// * `libs` - The collection of plugin libraries known to KPAL.
-pub fn get_library(id: usize, libs: &[TSLibrary]) -> Result<Response> {
+pub fn get_library(id: usize, libs: &Libraries) -> Result<Response> {
     let lib = read_library(id, libs)?;
 
     let response = LibraryRead::try_from(lib)?;
@@ -64,9 +222,14 @@ pub fn get_library(id: usize, libs: &[TSLibrary]) -> Result<Response> {
 /// # Arguments
 ///
 /// * `id` - The ID of the Peripheral to return.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
-pub fn get_peripheral(id: usize, txs: Arc<RwLock<Transmitters>>) -> Result<Response> {
-    let periph = read_peripheral(id, txs)?;
+pub fn get_peripheral(
+    id: usize,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let periph = read_peripheral(id, timeout, txs)?;
 
     let response = PeripheralRead::from(periph);
 
@@ -79,27 +242,108 @@ pub fn get_peripheral(id: usize, txs: Arc<RwLock<Transmitters>>) -> Result<Respo
 ///
 /// * `id` - The ID of the Peripheral that owns the Attribute to return.
 /// * `attr_id` - The ID of the Attribute to return.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
 pub fn get_peripheral_attribute(
     id: usize,
     attr_id: usize,
+    timeout: Duration,
     txs: Arc<RwLock<Transmitters>>,
 ) -> Result<Response> {
-    let attr = read_peripheral_attribute(id, attr_id, txs)?;
+    let attr = read_peripheral_attribute(id, attr_id, timeout, txs)?;
 
     let response = AttributeRead::try_from(attr)?;
 
     Ok(Response::json(&response))
 }
 
+/// Handles the GET /api/v0/peripherals/by-name/{name}/attributes/{attr_name} endpoint.
+///
+/// # Arguments
+///
+/// * `name` - The name of the Peripheral that owns the Attribute to return.
+/// * `attr_name` - The alias or the name of the Attribute to return.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn get_peripheral_attribute_by_name(
+    name: &str,
+    attr_name: &str,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let attr = read_peripheral_attribute_by_name(name, attr_name, timeout, txs)?;
+
+    let response = AttributeRead::try_from(attr)?;
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the GET /api/v0/peripherals/{id}/attributes/{attr_id}/history endpoint.
+///
+/// The optional `since` query parameter is a time in milliseconds since the Unix epoch. Only the
+/// samples that were taken after this time are returned.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `id` - The ID of the Peripheral that owns the Attribute.
+/// * `attr_id` - The ID of the sampled Attribute.
+/// * `samplers` - The collection of samplers.
+pub fn get_peripheral_attribute_history(
+    request: &Request,
+    id: usize,
+    attr_id: usize,
+    samplers: &Samplers,
+) -> Result<Response> {
+    let since = match request.get_param("since") {
+        Some(param) => Some(param.trim().parse::<u64>().map_err(|e| RestHandlerError {
+            message: format!("Invalid since parameter: {}", param),
+            http_status_code: 400,
+            side: Some(Box::new(e)),
+        })?),
+        None => None,
+    };
+
+    let samples = read_peripheral_attribute_history(id, attr_id, since, samplers)?;
+
+    let response: Vec<AttributeSampleRead> = samples
+        .into_iter()
+        .map(AttributeSampleRead::try_from)
+        .collect::<std::result::Result<Vec<AttributeSampleRead>, SchemaError>>()?;
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the GET /api/v0/peripherals/{id}/attributes/{attr_id}/sampler endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral that owns the Attribute.
+/// * `attr_id` - The ID of the sampled Attribute.
+/// * `samplers` - The collection of samplers.
+pub fn get_peripheral_attribute_sampler(
+    id: usize,
+    attr_id: usize,
+    samplers: &Samplers,
+) -> Result<Response> {
+    let config = read_sampler(id, attr_id, samplers)?;
+
+    Ok(Response::json(&AttributeSamplerRead::from(config)))
+}
+
 /// Handles the GET /api/v0/peripherals/{id}/attributes endpoint.
 ///
 /// # Arguments
 ///
 /// * `id` - The ID of the Peripheral that owns the attributes to return.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
-pub fn get_peripheral_attributes(id: usize, txs: Arc<RwLock<Transmitters>>) -> Result<Response> {
-    let attrs = read_peripheral_attributes(id, txs)?;
+pub fn get_peripheral_attributes(
+    id: usize,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let attrs = read_peripheral_attributes(id, timeout, txs)?;
 
     let response: Vec<AttributeRead> = attrs
         .into_iter()
@@ -110,19 +354,152 @@ pub fn get_peripheral_attributes(id: usize, txs: Arc<RwLock<Transmitters>>) -> R
     Ok(Response::json(&response))
 }
 
+/// Handles the GET /api/v0/peripherals/{id}/events endpoint.
+///
+/// The response is a stream of server-sent events. An event is sent every time the value of one
+/// of the peripheral's attributes changes. Clients that reconnect may provide the ID of the last
+/// event that they received in the `Last-Event-ID` header to receive the events that they missed.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `id` - The ID of the Peripheral whose events are streamed.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `chunk_size` - The size to which each message of the stream is padded.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn get_peripheral_events(
+    request: &Request,
+    id: usize,
+    timeout: Duration,
+    chunk_size: usize,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let last_event_id = match request.header("Last-Event-ID") {
+        Some(header) => Some(header.trim().parse::<u64>().map_err(|e| RestHandlerError {
+            message: format!("Invalid Last-Event-ID header: {}", header),
+            http_status_code: 400,
+            side: Some(Box::new(e)),
+        })?),
+        None => None,
+    };
+
+    let subscription = subscribe_peripheral_events(id, last_event_id, timeout, txs)?;
+
+    // The events are read from the stream by the server while the response body is being sent.
+    Ok(Response {
+        status_code: 200,
+        headers: vec![
+            ("Content-Type".into(), "text/event-stream".into()),
+            ("Cache-Control".into(), "no-cache".into()),
+        ],
+        data: ResponseBody::from_reader(EventStream::new(subscription, chunk_size)),
+        upgrade: None,
+    })
+}
+
 /// Handles the GET /api/v0/peripherals endpoint.
 ///
 /// # Arguments
 ///
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
-pub fn get_peripherals(txs: Arc<RwLock<Transmitters>>) -> Result<Response> {
-    let periphs = read_peripherals(txs)?;
+pub fn get_peripherals(timeout: Duration, txs: Arc<RwLock<Transmitters>>) -> Result<Response> {
+    let periphs = read_peripherals(timeout, txs)?;
 
     let response: Vec<PeripheralRead> = periphs.into_iter().map(|periph| periph.into()).collect();
 
     Ok(Response::json(&response))
 }
 
+/// Handles the GET /api/v0/rules/{id} endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Rule to return.
+/// * `rules` - The collection of rules.
+pub fn get_rule(id: usize, rules: &Rules) -> Result<Response> {
+    let status = read_rule(id, rules)?;
+
+    let response = RuleRead::try_from(status)?;
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the GET /api/v0/rules endpoint.
+///
+/// # Arguments
+///
+/// * `rules` - The collection of rules.
+pub fn get_rules(rules: &Rules) -> Result<Response> {
+    let statuses = read_rules(rules)?;
+
+    let response: Vec<RuleRead> = statuses
+        .into_iter()
+        .map(RuleRead::try_from)
+        .collect::<std::result::Result<Vec<RuleRead>, SchemaError>>()?;
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the GET /api/v0/sequences/{id} endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Sequence to return.
+/// * `sequences` - The collection of sequences.
+pub fn get_sequence(id: usize, sequences: &Sequences) -> Result<Response> {
+    let status = read_sequence(id, sequences)?;
+
+    let response = SequenceRead::try_from(status)?;
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the GET /api/v0/sequences endpoint.
+///
+/// # Arguments
+///
+/// * `sequences` - The collection of sequences.
+pub fn get_sequences(sequences: &Sequences) -> Result<Response> {
+    let statuses = read_sequences(sequences)?;
+
+    let response: Vec<SequenceRead> = statuses
+        .into_iter()
+        .map(SequenceRead::try_from)
+        .collect::<std::result::Result<Vec<SequenceRead>, SchemaError>>(
+    )?;
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the PATCH /api/v0/groups/{id}/attributes/{attr_id} endpoint.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `id` - The ID of the Group.
+/// * `attr_id` - The ID of the Attribute to update on every member of the Group.
+/// * `groups` - The collection of groups.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripherals.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn patch_group_attribute(
+    request: &Request,
+    id: usize,
+    attr_id: usize,
+    groups: &Groups,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let data: ValueReadUpdate = json_input(request)?;
+    let value = Value::try_from(data)?;
+
+    let results = update_group_attribute(id, attr_id, value, groups, timeout, txs)?;
+
+    let response = group_attribute_response(results)?;
+
+    Ok(Response::json(&response))
+}
+
 /// Handles the PATCH /api/v0/peripherals/{id}/attributes/{attr_id} endpoint.
 ///
 /// # Arguments
@@ -130,39 +507,306 @@ pub fn get_peripherals(txs: Arc<RwLock<Transmitters>>) -> Result<Response> {
 /// * `request` - The request object that contains the user-provided request data.
 /// * `id` - The ID of the Peripheral that owns the Attribute to return.
 /// * `attr_id` - The ID of the Attribute to return.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
 pub fn patch_peripheral_attribute(
     request: &Request,
     id: usize,
     attr_id: usize,
+    timeout: Duration,
     txs: Arc<RwLock<Transmitters>>,
 ) -> Result<Response> {
     let data: ValueReadUpdate = json_input(&request)?;
     let value = Value::try_from(data)?;
 
-    let attr = update_peripheral_attribute(id, attr_id, value, txs)?;
+    let attr = update_peripheral_attribute(id, attr_id, value, timeout, txs)?;
 
     let response = AttributeRead::try_from(attr)?;
 
     Ok(Response::json(&response))
 }
 
+/// Handles the PATCH /api/v0/peripherals/by-name/{name}/attributes/{attr_name} endpoint.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `name` - The name of the Peripheral that owns the Attribute to update.
+/// * `attr_name` - The alias or the name of the Attribute to update.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn patch_peripheral_attribute_by_name(
+    request: &Request,
+    name: &str,
+    attr_name: &str,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let data: ValueReadUpdate = json_input(request)?;
+    let value = Value::try_from(data)?;
+
+    let attr = update_peripheral_attribute_by_name(name, attr_name, value, timeout, txs)?;
+
+    let response = AttributeRead::try_from(attr)?;
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the PATCH /api/v0/peripherals/{id}/attributes endpoint.
+///
+/// The values are set in the order in which they are provided. The response contains one result
+/// for each of them, so the request succeeds even if some of the values could not be set. In an
+/// atomic update, the values that were set before a failure are restored and their results
+/// report whether this succeeded.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `id` - The ID of the Peripheral that owns the Attributes.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn patch_peripheral_attributes(
+    request: &Request,
+    id: usize,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let data: PeripheralAttributesUpdate = json_input(request)?;
+    let mode = data.mode();
+    let values = Vec::try_from(data)?;
+    let attr_ids: Vec<usize> = values.iter().map(|(attr_id, _)| *attr_id).collect();
+
+    let results = update_peripheral_attributes(id, values, mode, timeout, txs)?;
+
+    let mut response = Vec::new();
+    for (attr_id, result) in attr_ids.into_iter().zip(results) {
+        let result = match result {
+            AttributeUpdate::Applied(attr) => PeripheralAttributeUpdateResponse::Applied {
+                id: attr_id,
+                attribute: AttributeRead::try_from(attr)?,
+            },
+            AttributeUpdate::Failed(e) => PeripheralAttributeUpdateResponse::Failed {
+                id: attr_id,
+                http_status_code: status_from_reason(e.reason()),
+                message: e.message().to_owned(),
+            },
+            AttributeUpdate::RolledBack(attr) => PeripheralAttributeUpdateResponse::RolledBack {
+                id: attr_id,
+                attribute: AttributeRead::try_from(attr)?,
+            },
+            AttributeUpdate::RollbackFailed(e) => {
+                PeripheralAttributeUpdateResponse::RollbackFailed {
+                    id: attr_id,
+                    http_status_code: status_from_reason(e.reason()),
+                    message: e.message().to_owned(),
+                }
+            }
+            AttributeUpdate::Skipped => PeripheralAttributeUpdateResponse::Skipped { id: attr_id },
+        };
+        response.push(result);
+    }
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the POST /api/v0/peripherals/{id}/actions/{action_id} endpoint.
+///
+/// The request body is optional. If it is absent, the action is invoked without arguments.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `id` - The ID of the Peripheral that performs the Action.
+/// * `action_id` - The ID of the Action to invoke.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn post_peripheral_action(
+    request: &Request,
+    id: usize,
+    action_id: usize,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let args: Vec<Value> = match request.header("Content-Type") {
+        Some(_) => {
+            let data: PeripheralActionCreate = json_input(request)?;
+            Vec::try_from(data)?
+        }
+        None => Vec::new(),
+    };
+
+    let result = invoke_peripheral_action(id, action_id, args, timeout, txs)?;
+
+    let response = PeripheralActionCreateResponse::try_from(result)?;
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the POST /api/v0/peripherals/{id}/attributes/{attr_id}/sampler endpoint.
+///
+/// Any sampler of the same Attribute is replaced.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `id` - The ID of the Peripheral that owns the Attribute.
+/// * `attr_id` - The ID of the Attribute to sample.
+/// * `samplers` - The collection of samplers.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn post_peripheral_attribute_sampler(
+    request: &Request,
+    id: usize,
+    attr_id: usize,
+    samplers: &Samplers,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let data: AttributeSamplerCreate = json_input(request)?;
+
+    let config = create_sampler(id, attr_id, data.into(), samplers, timeout, txs)?;
+
+    let mut response = Response::json(&AttributeSamplerRead::from(config));
+    response.status_code = 201;
+
+    Ok(response)
+}
+
+/// Handles the POST /api/v0/peripherals/{id}/deinit endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral to deinitialize.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn post_peripheral_deinit(
+    id: usize,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let periph = deinit_peripheral(id, timeout, txs)?;
+
+    let response = PeripheralRead::from(periph);
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the POST /api/v0/peripherals/{id}/init endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Peripheral to initialize.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn post_peripheral_init(
+    id: usize,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let periph = init_peripheral(id, timeout, txs)?;
+
+    let response = PeripheralRead::from(periph);
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the POST /api/v0/peripherals/{id}/restart endpoint.
+///
+/// The request body is optional. If it is absent, the peripheral is restarted with its current
+/// attribute values.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `id` - The ID of the Peripheral to restart.
+/// * `store` - The store that records the configuration of peripherals.
+/// * `timeout` - The maximum amount of time to wait for a response from the peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn post_peripheral_restart(
+    request: &Request,
+    id: usize,
+    store: &Store,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let attrs: Vec<AttributeBuilder> = match request.header("Content-Type") {
+        Some(_) => {
+            let data: PeripheralRestartCreate = json_input(request)?;
+            Vec::try_from(data)?
+        }
+        None => Vec::new(),
+    };
+
+    let periph = restart_peripheral(id, attrs, store, timeout, txs)?;
+
+    let response = PeripheralRead::from(periph);
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the POST /api/v0/groups endpoint.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `groups` - The collection of groups.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn post_groups(
+    request: &Request,
+    groups: &Groups,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let data: GroupCreate = json_input(request)?;
+
+    let id = create_group(data.name, data.peripheral_ids, groups, txs)?;
+
+    let location = format!("/api/v0/groups/{}", id);
+    let mut response = Response::json(&GroupCreateResponse {
+        message: format!("Group successfully created. Location: {}", location),
+    });
+    response.status_code = 201;
+    response.headers.push(("Location".into(), location.into()));
+
+    Ok(response)
+}
+
+/// Handles the POST /api/v0/libraries/rescan endpoint.
+///
+/// The libraries that were loaded are returned.
+///
+/// # Arguments
+///
+/// * `libs` - The collection of plugin libraries known to KPAL.
+pub fn post_libraries_rescan(libs: &Libraries) -> Result<Response> {
+    let libs = rescan_libraries(libs)?;
+
+    let response: Vec<LibraryRead> =
+        libs.into_iter()
+            .map(|lib| lib.try_into())
+            .collect::<std::result::Result<Vec<LibraryRead>, SchemaError>>()?;
+
+    Ok(Response::json(&response))
+}
+
 /// Handles the POST /api/v0/peripherals endpoint.
 ///
 /// # Arguments
 ///
 /// * `request` - The request object that contains the user-provided request data.
 /// * `libs` - The collection of plugin libraries known to KPAL.
+/// * `store` - The store that records the configuration of peripherals.
 /// * `txs` - The collection of transmitters for sending messages into executor threads.
 pub fn post_peripherals(
     request: &Request,
-    libs: &[TSLibrary],
+    libs: &Libraries,
+    store: &Store,
     txs: Arc<RwLock<Transmitters>>,
 ) -> Result<Response> {
-    let data: PeripheralCreate = json_input(&request)?;
+    let mut data: PeripheralCreate = json_input(&request)?;
+    data.find_library(libs)?;
     let builder = PeripheralBuilder::try_from(data)?;
 
-    let id = create_peripheral(builder, libs, txs)?;
+    let id = create_peripheral(builder, libs, store, txs)?;
 
     let location = format!("/api/v0/peripherals/{}", id);
     let mut response = Response::json(&PeripheralCreateResponse {
@@ -173,3 +817,100 @@ pub fn post_peripherals(
 
     Ok(response)
 }
+
+/// Handles the POST /api/v0/rules endpoint.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `rules` - The collection of rules.
+/// * `timeout` - The maximum amount of time to wait for a response from a peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn post_rules(
+    request: &Request,
+    rules: &Rules,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let data: RuleCreate = json_input(request)?;
+    let rule = Rule::try_from(data)?;
+
+    let id = create_rule(rule, rules, timeout, txs)?;
+
+    let location = format!("/api/v0/rules/{}", id);
+    let mut response = Response::json(&RuleCreateResponse {
+        message: format!("Rule successfully created. Location: {}", location),
+    });
+    response.status_code = 201;
+    response.headers.push(("Location".into(), location.into()));
+
+    Ok(response)
+}
+
+/// Handles the POST /api/v0/sequences/{id}/cancel endpoint.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the Sequence to cancel.
+/// * `sequences` - The collection of sequences.
+pub fn post_sequence_cancel(id: usize, sequences: &Sequences) -> Result<Response> {
+    let status = cancel_sequence(id, sequences)?;
+
+    let response = SequenceRead::try_from(status)?;
+
+    Ok(Response::json(&response))
+}
+
+/// Handles the POST /api/v0/sequences endpoint.
+///
+/// # Arguments
+///
+/// * `request` - The request object that contains the user-provided request data.
+/// * `sequences` - The collection of sequences.
+/// * `timeout` - The maximum amount of time to wait for a response from a peripheral.
+/// * `txs` - The collection of transmitters for sending messages into executor threads.
+pub fn post_sequences(
+    request: &Request,
+    sequences: &Sequences,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<Response> {
+    let data: SequenceCreate = json_input(request)?;
+    let steps = Vec::try_from(data)?;
+
+    let id = create_sequence(steps, sequences, timeout, txs)?;
+
+    let location = format!("/api/v0/sequences/{}", id);
+    let mut response = Response::json(&SequenceCreateResponse {
+        message: format!("Sequence successfully started. Location: {}", location),
+    });
+    response.status_code = 201;
+    response.headers.push(("Location".into(), location.into()));
+
+    Ok(response)
+}
+
+/// Converts the per-member results of a Group operation into the response data.
+///
+/// # Arguments
+///
+/// * `results` - The result of the operation on each member, indexed by Peripheral ID.
+fn group_attribute_response(
+    results: BTreeMap<usize, std::result::Result<Attribute, IntegrationsError>>,
+) -> Result<BTreeMap<usize, GroupAttributeReadUpdateResponse>> {
+    let mut response = BTreeMap::new();
+    for (id, result) in results {
+        let result = match result {
+            Ok(attr) => GroupAttributeReadUpdateResponse::Succeeded {
+                attribute: AttributeRead::try_from(attr)?,
+            },
+            Err(e) => GroupAttributeReadUpdateResponse::Failed {
+                http_status_code: status_from_reason(e.reason()),
+                message: e.message().to_owned(),
+            },
+        };
+        response.insert(id, result);
+    }
+
+    Ok(response)
+}
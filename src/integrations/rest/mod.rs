@@ -2,7 +2,7 @@
 mod errors;
 mod handlers;
 mod routes;
-mod schemas;
+pub(crate) mod schemas;
 
 pub use errors::{status_from_reason, RestIntegrationError};
 pub use routes::routes;
@@ -1,11 +1,19 @@
 //! The endpoints of the REST API.
-use std::sync::{Arc, RwLock};
+use std::{
+    sync::{Arc, RwLock},
+    time::Duration,
+};
 
 use log;
 use rouille::{router, Request, Response};
 
-use crate::init::TSLibrary;
+use crate::groups::Groups;
+use crate::init::Libraries;
+use crate::init::Store;
 use crate::init::Transmitters;
+use crate::rules::Rules;
+use crate::sampler::Samplers;
+use crate::sequences::Sequences;
 
 use super::handlers;
 
@@ -14,10 +22,28 @@ use super::handlers;
 /// # Arguments
 ///
 /// * `request` - The object containing the information concerning the client's request
+/// * `groups` The collection of groups of peripherals
 /// * `libs` The set of libraries that is currently open by the daemon
+/// * `rules` The collection of automation rules
+/// * `samplers` The collection of samplers that periodically read attribute values
+/// * `sequences` The collection of sequences that are run by the daemon
+/// * `store` The store that records the configuration of peripherals
+/// * `timeout` The maximum amount of time to wait for a response from a peripheral
+/// * `event_chunk_size` The size to which each message of an event stream is padded
 /// * `txs` The set of transmitters for sending messages into each peripheral thread
-#[allow(clippy::cognitive_complexity)]
-pub fn routes(request: &Request, libs: &[TSLibrary], txs: Arc<RwLock<Transmitters>>) -> Response {
+#[allow(clippy::cognitive_complexity, clippy::too_many_arguments)]
+pub fn routes(
+    request: &Request,
+    groups: &Groups,
+    libs: &Libraries,
+    rules: &Rules,
+    samplers: &Samplers,
+    sequences: &Sequences,
+    store: &Store,
+    timeout: Duration,
+    event_chunk_size: usize,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Response {
     router!(request,
 
             (GET) (/) => {
@@ -26,45 +52,195 @@ pub fn routes(request: &Request, libs: &[TSLibrary], txs: Arc<RwLock<Transmitter
                 Response::empty_204()
             },
 
+            (GET) (/api/v0/groups) => {
+                log::info!("GET /api/v0/groups");
+                handlers::get_groups(groups).unwrap_or_else(log_error)
+            },
+
+            (POST) (/api/v0/groups) => {
+                log::info!("POST /api/v0/groups");
+                handlers::post_groups(request, groups, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (GET) (/api/v0/groups/{id: usize}) => {
+                log::info!("GET /api/v0/groups/{}", id);
+                handlers::get_group(id, groups).unwrap_or_else(log_error)
+            },
+
+            (DELETE) (/api/v0/groups/{id: usize}) => {
+                log::info!("DELETE /api/v0/groups/{}", id);
+                handlers::delete_group(id, groups).unwrap_or_else(log_error)
+            },
+
+            (GET) (/api/v0/groups/{id: usize}/attributes/{attr_id: usize}) => {
+                log::info!("GET /api/v0/groups/{}/attributes/{}", id, attr_id);
+                handlers::get_group_attribute(id, attr_id, groups, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (PATCH) (/api/v0/groups/{id: usize}/attributes/{attr_id: usize}) => {
+                log::info!("PATCH /api/v0/groups/{}/attributes/{}", id, attr_id);
+                handlers::patch_group_attribute(request, id, attr_id, groups, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
             (GET) (/api/v0/libraries) => {
                 log::info!("GET /api/v0/libraries");
                 handlers::get_libraries(libs).unwrap_or_else(log_error)
             },
 
+            (POST) (/api/v0/libraries/rescan) => {
+                log::info!("POST /api/v0/libraries/rescan");
+                handlers::post_libraries_rescan(libs).unwrap_or_else(log_error)
+            },
+
             (GET) (/api/v0/libraries/{id: usize}) => {
                 log::info!("GET /api/v0/libraries/{}", id);
                 handlers::get_library(id, libs).unwrap_or_else(log_error)
             },
 
+            (DELETE) (/api/v0/libraries/{id: usize}) => {
+                log::info!("DELETE /api/v0/libraries/{}", id);
+                handlers::delete_library(id, libs).unwrap_or_else(log_error)
+            },
+
             (GET) (/api/v0/peripherals) => {
                 log::info!("GET /api/v0/peripherals");
-                handlers::get_peripherals(txs.clone()).unwrap_or_else(log_error)
+                handlers::get_peripherals(timeout, txs.clone()).unwrap_or_else(log_error)
             },
 
             (POST) (/api/v0/peripherals) => {
                 log::info!("POST /api/v0/peripherals");
-                handlers::post_peripherals(&request, libs, txs.clone()).unwrap_or_else(log_error)
+                handlers::post_peripherals(&request, libs, store, txs.clone()).unwrap_or_else(log_error)
             },
 
 
             (GET) (/api/v0/peripherals/{id: usize}) => {
                 log::info!("GET /api/v0/peripherals/{}", id);
-                handlers::get_peripheral(id, txs.clone()).unwrap_or_else(log_error)
+                handlers::get_peripheral(id, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (DELETE) (/api/v0/peripherals/{id: usize}) => {
+                log::info!("DELETE /api/v0/peripherals/{}", id);
+                handlers::delete_peripheral(id, samplers, store, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (POST) (/api/v0/peripherals/{id: usize}/actions/{action_id: usize}) => {
+                log::info!("POST /api/v0/peripherals/{}/actions/{}", id, action_id);
+                handlers::post_peripheral_action(request, id, action_id, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (POST) (/api/v0/peripherals/{id: usize}/deinit) => {
+                log::info!("POST /api/v0/peripherals/{}/deinit", id);
+                handlers::post_peripheral_deinit(id, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (POST) (/api/v0/peripherals/{id: usize}/init) => {
+                log::info!("POST /api/v0/peripherals/{}/init", id);
+                handlers::post_peripheral_init(id, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (POST) (/api/v0/peripherals/{id: usize}/restart) => {
+                log::info!("POST /api/v0/peripherals/{}/restart", id);
+                handlers::post_peripheral_restart(request, id, store, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (GET) (/api/v0/peripherals/{id: usize}/events) => {
+                log::info!("GET /api/v0/peripherals/{}/events", id);
+                handlers::get_peripheral_events(request, id, timeout, event_chunk_size, txs.clone()).unwrap_or_else(log_error)
             },
 
             (GET) (/api/v0/peripherals/{id: usize}/attributes) => {
                 log::info!("GET /api/v0/peripherals/{}/attributes", id);
-                handlers::get_peripheral_attributes(id, txs.clone()).unwrap_or_else(log_error)
+                handlers::get_peripheral_attributes(id, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (PATCH) (/api/v0/peripherals/{id: usize}/attributes) => {
+                log::info!("PATCH /api/v0/peripherals/{}/attributes", id);
+                handlers::patch_peripheral_attributes(request, id, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (GET) (/api/v0/peripherals/by-name/{name: String}/attributes/{attr_name: String}) => {
+                log::info!("GET /api/v0/peripherals/by-name/{}/attributes/{}", name, attr_name);
+                handlers::get_peripheral_attribute_by_name(&name, &attr_name, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (PATCH) (/api/v0/peripherals/by-name/{name: String}/attributes/{attr_name: String}) => {
+                log::info!("PATCH /api/v0/peripherals/by-name/{}/attributes/{}", name, attr_name);
+                handlers::patch_peripheral_attribute_by_name(request, &name, &attr_name, timeout, txs.clone()).unwrap_or_else(log_error)
             },
 
             (GET) (/api/v0/peripherals/{id: usize}/attributes/{attr_id: usize}) => {
                 log::info!("GET /api/v0/peripherals/{}/attributes/{}", id, attr_id);
-                handlers::get_peripheral_attribute(id, attr_id, txs.clone()).unwrap_or_else(log_error)
+                handlers::get_peripheral_attribute(id, attr_id, timeout, txs.clone()).unwrap_or_else(log_error)
             },
 
             (PATCH) (/api/v0/peripherals/{id: usize}/attributes/{attr_id: usize}) => {
                 log::info!("PATCH /api/v0/peripherals/{}/attributes/{}", id, attr_id);
-                handlers::patch_peripheral_attribute(&request, id, attr_id, txs).unwrap_or_else(log_error)
+                handlers::patch_peripheral_attribute(&request, id, attr_id, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (GET) (/api/v0/peripherals/{id: usize}/attributes/{attr_id: usize}/history) => {
+                log::info!("GET /api/v0/peripherals/{}/attributes/{}/history", id, attr_id);
+                handlers::get_peripheral_attribute_history(request, id, attr_id, samplers).unwrap_or_else(log_error)
+            },
+
+            (GET) (/api/v0/peripherals/{id: usize}/attributes/{attr_id: usize}/sampler) => {
+                log::info!("GET /api/v0/peripherals/{}/attributes/{}/sampler", id, attr_id);
+                handlers::get_peripheral_attribute_sampler(id, attr_id, samplers).unwrap_or_else(log_error)
+            },
+
+            (POST) (/api/v0/peripherals/{id: usize}/attributes/{attr_id: usize}/sampler) => {
+                log::info!("POST /api/v0/peripherals/{}/attributes/{}/sampler", id, attr_id);
+                handlers::post_peripheral_attribute_sampler(request, id, attr_id, samplers, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (DELETE) (/api/v0/peripherals/{id: usize}/attributes/{attr_id: usize}/sampler) => {
+                log::info!("DELETE /api/v0/peripherals/{}/attributes/{}/sampler", id, attr_id);
+                handlers::delete_peripheral_attribute_sampler(id, attr_id, samplers).unwrap_or_else(log_error)
+            },
+
+            (GET) (/api/v0/rules) => {
+                log::info!("GET /api/v0/rules");
+                handlers::get_rules(rules).unwrap_or_else(log_error)
+            },
+
+            (POST) (/api/v0/rules) => {
+                log::info!("POST /api/v0/rules");
+                handlers::post_rules(request, rules, timeout, txs.clone()).unwrap_or_else(log_error)
+            },
+
+            (GET) (/api/v0/rules/{id: usize}) => {
+                log::info!("GET /api/v0/rules/{}", id);
+                handlers::get_rule(id, rules).unwrap_or_else(log_error)
+            },
+
+            (DELETE) (/api/v0/rules/{id: usize}) => {
+                log::info!("DELETE /api/v0/rules/{}", id);
+                handlers::delete_rule(id, rules).unwrap_or_else(log_error)
+            },
+
+            (GET) (/api/v0/sequences) => {
+                log::info!("GET /api/v0/sequences");
+                handlers::get_sequences(sequences).unwrap_or_else(log_error)
+            },
+
+            (POST) (/api/v0/sequences) => {
+                log::info!("POST /api/v0/sequences");
+                handlers::post_sequences(request, sequences, timeout, txs).unwrap_or_else(log_error)
+            },
+
+            (GET) (/api/v0/sequences/{id: usize}) => {
+                log::info!("GET /api/v0/sequences/{}", id);
+                handlers::get_sequence(id, sequences).unwrap_or_else(log_error)
+            },
+
+            (DELETE) (/api/v0/sequences/{id: usize}) => {
+                log::info!("DELETE /api/v0/sequences/{}", id);
+                handlers::delete_sequence(id, sequences).unwrap_or_else(log_error)
+            },
+
+            (POST) (/api/v0/sequences/{id: usize}/cancel) => {
+                log::info!("POST /api/v0/sequences/{}/cancel", id);
+                handlers::post_sequence_cancel(id, sequences).unwrap_or_else(log_error)
             },
 
             _ => Response::empty_404()
@@ -5,6 +5,8 @@ use std::{
     fmt,
 };
 
+use base64::DecodeError;
+
 /// An error raised when schema conversions and/or validations fail.
 #[derive(Debug)]
 pub struct SchemaError {
@@ -12,6 +14,19 @@ pub struct SchemaError {
     side: Option<Box<dyn Error + 'static>>,
 }
 
+impl SchemaError {
+    /// Returns a new SchemaError that is caused by an invalid input.
+    ///
+    /// # Arguments
+    ///
+    /// * `message` - A description of the invalid input
+    pub fn new(message: &str) -> SchemaError {
+        SchemaError {
+            side: Some(message.into()),
+        }
+    }
+}
+
 impl Error for SchemaError {
     fn source(&self) -> Option<&(dyn Error + 'static)> {
         self.side.as_ref().map(|e| e.as_ref())
@@ -24,6 +39,14 @@ impl fmt::Display for SchemaError {
     }
 }
 
+impl From<DecodeError> for SchemaError {
+    fn from(error: DecodeError) -> SchemaError {
+        SchemaError {
+            side: Some(Box::new(error)),
+        }
+    }
+}
+
 impl From<IntoStringError> for SchemaError {
     fn from(error: IntoStringError) -> SchemaError {
         SchemaError {
@@ -14,14 +14,25 @@
 mod errors;
 
 use std::{
+    collections::BTreeSet,
     convert::{TryFrom, TryInto},
     ffi::CString,
+    time::Duration,
 };
 
 use serde::{Deserialize, Serialize};
 
-use crate::models::{
-    Attribute, AttributeBuilder, Library, Model, Peripheral, PeripheralBuilder, Value,
+use crate::{
+    init::Libraries,
+    integrations::{
+        find_library_id, Comparison, Condition, ErrorReason, Group, IntegrationsError, Reading,
+        Rule, RuleStatus, Sample, SamplerConfig, SequenceStatus, SetAttribute, State, Step,
+        UpdateMode,
+    },
+    models::{
+        Action, Attribute, AttributeBuilder, Library, Metadata, Model, Peripheral,
+        PeripheralBuilder, PeripheralState, Settable, Value,
+    },
 };
 
 use super::BASE_URL_PATH;
@@ -31,8 +42,12 @@ pub use errors::SchemaError;
 /// Data returned when a Peripheral Attribute is read.
 #[derive(Debug, Serialize)]
 pub struct AttributeRead {
+    alias: String,
+    choices: Vec<String>,
     id: usize,
+    metadata: AttributeMetadataRead,
     name: String,
+    settable: AttributeSettableRead,
     value: ValueReadUpdate,
 }
 
@@ -41,19 +56,200 @@ impl TryFrom<Attribute> for AttributeRead {
 
     fn try_from(attr: Attribute) -> Result<AttributeRead, Self::Error> {
         Ok(AttributeRead {
+            alias: attr.alias().to_owned(),
+            choices: attr.choices().to_vec(),
             id: attr.id(),
+            metadata: attr.metadata().clone().into(),
             name: attr.name().to_owned(),
+            settable: attr.settable().into(),
             value: attr.value().clone().try_into()?,
         })
     }
 }
 
+/// Data returned in a request for the units, range, step and description of an Attribute.
+#[derive(Debug, Serialize)]
+pub struct AttributeMetadataRead {
+    description: Option<String>,
+    max: Option<f64>,
+    min: Option<f64>,
+    step: Option<f64>,
+    units: Option<String>,
+}
+
+impl From<Metadata> for AttributeMetadataRead {
+    fn from(metadata: Metadata) -> AttributeMetadataRead {
+        AttributeMetadataRead {
+            description: metadata.description,
+            max: metadata.max,
+            min: metadata.min,
+            step: metadata.step,
+            units: metadata.units,
+        }
+    }
+}
+
+/// Data returned in a request for the recorded samples of an Attribute.
+#[derive(Debug, Serialize)]
+pub struct AttributeSampleRead {
+    timestamp: u64,
+    value: ValueReadUpdate,
+}
+
+impl TryFrom<Sample> for AttributeSampleRead {
+    type Error = SchemaError;
+
+    fn try_from(sample: Sample) -> Result<AttributeSampleRead, Self::Error> {
+        Ok(AttributeSampleRead {
+            timestamp: sample.timestamp,
+            value: sample.value.try_into()?,
+        })
+    }
+}
+
+/// Input data that is used to start sampling an Attribute.
+#[derive(Debug, Deserialize)]
+pub struct AttributeSamplerCreate {
+    interval_ms: u64,
+    record: Option<bool>,
+}
+
+impl From<AttributeSamplerCreate> for SamplerConfig {
+    fn from(data: AttributeSamplerCreate) -> SamplerConfig {
+        SamplerConfig {
+            interval: Duration::from_millis(data.interval_ms),
+            record: data.record.unwrap_or(false),
+        }
+    }
+}
+
+/// Data returned in a request for the settings of the sampler of an Attribute.
+#[derive(Debug, Serialize)]
+pub struct AttributeSamplerRead {
+    interval_ms: u64,
+    record: bool,
+}
+
+impl From<SamplerConfig> for AttributeSamplerRead {
+    fn from(config: SamplerConfig) -> AttributeSamplerRead {
+        AttributeSamplerRead {
+            interval_ms: config.interval.as_millis() as u64,
+            record: config.record,
+        }
+    }
+}
+
+/// Data returned in a request for whether an Attribute may be set in each lifecycle phase.
+#[derive(Debug, Serialize)]
+pub struct AttributeSettableRead {
+    init: bool,
+    run: bool,
+}
+
+impl From<Settable> for AttributeSettableRead {
+    fn from(settable: Settable) -> AttributeSettableRead {
+        AttributeSettableRead {
+            init: settable.init,
+            run: settable.run,
+        }
+    }
+}
+
+/// The result of reading or updating an Attribute of a single member of a Group.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum GroupAttributeReadUpdateResponse {
+    /// The Attribute was read or updated.
+    Succeeded { attribute: AttributeRead },
+
+    /// The Attribute could not be read or updated.
+    Failed {
+        http_status_code: u16,
+        message: String,
+    },
+}
+
+/// Input data that is used to create a new Group.
+#[derive(Debug, Deserialize)]
+pub struct GroupCreate {
+    pub name: String,
+    pub peripheral_ids: BTreeSet<usize>,
+}
+
+/// Data returned in a response to a request that resulted in the creation of a Group.
+#[derive(Debug, Serialize)]
+pub struct GroupCreateResponse {
+    pub message: String,
+}
+
+/// Data returned when a Group is read.
+#[derive(Debug, Serialize)]
+pub struct GroupRead {
+    id: usize,
+    name: String,
+    peripheral_ids: BTreeSet<usize>,
+}
+
+impl From<Group> for GroupRead {
+    fn from(group: Group) -> GroupRead {
+        GroupRead {
+            id: group.id,
+            name: group.name,
+            peripheral_ids: group.peripheral_ids,
+        }
+    }
+}
+
+/// Data returned in a request for a Library Action.
+#[derive(Debug, Serialize)]
+pub struct LibraryActionRead {
+    arguments: Vec<LibraryActionArgumentRead>,
+    id: usize,
+    name: String,
+}
+
+impl TryFrom<Action> for LibraryActionRead {
+    type Error = SchemaError;
+
+    fn try_from(action: Action) -> Result<LibraryActionRead, Self::Error> {
+        let arguments = action
+            .arguments()
+            .iter()
+            .map(|arg| {
+                Ok(LibraryActionArgumentRead {
+                    name: arg.name.clone(),
+                    value: arg.value.clone().try_into()?,
+                })
+            })
+            .collect::<Result<Vec<LibraryActionArgumentRead>, SchemaError>>()?;
+
+        Ok(LibraryActionRead {
+            arguments,
+            id: action.id(),
+            name: action.name().to_owned(),
+        })
+    }
+}
+
+/// Data returned in a request for an argument of a Library Action.
+///
+/// The value is an example whose type determines the type of the argument.
+#[derive(Debug, Serialize)]
+pub struct LibraryActionArgumentRead {
+    name: String,
+    value: ValueReadUpdate,
+}
+
 /// Data returned in a request for a Library Attribute.
 #[derive(Debug, Serialize)]
 pub struct LibraryAttributeRead {
+    alias: String,
+    choices: Vec<String>,
     id: usize,
+    metadata: AttributeMetadataRead,
     name: String,
     pre_init: bool,
+    settable: AttributeSettableRead,
     value: ValueReadUpdate,
 }
 
@@ -62,9 +258,13 @@ impl TryFrom<Attribute> for LibraryAttributeRead {
 
     fn try_from(attr: Attribute) -> Result<LibraryAttributeRead, Self::Error> {
         Ok(LibraryAttributeRead {
+            alias: attr.alias().to_owned(),
+            choices: attr.choices().to_vec(),
             id: attr.id(),
+            metadata: attr.metadata().clone().into(),
             name: attr.name().to_owned(),
             pre_init: attr.pre_init(),
+            settable: attr.settable().into(),
             value: attr.value().clone().try_into()?,
         })
     }
@@ -73,9 +273,13 @@ impl TryFrom<Attribute> for LibraryAttributeRead {
 /// Data returned in a request for a Library or Libraries.
 #[derive(Debug, Serialize)]
 pub struct LibraryRead {
+    abi_version: u32,
+    actions: Vec<LibraryActionRead>,
     attributes: Vec<LibraryAttributeRead>,
     id: usize,
     name: String,
+    path: String,
+    version: Option<String>,
 }
 
 impl TryFrom<Library> for LibraryRead {
@@ -89,10 +293,55 @@ impl TryFrom<Library> for LibraryRead {
             .map(|attr| attr.try_into())
             .collect::<Result<Vec<LibraryAttributeRead>, SchemaError>>()?;
 
+        let actions: Vec<LibraryActionRead> = lib
+            .actions()
+            .values()
+            .cloned()
+            .map(|action| action.try_into())
+            .collect::<Result<Vec<LibraryActionRead>, SchemaError>>()?;
+
         Ok(LibraryRead {
+            abi_version: lib.abi_version(),
+            actions,
             attributes: attrs,
             id: lib.id(),
             name: lib.name().to_owned(),
+            path: lib.path().to_string_lossy().into_owned(),
+            version: lib.version().map(|version| version.to_string()),
+        })
+    }
+}
+
+/// Input data that is used to invoke a peripheral action.
+#[derive(Debug, Deserialize)]
+pub struct PeripheralActionCreate {
+    arguments: Option<Vec<ValueReadUpdate>>,
+}
+
+impl TryFrom<PeripheralActionCreate> for Vec<Value> {
+    type Error = SchemaError;
+
+    fn try_from(data: PeripheralActionCreate) -> Result<Vec<Value>, Self::Error> {
+        data.arguments
+            .unwrap_or_default()
+            .into_iter()
+            .map(Value::try_from)
+            .collect()
+    }
+}
+
+/// Data returned in a response to a request that invoked a Peripheral Action.
+#[derive(Debug, Serialize)]
+pub struct PeripheralActionCreateResponse {
+    result: ValueReadUpdate,
+}
+
+impl TryFrom<Value> for PeripheralActionCreateResponse {
+    type Error = SchemaError;
+
+    fn try_from(value: Value) -> Result<PeripheralActionCreateResponse, Self::Error> {
+        Ok(PeripheralActionCreateResponse {
+            result: value.try_into()?,
         })
     }
 }
@@ -112,15 +361,55 @@ enum PeripheralAttributeCreate {
 
     #[serde(rename(serialize = "unsigned_integer", deserialize = "unsigned_integer"))]
     Uint { id: usize, value: u32 },
+
+    #[serde(rename(serialize = "boolean", deserialize = "boolean"))]
+    Bool { id: usize, value: bool },
+
+    #[serde(rename(serialize = "double_array", deserialize = "double_array"))]
+    DoubleArray { id: usize, value: Vec<f64> },
+
+    #[serde(rename(serialize = "integer_array", deserialize = "integer_array"))]
+    IntArray { id: usize, value: Vec<i32> },
+
+    /// Raw bytes are encoded as a base64 string.
+    #[serde(rename(serialize = "bytes", deserialize = "bytes"))]
+    Bytes { id: usize, value: String },
+
+    #[serde(rename(serialize = "integer64", deserialize = "integer64"))]
+    Int64 {
+        id: usize,
+        #[serde(with = "int64")]
+        value: i64,
+    },
+
+    #[serde(rename(serialize = "unsigned_integer64", deserialize = "unsigned_integer64"))]
+    Uint64 {
+        id: usize,
+        #[serde(with = "int64")]
+        value: u64,
+    },
+
+    #[serde(rename(serialize = "float", deserialize = "float"))]
+    Float { id: usize, value: f32 },
 }
 
 impl TryFrom<PeripheralAttributeCreate> for AttributeBuilder {
     type Error = SchemaError;
 
     fn try_from(data: PeripheralAttributeCreate) -> Result<AttributeBuilder, Self::Error> {
+        let (id, value) = <(usize, Value)>::try_from(data)?;
+
+        Ok(AttributeBuilder::new(id, value))
+    }
+}
+
+impl TryFrom<PeripheralAttributeCreate> for (usize, Value) {
+    type Error = SchemaError;
+
+    fn try_from(data: PeripheralAttributeCreate) -> Result<(usize, Value), Self::Error> {
         use PeripheralAttributeCreate::*;
 
-        let (id, value) = match data {
+        Ok(match data {
             Double { id, value } => (id, Value::Double { value }),
             Int { id, value } => (id, Value::Int { value }),
             String { id, value } => (
@@ -130,25 +419,128 @@ impl TryFrom<PeripheralAttributeCreate> for AttributeBuilder {
                 },
             ),
             Uint { id, value } => (id, Value::Uint { value }),
-        };
+            Bool { id, value } => (id, Value::Bool { value }),
+            DoubleArray { id, value } => (id, Value::DoubleArray { value }),
+            IntArray { id, value } => (id, Value::IntArray { value }),
+            Bytes { id, value } => (
+                id,
+                Value::Bytes {
+                    value: base64::decode(&value)?,
+                },
+            ),
+            Int64 { id, value } => (id, Value::Int64 { value }),
+            Uint64 { id, value } => (id, Value::Uint64 { value }),
+            Float { id, value } => (id, Value::Float { value }),
+        })
+    }
+}
 
-        Ok(AttributeBuilder::new(id, value))
+/// Input data that is used to update the values of several peripheral attributes at once.
+#[derive(Debug, Deserialize)]
+pub struct PeripheralAttributesUpdate {
+    atomic: Option<bool>,
+    attributes: Vec<PeripheralAttributeCreate>,
+    stop_on_error: Option<bool>,
+}
+
+impl PeripheralAttributesUpdate {
+    /// Returns what should happen after a value cannot be set.
+    ///
+    /// Atomic updates also stop at the first failure.
+    pub fn mode(&self) -> UpdateMode {
+        if self.atomic.unwrap_or(false) {
+            UpdateMode::Atomic
+        } else if self.stop_on_error.unwrap_or(false) {
+            UpdateMode::StopOnError
+        } else {
+            UpdateMode::Continue
+        }
     }
 }
 
+impl TryFrom<PeripheralAttributesUpdate> for Vec<(usize, Value)> {
+    type Error = SchemaError;
+
+    fn try_from(data: PeripheralAttributesUpdate) -> Result<Vec<(usize, Value)>, Self::Error> {
+        data.attributes
+            .into_iter()
+            .map(<(usize, Value)>::try_from)
+            .collect()
+    }
+}
+
+/// The result of updating a single attribute's value in a request that updates several of them.
+#[derive(Debug, Serialize)]
+#[serde(tag = "status", rename_all = "snake_case")]
+pub enum PeripheralAttributeUpdateResponse {
+    /// The value was set.
+    Applied { id: usize, attribute: AttributeRead },
+
+    /// The value could not be set.
+    Failed {
+        id: usize,
+        http_status_code: u16,
+        message: String,
+    },
+
+    /// The value was set and then restored to the attribute's previous value.
+    RolledBack { id: usize, attribute: AttributeRead },
+
+    /// The value was set, but the attribute's previous value could not be restored.
+    RollbackFailed {
+        id: usize,
+        http_status_code: u16,
+        message: String,
+    },
+
+    /// The value was not set because a preceding one failed.
+    Skipped { id: usize },
+}
+
 /// Input data that is used to create a new peripheral.
 #[derive(Debug, Deserialize)]
 pub struct PeripheralCreate {
     attributes: Option<Vec<PeripheralAttributeCreate>>,
-    library_id: usize,
+    isolated: Option<bool>,
+    library: Option<String>,
+    library_id: Option<usize>,
     name: String,
 }
 
+impl PeripheralCreate {
+    /// Sets the ID of the library from the name and version requirement of the library, if given.
+    ///
+    /// # Arguments
+    ///
+    /// * `libs` - The collection of plugin libraries known to KPAL.
+    pub fn find_library(&mut self, libs: &Libraries) -> Result<(), IntegrationsError> {
+        let name = match &self.library {
+            Some(name) => name,
+            None => return Ok(()),
+        };
+
+        if self.library_id.is_some() {
+            return Err(IntegrationsError::new(
+                "Only one of library and library_id may be provided".to_string(),
+                ErrorReason::UnprocessableRequest,
+                None,
+            ));
+        }
+        self.library_id = Some(find_library_id(name, libs)?);
+
+        Ok(())
+    }
+}
+
 impl TryFrom<PeripheralCreate> for PeripheralBuilder {
     type Error = SchemaError;
 
     fn try_from(data: PeripheralCreate) -> Result<PeripheralBuilder, Self::Error> {
-        let mut builder = PeripheralBuilder::new(data.library_id, data.name);
+        let library_id = data
+            .library_id
+            .ok_or_else(|| SchemaError::new("One of library and library_id must be provided"))?;
+        let mut builder = PeripheralBuilder::new(library_id, data.name)
+            .set_isolated(data.isolated.unwrap_or(false));
 
         if let Some(attrs) = data.attributes {
             for attr in attrs {
@@ -180,13 +572,33 @@ pub struct PeripheralCreateResponse {
     pub message: String,
 }
 
+/// Input data that is used to restart a peripheral.
+#[derive(Debug, Deserialize)]
+pub struct PeripheralRestartCreate {
+    attributes: Option<Vec<PeripheralAttributeCreate>>,
+}
+
+impl TryFrom<PeripheralRestartCreate> for Vec<AttributeBuilder> {
+    type Error = SchemaError;
+
+    fn try_from(data: PeripheralRestartCreate) -> Result<Vec<AttributeBuilder>, Self::Error> {
+        data.attributes
+            .unwrap_or_default()
+            .into_iter()
+            .map(AttributeBuilder::try_from)
+            .collect()
+    }
+}
+
 /// Data returned when a Peripheral is read.
 #[derive(Debug, Serialize)]
 pub struct PeripheralRead {
     attributes: Vec<PeripheralAttributeRead>,
     id: usize,
+    isolated: bool,
     library_id: usize,
     name: String,
+    state: PeripheralStateRead,
 }
 
 impl From<Peripheral> for PeripheralRead {
@@ -210,12 +622,413 @@ impl From<Peripheral> for PeripheralRead {
         PeripheralRead {
             attributes: attrs,
             id: periph.id(),
+            isolated: periph.isolated(),
             library_id: periph.library_id(),
             name: periph.name().to_owned(),
+            state: periph.state().into(),
+        }
+    }
+}
+
+/// The state of the plugin of a Peripheral, returned when the Peripheral is read.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum PeripheralStateRead {
+    Init,
+    Run,
+    Failed,
+}
+
+impl From<PeripheralState> for PeripheralStateRead {
+    fn from(state: PeripheralState) -> PeripheralStateRead {
+        match state {
+            PeripheralState::Init => PeripheralStateRead::Init,
+            PeripheralState::Run => PeripheralStateRead::Run,
+            PeripheralState::Failed => PeripheralStateRead::Failed,
         }
     }
 }
 
+/// Input data that describes the action of a Rule, also returned when a Rule is read.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RuleActionCreateRead {
+    attribute_id: usize,
+    peripheral_id: usize,
+    value: ValueReadUpdate,
+}
+
+impl TryFrom<RuleActionCreateRead> for SetAttribute {
+    type Error = SchemaError;
+
+    fn try_from(data: RuleActionCreateRead) -> Result<SetAttribute, Self::Error> {
+        Ok(SetAttribute {
+            peripheral_id: data.peripheral_id,
+            attribute_id: data.attribute_id,
+            value: data.value.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<SetAttribute> for RuleActionCreateRead {
+    type Error = SchemaError;
+
+    fn try_from(action: SetAttribute) -> Result<RuleActionCreateRead, Self::Error> {
+        Ok(RuleActionCreateRead {
+            attribute_id: action.attribute_id,
+            peripheral_id: action.peripheral_id,
+            value: action.value.try_into()?,
+        })
+    }
+}
+
+/// Input data that describes the comparison of a Rule's condition, also returned when a Rule is
+/// read.
+#[derive(Debug, Deserialize, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum RuleComparisonCreateRead {
+    Above {
+        threshold: f64,
+        #[serde(default)]
+        hysteresis: f64,
+    },
+    Below {
+        threshold: f64,
+        #[serde(default)]
+        hysteresis: f64,
+    },
+    Equal {
+        value: ValueReadUpdate,
+    },
+}
+
+impl TryFrom<RuleComparisonCreateRead> for Comparison {
+    type Error = SchemaError;
+
+    fn try_from(data: RuleComparisonCreateRead) -> Result<Comparison, Self::Error> {
+        use RuleComparisonCreateRead::*;
+
+        Ok(match data {
+            Above {
+                threshold,
+                hysteresis,
+            } => Comparison::Above {
+                threshold,
+                hysteresis,
+            },
+            Below {
+                threshold,
+                hysteresis,
+            } => Comparison::Below {
+                threshold,
+                hysteresis,
+            },
+            Equal { value } => Comparison::Equal {
+                value: value.try_into()?,
+            },
+        })
+    }
+}
+
+impl TryFrom<Comparison> for RuleComparisonCreateRead {
+    type Error = SchemaError;
+
+    fn try_from(comparison: Comparison) -> Result<RuleComparisonCreateRead, Self::Error> {
+        use RuleComparisonCreateRead::*;
+
+        Ok(match comparison {
+            Comparison::Above {
+                threshold,
+                hysteresis,
+            } => Above {
+                threshold,
+                hysteresis,
+            },
+            Comparison::Below {
+                threshold,
+                hysteresis,
+            } => Below {
+                threshold,
+                hysteresis,
+            },
+            Comparison::Equal { value } => Equal {
+                value: value.try_into()?,
+            },
+        })
+    }
+}
+
+/// Input data that describes the condition of a Rule, also returned when a Rule is read.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct RuleConditionCreateRead {
+    attribute_id: usize,
+    peripheral_id: usize,
+
+    #[serde(flatten)]
+    comparison: RuleComparisonCreateRead,
+}
+
+impl TryFrom<RuleConditionCreateRead> for Condition {
+    type Error = SchemaError;
+
+    fn try_from(data: RuleConditionCreateRead) -> Result<Condition, Self::Error> {
+        Ok(Condition {
+            peripheral_id: data.peripheral_id,
+            attribute_id: data.attribute_id,
+            comparison: data.comparison.try_into()?,
+        })
+    }
+}
+
+impl TryFrom<Condition> for RuleConditionCreateRead {
+    type Error = SchemaError;
+
+    fn try_from(condition: Condition) -> Result<RuleConditionCreateRead, Self::Error> {
+        Ok(RuleConditionCreateRead {
+            attribute_id: condition.attribute_id,
+            peripheral_id: condition.peripheral_id,
+            comparison: condition.comparison.try_into()?,
+        })
+    }
+}
+
+/// Input data that is used to create a new Rule.
+#[derive(Debug, Deserialize)]
+pub struct RuleCreate {
+    action: RuleActionCreateRead,
+    condition: RuleConditionCreateRead,
+    poll_interval_ms: Option<u64>,
+}
+
+impl TryFrom<RuleCreate> for Rule {
+    type Error = SchemaError;
+
+    fn try_from(data: RuleCreate) -> Result<Rule, Self::Error> {
+        Ok(Rule {
+            action: data.action.try_into()?,
+            condition: data.condition.try_into()?,
+            poll_interval: data.poll_interval_ms.map(Duration::from_millis),
+        })
+    }
+}
+
+/// Data returned in a response to a request that resulted in the creation of a Rule.
+#[derive(Debug, Serialize)]
+pub struct RuleCreateResponse {
+    pub message: String,
+}
+
+/// Data returned when a Rule is read.
+#[derive(Debug, Serialize)]
+pub struct RuleRead {
+    action: RuleActionCreateRead,
+    active: bool,
+    condition: RuleConditionCreateRead,
+    error: Option<String>,
+    fired: u64,
+    id: usize,
+    last_fired: Option<u64>,
+    poll_interval_ms: Option<u64>,
+}
+
+impl TryFrom<RuleStatus> for RuleRead {
+    type Error = SchemaError;
+
+    fn try_from(status: RuleStatus) -> Result<RuleRead, Self::Error> {
+        Ok(RuleRead {
+            action: status.rule.action.try_into()?,
+            active: status.active,
+            condition: status.rule.condition.try_into()?,
+            error: status.error,
+            fired: status.fired,
+            id: status.id,
+            last_fired: status.last_fired,
+            poll_interval_ms: status
+                .rule
+                .poll_interval
+                .map(|interval| interval.as_millis() as u64),
+        })
+    }
+}
+
+/// Input data that is used to start a new Sequence.
+#[derive(Debug, Deserialize)]
+pub struct SequenceCreate {
+    steps: Vec<SequenceStepCreate>,
+}
+
+impl TryFrom<SequenceCreate> for Vec<Step> {
+    type Error = SchemaError;
+
+    fn try_from(data: SequenceCreate) -> Result<Vec<Step>, Self::Error> {
+        data.steps.into_iter().map(Step::try_from).collect()
+    }
+}
+
+/// Data returned in a response to a request that resulted in the creation of a Sequence.
+#[derive(Debug, Serialize)]
+pub struct SequenceCreateResponse {
+    pub message: String,
+}
+
+/// Data returned when a Sequence is read.
+#[derive(Debug, Serialize)]
+pub struct SequenceRead {
+    error: Option<String>,
+    finished: Option<u64>,
+    id: usize,
+    readings: Vec<SequenceReadingRead>,
+    started: u64,
+    state: SequenceStateRead,
+    steps_run: usize,
+}
+
+impl TryFrom<SequenceStatus> for SequenceRead {
+    type Error = SchemaError;
+
+    fn try_from(status: SequenceStatus) -> Result<SequenceRead, Self::Error> {
+        Ok(SequenceRead {
+            error: status.error,
+            finished: status.finished,
+            id: status.id,
+            readings: status
+                .readings
+                .into_iter()
+                .map(SequenceReadingRead::try_from)
+                .collect::<Result<Vec<SequenceReadingRead>, SchemaError>>()?,
+            started: status.started,
+            state: status.state.into(),
+            steps_run: status.steps_run,
+        })
+    }
+}
+
+/// Data returned in a request for the values that were read by a Sequence.
+#[derive(Debug, Serialize)]
+pub struct SequenceReadingRead {
+    attribute_id: usize,
+    peripheral_id: usize,
+    timestamp: u64,
+    value: ValueReadUpdate,
+}
+
+impl TryFrom<Reading> for SequenceReadingRead {
+    type Error = SchemaError;
+
+    fn try_from(reading: Reading) -> Result<SequenceReadingRead, Self::Error> {
+        Ok(SequenceReadingRead {
+            attribute_id: reading.attribute_id,
+            peripheral_id: reading.peripheral_id,
+            timestamp: reading.timestamp,
+            value: reading.value.try_into()?,
+        })
+    }
+}
+
+/// Data returned in a request for the state of a Sequence.
+#[derive(Debug, Serialize)]
+#[serde(rename_all = "snake_case")]
+pub enum SequenceStateRead {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+impl From<State> for SequenceStateRead {
+    fn from(state: State) -> SequenceStateRead {
+        match state {
+            State::Running => SequenceStateRead::Running,
+            State::Completed => SequenceStateRead::Completed,
+            State::Cancelled => SequenceStateRead::Cancelled,
+            State::Failed => SequenceStateRead::Failed,
+        }
+    }
+}
+
+/// Input data that describes a single step of a Sequence.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum SequenceStepCreate {
+    Get {
+        peripheral_id: usize,
+        attribute_id: usize,
+    },
+    Invoke {
+        peripheral_id: usize,
+        action_id: usize,
+        arguments: Option<Vec<ValueReadUpdate>>,
+    },
+    Loop {
+        count: usize,
+        steps: Vec<SequenceStepCreate>,
+    },
+    Set {
+        peripheral_id: usize,
+        attribute_id: usize,
+        value: ValueReadUpdate,
+    },
+    Wait {
+        duration_ms: u64,
+    },
+}
+
+impl TryFrom<SequenceStepCreate> for Step {
+    type Error = SchemaError;
+
+    fn try_from(data: SequenceStepCreate) -> Result<Step, Self::Error> {
+        use SequenceStepCreate::*;
+
+        Ok(match data {
+            Get {
+                peripheral_id,
+                attribute_id,
+            } => Step::Get {
+                peripheral_id,
+                attribute_id,
+            },
+            Invoke {
+                peripheral_id,
+                action_id,
+                arguments,
+            } => Step::Invoke {
+                peripheral_id,
+                action_id,
+                arguments: arguments
+                    .unwrap_or_default()
+                    .into_iter()
+                    .map(Value::try_from)
+                    .collect::<Result<Vec<Value>, SchemaError>>()?,
+            },
+            Loop { count, steps } => {
+                // An empty loop would never check whether its sequence was cancelled.
+                if steps.is_empty() {
+                    return Err(SchemaError::new("A loop must contain at least one step"));
+                }
+
+                Step::Loop {
+                    count,
+                    steps: steps
+                        .into_iter()
+                        .map(Step::try_from)
+                        .collect::<Result<Vec<Step>, SchemaError>>()?,
+                }
+            }
+            Set {
+                peripheral_id,
+                attribute_id,
+                value,
+            } => Step::Set {
+                peripheral_id,
+                attribute_id,
+                value: value.try_into()?,
+            },
+            Wait { duration_ms } => Step::Wait {
+                duration: Duration::from_millis(duration_ms),
+            },
+        })
+    }
+}
+
 /// Data returned in a request for a Value or used to update an attribute's value.
 #[derive(Debug, Deserialize, Serialize)]
 #[serde(tag = "type", content = "value")]
@@ -231,6 +1044,34 @@ pub enum ValueReadUpdate {
 
     #[serde(rename(deserialize = "unsigned_integer", serialize = "unsigned_integer"))]
     Uint(u32),
+
+    #[serde(rename(deserialize = "boolean", serialize = "boolean"))]
+    Bool(bool),
+
+    #[serde(rename(deserialize = "double_array", serialize = "double_array"))]
+    DoubleArray(Vec<f64>),
+
+    #[serde(rename(deserialize = "integer_array", serialize = "integer_array"))]
+    IntArray(Vec<i32>),
+
+    /// Raw bytes are encoded as a base64 string.
+    #[serde(rename(deserialize = "bytes", serialize = "bytes"))]
+    Bytes(String),
+
+    #[serde(
+        rename(deserialize = "integer64", serialize = "integer64"),
+        with = "int64"
+    )]
+    Int64(i64),
+
+    #[serde(
+        rename(deserialize = "unsigned_integer64", serialize = "unsigned_integer64"),
+        with = "int64"
+    )]
+    Uint64(u64),
+
+    #[serde(rename(deserialize = "float", serialize = "float"))]
+    Float(f32),
 }
 
 impl TryFrom<Value> for ValueReadUpdate {
@@ -245,6 +1086,13 @@ impl TryFrom<Value> for ValueReadUpdate {
                 ValueReadUpdate::String(string)
             }
             Value::Uint { value, .. } => ValueReadUpdate::Uint(value),
+            Value::Bool { value, .. } => ValueReadUpdate::Bool(value),
+            Value::DoubleArray { value, .. } => ValueReadUpdate::DoubleArray(value),
+            Value::IntArray { value, .. } => ValueReadUpdate::IntArray(value),
+            Value::Bytes { value, .. } => ValueReadUpdate::Bytes(base64::encode(&value)),
+            Value::Int64 { value, .. } => ValueReadUpdate::Int64(value),
+            Value::Uint64 { value, .. } => ValueReadUpdate::Uint64(value),
+            Value::Float { value, .. } => ValueReadUpdate::Float(value),
         };
 
         Ok(value)
@@ -264,8 +1112,103 @@ impl TryFrom<ValueReadUpdate> for Value {
                 value: CString::new(value)?,
             },
             Uint(value) => Value::Uint { value },
+            Bool(value) => Value::Bool { value },
+            DoubleArray(value) => Value::DoubleArray { value },
+            IntArray(value) => Value::IntArray { value },
+            Bytes(value) => Value::Bytes {
+                value: base64::decode(&value)?,
+            },
+            Int64(value) => Value::Int64 { value },
+            Uint64(value) => Value::Uint64 { value },
+            Float(value) => Value::Float { value },
         };
 
         Ok(value)
     }
 }
+
+/// Serialization of 64-bit integers.
+///
+/// Many JSON parsers, including JavaScript's, store all numbers as doubles and cannot represent
+/// integers larger than 2^53 exactly. 64-bit integers are therefore serialized as decimal strings.
+/// Both strings and JSON numbers are accepted as input; numbers that are not integers or that do
+/// not fit in the target type are rejected rather than rounded.
+mod int64 {
+    use std::{fmt::Display, str::FromStr};
+
+    use serde::{de, Deserialize, Deserializer, Serializer};
+
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum StringOrNumber<T> {
+        String(String),
+        Number(T),
+    }
+
+    pub fn serialize<T: Display, S: Serializer>(
+        value: &T,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(value)
+    }
+
+    pub fn deserialize<'de, T, D>(deserializer: D) -> Result<T, D::Error>
+    where
+        T: Deserialize<'de> + FromStr,
+        T::Err: Display,
+        D: Deserializer<'de>,
+    {
+        match StringOrNumber::deserialize(deserializer)? {
+            StringOrNumber::String(string) => string.parse().map_err(de::Error::custom),
+            StringOrNumber::Number(number) => Ok(number),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_int64_value_precision() {
+        let cases = vec![
+            (r#"{"type":"integer64","value":9007199254740993}"#, true),
+            (
+                r#"{"type":"integer64","value":"-9223372036854775808"}"#,
+                true,
+            ),
+            (
+                r#"{"type":"unsigned_integer64","value":"18446744073709551615"}"#,
+                true,
+            ),
+            (r#"{"type":"integer64","value":9007199254740993.0}"#, false),
+            (r#"{"type":"unsigned_integer64","value":-1}"#, false),
+            (
+                r#"{"type":"unsigned_integer64","value":"18446744073709551616"}"#,
+                false,
+            ),
+        ];
+
+        for (json, valid) in cases {
+            let result: Result<ValueReadUpdate, _> = serde_json::from_str(json);
+            assert_eq!(result.is_ok(), valid, "Unexpected result for {}", json);
+        }
+
+        let value = ValueReadUpdate::try_from(Value::Uint64 { value: u64::MAX }).unwrap();
+        assert_eq!(
+            serde_json::to_string(&value).unwrap(),
+            r#"{"type":"unsigned_integer64","value":"18446744073709551615"}"#
+        );
+    }
+
+    #[test]
+    fn test_empty_loops_are_rejected() {
+        let json = r#"{"type":"loop","count":18446744073709551615,"steps":[]}"#;
+        let step: SequenceStepCreate = serde_json::from_str(json).unwrap();
+        assert!(Step::try_from(step).is_err());
+
+        let json = r#"{"type":"loop","count":2,"steps":[{"type":"wait","duration_ms":1}]}"#;
+        let step: SequenceStepCreate = serde_json::from_str(json).unwrap();
+        assert!(Step::try_from(step).is_ok());
+    }
+}
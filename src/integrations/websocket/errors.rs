@@ -0,0 +1,90 @@
+use std::{boxed::Box, error::Error, fmt, io};
+
+use super::super::rest::schemas::SchemaError;
+
+use crate::integrations::{ErrorReason, IntegrationsError};
+
+/// An error that is raised when the websocket integration fails to process a request.
+#[derive(Debug)]
+pub struct WebsocketIntegrationError {
+    /// The message to return to the client.
+    pub message: String,
+
+    /// The reason for the error that is returned to the client.
+    pub reason: &'static str,
+
+    /// The cause of the error, if any.
+    side: Option<Box<dyn Error + 'static>>,
+}
+
+impl Error for WebsocketIntegrationError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.side.as_ref().map(|e| e.as_ref())
+    }
+}
+
+impl fmt::Display for WebsocketIntegrationError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "WebsocketIntegrationError {{ reason: {}, message: {}, side: {:?} }}",
+            self.reason,
+            self.message,
+            self.source()
+        )
+    }
+}
+
+impl From<IntegrationsError> for WebsocketIntegrationError {
+    fn from(error: IntegrationsError) -> WebsocketIntegrationError {
+        WebsocketIntegrationError {
+            message: format!("Error from the KPAL core API: {}", error.message()),
+            reason: reason_from_error(error.reason()),
+            side: Some(Box::new(error)),
+        }
+    }
+}
+
+impl From<io::Error> for WebsocketIntegrationError {
+    fn from(error: io::Error) -> WebsocketIntegrationError {
+        WebsocketIntegrationError {
+            message: format!("Websocket server error: {}", error),
+            reason: "internal_error",
+            side: Some(Box::new(error)),
+        }
+    }
+}
+
+impl From<SchemaError> for WebsocketIntegrationError {
+    fn from(error: SchemaError) -> WebsocketIntegrationError {
+        WebsocketIntegrationError {
+            message: format!(
+                "Error processing data in the websocket integration: {}",
+                error
+            ),
+            reason: "unprocessable_request",
+            side: Some(Box::new(error)),
+        }
+    }
+}
+
+impl From<serde_json::Error> for WebsocketIntegrationError {
+    fn from(error: serde_json::Error) -> WebsocketIntegrationError {
+        WebsocketIntegrationError {
+            message: format!("Error when deserializing JSON: {}", error),
+            reason: "bad_request",
+            side: Some(Box::new(error)),
+        }
+    }
+}
+
+/// Maps a reason for an error returned by the KPAL core onto the reason returned to clients.
+pub fn reason_from_error(reason: ErrorReason) -> &'static str {
+    use ErrorReason::*;
+
+    match reason {
+        InternalError => "internal_error",
+        ResourceNotFound => "resource_not_found",
+        UnprocessableRequest => "unprocessable_request",
+    }
+}
@@ -0,0 +1,75 @@
+//! The JSON messages that are exchanged with websocket clients.
+//!
+//! Messages from clients mirror the operations of the REST API. The bodies of these messages and
+//! the data that is returned to clients use the same representations as the REST API.
+use serde::{Deserialize, Serialize};
+
+use super::super::rest::schemas::{AttributeRead, PeripheralCreate, ValueReadUpdate};
+
+/// A request from a client.
+#[derive(Debug, Deserialize)]
+pub struct Request {
+    /// An ID that is chosen by the client and returned in the response to this request.
+    pub id: Option<u64>,
+
+    /// The operation to perform.
+    #[serde(flatten)]
+    pub operation: Operation,
+}
+
+/// An operation requested by a client.
+#[derive(Debug, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Operation {
+    CreatePeripheral {
+        data: PeripheralCreate,
+    },
+    PatchAttribute {
+        peripheral_id: usize,
+        attribute_id: usize,
+        data: ValueReadUpdate,
+    },
+    ReadAttribute {
+        peripheral_id: usize,
+        attribute_id: usize,
+    },
+    ReadAttributes {
+        peripheral_id: usize,
+    },
+    ReadLibraries,
+    ReadPeripheral {
+        peripheral_id: usize,
+    },
+    ReadPeripherals,
+    Subscribe {
+        peripheral_id: usize,
+    },
+    Unsubscribe {
+        peripheral_id: usize,
+    },
+}
+
+/// A message that is sent to a client.
+#[derive(Debug, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+pub enum Response {
+    /// A notification that the value of an attribute of a subscribed peripheral has changed.
+    Event {
+        peripheral_id: usize,
+        event_id: u64,
+        data: AttributeRead,
+    },
+
+    /// The response to a request that failed.
+    Error {
+        id: Option<u64>,
+        reason: &'static str,
+        message: String,
+    },
+
+    /// The response to a request that succeeded.
+    Result {
+        id: Option<u64>,
+        data: serde_json::Value,
+    },
+}
@@ -0,0 +1,462 @@
+//! A websocket integration for KPAL based on JSON messages.
+//!
+//! Clients hold a single connection over which they may send the same operations that are
+//! provided by the REST API and receive notifications about changes to the attributes of
+//! peripherals. Each message from a client is a JSON object whose `type` field names the
+//! operation. An optional `id` field is returned in the response so that clients can match
+//! responses to their requests. For example:
+//!
+//! ```json
+//! {"id": 1, "type": "patch_attribute", "peripheral_id": 0, "attribute_id": 0,
+//!  "data": {"type": "double", "value": 1.0}}
+//! ```
+//!
+//! is answered with either `{"type": "result", "id": 1, "data": {...}}` or
+//! `{"type": "error", "id": 1, "reason": "...", "message": "..."}`. After a client sends a
+//! `subscribe` message for a peripheral, every change to one of its attributes is pushed to the
+//! client as an `event` message.
+//!
+//! The websocket server listens on its own address. Each connection is served by its own thread,
+//! which reads the requests from the client and sends the responses. The events of each
+//! subscription are sent by another thread as they occur.
+mod errors;
+mod messages;
+
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    io::{self, Read, Write},
+    net::{Shutdown, SocketAddr, TcpListener, TcpStream},
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::Receiver,
+        Arc, Mutex, RwLock,
+    },
+    thread,
+    time::Duration,
+};
+
+use {
+    log,
+    tungstenite::{accept, protocol::Role, Error as WsError, Message, WebSocket},
+};
+
+use crate::{
+    init::{Libraries, Store, Transmitters},
+    integrations::{
+        create_peripheral, read_libraries, read_peripheral, read_peripheral_attribute,
+        read_peripheral_attributes, read_peripherals, subscribe_peripheral_events,
+        update_peripheral_attribute, Event,
+    },
+    models::{PeripheralBuilder, Value},
+};
+
+use super::rest::schemas::{AttributeRead, LibraryRead, PeripheralRead, SchemaError};
+
+pub use errors::WebsocketIntegrationError;
+use messages::{Operation, Request, Response};
+
+/// The Result type returned by the websocket integration.
+type Result<T> = std::result::Result<T, WebsocketIntegrationError>;
+
+/// The Result type returned when communicating over a connection. The error is boxed because it
+/// is large.
+type ConnectionResult = std::result::Result<(), Box<WsError>>;
+
+/// The shared daemon state that is used to serve each connection.
+#[derive(Clone)]
+struct Context {
+    libs: Arc<Libraries>,
+    store: Arc<Store>,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+}
+
+/// One end of a client's connection.
+///
+/// Each end reads from its own handle to the connection, so a thread that waits for a message
+/// from the client does not prevent the other threads from sending theirs. Writes are shared by
+/// all the ends and are completed while the connection is locked so that the messages of
+/// different threads are not interleaved.
+struct Socket {
+    /// The handle that is used to read from the connection.
+    reader: TcpStream,
+
+    /// The handle that is used to write to the connection.
+    writer: Arc<Mutex<TcpStream>>,
+}
+
+impl Socket {
+    /// Returns the first end of a connection.
+    ///
+    /// # Arguments
+    ///
+    /// * `stream` - The client's connection
+    fn new(stream: TcpStream) -> io::Result<Socket> {
+        Ok(Socket {
+            writer: Arc::new(Mutex::new(stream.try_clone()?)),
+            reader: stream,
+        })
+    }
+
+    /// Returns another end of the same connection.
+    fn try_clone(&self) -> io::Result<Socket> {
+        Ok(Socket {
+            reader: self.reader.try_clone()?,
+            writer: self.writer.clone(),
+        })
+    }
+
+    /// Closes the connection for all of its ends.
+    fn shutdown(&self) {
+        if let Err(e) = self.reader.shutdown(Shutdown::Both) {
+            log::debug!("Could not shut down the websocket connection: {}", e);
+        }
+    }
+}
+
+impl Read for Socket {
+    fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+        self.reader.read(buf)
+    }
+}
+
+impl Write for Socket {
+    fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| io::Error::other("The websocket connection is poisoned"))?;
+        writer.write_all(buf)?;
+
+        Ok(buf.len())
+    }
+
+    fn flush(&mut self) -> io::Result<()> {
+        let mut writer = self
+            .writer
+            .lock()
+            .map_err(|_| io::Error::other("The websocket connection is poisoned"))?;
+
+        writer.flush()
+    }
+}
+
+/// The subscriptions of a single client to the events of each peripheral.
+struct Subscriptions {
+    /// The end of the connection from which the ends that send the events are cloned.
+    socket: Socket,
+
+    /// The flags that are cleared to end each subscription, indexed by peripheral ID.
+    active: BTreeMap<usize, Arc<AtomicBool>>,
+}
+
+impl Subscriptions {
+    /// Starts a thread that sends the events of a peripheral to the client.
+    ///
+    /// Any previous subscription to the same peripheral is ended.
+    ///
+    /// # Arguments
+    ///
+    /// * `peripheral_id` - The ID of the peripheral
+    /// * `rx` - The receiver of the peripheral's events
+    fn insert(&mut self, peripheral_id: usize, rx: Receiver<Event>) -> io::Result<()> {
+        let ws = WebSocket::from_raw_socket(self.socket.try_clone()?, Role::Server, None);
+        let active = Arc::new(AtomicBool::new(true));
+        self.remove(peripheral_id);
+        self.active.insert(peripheral_id, active.clone());
+
+        thread::spawn(move || forward_events(peripheral_id, rx, active, ws));
+
+        Ok(())
+    }
+
+    /// Ends the subscription to the events of a peripheral.
+    ///
+    /// # Arguments
+    ///
+    /// * `peripheral_id` - The ID of the peripheral
+    fn remove(&mut self, peripheral_id: usize) {
+        if let Some(active) = self.active.remove(&peripheral_id) {
+            active.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+impl Drop for Subscriptions {
+    fn drop(&mut self) {
+        for active in self.active.values() {
+            active.store(false, Ordering::SeqCst);
+        }
+    }
+}
+
+/// Starts the websocket server in a new thread.
+///
+/// An error is returned if the server cannot listen on the given address.
+///
+/// # Arguments
+///
+/// * `addr` - The address that the server will use to listen for connections
+/// * `libs` The set of libraries that is currently open by the daemon
+/// * `store` The store that records the configuration of peripherals
+/// * `timeout` The maximum amount of time to wait for a response from a peripheral
+/// * `txs` The set of transmitters for sending messages into each peripheral thread
+pub fn start(
+    addr: SocketAddr,
+    libs: Arc<Libraries>,
+    store: Arc<Store>,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+) -> Result<()> {
+    let listener = TcpListener::bind(addr)?;
+    let context = Context {
+        libs,
+        store,
+        timeout,
+        txs,
+    };
+
+    thread::spawn(move || {
+        for stream in listener.incoming() {
+            match stream {
+                Ok(stream) => {
+                    let context = context.clone();
+                    thread::spawn(move || serve(stream, context));
+                }
+                Err(e) => log::error!("Failed to accept websocket connection: {}", e),
+            }
+        }
+    });
+
+    Ok(())
+}
+
+/// Serves a single websocket connection until it is closed.
+///
+/// # Arguments
+///
+/// * `stream` - The client's connection
+/// * `context` - The shared daemon state
+fn serve(stream: TcpStream, context: Context) {
+    let peer = stream.peer_addr().ok();
+    let socket = match Socket::new(stream) {
+        Ok(socket) => socket,
+        Err(e) => {
+            log::error!("Could not open the websocket connection: {}", e);
+            return;
+        }
+    };
+    let mut subscriptions = match socket.try_clone() {
+        Ok(socket) => Subscriptions {
+            socket,
+            active: BTreeMap::new(),
+        },
+        Err(e) => {
+            log::error!("Could not open the websocket connection: {}", e);
+            return;
+        }
+    };
+    let mut ws = match accept(socket) {
+        Ok(ws) => ws,
+        Err(e) => {
+            log::error!("Websocket handshake failed: {}", e);
+            return;
+        }
+    };
+    log::info!("Websocket connection opened: {:?}", peer);
+
+    loop {
+        let response = match ws.read() {
+            Ok(Message::Text(text)) => handle(&text, &context, &mut subscriptions),
+            Ok(Message::Binary(_)) => Response::Error {
+                id: None,
+                reason: "bad_request",
+                message: "Binary messages are not supported".to_string(),
+            },
+            Ok(_) => continue,
+            Err(WsError::ConnectionClosed) | Err(WsError::AlreadyClosed) => break,
+            Err(e) => {
+                log::error!("Websocket connection error: {}", e);
+                break;
+            }
+        };
+
+        if let Err(e) = send(&mut ws, &response) {
+            log::error!("Websocket connection error: {}", e);
+            break;
+        }
+    }
+
+    // The threads of the subscriptions fail to send any further events.
+    subscriptions.socket.shutdown();
+
+    log::info!("Websocket connection closed: {:?}", peer);
+}
+
+/// Processes a single request from a client and returns the response.
+///
+/// # Arguments
+///
+/// * `text` - The JSON representation of the request
+/// * `context` - The shared daemon state
+/// * `subscriptions` - The client's subscriptions to peripheral events
+fn handle(text: &str, context: &Context, subscriptions: &mut Subscriptions) -> Response {
+    let (id, result) = match serde_json::from_str::<Request>(text) {
+        Ok(request) => (
+            request.id,
+            perform(request.operation, context, subscriptions),
+        ),
+        Err(e) => (None, Err(e.into())),
+    };
+
+    match result {
+        Ok(data) => Response::Result { id, data },
+        Err(e) => {
+            log::error!("{}", e);
+            Response::Error {
+                id,
+                reason: e.reason,
+                message: e.message,
+            }
+        }
+    }
+}
+
+/// Performs an operation that was requested by a client.
+///
+/// # Arguments
+///
+/// * `operation` - The operation to perform
+/// * `context` - The shared daemon state
+/// * `subscriptions` - The client's subscriptions to peripheral events
+fn perform(
+    operation: Operation,
+    context: &Context,
+    subscriptions: &mut Subscriptions,
+) -> Result<serde_json::Value> {
+    let timeout = context.timeout;
+    let txs = context.txs.clone();
+
+    let data = match operation {
+        Operation::CreatePeripheral { mut data } => {
+            data.find_library(&context.libs)?;
+            let builder = PeripheralBuilder::try_from(data)?;
+            let id = create_peripheral(builder, &context.libs, &context.store, txs.clone())?;
+            serde_json::to_value(PeripheralRead::from(read_peripheral(id, timeout, txs)?))?
+        }
+        Operation::PatchAttribute {
+            peripheral_id,
+            attribute_id,
+            data,
+        } => {
+            let value = Value::try_from(data)?;
+            let attr =
+                update_peripheral_attribute(peripheral_id, attribute_id, value, timeout, txs)?;
+            serde_json::to_value(AttributeRead::try_from(attr)?)?
+        }
+        Operation::ReadAttribute {
+            peripheral_id,
+            attribute_id,
+        } => {
+            let attr = read_peripheral_attribute(peripheral_id, attribute_id, timeout, txs)?;
+            serde_json::to_value(AttributeRead::try_from(attr)?)?
+        }
+        Operation::ReadAttributes { peripheral_id } => {
+            let attrs: Vec<AttributeRead> =
+                read_peripheral_attributes(peripheral_id, timeout, txs)?
+                    .into_iter()
+                    .map(AttributeRead::try_from)
+                    .collect::<std::result::Result<Vec<AttributeRead>, SchemaError>>()?;
+            serde_json::to_value(attrs)?
+        }
+        Operation::ReadLibraries => {
+            let libs: Vec<LibraryRead> = read_libraries(&context.libs)?
+                .into_iter()
+                .map(LibraryRead::try_from)
+                .collect::<std::result::Result<Vec<LibraryRead>, SchemaError>>()?;
+            serde_json::to_value(libs)?
+        }
+        Operation::ReadPeripheral { peripheral_id } => serde_json::to_value(PeripheralRead::from(
+            read_peripheral(peripheral_id, timeout, txs)?,
+        ))?,
+        Operation::ReadPeripherals => {
+            let periphs: Vec<PeripheralRead> = read_peripherals(timeout, txs)?
+                .into_iter()
+                .map(PeripheralRead::from)
+                .collect();
+            serde_json::to_value(periphs)?
+        }
+        Operation::Subscribe { peripheral_id } => {
+            let subscription = subscribe_peripheral_events(peripheral_id, None, timeout, txs)?;
+            subscriptions.insert(peripheral_id, subscription.rx)?;
+            serde_json::Value::Null
+        }
+        Operation::Unsubscribe { peripheral_id } => {
+            subscriptions.remove(peripheral_id);
+            serde_json::Value::Null
+        }
+    };
+
+    Ok(data)
+}
+
+/// Sends the events of a peripheral to a client until the subscription ends.
+///
+/// The thread waits for each event, so it only notices that the client unsubscribed or
+/// disconnected when the next event occurs. It ends as soon as the peripheral is removed.
+///
+/// # Arguments
+///
+/// * `peripheral_id` - The ID of the peripheral
+/// * `rx` - The receiver of the peripheral's events
+/// * `active` - The flag that is cleared when the subscription ends
+/// * `ws` - The client's connection
+fn forward_events(
+    peripheral_id: usize,
+    rx: Receiver<Event>,
+    active: Arc<AtomicBool>,
+    mut ws: WebSocket<Socket>,
+) {
+    for event in rx.iter() {
+        if !active.load(Ordering::SeqCst) {
+            break;
+        }
+
+        let data = match AttributeRead::try_from(event.attribute().clone()) {
+            Ok(data) => data,
+            Err(e) => {
+                log::error!("Could not serialize event {}: {}", event.id(), e);
+                continue;
+            }
+        };
+
+        let event = Response::Event {
+            peripheral_id,
+            event_id: event.id(),
+            data,
+        };
+        if let Err(e) = send(&mut ws, &event) {
+            log::debug!("Websocket subscription ended: {}", e);
+            break;
+        }
+    }
+}
+
+/// Sends a single message to a client.
+///
+/// # Arguments
+///
+/// * `ws` - The client's connection
+/// * `response` - The message to send
+fn send(ws: &mut WebSocket<Socket>, response: &Response) -> ConnectionResult {
+    let text = match serde_json::to_string(response) {
+        Ok(text) => text,
+        Err(e) => {
+            log::error!("Could not serialize websocket message: {}", e);
+            return Ok(());
+        }
+    };
+
+    ws.send(Message::Text(text)).map_err(Box::new)
+}
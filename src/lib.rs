@@ -32,6 +32,31 @@
 //! RUST_LOG=info ./kpald
 //! ```
 //!
+//! The daemon may also be configured with a TOML or JSON file that is passed with the `--config`
+//! option. The file may set the server and websocket addresses, the library directories, the log
+//! level, the request timeout, the chunk size of event streams, and a list of peripherals to
+//! create at startup:
+//!
+//! ```toml
+//! server_address = "0.0.0.0:8000"
+//! websocket_address = "0.0.0.0:8001"
+//! library_dirs = ["/home/pi/.kpal/libraries"]
+//! log_level = "info"
+//! request_timeout_ms = 5000
+//! event_chunk_size = 8192
+//!
+//! [[peripherals]]
+//! library_name = "libbasic-plugin.so"
+//! name = "foo"
+//! attributes = [{ id = 0, type = "double", value = 1.0 }]
+//! ```
+//!
+//! Peripherals that you create are recorded in the file `~/.kpal/peripherals.json` and are
+//! recreated the next time that the daemon starts. Use the `--store-file` option to choose a
+//! different file or the `--no-restore` flag to start without the recorded peripherals. The IDs of
+//! the peripherals of the configuration file are recorded as well so that they do not change when
+//! the configuration does. The records are kept when the daemon starts with `--no-restore`.
+//!
 //! You may now make HTTP requests to the daemon. The following examples use the UNIX `curl`
 //! command line utility to make the requests, but you may use the HTTP client of your choice.
 //!
@@ -42,6 +67,12 @@
 //! # Get the library with ID 0
 //! curl -s localhost:8000/api/v0/libraries/0
 //!
+//! # Load the library files that were added to the library directories since the daemon started
+//! curl -s --request POST localhost:8000/api/v0/libraries/rescan
+//!
+//! # Unload the library with ID 1
+//! curl -s --request DELETE localhost:8000/api/v0/libraries/1
+//!
 //! # Create a new peripheral from the library with ID 0
 //! curl -s \
 //!      --request POST \
@@ -49,6 +80,20 @@
 //!      --header "Content-Type: application/json" \
 //!      --data '{"name":"foo","library_id":0}'
 //!
+//! # Create a new peripheral from the newest library named basic-plugin with a version of 0.3.x
+//! curl -s \
+//!      --request POST \
+//!      localhost:8000/api/v0/peripherals \
+//!      --header "Content-Type: application/json" \
+//!      --data '{"name":"bar","library":"basic-plugin@0.3"}'
+//!
+//! # Create a new peripheral whose plugin runs in its own process
+//! curl -s \
+//!      --request POST \
+//!      localhost:8000/api/v0/peripherals \
+//!      --header "Content-Type: application/json" \
+//!      --data '{"name":"baz","library_id":0,"isolated":true}'
+//!
 //! # Create a new peripheral and override the default value of a pre-init attribute
 //! curl -s \
 //!      --request POST \
@@ -65,7 +110,7 @@
 //! # Get all the peripherals currently managed by the daemon
 //! curl -s localhost:8000/api/v0/peripherals
 //!
-//! # Get the peripheral with ID 0
+//! # Get the peripheral with ID 0, including whether its plugin is in the init or run phase
 //! curl -s localhost:8000/api/v0/peripherals/0
 //!
 //! # Get the attributes of the peripheral with ID 0
@@ -80,8 +125,155 @@
 //!      localhost:8000/api/v0/peripherals/0/attributes/0 \
 //!      --header "Content-Type: application/json" \
 //!      --data '{"type":"double","value":42}'
+//!
+//! # Get the attribute with the alias x from the peripheral named foo
+//! curl -s localhost:8000/api/v0/peripherals/by-name/foo/attributes/x
+//!
+//! # Set the value of the attribute with the alias x of the peripheral named foo
+//! curl -s \
+//!      --request PATCH \
+//!      localhost:8000/api/v0/peripherals/by-name/foo/attributes/x \
+//!      --header "Content-Type: application/json" \
+//!      --data '{"type":"double","value":42}'
+//!
+//! # Set the values of several attributes of the peripheral with ID 0 in the given order, restoring
+//! # the previous values if any of them cannot be set
+//! curl -s \
+//!      --request PATCH \
+//!      localhost:8000/api/v0/peripherals/0/attributes \
+//!      --header "Content-Type: application/json" \
+//!      --data '{"attributes":[{"id":0,"type":"double","value":42},{"id":4,"type":"boolean","value":true}],"atomic":true}'
+//!
+//! # Stream the changes to the attributes of the peripheral with ID 0 as server-sent events
+//! curl -sN localhost:8000/api/v0/peripherals/0/events
+//!
+//! # Deinitialize the peripheral with ID 0, returning it to its init phase
+//! curl -s --request POST localhost:8000/api/v0/peripherals/0/deinit
+//!
+//! # Initialize the peripheral with ID 0 again
+//! curl -s --request POST localhost:8000/api/v0/peripherals/0/init
+//!
+//! # Restart the peripheral with ID 0 with a new value for a pre-init attribute
+//! curl -s \
+//!      --request POST \
+//!      localhost:8000/api/v0/peripherals/0/restart \
+//!      --header "Content-Type: application/json" \
+//!      --data '{"attributes":[{"id":0,"type":"double","value":1.0}]}'
+//!
+//! # Read the attribute with ID 0 of the peripheral with ID 0 every 100 ms and record its values
+//! curl -s \
+//!      --request POST \
+//!      localhost:8000/api/v0/peripherals/0/attributes/0/sampler \
+//!      --header "Content-Type: application/json" \
+//!      --data '{"interval_ms":100,"record":true}'
+//!
+//! # Get the values that were sampled after the given time in milliseconds since the Unix epoch
+//! curl -s localhost:8000/api/v0/peripherals/0/attributes/0/history?since=1585500000000
+//!
+//! # Stop sampling the attribute with ID 0 of the peripheral with ID 0
+//! curl -s --request DELETE localhost:8000/api/v0/peripherals/0/attributes/0/sampler
+//!
+//! # Set an attribute, then read another one three times with 50 ms between each reading
+//! curl -s \
+//!      --request POST \
+//!      localhost:8000/api/v0/sequences \
+//!      --header "Content-Type: application/json" \
+//!      --data '{"steps":[{"type":"set","peripheral_id":0,"attribute_id":0,"value":{"type":"double","value":1.0}},{"type":"loop","count":3,"steps":[{"type":"wait","duration_ms":50},{"type":"get","peripheral_id":1,"attribute_id":0}]}]}'
+//!
+//! # Get the state of the sequence with ID 0 and the values that it read
+//! curl -s localhost:8000/api/v0/sequences/0
+//!
+//! # Cancel the sequence with ID 0
+//! curl -s --request POST localhost:8000/api/v0/sequences/0/cancel
+//!
+//! # Set attribute 0 of peripheral 1 to 1 every time that attribute 0 of peripheral 0 rises above
+//! # 40.0, checking it every second in addition to the changes reported by the peripheral
+//! curl -s \
+//!      --request POST \
+//!      localhost:8000/api/v0/rules \
+//!      --header "Content-Type: application/json" \
+//!      --data '{"condition":{"peripheral_id":0,"attribute_id":0,"type":"above","threshold":40.0,"hysteresis":2.0},"action":{"peripheral_id":1,"attribute_id":0,"value":{"type":"integer","value":1}},"poll_interval_ms":1000}'
+//!
+//! # Get the state of the rule with ID 0
+//! curl -s localhost:8000/api/v0/rules/0
+//!
+//! # Delete the rule with ID 0
+//! curl -s --request DELETE localhost:8000/api/v0/rules/0
+//!
+//! # Create a group of the peripherals with IDs 0 and 1
+//! curl -s \
+//!      --request POST \
+//!      localhost:8000/api/v0/groups \
+//!      --header "Content-Type: application/json" \
+//!      --data '{"name":"bank","peripheral_ids":[0,1]}'
+//!
+//! # Set attribute 0 of every peripheral in the group with ID 0 to 42
+//! curl -s \
+//!      --request PATCH \
+//!      localhost:8000/api/v0/groups/0/attributes/0 \
+//!      --header "Content-Type: application/json" \
+//!      --data '{"type":"integer","value":42}'
+//!
+//! # Get attribute 0 of every peripheral in the group with ID 0
+//! curl -s localhost:8000/api/v0/groups/0/attributes/0
+//!
+//! # Delete the group with ID 0
+//! curl -s --request DELETE localhost:8000/api/v0/groups/0
+//!
+//! # Delete the peripheral with ID 0
+//! curl -s --request DELETE localhost:8000/api/v0/peripherals/0
 //! ```
 //!
+//! Rules are saved to `~/.kpal/rules.json` and are evaluated again when the daemon restarts; use the
+//! `--rules-file` option to choose a different file. A threshold rule fires once when its value
+//! crosses the threshold and does not fire again until the value has fallen back past the threshold
+//! by more than the hysteresis.
+//!
+//! Peripheral names are unique. Each attribute has an alias, which is the key that its plugin uses
+//! to look it up, e.g. `offset`. The `by-name` routes find an attribute by its alias or, failing
+//! that, by its name, so that scripts continue to work when IDs change.
+//!
+//! Each library reports its name and version, e.g. `basic-plugin` and `0.3.0`. Instead of its ID, a
+//! library may be referred to by its name, optionally followed by a version requirement such as
+//! `basic-plugin@0.3`. Peripherals are recorded together with the name and version of their library,
+//! so they are restored even when the libraries are loaded in a different order.
+//!
+//! Libraries also report the version of the binary interface (ABI) between the daemon and plugins
+//! that they were built for. The daemon refuses to load libraries that were built for a different ABI
+//! version, such as those built with an older version of `kpal-plugin`, and logs the reason.
+//!
+//! Libraries may be added and removed without restarting the daemon. A rescan loads the files in the
+//! library directories that are not loaded yet and assigns them new IDs. A library is not unloaded
+//! while any peripheral that was created from it still exists.
+//!
+//! A peripheral that is created with `"isolated": true` runs its plugin in a separate
+//! `kpal-plugin-host` process, which must be installed next to `kpald`. If the plugin crashes, or
+//! does not reply within five seconds and its host process is killed, only that peripheral fails.
+//! Its `state` is reported as `failed` and its requests return errors until it is restarted with a
+//! `POST` request to `/api/v0/peripherals/{id}/restart`, which starts a new host process.
+//!
+//! Groups are kept in memory only. Requests to a group are sent to all of its peripherals at once,
+//! and the response maps the ID of each peripheral to either its attribute or the error that it
+//! returned.
+//!
+//! The most recent samples of each attribute are kept in memory. Recorded samples are also appended
+//! to a CSV file in `~/.kpal/samples`; use the `--samples-dir` option to choose a different
+//! directory.
+//!
+//! Each change to the value of a peripheral's attributes is sent to the clients of its `events`
+//! stream as a server-sent event. The HTTP server only sends a streamed body in chunks of 8 KiB, so
+//! by default every message, including the keep-alive comment that an idle stream sends every 15
+//! seconds, is padded with spaces to 8 KiB in order to be sent immediately. A peripheral whose
+//! attributes change 100 times per second therefore costs each subscriber about 800 KiB/s. The
+//! `event_chunk_size` configuration key pads messages to a smaller size that divides 8192 bytes
+//! instead; the messages are then sent in batches that fill 8 KiB, e.g. eight at a time for 1024.
+//!
+//! The same operations are available over a single websocket connection at `ws://localhost:8001`.
+//! Each message is a JSON object whose `type` field names the operation, such as
+//! `create_peripheral`, `read_attributes`, or `patch_attribute`. After sending
+//! `{"type":"subscribe","peripheral_id":0}`, the client receives an `event` message every time the
+//! value of one of the peripheral's attributes changes.
+//!
 //! # Core components
 //!
 //!                +--------------------------------+
@@ -151,7 +343,13 @@
 //! A plugin combines the data that represents a peripheral's state with the functionality for
 //! controlling the hardware device that is modeled by the peripheral.
 pub mod constants;
+pub mod groups;
 pub mod init;
 pub mod integrations;
 pub mod models;
 pub mod plugins;
+pub mod rules;
+pub mod sampler;
+pub mod sequences;
+
+mod util;
@@ -31,6 +31,14 @@ impl From<BuilderPartiallyInitializedError> for ModelError {
     }
 }
 
+impl From<NullPointerError> for ModelError {
+    fn from(error: NullPointerError) -> Self {
+        ModelError {
+            side: Some(Box::new(error)),
+        }
+    }
+}
+
 impl From<FromBytesWithNulError> for ModelError {
     fn from(error: FromBytesWithNulError) -> Self {
         ModelError {
@@ -66,3 +74,15 @@ impl fmt::Display for BuilderPartiallyInitializedError {
         write!(f, "{:?}", self)
     }
 }
+
+/// An error raised when a non-empty value from a plugin refers to its data with a null pointer.
+#[derive(Debug)]
+pub struct NullPointerError();
+
+impl Error for NullPointerError {}
+
+impl fmt::Display for NullPointerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{:?}", self)
+    }
+}
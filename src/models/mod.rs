@@ -4,22 +4,27 @@
 //!
 //! - peripherals
 //! - attributes
+//! - actions
 //! - values
 //! - libraries
 mod errors;
 
 use std::{
     collections::BTreeMap,
+    convert::TryFrom,
     ffi::{CStr, CString},
+    path::{Path, PathBuf},
     slice,
 };
 
+use libc::{c_char, c_uint};
 use libloading::Library as Dll;
+use semver::{Version, VersionReq};
 
-use kpal_plugin::Val as PluginValue;
+use kpal_plugin::{MetadataVal as PluginMetadata, Val as PluginValue};
 
-use errors::BuilderPartiallyInitializedError;
 pub use errors::ModelError;
+use errors::{BuilderPartiallyInitializedError, NullPointerError};
 
 /// A model represents one of the system's core abstractions.
 pub trait Model {
@@ -31,21 +36,95 @@ pub trait Model {
     fn key() -> &'static str;
 }
 
+/// Actions are operations that a peripheral performs which do not map onto its attributes.
+///
+/// Each action is owned by one and only one library. Its ID is unique within that library only.
+#[derive(Clone, Debug)]
+pub struct Action {
+    /// The arguments that must be provided when the Action is invoked
+    arguments: Vec<ActionArgument>,
+
+    /// The ID of the Action
+    id: usize,
+
+    /// The name of the Action
+    name: String,
+}
+
+impl Action {
+    /// Creates a new Action instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The numeric ID of the action
+    /// * `name` - The action's name
+    /// * `arguments` - The arguments that must be provided when the action is invoked
+    pub fn new(id: usize, name: String, arguments: Vec<ActionArgument>) -> Action {
+        Action {
+            arguments,
+            id,
+            name,
+        }
+    }
+
+    /// Returns the arguments of the action.
+    pub fn arguments(&self) -> &[ActionArgument] {
+        &self.arguments
+    }
+
+    /// Returns the name of the action.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+}
+
+impl Model for Action {
+    fn id(&self) -> usize {
+        self.id
+    }
+
+    fn key() -> &'static str {
+        "actions"
+    }
+}
+
+/// A named argument of an Action.
+#[derive(Clone, Debug)]
+pub struct ActionArgument {
+    /// The name of the argument
+    pub name: String,
+
+    /// An example value of the argument. Its variant determines the argument's type.
+    pub value: Value,
+}
+
 /// Attributes represent part of the entire state of a peripheral.
 ///
 /// Each attribute is owned by one and only one peripheral. Its ID is unique within that peripheral
 /// only.
 #[derive(Clone, Debug)]
 pub struct Attribute {
+    /// The key by which programs look up the Attribute, e.g. `offset`
+    alias: String,
+
+    /// The values that the Attribute may take. Any value is allowed if this is empty.
+    choices: Vec<String>,
+
     /// The ID of the Attribute
     id: usize,
 
+    /// The units, range, step and description of the Attribute
+    metadata: Metadata,
+
     /// The name of the Attribute
     name: String,
 
     /// Whether the attribute's default value may be overridden when the plugin is initialized
     pre_init: bool,
 
+    /// Whether the Attribute may be set in each phase of the plugin lifecycle
+    settable: Settable,
+
     /// The value of the Attribute
     value: Value,
 }
@@ -61,48 +140,119 @@ impl Attribute {
     /// * `value` - The value to assign to the new attribute
     /// * `id` - The numeric ID of the attribute
     /// * `name` - The attribute's name
+    /// * `alias` - The key by which programs look up the attribute
     /// * `pre_init` - Detemines whether the attribute may be set before plugin initialization
+    /// * `choices` - The values that the attribute may take; if empty, then any value is allowed
+    /// * `metadata` - The attribute's units, range, step and description
+    /// * `settable` - Whether the attribute may be set in each phase of the plugin lifecycle
+    #[allow(clippy::too_many_arguments)]
     pub fn new(
         value: PluginValue,
         id: usize,
         name: String,
+        alias: String,
         pre_init: bool,
+        choices: Vec<String>,
+        metadata: Metadata,
+        settable: Settable,
     ) -> Result<Attribute, ModelError> {
+        let value = Value::try_from(value)?;
+
+        Ok(Attribute {
+            alias,
+            choices,
+            id,
+            metadata,
+            name,
+            pre_init,
+            settable,
+            value,
+        })
+    }
+
+    /// Returns the key by which programs look up the attribute.
+    pub fn alias(&self) -> &str {
+        &self.alias
+    }
+
+    /// Indicates whether a value is one of the attribute's choices.
+    ///
+    /// Choices only restrict string values. Any value is allowed if the attribute has no choices.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to check
+    pub fn allows(&self, value: &Value) -> bool {
+        if self.choices.is_empty() {
+            return true;
+        }
+
         match value {
-            PluginValue::Int(value) => Ok(Attribute {
-                id,
-                name,
-                pre_init,
-                value: Value::Int { value },
-            }),
-            PluginValue::Double(value) => Ok(Attribute {
-                id,
-                name,
-                pre_init,
-                value: Value::Double { value },
-            }),
-            PluginValue::String(p_value, length) => {
-                let value = unsafe {
-                    let slice = slice::from_raw_parts(p_value, length);
-                    let string = CStr::from_bytes_with_nul(slice)?.to_str()?;
-                    CString::new(string.to_owned())?
-                };
-                Ok(Attribute {
-                    id,
-                    name,
-                    pre_init,
-                    value: Value::String { value },
-                })
-            }
-            PluginValue::Uint(value) => Ok(Attribute {
-                id,
-                name,
-                pre_init,
-                value: Value::Uint { value },
-            }),
+            Value::String { value } => match value.to_str() {
+                Ok(value) => self.choices.iter().any(|choice| choice == value),
+                Err(_) => false,
+            },
+            _ => true,
+        }
+    }
+
+    /// Returns the values that the attribute may take.
+    pub fn choices(&self) -> &[String] {
+        &self.choices
+    }
+
+    /// Indicates whether a value lies within the attribute's range.
+    ///
+    /// The range only restricts numeric values and the elements of numeric arrays. Any value is
+    /// allowed if the attribute has no range.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The value to check
+    pub fn in_range(&self, value: &Value) -> bool {
+        let in_range = |number: f64| {
+            let above_min = match self.metadata.min {
+                Some(min) => number >= min,
+                None => true,
+            };
+            let below_max = match self.metadata.max {
+                Some(max) => number <= max,
+                None => true,
+            };
+            above_min && below_max
+        };
+        // Integers are compared with the bounds rounded towards the inside of the range so that
+        // 64-bit integers do not lose precision. Out of range bounds saturate the casts.
+        let in_integer_range = |number: i128| {
+            let above_min = match self.metadata.min {
+                Some(min) => number >= min.ceil() as i128,
+                None => true,
+            };
+            let below_max = match self.metadata.max {
+                Some(max) => number <= max.floor() as i128,
+                None => true,
+            };
+            above_min && below_max
+        };
+
+        match value {
+            Value::Int { value } => in_integer_range(i128::from(*value)),
+            Value::Double { value } => in_range(*value),
+            Value::Uint { value } => in_integer_range(i128::from(*value)),
+            Value::DoubleArray { value } => value.iter().all(|v| in_range(*v)),
+            Value::IntArray { value } => value.iter().all(|v| in_integer_range(i128::from(*v))),
+            Value::Int64 { value } => in_integer_range(i128::from(*value)),
+            Value::Uint64 { value } => in_integer_range(i128::from(*value)),
+            Value::Float { value } => in_range(f64::from(*value)),
+            Value::String { .. } | Value::Bool { .. } | Value::Bytes { .. } => true,
         }
     }
 
+    /// Returns the units, range, step and description of the attribute.
+    pub fn metadata(&self) -> &Metadata {
+        &self.metadata
+    }
+
     /// Returns the name of an attribute.
     pub fn name(&self) -> &str {
         &self.name
@@ -113,6 +263,11 @@ impl Attribute {
         self.pre_init
     }
 
+    /// Indicates whether the Attribute may be set in each phase of the plugin lifecycle.
+    pub fn settable(&self) -> Settable {
+        self.settable
+    }
+
     /// Returns a new value instance that is created from an attribute.
     pub fn to_value(&self) -> Result<Value, ModelError> {
         let value = match &self.value {
@@ -123,6 +278,19 @@ impl Attribute {
                 Value::String { value: c_string }
             }
             Value::Uint { value, .. } => Value::Uint { value: *value },
+            Value::Bool { value, .. } => Value::Bool { value: *value },
+            Value::DoubleArray { value, .. } => Value::DoubleArray {
+                value: value.clone(),
+            },
+            Value::IntArray { value, .. } => Value::IntArray {
+                value: value.clone(),
+            },
+            Value::Bytes { value, .. } => Value::Bytes {
+                value: value.clone(),
+            },
+            Value::Int64 { value, .. } => Value::Int64 { value: *value },
+            Value::Uint64 { value, .. } => Value::Uint64 { value: *value },
+            Value::Float { value, .. } => Value::Float { value: *value },
         };
 
         Ok(value)
@@ -157,17 +325,29 @@ impl PartialEq for Attribute {
 /// AttributeBuilders allow the daemon to build new Attribute instances explicitly and sequentially
 /// by setting values for an attribute's fields prior to initialization. When the Attribute is
 /// ready to be intialized, the `build` method is called.
-#[derive(Debug)]
+#[derive(Clone, Debug)]
 pub struct AttributeBuilder {
+    /// The key by which programs look up the Attribute
+    alias: Option<String>,
+
+    /// The values that the Attribute may take
+    choices: Vec<String>,
+
     /// The ID of the Attribute
     id: usize,
 
+    /// The units, range, step and description of the Attribute
+    metadata: Metadata,
+
     /// The name of the Attribute
     name: Option<String>,
 
     /// Whether the Attribute's default value may be overridden when the plugin is initialized
     pre_init: Option<bool>,
 
+    /// Whether the Attribute may be set in each phase of the plugin lifecycle
+    settable: Settable,
+
     /// The value of the Attribute
     value: Value,
 }
@@ -181,9 +361,13 @@ impl AttributeBuilder {
     /// * `value` - The current value of the AttributeBuilder
     pub fn new(id: usize, value: Value) -> AttributeBuilder {
         AttributeBuilder {
+            alias: None,
+            choices: Vec::new(),
             id,
+            metadata: Metadata::default(),
             name: None,
             pre_init: None,
+            settable: Settable::default(),
             value,
         }
     }
@@ -193,13 +377,47 @@ impl AttributeBuilder {
     /// This method will consume the builder.
     pub fn build(self) -> Result<Attribute, ModelError> {
         Ok(Attribute {
+            alias: self.alias.ok_or(BuilderPartiallyInitializedError())?,
+            choices: self.choices,
             id: self.id,
+            metadata: self.metadata,
             name: self.name.ok_or(BuilderPartiallyInitializedError())?,
             pre_init: self.pre_init.ok_or(BuilderPartiallyInitializedError())?,
+            settable: self.settable,
             value: self.value,
         })
     }
 
+    /// Sets the key by which programs look up the AttributeBuilder.
+    ///
+    /// # Arguments
+    ///
+    /// * `alias` - The new alias of the AttributeBuilder
+    pub fn set_alias(mut self, alias: String) -> AttributeBuilder {
+        self.alias = Some(alias);
+        self
+    }
+
+    /// Sets the values that the Attribute may take.
+    ///
+    /// # Arguments
+    ///
+    /// * `choices` - The allowed values of the Attribute; if empty, then any value is allowed
+    pub fn set_choices(mut self, choices: Vec<String>) -> AttributeBuilder {
+        self.choices = choices;
+        self
+    }
+
+    /// Sets the units, range, step and description of the AttributeBuilder.
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - The metadata of the Attribute
+    pub fn set_metadata(mut self, metadata: Metadata) -> AttributeBuilder {
+        self.metadata = metadata;
+        self
+    }
+
     /// Returns the ID of the Attribute builder.
     pub fn id(&self) -> &usize {
         &self.id
@@ -224,6 +442,76 @@ impl AttributeBuilder {
         self.pre_init = Some(pre_init);
         self
     }
+
+    /// Sets whether the AttributeBuilder may be set in each phase of the plugin lifecycle.
+    ///
+    /// # Arguments
+    ///
+    /// * `settable` - Whether the Attribute may be set in the init and run phases
+    pub fn set_settable(mut self, settable: Settable) -> AttributeBuilder {
+        self.settable = settable;
+        self
+    }
+
+    /// Returns the value of the AttributeBuilder.
+    pub fn value(&self) -> &Value {
+        &self.value
+    }
+}
+
+/// Metadata describes the units, range, step and purpose of an Attribute.
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Metadata {
+    /// A human-readable description of the Attribute
+    pub description: Option<String>,
+
+    /// The largest value that the Attribute may take
+    pub max: Option<f64>,
+
+    /// The smallest value that the Attribute may take
+    pub min: Option<f64>,
+
+    /// The increment between successive values of the Attribute
+    pub step: Option<f64>,
+
+    /// The physical units of the Attribute's value
+    pub units: Option<String>,
+}
+
+impl Metadata {
+    /// Creates a new Metadata instance from the metadata returned by a plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - The metadata returned by the plugin
+    pub fn new(metadata: &PluginMetadata) -> Result<Metadata, ModelError> {
+        let to_string = |p_string: *const u8| -> Result<Option<String>, ModelError> {
+            if p_string.is_null() {
+                return Ok(None);
+            }
+            let string = unsafe { CStr::from_ptr(p_string as *const c_char) };
+            Ok(Some(string.to_str()?.to_owned()))
+        };
+        let to_number = |number: f64| if number.is_nan() { None } else { Some(number) };
+
+        Ok(Metadata {
+            description: to_string(metadata.description)?,
+            max: to_number(metadata.max),
+            min: to_number(metadata.min),
+            step: to_number(metadata.step),
+            units: to_string(metadata.units)?,
+        })
+    }
+}
+
+/// Settable describes whether an Attribute may be set in each phase of the plugin lifecycle.
+#[derive(Clone, Copy, Debug, Default, PartialEq)]
+pub struct Settable {
+    /// Whether the Attribute may be set before the plugin is initialized
+    pub init: bool,
+
+    /// Whether the Attribute may be set after the plugin is initialized
+    pub run: bool,
 }
 
 /// A Library represents an interface to a plugin.
@@ -235,6 +523,12 @@ impl AttributeBuilder {
 /// this Model.
 #[derive(Debug)]
 pub struct Library {
+    /// The version of the binary interface that the library was built for.
+    abi_version: c_uint,
+
+    /// The plugin actions that are defined by this Library.
+    actions: BTreeMap<usize, Action>,
+
     /// The plugin attributes that are defined by this Library.
     attributes: BTreeMap<usize, Attribute>,
 
@@ -246,16 +540,30 @@ pub struct Library {
 
     /// The name of the library.
     name: String,
+
+    /// The path to the library file.
+    path: PathBuf,
+
+    /// The number of peripherals that use the library.
+    peripherals: usize,
+
+    /// The semantic version of the library, if it reports one.
+    version: Option<Version>,
 }
 
 impl Clone for Library {
     /// Clones a library by ignoring any dynamic library owned by the model.
     fn clone(&self) -> Self {
         Library {
+            abi_version: self.abi_version,
             id: self.id,
             name: self.name.clone(),
+            actions: self.actions.clone(),
             attributes: self.attributes.clone(),
             library: None,
+            path: self.path.clone(),
+            peripherals: self.peripherals,
+            version: self.version.clone(),
         }
     }
 }
@@ -268,34 +576,132 @@ impl Library {
     ///
     /// # Arguments
     ///
-    /// * `id` - The numeric ID of the attribute
-    /// * `name` - The attribute's name
+    /// * `id` - The numeric ID of the library
+    /// * `name` - The library's name
+    /// * `version` - The library's semantic version, if it reports one
+    /// * `abi_version` - The version of the binary interface that the library was built for
+    /// * `path` - The path to the library file
     /// * `library` The shared library that is used to manipulate the plugin
-    pub fn new(id: usize, name: String, library: Option<Dll>) -> Library {
+    pub fn new(
+        id: usize,
+        name: String,
+        version: Option<Version>,
+        abi_version: c_uint,
+        path: PathBuf,
+        library: Option<Dll>,
+    ) -> Library {
         let attributes: BTreeMap<usize, Attribute> = BTreeMap::new();
         Library {
+            abi_version,
             id,
             name,
+            actions: BTreeMap::new(),
             attributes,
             library,
+            path,
+            peripherals: 0,
+            version,
         }
     }
 
+    /// Returns the version of the binary interface that the library was built for.
+    pub fn abi_version(&self) -> c_uint {
+        self.abi_version
+    }
+
     /// Returns the shared library instance.
     pub fn dll(&self) -> &Option<Dll> {
         &self.library
     }
 
+    /// Returns the collection of actions provided by the plugin library.
+    pub fn actions(&self) -> &BTreeMap<usize, Action> {
+        &self.actions
+    }
+
     /// Returns the collection of attributes provided by the plugin library.
     pub fn attributes(&self) -> &BTreeMap<usize, Attribute> {
         &self.attributes
     }
 
+    /// Returns the name of the library file, e.g. `libgpio_cdev.so`.
+    pub fn file_name(&self) -> String {
+        self.path
+            .file_name()
+            .map(|name| name.to_string_lossy().into_owned())
+            .unwrap_or_default()
+    }
+
+    /// Indicates whether the Library is the one that is referred to by a name.
+    ///
+    /// The name is either the name of the library file, e.g. `libgpio_cdev.so`, or the name of the
+    /// Library that is optionally followed by a version requirement, e.g. `gpio-cdev@0.3`.
+    ///
+    /// # Arguments
+    ///
+    /// * `name` - The name that refers to a Library
+    pub fn matches(&self, name: &str) -> bool {
+        if name == self.file_name() {
+            return true;
+        }
+
+        match name.split_once('@') {
+            Some((name, req)) => {
+                let req = match VersionReq::parse(req) {
+                    Ok(req) => req,
+                    Err(_) => return false,
+                };
+                name == self.name && self.version.as_ref().is_some_and(|v| req.matches(v))
+            }
+            None => name == self.name,
+        }
+    }
+
     /// Returns the name of the Library.
     pub fn name(&self) -> &str {
         &self.name
     }
 
+    /// Returns the number of peripherals that use the Library.
+    pub fn peripherals(&self) -> usize {
+        self.peripherals
+    }
+
+    /// Records that a peripheral started using the Library.
+    pub fn acquire(&mut self) {
+        self.peripherals += 1;
+    }
+
+    /// Records that a peripheral stopped using the Library.
+    pub fn release(&mut self) {
+        self.peripherals = self.peripherals.saturating_sub(1);
+    }
+
+    /// Returns the path to the library file.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns the name by which the Library is recorded, e.g. `gpio-cdev@0.3.0`.
+    ///
+    /// The version is omitted if the Library does not report one.
+    pub fn qualified_name(&self) -> String {
+        match &self.version {
+            Some(version) => format!("{}@{}", self.name, version),
+            None => self.name.clone(),
+        }
+    }
+
+    /// Returns the semantic version of the Library, if it reports one.
+    pub fn version(&self) -> Option<&Version> {
+        self.version.as_ref()
+    }
+
+    /// Allows a Library's actions to be set.
+    pub fn set_actions(&mut self, actions: BTreeMap<usize, Action>) {
+        self.actions = actions;
+    }
+
     /// Allows a Library's attributes to be set.
     pub fn set_attributes(&mut self, attributes: BTreeMap<usize, Attribute>) {
         self.attributes = attributes;
@@ -320,8 +726,10 @@ impl Model for Library {
 pub struct Peripheral {
     attributes: BTreeMap<usize, Attribute>,
     id: usize,
+    isolated: bool,
     library_id: usize,
     name: String,
+    state: PeripheralState,
 }
 
 impl Peripheral {
@@ -330,6 +738,11 @@ impl Peripheral {
         &self.attributes
     }
 
+    /// Returns true if the Peripheral's plugin runs in its own process.
+    pub fn isolated(&self) -> bool {
+        self.isolated
+    }
+
     /// Returns the ID of the Peripheral.
     pub fn library_id(&self) -> usize {
         self.library_id
@@ -352,7 +765,16 @@ impl Peripheral {
         value: PluginValue,
     ) -> Result<(), ModelError> {
         let attribute = self.attributes.get_mut(&id).unwrap();
-        *attribute = Attribute::new(value, id, attribute.name().to_owned(), attribute.pre_init())?;
+        *attribute = Attribute::new(
+            value,
+            id,
+            attribute.name().to_owned(),
+            attribute.alias().to_owned(),
+            attribute.pre_init(),
+            attribute.choices().to_vec(),
+            attribute.metadata().clone(),
+            attribute.settable(),
+        )?;
         Ok(())
     }
 
@@ -364,6 +786,20 @@ impl Peripheral {
     pub fn set_id(&mut self, id: usize) {
         self.id = id;
     }
+
+    /// Sets the state of the Peripheral's plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `state` - The new state of the plugin
+    pub fn set_state(&mut self, state: PeripheralState) {
+        self.state = state;
+    }
+
+    /// Returns the state of the Peripheral's plugin.
+    pub fn state(&self) -> PeripheralState {
+        self.state
+    }
 }
 
 impl Model for Peripheral {
@@ -376,6 +812,19 @@ impl Model for Peripheral {
     }
 }
 
+/// The lifecycle state of the plugin that controls a Peripheral.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum PeripheralState {
+    /// The plugin has not been initialized, or it was deinitialized.
+    Init,
+
+    /// The plugin is initialized and running.
+    Run,
+
+    /// The process that hosts the plugin has exited, so the plugin must be restarted.
+    Failed,
+}
+
 /// PeripheralBuilders are used to initialize parts of new Peripherals at different points in time.
 ///
 /// PeripheralBuilders allow the daemon to build new Peripheral instances explicitly and
@@ -392,6 +841,9 @@ pub struct PeripheralBuilder {
     /// The ID of the PeripheralBuilder.
     id: Option<usize>,
 
+    /// Whether the plugin of the Peripheral runs in its own process.
+    isolated: bool,
+
     /// The ID of the plugin library that is used to control this Peripheral.
     library_id: usize,
 
@@ -405,6 +857,7 @@ impl PeripheralBuilder {
             attributes: BTreeMap::new(),
             attribute_builders: BTreeMap::new(),
             id: None,
+            isolated: false,
             library_id,
             name,
         }
@@ -421,8 +874,10 @@ impl PeripheralBuilder {
         Ok(Peripheral {
             attributes: self.attributes,
             id: self.id.ok_or(BuilderPartiallyInitializedError())?,
+            isolated: self.isolated,
             library_id: self.library_id,
             name: self.name,
+            state: PeripheralState::Init,
         })
     }
 
@@ -449,11 +904,26 @@ impl PeripheralBuilder {
         self.attribute_builders.remove(&id)
     }
 
+    /// Returns all AttributeBuilders of the builder.
+    pub fn attribute_builders(&self) -> &BTreeMap<usize, AttributeBuilder> {
+        &self.attribute_builders
+    }
+
+    /// Returns true if the plugin of the Peripheral will run in its own process.
+    pub fn isolated(&self) -> bool {
+        self.isolated
+    }
+
     /// Returns the library ID of the AttributeBuilder
     pub fn library_id(&self) -> &usize {
         &self.library_id
     }
 
+    /// Returns the name of the PeripheralBuilder.
+    pub fn name(&self) -> &str {
+        &self.name
+    }
+
     /// Inserts an Attribute into the collection of Attributes owned by this builder.
     ///
     /// # Arguments
@@ -485,15 +955,32 @@ impl PeripheralBuilder {
         self.id = Some(id);
         self
     }
+
+    /// Sets whether the plugin of the Peripheral runs in its own process.
+    ///
+    /// # Arguments
+    ///
+    /// * `isolated` - If true, the plugin is hosted by a child process of the daemon
+    pub fn set_isolated(mut self, isolated: bool) -> PeripheralBuilder {
+        self.isolated = isolated;
+        self
+    }
 }
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq)]
 /// A Value represents the current value of an Attribute.
 pub enum Value {
     Int { value: i32 },
     Double { value: f64 },
     String { value: CString },
     Uint { value: u32 },
+    Bool { value: bool },
+    DoubleArray { value: Vec<f64> },
+    IntArray { value: Vec<i32> },
+    Bytes { value: Vec<u8> },
+    Int64 { value: i64 },
+    Uint64 { value: u64 },
+    Float { value: f32 },
 }
 
 impl Value {
@@ -507,8 +994,89 @@ impl Value {
                 PluginValue::String(slice.as_ptr(), slice.len())
             }
             Value::Uint { value } => PluginValue::Uint(*value),
+            Value::Bool { value } => PluginValue::Bool(*value),
+            Value::DoubleArray { value } => PluginValue::DoubleArray(value.as_ptr(), value.len()),
+            Value::IntArray { value } => PluginValue::IntArray(value.as_ptr(), value.len()),
+            Value::Bytes { value } => PluginValue::Bytes(value.as_ptr(), value.len()),
+            Value::Int64 { value } => PluginValue::Int64(*value),
+            Value::Uint64 { value } => PluginValue::Uint64(*value),
+            Value::Float { value } => PluginValue::Float(*value),
         }
     }
+
+    /// Returns the value as a number, or None if it is not a single numeric value.
+    pub fn as_f64(&self) -> Option<f64> {
+        match self {
+            Value::Int { value } => Some(f64::from(*value)),
+            Value::Double { value } => Some(*value),
+            Value::Uint { value } => Some(f64::from(*value)),
+            Value::Int64 { value } => Some(*value as f64),
+            Value::Uint64 { value } => Some(*value as f64),
+            Value::Float { value } => Some(f64::from(*value)),
+            _ => None,
+        }
+    }
+}
+
+impl TryFrom<PluginValue> for Value {
+    type Error = ModelError;
+
+    /// Creates a new Value by copying the data that is referenced by a PluginValue.
+    fn try_from(value: PluginValue) -> Result<Value, Self::Error> {
+        let value = match value {
+            PluginValue::Int(value) => Value::Int { value },
+            PluginValue::Double(value) => Value::Double { value },
+            PluginValue::String(p_value, length) => {
+                let value = unsafe {
+                    let slice = plugin_slice(p_value, length)?;
+                    let string = CStr::from_bytes_with_nul(slice)?.to_str()?;
+                    CString::new(string.to_owned())?
+                };
+                Value::String { value }
+            }
+            PluginValue::Uint(value) => Value::Uint { value },
+            PluginValue::Bool(value) => Value::Bool { value },
+            PluginValue::DoubleArray(p_value, length) => {
+                let value = unsafe { plugin_slice(p_value, length)?.to_vec() };
+                Value::DoubleArray { value }
+            }
+            PluginValue::IntArray(p_value, length) => {
+                let value = unsafe { plugin_slice(p_value, length)?.to_vec() };
+                Value::IntArray { value }
+            }
+            PluginValue::Bytes(p_value, length) => {
+                let value = unsafe { plugin_slice(p_value, length)?.to_vec() };
+                Value::Bytes { value }
+            }
+            PluginValue::Int64(value) => Value::Int64 { value },
+            PluginValue::Uint64(value) => Value::Uint64 { value },
+            PluginValue::Float(value) => Value::Float { value },
+        };
+
+        Ok(value)
+    }
+}
+
+/// Returns the data that a value from a plugin refers to as a slice.
+///
+/// Empty slices are returned without reading the pointer, which may be null in this case.
+///
+/// # Safety
+///
+/// The pointer must be valid for reads of `length` elements if `length` is not zero.
+///
+/// # Arguments
+///
+/// * `p_value` - A pointer to the first element of the data
+/// * `length` - The number of elements of the data
+unsafe fn plugin_slice<'a, T>(p_value: *const T, length: usize) -> Result<&'a [T], ModelError> {
+    if length == 0 {
+        Ok(&[])
+    } else if p_value.is_null() {
+        Err(NullPointerError().into())
+    } else {
+        Ok(slice::from_raw_parts(p_value, length))
+    }
 }
 
 #[cfg(test)]
@@ -517,7 +1085,7 @@ mod tests {
 
     use std::f64::consts::PI;
 
-    use kpal_plugin::Val as PluginValue;
+    use kpal_plugin::{Val as PluginValue, ABI_VERSION};
 
     #[test]
     fn test_attribute_new() {
@@ -533,11 +1101,33 @@ mod tests {
                 context.float_id,
                 context.attributes.get(&context.float_id).unwrap(),
             ),
+            (
+                PluginValue::Bool(context.bool_value),
+                context.bool_id,
+                context.attributes.get(&context.bool_id).unwrap(),
+            ),
+            (
+                PluginValue::DoubleArray(
+                    context.double_array_value.as_ptr(),
+                    context.double_array_value.len(),
+                ),
+                context.double_array_id,
+                context.attributes.get(&context.double_array_id).unwrap(),
+            ),
         ];
 
         for (value, id, attr) in cases {
-            let converted_attr =
-                Attribute::new(value, id, context.name.clone(), context.pre_init).unwrap();
+            let converted_attr = Attribute::new(
+                value,
+                id,
+                context.name.clone(),
+                context.name.clone(),
+                context.pre_init,
+                Vec::new(),
+                Metadata::default(),
+                Settable::default(),
+            )
+            .unwrap();
             assert_eq!(attr, &converted_attr);
         }
     }
@@ -574,21 +1164,60 @@ mod tests {
     #[test]
     fn test_library_new() {
         let context = set_up();
-        let library = Library::new(0, context.name.clone(), None);
+        let library = Library::new(
+            0,
+            context.name.clone(),
+            None,
+            ABI_VERSION,
+            PathBuf::from("libfoo.so"),
+            None,
+        );
 
         assert_eq!(library.id, 0);
         assert_eq!(library.name, context.name);
+        assert_eq!(library.abi_version(), ABI_VERSION);
         assert!(library.library.is_none());
     }
 
+    #[test]
+    fn test_library_matches() {
+        let library = Library::new(
+            0,
+            String::from("foo"),
+            Some(Version::new(0, 3, 1)),
+            ABI_VERSION,
+            PathBuf::from("/plugins/libfoo.so"),
+            None,
+        );
+
+        assert_eq!("foo@0.3.1", library.qualified_name());
+        for name in &["libfoo.so", "foo", "foo@0.3", "foo@0.3.0", "foo@>=0.2"] {
+            assert!(library.matches(name), "Library does not match {}", name);
+        }
+        for name in &[
+            "bar",
+            "foo@0.4",
+            "foo@1",
+            "foo@latest",
+            "/plugins/libfoo.so",
+        ] {
+            assert!(!library.matches(name), "Library matches {}", name);
+        }
+    }
+
     #[test]
     fn test_library_dll() {
         let context = set_up();
         let library = Library {
+            abi_version: ABI_VERSION,
+            actions: BTreeMap::new(),
             id: 0,
             name: context.name,
             attributes: context.attributes,
             library: None,
+            path: PathBuf::new(),
+            peripherals: 0,
+            version: None,
         };
 
         assert!(library.dll().is_none());
@@ -611,9 +1240,13 @@ mod tests {
         let mut context = set_up();
         let new_value = PluginValue::Double(PI);
         let new_attr = Attribute {
+            alias: context.name.clone(),
+            choices: Vec::new(),
             id: context.float_id,
+            metadata: Metadata::default(),
             name: context.name.clone(),
             pre_init: context.pre_init,
+            settable: Settable::default(),
             value: Value::Double { value: PI },
         };
 
@@ -633,8 +1266,135 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_attribute_allows() {
+        let value = |s: &str| Value::String {
+            value: CString::new(s).unwrap(),
+        };
+        let choices = vec![String::from("low"), String::from("high")];
+        let attr = Attribute::new(
+            PluginValue::Int(0),
+            0,
+            String::from("gain"),
+            String::from("gain"),
+            false,
+            choices,
+            Metadata::default(),
+            Settable::default(),
+        )
+        .unwrap();
+
+        assert!(attr.allows(&value("low")));
+        assert!(!attr.allows(&value("medium")));
+
+        let attr = Attribute::new(
+            PluginValue::Int(0),
+            0,
+            String::from("gain"),
+            String::from("gain"),
+            false,
+            Vec::new(),
+            Metadata::default(),
+            Settable::default(),
+        )
+        .unwrap();
+        assert!(attr.allows(&value("medium")));
+    }
+
+    #[test]
+    fn test_attribute_in_range() {
+        let metadata = Metadata {
+            min: Some(-1.0),
+            max: Some(1.0),
+            ..Metadata::default()
+        };
+        let attr = Attribute::new(
+            PluginValue::Double(0.0),
+            0,
+            String::from("x"),
+            String::from("x"),
+            false,
+            Vec::new(),
+            metadata,
+            Settable::default(),
+        )
+        .unwrap();
+
+        assert!(attr.in_range(&Value::Double { value: 1.0 }));
+        assert!(!attr.in_range(&Value::Double { value: 1.5 }));
+        assert!(!attr.in_range(&Value::Int { value: -2 }));
+        assert!(!attr.in_range(&Value::DoubleArray {
+            value: vec![0.0, 2.0]
+        }));
+        assert!(attr.in_range(&Value::Bool { value: true }));
+    }
+
+    #[test]
+    fn test_attribute_in_range_64_bit_integers() {
+        let metadata = Metadata {
+            max: Some(9_007_199_254_740_992.0),
+            ..Metadata::default()
+        };
+        let attr = Attribute::new(
+            PluginValue::Int64(0),
+            0,
+            String::from("count"),
+            String::from("count"),
+            false,
+            Vec::new(),
+            metadata,
+            Settable::default(),
+        )
+        .unwrap();
+
+        // 2^53 + 1 rounds down to the maximum when it is converted to a float.
+        assert!(attr.in_range(&Value::Int64 {
+            value: 9_007_199_254_740_992
+        }));
+        assert!(!attr.in_range(&Value::Int64 {
+            value: 9_007_199_254_740_993
+        }));
+        assert!(!attr.in_range(&Value::Uint64 { value: u64::MAX }));
+    }
+
+    #[test]
+    fn test_value_try_from_null_pointer() {
+        let value = Value::try_from(PluginValue::IntArray(std::ptr::null(), 0)).unwrap();
+        assert_eq!(value, Value::IntArray { value: Vec::new() });
+
+        assert!(Value::try_from(PluginValue::Bytes(std::ptr::null(), 2)).is_err());
+        assert!(Value::try_from(PluginValue::String(std::ptr::null(), 0)).is_err());
+    }
+
+    #[test]
+    fn test_metadata_new() {
+        let units = CString::new("mm").unwrap();
+        let plugin_metadata = PluginMetadata {
+            description: std::ptr::null(),
+            units: units.as_ptr() as *const u8,
+            min: 0.0,
+            max: f64::NAN,
+            step: f64::NAN,
+        };
+
+        let metadata = Metadata::new(&plugin_metadata).unwrap();
+
+        assert_eq!(
+            Metadata {
+                min: Some(0.0),
+                units: Some(String::from("mm")),
+                ..Metadata::default()
+            },
+            metadata
+        );
+    }
+
     struct Context {
         attributes: BTreeMap<usize, Attribute>,
+        bool_id: usize,
+        bool_value: bool,
+        double_array_id: usize,
+        double_array_value: Vec<f64>,
         float_id: usize,
         float_value: f64,
         int_id: usize,
@@ -647,38 +1407,82 @@ mod tests {
 
     fn set_up() -> Context {
         let (name, int_value, float_value) = (String::from("foo"), 42, 42.42);
-        let (int_id, float_id) = (0, 1);
+        let (int_id, float_id, bool_id, double_array_id) = (0, 1, 2, 3);
+        let bool_value = true;
+        let double_array_value = vec![0.0, 0.5, 1.0];
         let library_id = 1;
         let pre_init = false;
         let mut attributes: BTreeMap<usize, Attribute> = BTreeMap::new();
         attributes.insert(
             int_id,
             Attribute {
+                alias: name.clone(),
+                choices: Vec::new(),
                 id: int_id,
+                metadata: Metadata::default(),
                 name: name.clone(),
                 pre_init,
+                settable: Settable::default(),
                 value: Value::Int { value: int_value },
             },
         );
         attributes.insert(
             float_id,
             Attribute {
+                alias: name.clone(),
+                choices: Vec::new(),
                 id: float_id,
+                metadata: Metadata::default(),
                 name: name.clone(),
                 pre_init,
+                settable: Settable::default(),
                 value: Value::Double { value: float_value },
             },
         );
+        attributes.insert(
+            bool_id,
+            Attribute {
+                alias: name.clone(),
+                choices: Vec::new(),
+                id: bool_id,
+                metadata: Metadata::default(),
+                name: name.clone(),
+                pre_init,
+                settable: Settable::default(),
+                value: Value::Bool { value: bool_value },
+            },
+        );
+        attributes.insert(
+            double_array_id,
+            Attribute {
+                alias: name.clone(),
+                choices: Vec::new(),
+                id: double_array_id,
+                metadata: Metadata::default(),
+                name: name.clone(),
+                pre_init,
+                settable: Settable::default(),
+                value: Value::DoubleArray {
+                    value: double_array_value.clone(),
+                },
+            },
+        );
 
         let peripheral = Peripheral {
             library_id,
             name: name.clone(),
             attributes: attributes.clone(),
             id: 0,
+            isolated: false,
+            state: PeripheralState::Init,
         };
 
         Context {
             attributes,
+            bool_id,
+            bool_value,
+            double_array_id,
+            double_array_value,
             float_id,
             float_value,
             int_id,
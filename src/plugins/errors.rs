@@ -13,7 +13,7 @@ use crate::{
     models::{Library, ModelError},
 };
 
-use super::executor::ExecutorError;
+use super::{executor::ExecutorError, host::HostError};
 
 /// Contains information for clients about errors that occur while communicating with a plugin.
 ///
@@ -101,10 +101,25 @@ impl From<ExecutorError> for PluginError {
     }
 }
 
+impl From<HostError> for PluginError {
+    fn from(error: HostError) -> Self {
+        PluginError {
+            message: error.message().to_owned(),
+            reason: ErrorReason::InternalError,
+            side: Some(Box::new(error)),
+        }
+    }
+}
+
 impl From<MergeAttributesError> for PluginError {
     fn from(error: MergeAttributesError) -> Self {
         let err2 = error.clone();
         match err2 {
+            MergeAttributesError::DoesNotExist(msg) => PluginError {
+                message: msg,
+                reason: ErrorReason::UnprocessableRequest,
+                side: Some(Box::new(error)),
+            },
             MergeAttributesError::Failure(msg) => PluginError {
                 message: msg,
                 reason: ErrorReason::InternalError,
@@ -115,6 +130,16 @@ impl From<MergeAttributesError> for PluginError {
                 reason: ErrorReason::UnprocessableRequest,
                 side: Some(Box::new(error)),
             },
+            MergeAttributesError::NotAllowed(msg) => PluginError {
+                message: msg,
+                reason: ErrorReason::UnprocessableRequest,
+                side: Some(Box::new(error)),
+            },
+            MergeAttributesError::OutOfRange(msg) => PluginError {
+                message: msg,
+                reason: ErrorReason::UnprocessableRequest,
+                side: Some(Box::new(error)),
+            },
             MergeAttributesError::VariantMismatch(msg) => PluginError {
                 message: msg,
                 reason: ErrorReason::UnprocessableRequest,
@@ -147,8 +172,11 @@ impl<'a> From<PoisonError<RwLockWriteGuard<'a, Transmitters>>> for PluginError {
 /// Raised when the user-provided attribute values cannot be merged into the defaults.
 #[derive(Debug, Clone)]
 pub enum MergeAttributesError {
+    DoesNotExist(String),
     Failure(String),
     IsNotPreInit(String),
+    NotAllowed(String),
+    OutOfRange(String),
     VariantMismatch(String),
 }
 
@@ -0,0 +1,215 @@
+//! Events that notify subscribers of changes to the state of a peripheral.
+//!
+//! Each executor owns an event log. Every time the cached value of one of its peripheral's
+//! attributes changes, a new event is appended to the log and sent to all the subscribers. A
+//! limited number of past events is kept so that subscribers which reconnect may resume from the
+//! last event that they received.
+//!
+//! Event IDs are prefixed with the time at which the event log was created. This keeps them
+//! increasing when a peripheral is recreated, e.g. after the daemon restarts, so that a subscriber
+//! that resumes with an ID from an earlier log receives the recorded events instead of none.
+
+use std::{
+    collections::VecDeque,
+    sync::mpsc::{channel, Receiver, Sender},
+};
+
+use crate::{
+    constants::{EVENT_HISTORY_LENGTH, EVENT_ID_COUNTER_BITS},
+    models::Attribute,
+    util::timestamp,
+};
+
+/// A change to the value of a peripheral's attribute.
+#[derive(Clone, Debug)]
+pub struct Event {
+    /// The attribute after the change.
+    attribute: Attribute,
+
+    /// The ID of the event. IDs increase by 1 with each event of a peripheral.
+    id: u64,
+}
+
+impl Event {
+    /// Returns the attribute after the change.
+    pub fn attribute(&self) -> &Attribute {
+        &self.attribute
+    }
+
+    /// Returns the ID of the event.
+    pub fn id(&self) -> u64 {
+        self.id
+    }
+}
+
+/// A subscription to the events of a single peripheral.
+#[derive(Debug)]
+pub struct Subscription {
+    /// The events that occurred after the last event ID provided by the subscriber.
+    pub missed: Vec<Event>,
+
+    /// The receiver of all future events.
+    pub rx: Receiver<Event>,
+}
+
+/// The log of events of a single peripheral.
+#[derive(Debug)]
+pub struct Events {
+    /// The most recent events, ordered from oldest to newest.
+    history: VecDeque<Event>,
+
+    /// The ID of the most recent event.
+    last_id: u64,
+
+    /// The transmitters for sending events to each subscriber.
+    subscribers: Vec<Sender<Event>>,
+}
+
+impl Events {
+    /// Returns a new, empty event log.
+    ///
+    /// The ID of the first event is prefixed with the current time in milliseconds since the Unix
+    /// epoch.
+    pub fn new() -> Events {
+        Events {
+            history: VecDeque::new(),
+            last_id: timestamp() << EVENT_ID_COUNTER_BITS,
+            subscribers: Vec::new(),
+        }
+    }
+
+    /// Records that an attribute has changed and sends the event to all subscribers.
+    ///
+    /// Subscribers that have disconnected are removed.
+    ///
+    /// # Arguments
+    ///
+    /// * `attribute` - The attribute after the change
+    pub fn publish(&mut self, attribute: Attribute) {
+        self.last_id += 1;
+        let event = Event {
+            attribute,
+            id: self.last_id,
+        };
+
+        self.subscribers
+            .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+
+        if self.history.len() == EVENT_HISTORY_LENGTH {
+            self.history.pop_front();
+        }
+        self.history.push_back(event);
+    }
+
+    /// Adds a new subscriber to the event log.
+    ///
+    /// # Arguments
+    ///
+    /// * `last_event_id` - The ID of the last event received by a subscriber that is resuming.
+    ///   Recorded events that occurred after this one are returned in the subscription.
+    pub fn subscribe(&mut self, last_event_id: Option<u64>) -> Subscription {
+        let missed = match last_event_id {
+            Some(last_event_id) => self
+                .history
+                .iter()
+                .filter(|event| event.id > last_event_id)
+                .cloned()
+                .collect(),
+            None => Vec::new(),
+        };
+
+        let (tx, rx) = channel();
+        self.subscribers.push(tx);
+
+        Subscription { missed, rx }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use kpal_plugin::Val;
+
+    use crate::models::{Metadata, Settable};
+
+    fn attribute(value: i32) -> Attribute {
+        Attribute::new(
+            Val::Int(value),
+            0,
+            "x".to_string(),
+            "x".to_string(),
+            false,
+            Vec::new(),
+            Metadata::default(),
+            Settable::default(),
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn test_publish() {
+        let mut events = Events::new();
+        let subscription = events.subscribe(None);
+        assert!(subscription.missed.is_empty());
+
+        let first = events.last_id + 1;
+        events.publish(attribute(1));
+        events.publish(attribute(2));
+
+        let received: Vec<Event> = subscription.rx.try_iter().collect();
+        assert_eq!(received.len(), 2);
+        assert_eq!(received[0].id(), first);
+        assert_eq!(received[1].id(), first + 1);
+        assert_eq!(received[1].attribute().value(), attribute(2).value());
+    }
+
+    #[test]
+    fn test_publish_removes_disconnected_subscribers() {
+        let mut events = Events::new();
+        let subscription = events.subscribe(None);
+        drop(subscription);
+
+        events.publish(attribute(1));
+
+        assert!(events.subscribers.is_empty());
+    }
+
+    #[test]
+    fn test_subscribe_resumes_from_last_event_id() {
+        let mut events = Events::new();
+        let start = events.last_id;
+        for value in 0..(EVENT_HISTORY_LENGTH as i32 + 5) {
+            events.publish(attribute(value));
+        }
+
+        let subscription = events.subscribe(Some(start + EVENT_HISTORY_LENGTH as u64 + 2));
+        let ids: Vec<u64> = subscription.missed.iter().map(|event| event.id()).collect();
+        assert_eq!(
+            ids,
+            vec![
+                start + EVENT_HISTORY_LENGTH as u64 + 3,
+                start + EVENT_HISTORY_LENGTH as u64 + 4,
+                start + EVENT_HISTORY_LENGTH as u64 + 5
+            ]
+        );
+
+        // Events that are no longer in the history cannot be replayed.
+        let subscription = events.subscribe(Some(0));
+        assert_eq!(subscription.missed.len(), EVENT_HISTORY_LENGTH);
+        assert_eq!(subscription.missed[0].id(), start + 6);
+    }
+
+    #[test]
+    fn test_ids_increase_across_event_logs() {
+        let mut events = Events::new();
+        events.publish(attribute(1));
+        let last = events.last_id;
+
+        std::thread::sleep(std::time::Duration::from_millis(2));
+        let mut events = Events::new();
+        events.publish(attribute(1));
+
+        assert!(events.last_id > last);
+    }
+}
@@ -2,7 +2,7 @@
 
 use std::{boxed::Box, error::Error, fmt, fmt::Debug, mem::discriminant};
 
-use crate::{integrations::ErrorReason, models::ModelError};
+use crate::{integrations::ErrorReason, models::ModelError, plugins::host::HostError};
 
 /// An error returned when an operation in an executor fails.
 #[derive(Debug)]
@@ -73,6 +73,19 @@ impl PartialEq for ExecutorError {
     }
 }
 
+impl From<ActionError> for ExecutorError {
+    fn from(error: ActionError) -> ExecutorError {
+        let (body, reason) = match error {
+            ActionError::DoesNotExist(ref msg) => (msg.clone(), ErrorReason::ResourceNotFound),
+            ActionError::Failure(ref msg) => (msg.clone(), ErrorReason::InternalError),
+            ActionError::InvalidArguments(ref msg) => {
+                (msg.clone(), ErrorReason::UnprocessableRequest)
+            }
+        };
+        ExecutorError::new(body, reason, Some(Box::new(error)))
+    }
+}
+
 impl From<AdvancePhaseError> for ExecutorError {
     fn from(error: AdvancePhaseError) -> ExecutorError {
         ExecutorError::new(
@@ -83,6 +96,16 @@ impl From<AdvancePhaseError> for ExecutorError {
     }
 }
 
+impl From<ChoicesError> for ExecutorError {
+    fn from(error: ChoicesError) -> ExecutorError {
+        let (body, reason) = match error {
+            ChoicesError::DoesNotExist(ref msg) => (msg.clone(), ErrorReason::ResourceNotFound),
+            ChoicesError::Failure(ref msg) => (msg.clone(), ErrorReason::InternalError),
+        };
+        ExecutorError::new(body, reason, Some(Box::new(error)))
+    }
+}
+
 impl From<CountError> for ExecutorError {
     fn from(error: CountError) -> ExecutorError {
         ExecutorError::new(
@@ -93,6 +116,16 @@ impl From<CountError> for ExecutorError {
     }
 }
 
+impl From<DeinitError> for ExecutorError {
+    fn from(error: DeinitError) -> ExecutorError {
+        ExecutorError::new(
+            "Could not deinitialize the plugin".to_string(),
+            ErrorReason::InternalError,
+            Some(Box::new(error)),
+        )
+    }
+}
+
 impl From<IdsError> for ExecutorError {
     fn from(error: IdsError) -> ExecutorError {
         ExecutorError::new(
@@ -123,6 +156,16 @@ impl From<NameError> for ExecutorError {
     }
 }
 
+impl From<NotifierError> for ExecutorError {
+    fn from(error: NotifierError) -> ExecutorError {
+        ExecutorError::new(
+            "Could not set the plugin's notifier".to_string(),
+            ErrorReason::InternalError,
+            Some(Box::new(error)),
+        )
+    }
+}
+
 impl From<PreInitError> for ExecutorError {
     fn from(error: PreInitError) -> ExecutorError {
         ExecutorError::new(
@@ -133,6 +176,16 @@ impl From<PreInitError> for ExecutorError {
     }
 }
 
+impl From<RetreatPhaseError> for ExecutorError {
+    fn from(error: RetreatPhaseError) -> ExecutorError {
+        ExecutorError::new(
+            "Could not return the plugin to its init phase".to_string(),
+            ErrorReason::UnprocessableRequest,
+            Some(Box::new(error)),
+        )
+    }
+}
+
 impl From<SetValueError> for ExecutorError {
     fn from(error: SetValueError) -> ExecutorError {
         let (body, reason) = match error {
@@ -144,6 +197,26 @@ impl From<SetValueError> for ExecutorError {
     }
 }
 
+impl From<MetadataError> for ExecutorError {
+    fn from(error: MetadataError) -> ExecutorError {
+        let (body, reason) = match error {
+            MetadataError::DoesNotExist(ref msg) => (msg.clone(), ErrorReason::ResourceNotFound),
+            MetadataError::Failure(ref msg) => (msg.clone(), ErrorReason::InternalError),
+        };
+        ExecutorError::new(body, reason, Some(Box::new(error)))
+    }
+}
+
+impl From<SettableError> for ExecutorError {
+    fn from(error: SettableError) -> ExecutorError {
+        let (body, reason) = match error {
+            SettableError::DoesNotExist(ref msg) => (msg.clone(), ErrorReason::ResourceNotFound),
+            SettableError::Failure(ref msg) => (msg.clone(), ErrorReason::InternalError),
+        };
+        ExecutorError::new(body, reason, Some(Box::new(error)))
+    }
+}
+
 impl From<ModelError> for ExecutorError {
     fn from(error: ModelError) -> ExecutorError {
         ExecutorError::new(
@@ -164,6 +237,16 @@ impl From<ValueError> for ExecutorError {
     }
 }
 
+impl From<HostError> for ExecutorError {
+    fn from(error: HostError) -> ExecutorError {
+        ExecutorError {
+            message: error.message().to_owned(),
+            reason: ErrorReason::InternalError,
+            side: Some(Box::new(error)),
+        }
+    }
+}
+
 impl From<std::str::Utf8Error> for ExecutorError {
     fn from(error: std::str::Utf8Error) -> Self {
         ExecutorError::new(
@@ -174,6 +257,22 @@ impl From<std::str::Utf8Error> for ExecutorError {
     }
 }
 
+/// Represents the state of a result obtained by querying or invoking an action.
+#[derive(Debug, PartialEq)]
+pub enum ActionError {
+    DoesNotExist(String),
+    Failure(String),
+    InvalidArguments(String),
+}
+
+impl Error for ActionError {}
+
+impl fmt::Display for ActionError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ActionError: {:?}", self)
+    }
+}
+
 /// Represents an error which prevents the advance of the plugin's lifecycle phase.
 #[derive(Debug)]
 pub struct AdvancePhaseError(pub i32);
@@ -186,6 +285,21 @@ impl fmt::Display for AdvancePhaseError {
     }
 }
 
+/// Represents the state of a result obtained by fetching the choices of an attribute.
+#[derive(Debug, PartialEq)]
+pub enum ChoicesError {
+    DoesNotExist(String),
+    Failure(String),
+}
+
+impl Error for ChoicesError {}
+
+impl fmt::Display for ChoicesError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "ChoicesError: {:?}", self)
+    }
+}
+
 /// Represents an error encountered when fetching the attribute count.
 #[derive(Debug, PartialEq)]
 pub struct CountError(pub String);
@@ -198,6 +312,18 @@ impl fmt::Display for CountError {
     }
 }
 
+/// An error raised during the plugin's deinitialization routine.
+#[derive(Debug)]
+pub struct DeinitError(pub String);
+
+impl Error for DeinitError {}
+
+impl fmt::Display for DeinitError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "DeinitError: {}", self.0)
+    }
+}
+
 /// Represents an error encountered when fetching the attribute IDs.
 #[derive(Debug, PartialEq)]
 pub struct IdsError(pub String);
@@ -222,6 +348,21 @@ impl fmt::Display for InitError {
     }
 }
 
+/// Represents the state of a result obtained by fetching the metadata of an attribute.
+#[derive(Debug, PartialEq)]
+pub enum MetadataError {
+    DoesNotExist(String),
+    Failure(String),
+}
+
+impl Error for MetadataError {}
+
+impl fmt::Display for MetadataError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "MetadataError: {:?}", self)
+    }
+}
+
 /// Represents the state of a result obtained by fetching a name from an attribute.
 #[derive(Debug, PartialEq)]
 pub enum NameError {
@@ -237,6 +378,18 @@ impl fmt::Display for NameError {
     }
 }
 
+/// An error raised when the plugin does not accept the daemon's notifier.
+#[derive(Debug)]
+pub struct NotifierError(pub String);
+
+impl Error for NotifierError {}
+
+impl fmt::Display for NotifierError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "NotifierError: {}", self.0)
+    }
+}
+
 /// Represents the state of a result obtained by determining whether an attribute is pre-init.
 #[derive(Debug, PartialEq)]
 pub enum PreInitError {
@@ -252,6 +405,22 @@ impl fmt::Display for PreInitError {
     }
 }
 
+/// Represents an error which prevents the plugin from returning to its init phase.
+#[derive(Debug)]
+pub struct RetreatPhaseError(pub i32);
+
+impl Error for RetreatPhaseError {}
+
+impl fmt::Display for RetreatPhaseError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "Cannot return to the init phase from current phase: {}",
+            self.0
+        )
+    }
+}
+
 /// Represents the state of a result obtained by setting a value of an attribute.
 #[derive(Debug, PartialEq)]
 pub enum SetValueError {
@@ -268,6 +437,21 @@ impl fmt::Display for SetValueError {
     }
 }
 
+/// Represents the state of a result obtained by determining whether an attribute is settable.
+#[derive(Debug, PartialEq)]
+pub enum SettableError {
+    DoesNotExist(String),
+    Failure(String),
+}
+
+impl Error for SettableError {}
+
+impl fmt::Display for SettableError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SettableError: {:?}", self)
+    }
+}
+
 /// Represents the state of a result obtained by fetching a value from an attribute.
 #[derive(Debug, PartialEq)]
 pub enum ValueError {
@@ -2,38 +2,58 @@
 
 mod errors;
 
-use std::{collections::BTreeMap, error::Error, ffi::CStr, sync::mpsc::channel, thread};
+use std::{
+    collections::BTreeMap,
+    convert::TryFrom,
+    error::Error,
+    ffi::CStr,
+    sync::{mpsc::channel, Mutex},
+    thread,
+};
 
 use {
-    libc::{c_char, c_int, c_uchar, size_t},
+    libc::{c_char, c_int, c_uchar, c_void, size_t},
     log,
     memchr::memchr,
 };
 
-use kpal_plugin::{error_codes::*, Val};
-use kpal_plugin::{ATTRIBUTE_PRE_INIT_FALSE, ATTRIBUTE_PRE_INIT_TRUE, INIT_PHASE, RUN_PHASE};
+use kpal_plugin::{error_codes::*, MetadataVal, Notifier, Val};
+use kpal_plugin::{
+    Phase, ATTRIBUTE_PRE_INIT_FALSE, ATTRIBUTE_PRE_INIT_TRUE, ATTRIBUTE_SETTABLE_FALSE,
+    ATTRIBUTE_SETTABLE_TRUE, INIT_PHASE, RUN_PHASE,
+};
 
 use super::{
-    messaging::{Receiver, Transmitter},
+    events::Events,
+    host,
+    messaging::{log_and_send, Message, Receiver, Transmitter},
     Plugin,
 };
 
 use crate::{
     constants::*,
+    init::TSLibrary,
     integrations::ErrorReason,
-    models::{Attribute, Model, Peripheral, PeripheralBuilder},
+    models::{
+        Action, ActionArgument, Attribute, Metadata, Model, Peripheral, PeripheralState, Settable,
+        Value,
+    },
 };
 
 pub use errors::ExecutorError;
 use errors::{
-    AdvancePhaseError, CountError, IdsError, InitError, NameError, PreInitError, SetValueError,
-    ValueError,
+    ActionError, AdvancePhaseError, ChoicesError, CountError, DeinitError, IdsError, InitError,
+    MetadataError, NameError, NotifierError, PreInitError, RetreatPhaseError, SetValueError,
+    SettableError, ValueError,
 };
 
 /// Executes tasks on a Plugin in response to messages.
 ///
 /// Each Plugin is powered by a single executor.
 pub struct Executor {
+    /// The log of changes to the peripheral's attributes.
+    pub events: Events,
+
     /// The Plugin instance that is managed by this executor.
     pub plugin: Plugin,
 
@@ -43,8 +63,22 @@ pub struct Executor {
     /// The executor's transmitter.
     pub tx: Transmitter,
 
+    /// The transmitter that forwards the plugin's notifications into the executor's queue.
+    ///
+    /// Its address is the context of the plugin's notifier. It is declared after the plugin so
+    /// that it is dropped only after the plugin is freed.
+    notifications: Box<Mutex<Transmitter>>,
+
+    /// Whether the plugin is hosted by a child process.
+    isolated: bool,
+
     /// The current phase of the plugin's lifetime
     phase: i32,
+
+    /// The library that provides the plugin, if any.
+    ///
+    /// It is declared last so that the library is not unloaded until the plugin has been freed.
+    library: Option<TSLibrary>,
 }
 
 impl Executor {
@@ -57,12 +91,91 @@ impl Executor {
         let (tx, rx) = channel();
         let phase = INIT_PHASE;
 
+        let notifications = Box::new(Mutex::new(tx.clone()));
+
         Executor {
+            events: Events::new(),
             plugin,
             rx,
             tx,
+            notifications,
+            isolated: false,
             phase,
+            library: None,
+        }
+    }
+
+    /// Keeps a reference to the library that provides the plugin for as long as the executor
+    /// exists.
+    ///
+    /// The executor is counted as a user of the library so that the library is not unloaded while
+    /// the executor exists.
+    ///
+    /// # Arguments
+    ///
+    /// * `library` - The library that provides the plugin
+    pub fn set_library(&mut self, library: TSLibrary) {
+        if let Ok(mut lib) = library.lock() {
+            lib.acquire();
         }
+        if let Some(old) = self.library.replace(library) {
+            if let Ok(mut lib) = old.lock() {
+                lib.release();
+            }
+        }
+    }
+
+    /// Marks the plugin as hosted by a child process so that it may be replaced when the process
+    /// exits.
+    ///
+    /// # Arguments
+    ///
+    /// * `isolated` - Whether the plugin was returned by `host::spawn`
+    pub fn set_isolated(&mut self, isolated: bool) {
+        self.isolated = isolated;
+    }
+
+    /// Returns true if the plugin is hosted by a process that has exited.
+    pub fn failed(&self) -> bool {
+        self.isolated && unsafe { host::exited(&self.plugin) }
+    }
+
+    /// Replaces the plugin with a new instance if it is hosted by a process that has exited.
+    ///
+    /// The new plugin is in the init phase and has already been passed the notifier. Returns true
+    /// if the plugin was replaced.
+    pub fn revive(&mut self) -> Result<bool, ExecutorError> {
+        if !self.failed() {
+            return Ok(false);
+        }
+
+        let path = match &self.library {
+            Some(library) => library
+                .lock()
+                .map_err(|_| {
+                    ExecutorError::new(
+                        "The plugin's library is poisoned".to_string(),
+                        ErrorReason::InternalError,
+                        None,
+                    )
+                })?
+                .path()
+                .to_owned(),
+            None => {
+                return Err(ExecutorError::new(
+                    "The plugin's library is unknown".to_string(),
+                    ErrorReason::InternalError,
+                    None,
+                ))
+            }
+        };
+
+        log::warn!("The plugin host process exited; starting a new one");
+        self.plugin = host::spawn(&path)?;
+        self.phase = INIT_PHASE;
+        self.set_notifier()?;
+
+        Ok(true)
     }
 
     /// Starts an Executor.
@@ -72,6 +185,10 @@ impl Executor {
     /// communicating with the peripheral through the plugin interface) and returns the result via
     /// the return transmitter that was passed alongside the message.
     ///
+    /// The loop exits when a `Shutdown` message is received. The Executor and its Plugin are
+    /// dropped before the response is sent so that any hardware resources held by the plugin are
+    /// released by the time the sender is notified.
+    ///
     /// # Arguments
     ///
     /// * `peripheral` - The instance of a peripheral model that is modified in response to actions
@@ -90,6 +207,22 @@ impl Executor {
                         Some(Box::new(e)),
                     )
                 })?;
+
+                if let Message::Shutdown(tx) = msg {
+                    let id = peripheral.id();
+                    log::info!("Shutting down the executor for peripheral: {}", id);
+
+                    drop(self);
+                    log_and_send(tx, Ok(()), id);
+                    return Ok(());
+                }
+
+                // The peripheral is marked as failed before the message is handled so that the
+                // failure is reported by reads of the peripheral until it is restarted.
+                if self.failed() {
+                    peripheral.set_state(PeripheralState::Failed);
+                }
+
                 msg.handle(&mut self, &mut peripheral);
             }
         });
@@ -127,6 +260,55 @@ impl Executor {
         }
     }
 
+    /// Returns the alias of an attribute from a Plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The attribute's unique ID
+    pub fn attribute_alias(&self, id: size_t) -> Result<String, ExecutorError> {
+        let mut alias = [0u8; ATTRIBUTE_NAME_BUFFER_LENGTH];
+
+        let result = unsafe {
+            (self.plugin.vtable.attribute_alias)(
+                self.plugin.plugin_data,
+                id,
+                &mut alias[0] as *mut c_uchar,
+                ATTRIBUTE_NAME_BUFFER_LENGTH,
+            )
+        };
+
+        if result == PLUGIN_OK {
+            let alias = memchr(0, &alias)
+                .ok_or("could not find null byte")
+                .and_then(|null_byte| {
+                    CStr::from_bytes_with_nul(&alias[..=null_byte])
+                        .map_err(|_| "could not convert alias from C string")
+                })
+                .map(|alias| alias.to_string_lossy().into_owned())
+                .map_err(|err| NameError::Failure(err.to_string()))?;
+
+            log::debug!("Received alias: {:?}", alias);
+            Ok(alias)
+        } else if result == ATTRIBUTE_DOES_NOT_EXIST {
+            log::debug!("Attribute does not exist: {}", result);
+            let msg = unsafe {
+                self.error_message(result)
+                    .unwrap_or_else(|_| String::from(""))
+            };
+            Err(NameError::DoesNotExist(msg).into())
+        } else {
+            log::error!(
+                "Received error code while getting attribute alias: {}",
+                result
+            );
+            let msg = unsafe {
+                self.error_message(result)
+                    .unwrap_or_else(|_| String::from(""))
+            };
+            Err(NameError::Failure(msg).into())
+        }
+    }
+
     /// Returns the name of an attribute from a Plugin.
     ///
     /// # Arguments
@@ -232,6 +414,146 @@ impl Executor {
         }
     }
 
+    /// Determines whether an attribute may be set during a lifecycle phase.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The attribute's unique ID
+    /// * `phase` - The lifecycle phase of the plugin
+    pub fn attribute_settable(&self, id: size_t, phase: Phase) -> Result<bool, ExecutorError> {
+        let mut settable: c_char = 0;
+
+        let result = unsafe {
+            (self.plugin.vtable.attribute_settable)(
+                self.plugin.plugin_data,
+                id,
+                phase,
+                &mut settable as *mut c_char,
+            )
+        };
+
+        if result == PLUGIN_OK {
+            log::debug!("Received settable status: {}", settable);
+            if settable == ATTRIBUTE_SETTABLE_TRUE {
+                Ok(true)
+            } else if settable == ATTRIBUTE_SETTABLE_FALSE {
+                Ok(false)
+            } else {
+                Err(SettableError::Failure(
+                    "Could not determine error message from plugin".to_string(),
+                )
+                .into())
+            }
+        } else if result == ATTRIBUTE_DOES_NOT_EXIST {
+            log::debug!("Attribute does not exist: {}", result);
+            let msg = unsafe {
+                self.error_message(result).unwrap_or_else(|_| {
+                    String::from("Could not determine error message from plugin")
+                })
+            };
+            Err(SettableError::DoesNotExist(msg).into())
+        } else {
+            log::error!(
+                "Received error code while determining whether the attribute is settable: {}",
+                result
+            );
+            let msg = unsafe {
+                self.error_message(result).unwrap_or_else(|_| {
+                    String::from("Could not determine error message from plugin")
+                })
+            };
+            Err(SettableError::Failure(msg).into())
+        }
+    }
+
+    /// Returns the choices of an attribute from a Plugin.
+    ///
+    /// An empty Vec is returned if the attribute may take any value.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The attribute's unique ID
+    pub fn attribute_choices(&self, id: size_t) -> Result<Vec<String>, ExecutorError> {
+        let mut buffer = [0u8; ATTRIBUTE_CHOICES_BUFFER_LENGTH];
+
+        let result = unsafe {
+            (self.plugin.vtable.attribute_choices)(
+                self.plugin.plugin_data,
+                id,
+                &mut buffer[0] as *mut c_uchar,
+                ATTRIBUTE_CHOICES_BUFFER_LENGTH,
+            )
+        };
+
+        if result == PLUGIN_OK {
+            // The choices are null-terminated strings that end at the first empty string.
+            let choices: Vec<String> = buffer
+                .split(|byte| *byte == 0)
+                .take_while(|choice| !choice.is_empty())
+                .map(|choice| String::from_utf8_lossy(choice).into_owned())
+                .collect();
+
+            log::debug!("Received choices: {:?}", choices);
+            Ok(choices)
+        } else if result == ATTRIBUTE_DOES_NOT_EXIST {
+            log::debug!("Attribute does not exist: {}", result);
+            let msg = unsafe {
+                self.error_message(result)
+                    .unwrap_or_else(|_| String::from(""))
+            };
+            Err(ChoicesError::DoesNotExist(msg).into())
+        } else {
+            log::error!(
+                "Received error code while getting attribute choices: {}",
+                result
+            );
+            let msg = unsafe {
+                self.error_message(result)
+                    .unwrap_or_else(|_| String::from(""))
+            };
+            Err(ChoicesError::Failure(msg).into())
+        }
+    }
+
+    /// Returns the metadata of an attribute from a Plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The attribute's unique ID
+    pub fn attribute_metadata(&self, id: size_t) -> Result<Metadata, ExecutorError> {
+        let mut metadata = MetadataVal::default();
+
+        let result = unsafe {
+            (self.plugin.vtable.attribute_metadata)(
+                self.plugin.plugin_data,
+                id,
+                &mut metadata as *mut MetadataVal,
+            )
+        };
+
+        if result == PLUGIN_OK {
+            log::debug!("Received metadata: {:?}", metadata);
+            Ok(Metadata::new(&metadata)?)
+        } else if result == ATTRIBUTE_DOES_NOT_EXIST {
+            log::debug!("Attribute does not exist: {}", result);
+            let msg = unsafe {
+                self.error_message(result)
+                    .unwrap_or_else(|_| String::from(""))
+            };
+            Err(MetadataError::DoesNotExist(msg).into())
+        } else {
+            log::error!(
+                "Received error code while getting attribute metadata: {}",
+                result
+            );
+            let msg = unsafe {
+                self.error_message(result)
+                    .unwrap_or_else(|_| String::from(""))
+            };
+            Err(MetadataError::Failure(msg).into())
+        }
+    }
+
     /// Returns the value of an attribute from a Plugin.
     ///
     /// # Arguments
@@ -317,6 +639,153 @@ impl Executor {
         }
     }
 
+    /// Returns the set of action IDs of a Plugin.
+    pub fn action_ids(&self) -> Result<Vec<usize>, ExecutorError> {
+        let mut count: usize = 0;
+        let result = unsafe {
+            (self.plugin.vtable.action_count)(self.plugin.plugin_data, &mut count as *mut size_t)
+        };
+        if result != PLUGIN_OK {
+            return Err(ActionError::Failure(
+                "Could not determine the number of actions".to_string(),
+            )
+            .into());
+        }
+
+        let mut ids = vec![0usize; count];
+        let result = unsafe {
+            (self.plugin.vtable.action_ids)(self.plugin.plugin_data, ids.as_mut_ptr(), ids.len())
+        };
+
+        if result == PLUGIN_OK {
+            Ok(ids)
+        } else {
+            Err(ActionError::Failure("Could not determine the action IDs".to_string()).into())
+        }
+    }
+
+    /// Returns the name of an action from a Plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The action's unique ID
+    pub fn action_name(&self, id: size_t) -> Result<String, ExecutorError> {
+        let mut name = [0u8; ACTION_NAME_BUFFER_LENGTH];
+
+        let result = unsafe {
+            (self.plugin.vtable.action_name)(
+                self.plugin.plugin_data,
+                id,
+                &mut name[0] as *mut c_uchar,
+                ACTION_NAME_BUFFER_LENGTH,
+            )
+        };
+
+        if result == PLUGIN_OK {
+            let name = action_string(&name)?;
+            log::debug!("Received action name: {:?}", name);
+            Ok(name)
+        } else {
+            Err(self.action_error(result, "getting action name"))
+        }
+    }
+
+    /// Returns the arguments of an action from a Plugin.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The action's unique ID
+    pub fn action_arguments(&self, id: size_t) -> Result<Vec<ActionArgument>, ExecutorError> {
+        let mut count: usize = 0;
+        let result = unsafe {
+            (self.plugin.vtable.action_argument_count)(
+                self.plugin.plugin_data,
+                id,
+                &mut count as *mut size_t,
+            )
+        };
+        if result != PLUGIN_OK {
+            return Err(self.action_error(result, "getting the number of action arguments"));
+        }
+
+        let mut arguments = Vec::with_capacity(count);
+        for index in 0..count {
+            let mut name = [0u8; ACTION_NAME_BUFFER_LENGTH];
+            let mut value = Val::Int(0);
+            let result = unsafe {
+                (self.plugin.vtable.action_argument)(
+                    self.plugin.plugin_data,
+                    id,
+                    index,
+                    &mut name[0] as *mut c_uchar,
+                    ACTION_NAME_BUFFER_LENGTH,
+                    &mut value as *mut Val,
+                )
+            };
+            if result != PLUGIN_OK {
+                return Err(self.action_error(result, "getting action argument"));
+            }
+
+            arguments.push(ActionArgument {
+                name: action_string(&name)?,
+                value: Value::try_from(value)?,
+            });
+        }
+
+        log::debug!("Received action arguments: {:?}", arguments);
+        Ok(arguments)
+    }
+
+    /// Invokes an action of a Plugin and returns its result.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The action's unique ID
+    /// * `args` - The values of the action's arguments
+    pub fn invoke_action(&self, id: size_t, args: &[Val]) -> Result<Value, ExecutorError> {
+        let mut value = Val::Int(0);
+        let result = unsafe {
+            (self.plugin.vtable.invoke_action)(
+                self.plugin.plugin_data,
+                id,
+                args.as_ptr(),
+                args.len(),
+                &mut value as *mut Val,
+            )
+        };
+
+        if result == PLUGIN_OK {
+            log::debug!("Received action result: {:?}", value);
+            Ok(Value::try_from(value)?)
+        } else {
+            Err(self.action_error(result, "invoking action"))
+        }
+    }
+
+    /// Converts an error code returned by one of the plugin's action functions into an error.
+    ///
+    /// # Arguments
+    ///
+    /// * `error_code` - The error code returned by the plugin
+    /// * `context` - A description of the operation that failed
+    fn action_error(&self, error_code: c_int, context: &str) -> ExecutorError {
+        let msg = unsafe {
+            self.error_message(error_code)
+                .unwrap_or_else(|_| String::from("Could not determine error message from plugin"))
+        };
+
+        if error_code == ACTION_DOES_NOT_EXIST {
+            log::debug!("Action does not exist: {}", error_code);
+            ActionError::DoesNotExist(msg).into()
+        } else if error_code == ACTION_ARGUMENTS_MISMATCH || error_code == CONVERSION_ERR {
+            log::debug!("Action arguments are invalid: {}", error_code);
+            ActionError::InvalidArguments(msg).into()
+        } else {
+            log::error!("Received error code while {}: {}", context, error_code);
+            ActionError::Failure(msg).into()
+        }
+    }
+
     /// Requests an error message from a plugin given an error code.
     ///
     /// # Safety
@@ -353,11 +822,28 @@ impl Executor {
         Err(AdvancePhaseError(self.phase).into())
     }
 
-    /// Gets all attribute values and names from a Plugin and updates the corresponding Peripheral.
-    ///
-    /// This method is only called once to discover the attributes of the plugin.
-    pub fn discover_attributes(&mut self) -> Option<BTreeMap<usize, Attribute>> {
-        let ids = match self.attribute_ids() {
+    /// Returns the plugin to the init lifecycle phase.
+    pub fn retreat(&mut self) -> Result<i32, ExecutorError> {
+        if self.phase == RUN_PHASE {
+            self.phase = INIT_PHASE;
+            return Ok(self.phase);
+        }
+
+        Err(RetreatPhaseError(self.phase).into())
+    }
+
+    /// Returns the current lifecycle phase of the plugin.
+    pub fn phase(&self) -> i32 {
+        self.phase
+    }
+
+    /// Gets all attribute values and names from a Plugin and updates the corresponding Peripheral.
+    ///
+    /// This method is only called once to discover the attributes of the plugin. Attributes whose
+    /// choices, metadata, or settable status cannot be discovered have no choices, no metadata,
+    /// and may not be set, respectively.
+    pub fn discover_attributes(&mut self) -> Option<BTreeMap<usize, Attribute>> {
+        let ids = match self.attribute_ids() {
             Ok(ids) => ids,
             Err(e) => {
                 log::error!("Could not discover plugin attributes: {:?}", e);
@@ -384,6 +870,14 @@ impl Executor {
                 }
             };
 
+            let alias = match self.attribute_alias(id) {
+                Ok(alias) => alias,
+                Err(err) => {
+                    log::error!("Could not discover alias of attribute {}: {:?}", id, err);
+                    continue;
+                }
+            };
+
             let pre_init = match self.attribute_pre_init(id) {
                 Ok(pre_init) => pre_init,
                 Err(err) => {
@@ -396,7 +890,47 @@ impl Executor {
                 }
             };
 
-            let new_attr = match Attribute::new(value.clone(), id, name, pre_init) {
+            let choices = match self.attribute_choices(id) {
+                Ok(choices) => choices,
+                Err(err) => {
+                    log::error!("Could not discover choices of attribute {}: {:?}", id, err);
+                    Vec::new()
+                }
+            };
+
+            let metadata = match self.attribute_metadata(id) {
+                Ok(metadata) => metadata,
+                Err(err) => {
+                    log::error!("Could not discover metadata of attribute {}: {:?}", id, err);
+                    Metadata::default()
+                }
+            };
+
+            let settable = match (
+                self.attribute_settable(id, INIT_PHASE),
+                self.attribute_settable(id, RUN_PHASE),
+            ) {
+                (Ok(init), Ok(run)) => Settable { init, run },
+                (Err(err), _) | (_, Err(err)) => {
+                    log::error!(
+                        "Could not discover settable status of attribute {}: {:?}",
+                        id,
+                        err
+                    );
+                    Settable::default()
+                }
+            };
+
+            let new_attr = match Attribute::new(
+                value.clone(),
+                id,
+                name,
+                alias,
+                pre_init,
+                choices,
+                metadata,
+                settable,
+            ) {
                 Ok(new_attr) => new_attr,
                 Err(err) => {
                     log::error!("Could not create new attribute: {:?}", err);
@@ -413,6 +947,43 @@ impl Executor {
         }
     }
 
+    /// Gets the names and arguments of all actions from a Plugin.
+    ///
+    /// This method is only called once to discover the actions of the plugin. Actions that cannot
+    /// be discovered are skipped.
+    pub fn discover_actions(&self) -> BTreeMap<usize, Action> {
+        let ids = match self.action_ids() {
+            Ok(ids) => ids,
+            Err(e) => {
+                log::error!("Could not discover plugin actions: {:?}", e);
+                return BTreeMap::new();
+            }
+        };
+
+        let mut actions: BTreeMap<usize, Action> = BTreeMap::new();
+        for id in ids {
+            let name = match self.action_name(id) {
+                Ok(name) => name,
+                Err(err) => {
+                    log::error!("Could not discover name of action {}: {:?}", id, err);
+                    continue;
+                }
+            };
+
+            let arguments = match self.action_arguments(id) {
+                Ok(arguments) => arguments,
+                Err(err) => {
+                    log::error!("Could not discover arguments of action {}: {:?}", id, err);
+                    continue;
+                }
+            };
+
+            actions.insert(id, Action::new(id, name, arguments));
+        }
+
+        actions
+    }
+
     /// Initializes the plugin.
     pub fn init(&self) -> Result<(), ExecutorError> {
         let result = unsafe { (self.plugin.vtable.plugin_init)(self.plugin.plugin_data) };
@@ -433,13 +1004,60 @@ impl Executor {
         }
     }
 
+    /// Passes a notifier to the plugin so that it can report changes to its attributes.
+    ///
+    /// Each notification is forwarded to the executor as an `AttributeChanged` message.
+    pub fn set_notifier(&self) -> Result<(), ExecutorError> {
+        let notifier = Notifier {
+            callback: forward_notification,
+            context: &*self.notifications as *const Mutex<Transmitter> as *mut c_void,
+        };
+        let result =
+            unsafe { (self.plugin.vtable.set_notifier)(self.plugin.plugin_data, notifier) };
+
+        if result == PLUGIN_OK {
+            log::debug!("Passed the notifier to the plugin");
+            Ok(())
+        } else {
+            log::error!(
+                "Received error code while setting the plugin's notifier: {}",
+                result
+            );
+            let msg = unsafe {
+                self.error_message(result)
+                    .unwrap_or_else(|_| String::from(""))
+            };
+            Err(NotifierError(msg).into())
+        }
+    }
+
+    /// Deinitializes the plugin, releasing any hardware resources that it acquired during init.
+    pub fn deinit(&self) -> Result<(), ExecutorError> {
+        let result = unsafe { (self.plugin.vtable.plugin_deinit)(self.plugin.plugin_data) };
+
+        if result == PLUGIN_OK {
+            log::debug!("Plugin's deinitialzation routine ran successfully");
+            Ok(())
+        } else {
+            log::error!(
+                "Received error code while deinitialzing the plugin: {}",
+                result
+            );
+            let msg = unsafe {
+                self.error_message(result)
+                    .unwrap_or_else(|_| String::from(""))
+            };
+            Err(DeinitError(msg).into())
+        }
+    }
+
     /// Synchronizes the plugin with the peripheral by setting all settable plugin attributes.
     ///
     /// # Arguments
     ///
-    /// * `builder` - A reference to peripheral data
-    pub fn sync(&mut self, builder: &PeripheralBuilder) -> Result<(), ExecutorError> {
-        for attr in builder.attributes().values() {
+    /// * `attributes` - A reference to the peripheral's attributes
+    pub fn sync(&mut self, attributes: &BTreeMap<usize, Attribute>) -> Result<(), ExecutorError> {
+        for attr in attributes.values() {
             let value = attr.to_value()?;
             let val = value.as_val();
 
@@ -468,11 +1086,68 @@ impl Executor {
     }
 }
 
+impl Drop for Executor {
+    /// Stops counting the executor as a user of its library.
+    fn drop(&mut self) {
+        if let Some(Ok(mut lib)) = self.library.as_ref().map(|library| library.lock()) {
+            lib.release();
+        }
+    }
+}
+
+/// Forwards a notification from a plugin into the message queue of its executor.
+///
+/// This function is called by the plugin, possibly from a thread that it owns.
+///
+/// # Arguments
+///
+/// * `context` - A pointer to the executor's notification transmitter
+/// * `id` - The ID of the attribute that changed
+extern "C" fn forward_notification(context: *mut c_void, id: size_t) {
+    if context.is_null() {
+        log::error!("Received a notification with a null context");
+        return;
+    }
+
+    let notifications = unsafe { &*(context as *const Mutex<Transmitter>) };
+    let result = match notifications.lock() {
+        Ok(tx) => tx
+            .send(Message::AttributeChanged(id))
+            .map_err(|e| e.to_string()),
+        Err(e) => Err(e.to_string()),
+    };
+
+    if let Err(e) = result {
+        log::error!("Could not forward notification for attribute {}: {}", id, e);
+    }
+}
+
+/// Converts a null-terminated buffer that was written by one of the plugin's action functions
+/// into a String.
+///
+/// # Arguments
+///
+/// * `buffer` - The buffer that contains the C-string
+fn action_string(buffer: &[u8]) -> Result<String, ExecutorError> {
+    let string = memchr(0, buffer)
+        .ok_or("could not find null byte")
+        .and_then(|null_byte| {
+            CStr::from_bytes_with_nul(&buffer[..=null_byte])
+                .map_err(|_| "could not convert string from C string")
+        })
+        .map_err(|err| ActionError::Failure(err.to_string()))?;
+
+    Ok(string.to_string_lossy().into_owned())
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
 
-    use std::boxed::Box;
+    use std::{
+        boxed::Box,
+        sync::atomic::{AtomicBool, Ordering},
+    };
 
     use libc::{c_int, c_uchar, size_t};
 
@@ -482,6 +1157,7 @@ mod tests {
 
     type AttributeName = extern "C" fn(*const PluginData, size_t, *mut c_uchar, size_t) -> c_int;
     type AttributeValue = extern "C" fn(*const PluginData, size_t, *mut Val, Phase) -> c_int;
+    type PluginDeinit = unsafe extern "C" fn(*mut PluginData) -> c_int;
 
     #[test]
     fn test_advance() {
@@ -499,6 +1175,85 @@ mod tests {
         assert_eq!(RUN_PHASE, executor.phase);
     }
 
+    #[test]
+    fn test_retreat() {
+        let (plugin, _) = set_up();
+        let mut executor = Executor::new(plugin);
+
+        let mut result = executor.retreat();
+        assert!(result.is_err());
+        assert_eq!(INIT_PHASE, executor.phase);
+
+        executor.advance().unwrap();
+        result = executor.retreat();
+        assert!(result.is_ok());
+        assert_eq!(INIT_PHASE, executor.phase);
+    }
+
+    #[test]
+    fn test_deinit() {
+        let (mut plugin, _) = set_up();
+        let cases: Vec<(Result<(), ExecutorError>, PluginDeinit)> = vec![
+            (Ok(()), def_plugin_deinit),
+            (
+                Err(DeinitError(String::from("foo")).into()),
+                plugin_deinit_failure,
+            ),
+        ];
+
+        let mut executor: Executor;
+        for (expected, case) in cases {
+            plugin.vtable.plugin_deinit = case;
+            executor = Executor::new(plugin.clone());
+
+            assert_eq!(expected, executor.deinit());
+        }
+
+        tear_down(plugin);
+    }
+
+    #[test]
+    fn test_set_notifier() {
+        let (mut plugin, _) = set_up();
+
+        plugin.vtable.set_notifier = set_notifier_and_notify;
+        let executor = Executor::new(plugin.clone());
+        assert!(executor.set_notifier().is_ok());
+        assert!(matches!(
+            executor.rx.try_recv(),
+            Ok(Message::AttributeChanged(0))
+        ));
+
+        plugin.vtable.set_notifier = set_notifier_failure;
+        let executor = Executor::new(plugin.clone());
+        assert_eq!(
+            Err(NotifierError(String::from("foo")).into()),
+            executor.set_notifier()
+        );
+
+        tear_down(plugin);
+    }
+
+    #[test]
+    fn test_run_shutdown() {
+        let (mut plugin, peripheral) = set_up();
+        plugin.vtable.plugin_free = plugin_free_flag;
+        let executor = Executor::new(plugin);
+        let tx = executor.tx.clone();
+        executor.run(peripheral);
+
+        let (resp_tx, resp_rx) = channel();
+        tx.send(Message::Shutdown(resp_tx)).unwrap();
+        let result = resp_rx.recv_timeout(REQUEST_TIMEOUT).unwrap();
+
+        assert!(result.is_ok());
+        assert!(PLUGIN_FREED.load(Ordering::SeqCst));
+
+        // The executor's receiver is dropped when its thread exits.
+        let (resp_tx, _) = channel();
+        assert!(tx.send(Message::GetPeripheral(resp_tx)).is_err());
+    }
+
     #[test]
     fn test_error_message() {
         let (plugin, _) = set_up();
@@ -537,6 +1292,58 @@ mod tests {
         assert_eq!(0, ids[0]);
     }
 
+    #[test]
+    fn test_attribute_choices() {
+        let (plugin, _) = set_up();
+        let executor = Executor::new(plugin.clone());
+
+        assert_eq!(
+            vec![String::from("low"), String::from("high")],
+            executor.attribute_choices(0).unwrap()
+        );
+        assert_eq!(
+            Err(ChoicesError::DoesNotExist(String::from("foo")).into()),
+            executor.attribute_choices(1)
+        );
+
+        tear_down(plugin);
+    }
+
+    #[test]
+    fn test_attribute_metadata() {
+        let (plugin, _) = set_up();
+        let executor = Executor::new(plugin.clone());
+
+        assert_eq!(
+            Metadata {
+                max: Some(1.0),
+                ..Metadata::default()
+            },
+            executor.attribute_metadata(0).unwrap()
+        );
+        assert_eq!(
+            Err(MetadataError::DoesNotExist(String::from("foo")).into()),
+            executor.attribute_metadata(1)
+        );
+
+        tear_down(plugin);
+    }
+
+    #[test]
+    fn test_attribute_settable() {
+        let (plugin, _) = set_up();
+        let executor = Executor::new(plugin.clone());
+
+        assert!(!executor.attribute_settable(0, INIT_PHASE).unwrap());
+        assert!(executor.attribute_settable(0, RUN_PHASE).unwrap());
+        assert_eq!(
+            Err(SettableError::DoesNotExist(String::from("foo")).into()),
+            executor.attribute_settable(1, RUN_PHASE)
+        );
+
+        tear_down(plugin);
+    }
+
     #[test]
     fn test_attribute_name() {
         let (mut plugin, _) = set_up();
@@ -598,14 +1405,78 @@ mod tests {
         tear_down(plugin);
     }
 
+    #[test]
+    fn test_discover_actions() {
+        let (plugin, _) = set_up();
+        let executor = Executor::new(plugin);
+
+        let actions = executor.discover_actions();
+        let action = actions.get(&0).unwrap();
+        assert_eq!("move_by", action.name());
+        assert_eq!(1, action.arguments().len());
+        assert_eq!("distance", action.arguments()[0].name);
+    }
+
+    #[test]
+    fn test_invoke_action() {
+        let (plugin, _) = set_up();
+        let executor = Executor::new(plugin);
+
+        match executor.invoke_action(0, &[Val::Double(2.0)]) {
+            Ok(Value::Double { value }) => assert_eq!(1.0, value),
+            result => panic!("Unexpected result: {:?}", result),
+        };
+        assert_eq!(
+            Err(ActionError::InvalidArguments(String::from("foo")).into()),
+            executor.invoke_action(0, &[]).map(|_| ())
+        );
+        assert_eq!(
+            Err(ActionError::DoesNotExist(String::from("foo")).into()),
+            executor.invoke_action(1, &[]).map(|_| ())
+        );
+    }
+
     #[test]
     fn test_discover_attributes() {
         let (plugin, _) = set_up();
         let mut executor = Executor::new(plugin);
-        let attribute = Attribute::new(Val::Int(42), 0, String::from("bar"), true);
+        let choices = vec![String::from("low"), String::from("high")];
+        let attribute = Attribute::new(
+            Val::Int(42),
+            0,
+            String::from("bar"),
+            String::from("baz"),
+            true,
+            choices,
+            Metadata::default(),
+            Settable {
+                init: false,
+                run: true,
+            },
+        );
 
         let attrs = executor.discover_attributes().unwrap();
         assert_eq!(&attribute.unwrap(), attrs.get(&0).unwrap());
+        assert_eq!("baz", attrs.get(&0).unwrap().alias());
+        assert_eq!(&["low", "high"], attrs.get(&0).unwrap().choices());
+        assert_eq!(Some(1.0), attrs.get(&0).unwrap().metadata().max);
+        assert!(attrs.get(&0).unwrap().settable().run);
+    }
+
+    #[test]
+    fn test_discover_attributes_with_defaults() {
+        let (mut plugin, _) = set_up();
+        plugin.vtable.attribute_choices = attribute_choices_failure;
+        plugin.vtable.attribute_metadata = attribute_metadata_failure;
+        plugin.vtable.attribute_settable = attribute_settable_failure;
+        let mut executor = Executor::new(plugin);
+
+        let attrs = executor.discover_attributes().unwrap();
+        let attr = attrs.get(&0).unwrap();
+        assert_eq!("bar", attr.name());
+        assert!(attr.choices().is_empty());
+        assert_eq!(&Metadata::default(), attr.metadata());
+        assert_eq!(Settable::default(), attr.settable());
     }
 
     fn set_up() -> (Plugin, Peripheral) {
@@ -613,13 +1484,25 @@ mod tests {
         let vtable = VTable {
             plugin_free: def_peripheral_free,
             plugin_init: def_plugin_init,
+            plugin_deinit: def_plugin_deinit,
+            set_notifier: def_set_notifier,
             error_message_ns: def_error_message,
             attribute_count: def_attribute_count,
             attribute_ids: def_attribute_ids,
             attribute_name: def_attribute_name,
+            attribute_alias: def_attribute_alias,
             attribute_pre_init: def_attribute_pre_init,
+            attribute_settable: def_attribute_settable,
+            attribute_choices: def_attribute_choices,
+            attribute_metadata: def_attribute_metadata,
             attribute_value: def_attribute_value,
             set_attribute_value: def_set_attribute_value,
+            action_count: def_action_count,
+            action_ids: def_action_ids,
+            action_name: def_action_name,
+            action_argument_count: def_action_argument_count,
+            action_argument: def_action_argument,
+            invoke_action: def_invoke_action,
         };
         let plugin = Plugin {
             plugin_data,
@@ -638,6 +1521,9 @@ mod tests {
 
     struct MockPluginData {}
 
+    /// Set by `plugin_free_flag` when the plugin's data is freed.
+    static PLUGIN_FREED: AtomicBool = AtomicBool::new(false);
+
     // Default function pointers for the vtable
     extern "C" fn def_peripheral_free(_: *mut PluginData) {}
 
@@ -645,6 +1531,14 @@ mod tests {
         0
     }
 
+    extern "C" fn def_plugin_deinit(_: *mut PluginData) -> c_int {
+        0
+    }
+
+    extern "C" fn def_set_notifier(_: *mut PluginData, _: Notifier) -> c_int {
+        0
+    }
+
     extern "C" fn def_error_message(_: c_int) -> *const c_uchar {
         b"foo\0" as *const c_uchar
     }
@@ -684,9 +1578,69 @@ mod tests {
             ATTRIBUTE_DOES_NOT_EXIST
         }
     }
+    extern "C" fn def_attribute_alias(
+        _: *const PluginData,
+        id: size_t,
+        buffer: *mut c_uchar,
+        _: size_t,
+    ) -> c_int {
+        if id == 0 {
+            unsafe {
+                let string: &[u8] = b"baz\0";
+                let buffer = std::slice::from_raw_parts_mut(buffer, ATTRIBUTE_NAME_BUFFER_LENGTH);
+                buffer[0..4].copy_from_slice(string);
+            };
+            PLUGIN_OK
+        } else {
+            ATTRIBUTE_DOES_NOT_EXIST
+        }
+    }
     extern "C" fn def_attribute_pre_init(_: *const PluginData, _: size_t, _: *mut c_char) -> c_int {
         PLUGIN_OK
     }
+    extern "C" fn def_attribute_settable(
+        _: *const PluginData,
+        id: size_t,
+        phase: Phase,
+        settable: *mut c_char,
+    ) -> c_int {
+        if id == 0 {
+            unsafe { *settable = (phase == RUN_PHASE) as c_char };
+            PLUGIN_OK
+        } else {
+            ATTRIBUTE_DOES_NOT_EXIST
+        }
+    }
+    extern "C" fn def_attribute_choices(
+        _: *const PluginData,
+        id: size_t,
+        buffer: *mut c_uchar,
+        _: size_t,
+    ) -> c_int {
+        if id == 0 {
+            unsafe {
+                let string: &[u8] = b"low\0high\0\0";
+                let buffer =
+                    std::slice::from_raw_parts_mut(buffer, ATTRIBUTE_CHOICES_BUFFER_LENGTH);
+                buffer[0..string.len()].copy_from_slice(string);
+            };
+            PLUGIN_OK
+        } else {
+            ATTRIBUTE_DOES_NOT_EXIST
+        }
+    }
+    extern "C" fn def_attribute_metadata(
+        _: *const PluginData,
+        id: size_t,
+        metadata: *mut MetadataVal,
+    ) -> c_int {
+        if id == 0 {
+            unsafe { (*metadata).max = 1.0 };
+            PLUGIN_OK
+        } else {
+            ATTRIBUTE_DOES_NOT_EXIST
+        }
+    }
     extern "C" fn def_attribute_value(
         _: *const PluginData,
         id: size_t,
@@ -708,8 +1662,95 @@ mod tests {
     ) -> c_int {
         0
     }
+    extern "C" fn def_action_count(_: *const PluginData, count: *mut size_t) -> c_int {
+        unsafe { *count = 1 };
+        PLUGIN_OK
+    }
+    extern "C" fn def_action_ids(_: *const PluginData, buffer: *mut size_t, _: size_t) -> c_int {
+        unsafe { *buffer = 0 };
+        PLUGIN_OK
+    }
+    extern "C" fn def_action_name(
+        _: *const PluginData,
+        id: size_t,
+        buffer: *mut c_uchar,
+        _: size_t,
+    ) -> c_int {
+        if id == 0 {
+            unsafe {
+                let string: &[u8] = b"move_by\0";
+                let buffer = std::slice::from_raw_parts_mut(buffer, ACTION_NAME_BUFFER_LENGTH);
+                buffer[0..string.len()].copy_from_slice(string);
+            };
+            PLUGIN_OK
+        } else {
+            ACTION_DOES_NOT_EXIST
+        }
+    }
+    extern "C" fn def_action_argument_count(
+        _: *const PluginData,
+        id: size_t,
+        count: *mut size_t,
+    ) -> c_int {
+        if id == 0 {
+            unsafe { *count = 1 };
+            PLUGIN_OK
+        } else {
+            ACTION_DOES_NOT_EXIST
+        }
+    }
+    extern "C" fn def_action_argument(
+        _: *const PluginData,
+        id: size_t,
+        _: size_t,
+        buffer: *mut c_uchar,
+        _: size_t,
+        value: *mut Val,
+    ) -> c_int {
+        if id == 0 {
+            unsafe {
+                let string: &[u8] = b"distance\0";
+                let buffer = std::slice::from_raw_parts_mut(buffer, ACTION_NAME_BUFFER_LENGTH);
+                buffer[0..string.len()].copy_from_slice(string);
+                *value = Val::Double(0.0);
+            };
+            PLUGIN_OK
+        } else {
+            ACTION_DOES_NOT_EXIST
+        }
+    }
+    extern "C" fn def_invoke_action(
+        _: *mut PluginData,
+        id: size_t,
+        _: *const Val,
+        args_length: size_t,
+        result: *mut Val,
+    ) -> c_int {
+        if id != 0 {
+            ACTION_DOES_NOT_EXIST
+        } else if args_length != 1 {
+            ACTION_ARGUMENTS_MISMATCH
+        } else {
+            unsafe { *result = Val::Double(1.0) };
+            PLUGIN_OK
+        }
+    }
 
     // Function pointers used by different test cases
+    extern "C" fn plugin_free_flag(plugin_data: *mut PluginData) {
+        unsafe { drop(Box::from_raw(plugin_data as *mut MockPluginData)) };
+        PLUGIN_FREED.store(true, Ordering::SeqCst);
+    }
+    extern "C" fn plugin_deinit_failure(_: *mut PluginData) -> c_int {
+        999
+    }
+    extern "C" fn set_notifier_and_notify(_: *mut PluginData, notifier: Notifier) -> c_int {
+        notifier.notify(0);
+        PLUGIN_OK
+    }
+    extern "C" fn set_notifier_failure(_: *mut PluginData, _: Notifier) -> c_int {
+        999
+    }
     extern "C" fn attribute_name_ok(
         _: *const PluginData,
         _: size_t,
@@ -758,4 +1799,27 @@ mod tests {
     ) -> c_int {
         999
     }
+    extern "C" fn attribute_choices_failure(
+        _: *const PluginData,
+        _: size_t,
+        _: *mut c_uchar,
+        _: size_t,
+    ) -> c_int {
+        999
+    }
+    extern "C" fn attribute_metadata_failure(
+        _: *const PluginData,
+        _: size_t,
+        _: *mut MetadataVal,
+    ) -> c_int {
+        999
+    }
+    extern "C" fn attribute_settable_failure(
+        _: *const PluginData,
+        _: size_t,
+        _: Phase,
+        _: *mut c_char,
+    ) -> c_int {
+        999
+    }
 }
@@ -0,0 +1,53 @@
+//! Error types for the plugin host.
+
+use std::{error::Error, fmt, io};
+
+/// Raised when a plugin host process cannot be started or cannot host its plugin.
+#[derive(Debug)]
+pub struct HostError {
+    /// A description of the error.
+    message: String,
+
+    /// The lower-level instance of the Error that that caused this one, if any.
+    side: Option<Box<dyn Error + 'static + Send>>,
+}
+
+impl HostError {
+    pub fn new(message: String, side: Option<Box<dyn Error + 'static + Send>>) -> HostError {
+        HostError { message, side }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Error for HostError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.side.as_ref().map(|e| e.as_ref() as &_)
+    }
+}
+
+impl fmt::Display for HostError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "HostError {{ message: {} }}", self.message)
+    }
+}
+
+impl From<io::Error> for HostError {
+    fn from(error: io::Error) -> Self {
+        HostError::new(
+            "Could not communicate with the plugin host process".to_string(),
+            Some(Box::new(error)),
+        )
+    }
+}
+
+impl From<bincode::Error> for HostError {
+    fn from(error: bincode::Error) -> Self {
+        HostError::new(
+            "Could not exchange a message with the plugin host process".to_string(),
+            Some(Box::new(error)),
+        )
+    }
+}
@@ -0,0 +1,681 @@
+//! Hosts plugins in child processes so that a crashing plugin does not take down the daemon.
+//!
+//! A hosted plugin is driven by the same executor as an in-process plugin. The difference is that
+//! the functions of its VTable belong to the daemon: they serialize each call, send it to a
+//! `kpal-plugin-host` process that has loaded the plugin library, and wait for the result. If the
+//! host process dies or does not reply in time, every call returns the `PLUGIN_HOST_ERR` error
+//! code until the plugin is replaced by a new one.
+
+mod errors;
+mod protocol;
+mod server;
+
+use std::{
+    env,
+    io::{BufReader, BufWriter, Write},
+    path::Path,
+    process::{Child, ChildStdin, ChildStdout, Command, Stdio},
+    sync::{
+        mpsc::{channel, Receiver, RecvTimeoutError, Sender},
+        Arc, Mutex,
+    },
+    thread::{self, JoinHandle},
+    time::{Duration, Instant},
+};
+
+use libc::{c_char, c_int, c_uchar, size_t};
+use log;
+
+use kpal_plugin::{
+    error_codes::*, error_message_ns, Metadata, MetadataVal, Notifier, Phase, Plugin, PluginData,
+    VTable, Val, Value,
+};
+
+use crate::constants::{PLUGIN_HOST_BIN, PLUGIN_HOST_CALL_TIMEOUT, PLUGIN_HOST_SHUTDOWN_TIMEOUT};
+
+pub use errors::HostError;
+use protocol::{copy_buffer, Frame, Output, Reply, Request, WireValue};
+pub use server::serve;
+
+/// The daemon's side of the connection to a plugin host process.
+///
+/// A pointer to a Host is the plugin data of a hosted plugin.
+struct Host {
+    /// The host process.
+    child: Mutex<Child>,
+
+    /// The pipe through which requests are sent to the host process.
+    requests: Mutex<BufWriter<ChildStdin>>,
+
+    /// The replies to the requests, in the order that they were sent.
+    replies: Mutex<Receiver<Reply>>,
+
+    /// The daemon's notifier, which receives the notifications that are sent by the host process.
+    notifier: Arc<Mutex<Option<Notifier>>>,
+
+    /// The thread that reads the messages that are sent by the host process.
+    reader: JoinHandle<()>,
+
+    /// The data that is referenced by the Val most recently returned to the executor.
+    value: Mutex<Option<Value>>,
+
+    /// The data that is referenced by the MetadataVal most recently returned to the executor.
+    metadata: Mutex<Option<Metadata>>,
+}
+
+impl Host {
+    /// Sends a request to the host process and waits for its reply.
+    ///
+    /// A host process that does not reply within the timeout is killed so that a hanging plugin
+    /// cannot block its executor forever.
+    ///
+    /// # Arguments
+    ///
+    /// * `request` - The request to send
+    fn call(&self, request: Request) -> Result<Reply, HostError> {
+        let lock_error = || HostError::new("The plugin host is poisoned".to_string(), None);
+
+        {
+            let mut requests = self.requests.lock().map_err(|_| lock_error())?;
+            bincode::serialize_into(&mut *requests, &request)?;
+            requests.flush()?;
+        }
+
+        let replies = self.replies.lock().map_err(|_| lock_error())?;
+        match replies.recv_timeout(PLUGIN_HOST_CALL_TIMEOUT) {
+            Ok(reply) => Ok(reply),
+            Err(RecvTimeoutError::Timeout) => {
+                let mut child = self.child.lock().map_err(|_| lock_error())?;
+                log::error!(
+                    "Killing the unresponsive plugin host process {}",
+                    child.id()
+                );
+                let _ = child.kill();
+                let _ = child.wait();
+
+                Err(HostError::new(
+                    "The plugin host process did not reply in time".to_string(),
+                    None,
+                ))
+            }
+            Err(e) => Err(HostError::new(
+                "The plugin host process exited".to_string(),
+                Some(Box::new(e)),
+            )),
+        }
+    }
+
+    /// Returns true if the host process has exited.
+    fn exited(&self) -> bool {
+        match self.child.lock() {
+            Ok(mut child) => !matches!(child.try_wait(), Ok(None)),
+            Err(_) => true,
+        }
+    }
+
+    /// Closes the connection to the host process and waits for it to exit.
+    ///
+    /// The process is killed if it does not exit within the timeout after its plugin is asked to
+    /// free itself.
+    fn stop(self) {
+        let Host {
+            child,
+            requests,
+            reader,
+            ..
+        } = self;
+
+        // Closing the pipe tells the host process to free its plugin and exit.
+        drop(requests);
+
+        let mut child = child.into_inner().unwrap_or_else(|e| e.into_inner());
+        let start = Instant::now();
+        loop {
+            match child.try_wait() {
+                Ok(Some(status)) => {
+                    log::debug!("The plugin host process exited: {}", status);
+                    break;
+                }
+                Ok(None) if start.elapsed() < PLUGIN_HOST_SHUTDOWN_TIMEOUT => {
+                    thread::sleep(Duration::from_millis(10));
+                }
+                _ => {
+                    log::error!("Killing the plugin host process {}", child.id());
+                    let _ = child.kill();
+                    let _ = child.wait();
+                    break;
+                }
+            }
+        }
+
+        let _ = reader.join();
+    }
+}
+
+/// Starts a new plugin host process and returns the plugin that it hosts.
+///
+/// The host binary is expected to be in the same directory as the daemon's binary.
+///
+/// # Arguments
+///
+/// * `path` - The path to the library that contains the implementation of the Plugin API
+pub fn spawn(path: &Path) -> Result<Plugin, HostError> {
+    let exe = env::current_exe()?.with_file_name(PLUGIN_HOST_BIN);
+
+    log::info!("Starting a plugin host process for library {:?}", path);
+    let mut child = Command::new(&exe)
+        .arg(path)
+        .stdin(Stdio::piped())
+        .stdout(Stdio::piped())
+        .spawn()
+        .map_err(|e| {
+            HostError::new(
+                format!("Could not start the plugin host process {:?}", exe),
+                Some(Box::new(e)),
+            )
+        })?;
+
+    let (requests, stdout) = match (child.stdin.take(), child.stdout.take()) {
+        (Some(requests), Some(stdout)) => (requests, stdout),
+        _ => {
+            let _ = child.kill();
+            let _ = child.wait();
+            return Err(HostError::new(
+                "Could not open the pipes to the plugin host process".to_string(),
+                None,
+            ));
+        }
+    };
+
+    let mut stdout = BufReader::new(stdout);
+    let code = match bincode::deserialize_from(&mut stdout) {
+        Ok(Frame::Started(code)) => code,
+        _ => UNDEFINED_ERR,
+    };
+    if code != PLUGIN_OK {
+        let _ = child.kill();
+        let _ = child.wait();
+        return Err(HostError::new(
+            format!(
+                "The plugin host process could not create the plugin: {}",
+                code
+            ),
+            None,
+        ));
+    }
+
+    let notifier = Arc::new(Mutex::new(None));
+    let (tx, rx) = channel();
+    let reader = {
+        let notifier = notifier.clone();
+        thread::spawn(move || read_frames(stdout, tx, notifier))
+    };
+
+    let host = Box::new(Host {
+        child: Mutex::new(child),
+        requests: Mutex::new(BufWriter::new(requests)),
+        replies: Mutex::new(rx),
+        notifier,
+        reader,
+        value: Mutex::new(None),
+        metadata: Mutex::new(None),
+    });
+
+    Ok(Plugin {
+        plugin_data: Box::into_raw(host) as *mut PluginData,
+        vtable: vtable(),
+    })
+}
+
+/// Returns true if the process that hosts a plugin has exited.
+///
+/// # Safety
+///
+/// The plugin must have been returned by `spawn`.
+///
+/// # Arguments
+///
+/// * `plugin` - The hosted plugin
+pub unsafe fn exited(plugin: &Plugin) -> bool {
+    host(plugin.plugin_data).exited()
+}
+
+/// Reads the messages that are sent by a host process until it closes its standard output.
+///
+/// # Arguments
+///
+/// * `stdout` - The standard output of the host process
+/// * `replies` - The transmitter that forwards the replies to the executor
+/// * `notifier` - The daemon's notifier, if it has been set
+fn read_frames(
+    mut stdout: BufReader<ChildStdout>,
+    replies: Sender<Reply>,
+    notifier: Arc<Mutex<Option<Notifier>>>,
+) {
+    loop {
+        match bincode::deserialize_from(&mut stdout) {
+            Ok(Frame::Reply(reply)) => {
+                if replies.send(reply).is_err() {
+                    break;
+                }
+            }
+            Ok(Frame::Notification(id)) => {
+                if let Ok(Some(notifier)) = notifier.lock().map(|notifier| *notifier) {
+                    notifier.notify(id);
+                }
+            }
+            Ok(Frame::Started(_)) => log::error!("The plugin host process started twice"),
+            Err(e) => {
+                log::debug!(
+                    "The connection to the plugin host process was closed: {}",
+                    e
+                );
+                break;
+            }
+        }
+    }
+}
+
+/// Returns the table of functions that forward calls to a host process.
+fn vtable() -> VTable {
+    VTable {
+        plugin_free,
+        plugin_init,
+        plugin_deinit,
+        set_notifier,
+        error_message_ns,
+        attribute_count,
+        attribute_ids,
+        attribute_name,
+        attribute_alias,
+        attribute_pre_init,
+        attribute_settable,
+        attribute_choices,
+        attribute_metadata,
+        attribute_value,
+        set_attribute_value,
+        action_count,
+        action_ids,
+        action_name,
+        action_argument_count,
+        action_argument,
+        invoke_action,
+    }
+}
+
+/// Returns a reference to the Host behind a hosted plugin's data.
+///
+/// # Safety
+///
+/// The pointer must have been returned by `spawn` and must not have been freed.
+unsafe fn host<'a>(plugin_data: *const PluginData) -> &'a Host {
+    &*(plugin_data as *const Host)
+}
+
+/// Sends a request to the host process of a plugin.
+///
+/// Failures to communicate with the host process are logged and reported as `PLUGIN_HOST_ERR`.
+///
+/// # Safety
+///
+/// The pointer must have been returned by `spawn` and must not have been freed.
+///
+/// # Arguments
+///
+/// * `plugin_data` - The data of the hosted plugin
+/// * `request` - The request to send
+unsafe fn call(plugin_data: *const PluginData, request: Request) -> Reply {
+    host(plugin_data).call(request).unwrap_or_else(|e| {
+        log::error!("Could not call the plugin host process: {}", e);
+        Reply::new(PLUGIN_HOST_ERR, Output::None)
+    })
+}
+
+/// Stores a value returned by the host process and writes a Val that references it.
+///
+/// # Safety
+///
+/// The pointers must be valid.
+///
+/// # Arguments
+///
+/// * `plugin_data` - The data of the hosted plugin
+/// * `wire` - The value returned by the host process
+/// * `value` - The Val into which the value is written
+unsafe fn store_value(plugin_data: *const PluginData, wire: WireValue, value: *mut Val) -> c_int {
+    let mut stored = match (wire.into_value(), host(plugin_data).value.lock()) {
+        (Ok(new), Ok(mut stored)) => {
+            *stored = Some(new);
+            stored
+        }
+        (Err(_), _) => return CONVERSION_ERR,
+        (_, Err(_)) => return PLUGIN_HOST_ERR,
+    };
+    if let Some(stored) = stored.as_mut() {
+        *value = stored.as_val();
+    }
+
+    PLUGIN_OK
+}
+
+extern "C" fn plugin_free(plugin_data: *mut PluginData) {
+    if plugin_data.is_null() {
+        return;
+    }
+
+    let host = unsafe { Box::from_raw(plugin_data as *mut Host) };
+    host.stop();
+}
+
+unsafe extern "C" fn plugin_init(plugin_data: *mut PluginData) -> c_int {
+    call(plugin_data, Request::Init).code
+}
+
+unsafe extern "C" fn plugin_deinit(plugin_data: *mut PluginData) -> c_int {
+    call(plugin_data, Request::Deinit).code
+}
+
+unsafe extern "C" fn set_notifier(plugin_data: *mut PluginData, notifier: Notifier) -> c_int {
+    // The notifier is stored first so that no notification that is sent in reply is missed.
+    match host(plugin_data).notifier.lock() {
+        Ok(mut stored) => *stored = Some(notifier),
+        Err(_) => return PLUGIN_HOST_ERR,
+    }
+
+    call(plugin_data, Request::SetNotifier).code
+}
+
+unsafe extern "C" fn attribute_count(plugin_data: *const PluginData, count: *mut size_t) -> c_int {
+    match call(plugin_data, Request::AttributeCount) {
+        Reply {
+            code: PLUGIN_OK,
+            output: Output::Count(value),
+        } => {
+            *count = value;
+            PLUGIN_OK
+        }
+        reply => error_code(reply),
+    }
+}
+
+unsafe extern "C" fn attribute_ids(
+    plugin_data: *const PluginData,
+    ids: *mut size_t,
+    length: size_t,
+) -> c_int {
+    ids_reply(
+        call(plugin_data, Request::AttributeIds(length)),
+        ids,
+        length,
+    )
+}
+
+unsafe extern "C" fn attribute_name(
+    plugin_data: *const PluginData,
+    id: size_t,
+    buffer: *mut c_uchar,
+    length: size_t,
+) -> c_int {
+    let reply = call(plugin_data, Request::AttributeName(id, length));
+    buffer_reply(reply, buffer, length)
+}
+
+unsafe extern "C" fn attribute_alias(
+    plugin_data: *const PluginData,
+    id: size_t,
+    buffer: *mut c_uchar,
+    length: size_t,
+) -> c_int {
+    let reply = call(plugin_data, Request::AttributeAlias(id, length));
+    buffer_reply(reply, buffer, length)
+}
+
+unsafe extern "C" fn attribute_pre_init(
+    plugin_data: *const PluginData,
+    id: size_t,
+    pre_init: *mut c_char,
+) -> c_int {
+    flag_reply(call(plugin_data, Request::AttributePreInit(id)), pre_init)
+}
+
+unsafe extern "C" fn attribute_settable(
+    plugin_data: *const PluginData,
+    id: size_t,
+    phase: Phase,
+    settable: *mut c_char,
+) -> c_int {
+    let reply = call(plugin_data, Request::AttributeSettable(id, phase));
+    flag_reply(reply, settable)
+}
+
+unsafe extern "C" fn attribute_choices(
+    plugin_data: *const PluginData,
+    id: size_t,
+    buffer: *mut c_uchar,
+    length: size_t,
+) -> c_int {
+    let reply = call(plugin_data, Request::AttributeChoices(id, length));
+    buffer_reply(reply, buffer, length)
+}
+
+unsafe extern "C" fn attribute_metadata(
+    plugin_data: *const PluginData,
+    id: size_t,
+    metadata: *mut MetadataVal,
+) -> c_int {
+    let wire = match call(plugin_data, Request::AttributeMetadata(id)) {
+        Reply {
+            code: PLUGIN_OK,
+            output: Output::Metadata(wire),
+        } => wire,
+        reply => return error_code(reply),
+    };
+
+    let mut stored = match (wire.into_metadata(), host(plugin_data).metadata.lock()) {
+        (Ok(new), Ok(mut stored)) => {
+            *stored = Some(new);
+            stored
+        }
+        (Err(_), _) => return CONVERSION_ERR,
+        (_, Err(_)) => return PLUGIN_HOST_ERR,
+    };
+    if let Some(stored) = stored.as_mut() {
+        *metadata = stored.as_val();
+    }
+
+    PLUGIN_OK
+}
+
+unsafe extern "C" fn attribute_value(
+    plugin_data: *const PluginData,
+    id: size_t,
+    value: *mut Val,
+    phase: Phase,
+) -> c_int {
+    match call(plugin_data, Request::AttributeValue(id, phase)) {
+        Reply {
+            code: PLUGIN_OK,
+            output: Output::Value(wire),
+        } => store_value(plugin_data, wire, value),
+        reply => error_code(reply),
+    }
+}
+
+unsafe extern "C" fn set_attribute_value(
+    plugin_data: *mut PluginData,
+    id: size_t,
+    value: *const Val,
+    phase: Phase,
+) -> c_int {
+    let wire = match WireValue::from_val(&*value) {
+        Ok(wire) => wire,
+        Err(_) => return CONVERSION_ERR,
+    };
+
+    call(plugin_data, Request::SetAttributeValue(id, wire, phase)).code
+}
+
+unsafe extern "C" fn action_count(plugin_data: *const PluginData, count: *mut size_t) -> c_int {
+    match call(plugin_data, Request::ActionCount) {
+        Reply {
+            code: PLUGIN_OK,
+            output: Output::Count(value),
+        } => {
+            *count = value;
+            PLUGIN_OK
+        }
+        reply => error_code(reply),
+    }
+}
+
+unsafe extern "C" fn action_ids(
+    plugin_data: *const PluginData,
+    ids: *mut size_t,
+    length: size_t,
+) -> c_int {
+    ids_reply(call(plugin_data, Request::ActionIds(length)), ids, length)
+}
+
+unsafe extern "C" fn action_name(
+    plugin_data: *const PluginData,
+    id: size_t,
+    buffer: *mut c_uchar,
+    length: size_t,
+) -> c_int {
+    let reply = call(plugin_data, Request::ActionName(id, length));
+    buffer_reply(reply, buffer, length)
+}
+
+unsafe extern "C" fn action_argument_count(
+    plugin_data: *const PluginData,
+    id: size_t,
+    count: *mut size_t,
+) -> c_int {
+    match call(plugin_data, Request::ActionArgumentCount(id)) {
+        Reply {
+            code: PLUGIN_OK,
+            output: Output::Count(value),
+        } => {
+            *count = value;
+            PLUGIN_OK
+        }
+        reply => error_code(reply),
+    }
+}
+
+unsafe extern "C" fn action_argument(
+    plugin_data: *const PluginData,
+    id: size_t,
+    index: size_t,
+    buffer: *mut c_uchar,
+    length: size_t,
+    value: *mut Val,
+) -> c_int {
+    match call(plugin_data, Request::ActionArgument(id, index, length)) {
+        Reply {
+            code: PLUGIN_OK,
+            output: Output::Argument(name, wire),
+        } => {
+            copy_buffer(&name, buffer, length);
+            store_value(plugin_data, wire, value)
+        }
+        reply => error_code(reply),
+    }
+}
+
+unsafe extern "C" fn invoke_action(
+    plugin_data: *mut PluginData,
+    id: size_t,
+    args: *const Val,
+    args_length: size_t,
+    result: *mut Val,
+) -> c_int {
+    let args = if args_length == 0 {
+        &[]
+    } else {
+        std::slice::from_raw_parts(args, args_length)
+    };
+    let args = match args
+        .iter()
+        .map(WireValue::from_val)
+        .collect::<Result<Vec<WireValue>, _>>()
+    {
+        Ok(args) => args,
+        Err(_) => return CONVERSION_ERR,
+    };
+
+    match call(plugin_data, Request::InvokeAction(id, args)) {
+        Reply {
+            code: PLUGIN_OK,
+            output: Output::Value(wire),
+        } => store_value(plugin_data, wire, result),
+        reply => error_code(reply),
+    }
+}
+
+/// Copies the IDs in a reply into the buffer provided by the caller.
+///
+/// # Safety
+///
+/// The buffer must be valid for writes of `length` IDs.
+unsafe fn ids_reply(reply: Reply, ids: *mut size_t, length: size_t) -> c_int {
+    match reply {
+        Reply {
+            code: PLUGIN_OK,
+            output: Output::Ids(values),
+        } => {
+            let count = values.len().min(length);
+            std::ptr::copy_nonoverlapping(values.as_ptr(), ids, count);
+            PLUGIN_OK
+        }
+        reply => error_code(reply),
+    }
+}
+
+/// Copies the buffer in a reply into the buffer provided by the caller.
+///
+/// # Safety
+///
+/// The buffer must be valid for writes of `length` bytes.
+unsafe fn buffer_reply(reply: Reply, buffer: *mut c_uchar, length: size_t) -> c_int {
+    match reply {
+        Reply {
+            code: PLUGIN_OK,
+            output: Output::Buffer(values),
+        } => {
+            copy_buffer(&values, buffer, length);
+            PLUGIN_OK
+        }
+        reply => error_code(reply),
+    }
+}
+
+/// Writes the flag in a reply into the memory provided by the caller.
+///
+/// # Safety
+///
+/// The pointer to the flag must be valid.
+unsafe fn flag_reply(reply: Reply, flag: *mut c_char) -> c_int {
+    match reply {
+        Reply {
+            code: PLUGIN_OK,
+            output: Output::Flag(value),
+        } => {
+            *flag = value;
+            PLUGIN_OK
+        }
+        reply => error_code(reply),
+    }
+}
+
+/// Returns the error code of a reply that contains no usable output.
+///
+/// A successful reply without the expected output means that the host process is misbehaving.
+fn error_code(reply: Reply) -> c_int {
+    if reply.code == PLUGIN_OK {
+        log::error!("Received an unexpected reply from the plugin host process");
+        PLUGIN_HOST_ERR
+    } else {
+        reply.code
+    }
+}
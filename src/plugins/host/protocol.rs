@@ -0,0 +1,215 @@
+//! The messages that are exchanged between the daemon and a plugin host process.
+//!
+//! Each request corresponds to one function of the plugin's VTable. Pointers cannot cross the
+//! process boundary, so buffers and values are sent by value and copied into the caller's memory
+//! by the daemon.
+
+use std::{
+    ffi::{CStr, CString, NulError},
+    ptr,
+};
+
+use libc::{c_char, c_double, c_float, c_int, c_uchar, c_uint, size_t};
+use serde::{Deserialize, Serialize};
+
+use kpal_plugin::{Metadata, MetadataVal, Phase, Val, Value};
+
+/// A call to one of the functions of the plugin's VTable that is sent by the daemon.
+///
+/// Buffer lengths are those of the buffers provided by the caller.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Request {
+    Init,
+    Deinit,
+    SetNotifier,
+    AttributeCount,
+    AttributeIds(size_t),
+    AttributeName(size_t, size_t),
+    AttributeAlias(size_t, size_t),
+    AttributePreInit(size_t),
+    AttributeSettable(size_t, Phase),
+    AttributeChoices(size_t, size_t),
+    AttributeMetadata(size_t),
+    AttributeValue(size_t, Phase),
+    SetAttributeValue(size_t, WireValue, Phase),
+    ActionCount,
+    ActionIds(size_t),
+    ActionName(size_t, size_t),
+    ActionArgumentCount(size_t),
+    ActionArgument(size_t, size_t, size_t),
+    InvokeAction(size_t, Vec<WireValue>),
+}
+
+/// A message that is sent by the host process.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Frame {
+    /// The status code of the creation of the plugin. It is the first message that is sent.
+    Started(c_int),
+
+    /// The result of the most recent request.
+    Reply(Reply),
+
+    /// A notification from the plugin that the value of an attribute has changed.
+    Notification(size_t),
+}
+
+/// The result of a call to one of the functions of the VTable.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct Reply {
+    /// The status code that was returned by the function.
+    pub code: c_int,
+
+    /// The data that the function wrote into the memory provided by the caller.
+    pub output: Output,
+}
+
+impl Reply {
+    /// Returns a new Reply instance.
+    ///
+    /// # Arguments
+    ///
+    /// * `code` - The status code that was returned by the function
+    /// * `output` - The data that the function wrote into the memory provided by the caller
+    pub fn new(code: c_int, output: Output) -> Reply {
+        Reply { code, output }
+    }
+}
+
+/// The data that a function of the VTable writes into the memory provided by the caller.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum Output {
+    None,
+    Count(size_t),
+    Ids(Vec<size_t>),
+    Buffer(Vec<u8>),
+    Flag(c_char),
+    Metadata(WireMetadata),
+    Value(WireValue),
+    Argument(Vec<u8>, WireValue),
+}
+
+/// An owned copy of a Val that may be serialized.
+///
+/// Strings are stored without their terminating null byte.
+#[derive(Debug, Deserialize, Serialize)]
+pub enum WireValue {
+    Int(c_int),
+    Double(c_double),
+    String(Vec<u8>),
+    Uint(c_uint),
+    Bool(bool),
+    DoubleArray(Vec<c_double>),
+    IntArray(Vec<c_int>),
+    Bytes(Vec<c_uchar>),
+    Int64(i64),
+    Uint64(u64),
+    Float(c_float),
+}
+
+impl WireValue {
+    /// Copies the data that is referenced by a Val into a new WireValue.
+    ///
+    /// # Arguments
+    ///
+    /// * `val` - The Val to copy
+    pub fn from_val(val: &Val) -> Result<WireValue, kpal_plugin::Error> {
+        Ok(match val.to_value()? {
+            Value::Int(value) => WireValue::Int(value),
+            Value::Double(value) => WireValue::Double(value),
+            Value::String(value) => WireValue::String(value.into_bytes()),
+            Value::Uint(value) => WireValue::Uint(value),
+            Value::Bool(value) => WireValue::Bool(value),
+            Value::DoubleArray(value) => WireValue::DoubleArray(value),
+            Value::IntArray(value) => WireValue::IntArray(value),
+            Value::Bytes(value) => WireValue::Bytes(value),
+            Value::Int64(value) => WireValue::Int64(value),
+            Value::Uint64(value) => WireValue::Uint64(value),
+            Value::Float(value) => WireValue::Float(value),
+        })
+    }
+
+    /// Converts the WireValue into a Value that owns the data referenced by its Vals.
+    pub fn into_value(self) -> Result<Value, NulError> {
+        Ok(match self {
+            WireValue::Int(value) => Value::Int(value),
+            WireValue::Double(value) => Value::Double(value),
+            WireValue::String(value) => Value::String(CString::new(value)?),
+            WireValue::Uint(value) => Value::Uint(value),
+            WireValue::Bool(value) => Value::Bool(value),
+            WireValue::DoubleArray(value) => Value::DoubleArray(value),
+            WireValue::IntArray(value) => Value::IntArray(value),
+            WireValue::Bytes(value) => Value::Bytes(value),
+            WireValue::Int64(value) => Value::Int64(value),
+            WireValue::Uint64(value) => Value::Uint64(value),
+            WireValue::Float(value) => Value::Float(value),
+        })
+    }
+}
+
+/// An owned copy of a MetadataVal that may be serialized.
+#[derive(Debug, Deserialize, Serialize)]
+pub struct WireMetadata {
+    pub description: Option<Vec<u8>>,
+    pub units: Option<Vec<u8>>,
+    pub min: c_double,
+    pub max: c_double,
+    pub step: c_double,
+}
+
+impl WireMetadata {
+    /// Copies the data that is referenced by a MetadataVal into a new WireMetadata.
+    ///
+    /// # Safety
+    ///
+    /// The strings of the MetadataVal must be null pointers or null-terminated.
+    ///
+    /// # Arguments
+    ///
+    /// * `metadata` - The MetadataVal to copy
+    pub unsafe fn from_val(metadata: &MetadataVal) -> WireMetadata {
+        let to_bytes = |string: *const c_uchar| {
+            if string.is_null() {
+                None
+            } else {
+                Some(CStr::from_ptr(string as *const c_char).to_bytes().to_vec())
+            }
+        };
+
+        WireMetadata {
+            description: to_bytes(metadata.description),
+            units: to_bytes(metadata.units),
+            min: metadata.min,
+            max: metadata.max,
+            step: metadata.step,
+        }
+    }
+
+    /// Converts the WireMetadata into a Metadata instance that owns its strings.
+    pub fn into_metadata(self) -> Result<Metadata, NulError> {
+        let to_number = |number: c_double| if number.is_nan() { None } else { Some(number) };
+
+        Ok(Metadata {
+            description: self.description.map(CString::new).transpose()?,
+            units: self.units.map(CString::new).transpose()?,
+            min: to_number(self.min),
+            max: to_number(self.max),
+            step: to_number(self.step),
+        })
+    }
+}
+
+/// Copies as much of a buffer as fits into the memory provided by the caller.
+///
+/// # Safety
+///
+/// The destination must be valid for writes of `length` bytes.
+///
+/// # Arguments
+///
+/// * `source` - The buffer to copy
+/// * `destination` - A pointer to the caller's buffer
+/// * `length` - The length of the caller's buffer
+pub unsafe fn copy_buffer(source: &[u8], destination: *mut c_uchar, length: size_t) {
+    let count = source.len().min(length);
+    ptr::copy_nonoverlapping(source.as_ptr(), destination, count);
+}
@@ -0,0 +1,346 @@
+//! The loop that runs inside a plugin host process.
+//!
+//! The host reads requests from its standard input, calls the corresponding function of the
+//! plugin's VTable, and writes the results to its standard output. Anything that the plugin itself
+//! prints to the standard output is redirected to the standard error so that it cannot corrupt the
+//! messages that are read by the daemon.
+
+use std::{
+    fs::File,
+    io::{self, BufReader, BufWriter, Write},
+    mem::MaybeUninit,
+    os::unix::io::FromRawFd,
+    path::Path,
+    sync::Mutex,
+};
+
+use libc::{c_char, c_uchar, c_void, size_t};
+use libloading::{Library as Dll, Symbol};
+use log;
+
+use kpal_plugin::{
+    error_codes::*, KpalLibraryInit, KpalPluginInit, MetadataVal, Notifier, Plugin, Val, Value,
+};
+
+use super::{
+    errors::HostError,
+    protocol::{Frame, Output, Reply, Request, WireMetadata, WireValue},
+};
+
+/// The destination of the messages that are sent to the daemon.
+type Writer = Mutex<BufWriter<File>>;
+
+/// Loads a plugin library and serves requests for a single instance of its plugin.
+///
+/// This function returns when the daemon closes the standard input of the process.
+///
+/// # Arguments
+///
+/// * `path` - The path to the plugin library
+pub fn serve(path: &Path) -> Result<(), HostError> {
+    let writer: Box<Writer> = Box::new(Mutex::new(BufWriter::new(take_stdout()?)));
+
+    log::info!("Loading the plugin library {:?}", path);
+    let dll = Dll::new(path)?;
+
+    let (code, plugin) = unsafe { new_plugin(&dll)? };
+    send(&writer, &Frame::Started(code))?;
+    let plugin = match plugin {
+        Some(plugin) => plugin,
+        None => return Ok(()),
+    };
+
+    let stdin = io::stdin();
+    let mut reader = BufReader::new(stdin.lock());
+    loop {
+        let request: Request = match bincode::deserialize_from(&mut reader) {
+            Ok(request) => request,
+            Err(e) => {
+                if let bincode::ErrorKind::Io(ref e) = *e {
+                    if e.kind() == io::ErrorKind::UnexpectedEof {
+                        log::debug!("The daemon closed the connection to the plugin host");
+                        break;
+                    }
+                }
+                return Err(e.into());
+            }
+        };
+
+        log::debug!("Received request: {:?}", request);
+        let reply = unsafe { handle(&plugin, &writer, request) };
+        send(&writer, &Frame::Reply(reply))?;
+    }
+
+    // The plugin is freed before the writer that is used by its notifier and before its library.
+    drop(plugin);
+    drop(dll);
+
+    Ok(())
+}
+
+/// Returns a file for the original standard output and redirects the standard output to the
+/// standard error.
+fn take_stdout() -> Result<File, HostError> {
+    io::stdout().flush()?;
+
+    unsafe {
+        let fd = libc::dup(libc::STDOUT_FILENO);
+        if fd < 0 || libc::dup2(libc::STDERR_FILENO, libc::STDOUT_FILENO) < 0 {
+            return Err(io::Error::last_os_error().into());
+        }
+
+        Ok(File::from_raw_fd(fd))
+    }
+}
+
+/// Initializes a library and requests a new Plugin object from it.
+///
+/// The status code of the first call that fails is returned, along with no plugin.
+///
+/// # Safety
+///
+/// This function is unsafe because it calls functions that are provided by the shared library
+/// through the FFI.
+///
+/// # Arguments
+///
+/// * `dll` - The library that contains the implementation of the Plugin API
+unsafe fn new_plugin(dll: &Dll) -> Result<(i32, Option<Plugin>), HostError> {
+    let kpal_library_init: Symbol<KpalLibraryInit> = dll.get(b"kpal_library_init\0")?;
+    let result = kpal_library_init();
+    if result != PLUGIN_OK {
+        log::error!("Library initialization failed: {}", result);
+        return Ok((result, None));
+    }
+
+    let kpal_plugin_new: Symbol<KpalPluginInit> = dll.get(b"kpal_plugin_new\0")?;
+    let mut plugin = MaybeUninit::<Plugin>::uninit();
+    let result = kpal_plugin_new(plugin.as_mut_ptr());
+    if result != PLUGIN_OK {
+        log::error!("Plugin initialization failed: {}", result);
+        return Ok((result, None));
+    }
+
+    Ok((result, Some(plugin.assume_init())))
+}
+
+/// Writes a single message to the daemon.
+///
+/// # Arguments
+///
+/// * `writer` - The destination of the message
+/// * `frame` - The message to write
+fn send(writer: &Writer, frame: &Frame) -> Result<(), HostError> {
+    let mut writer = writer.lock().map_err(|_| {
+        HostError::new(
+            "The writer of the plugin host is poisoned".to_string(),
+            None,
+        )
+    })?;
+    bincode::serialize_into(&mut *writer, frame)?;
+    writer.flush()?;
+
+    Ok(())
+}
+
+/// Forwards a notification from the plugin to the daemon.
+///
+/// # Arguments
+///
+/// * `context` - A pointer to the writer that sends messages to the daemon
+/// * `id` - The ID of the attribute whose value changed
+extern "C" fn forward_notification(context: *mut c_void, id: size_t) {
+    let writer = unsafe { &*(context as *const Writer) };
+    if let Err(e) = send(writer, &Frame::Notification(id)) {
+        log::error!("Could not forward a notification to the daemon: {}", e);
+    }
+}
+
+/// Calls the function of the plugin's VTable that corresponds to a request.
+///
+/// # Safety
+///
+/// This function is unsafe because it calls functions that are provided by the shared library
+/// through the FFI.
+///
+/// # Arguments
+///
+/// * `plugin` - The plugin that serves the request
+/// * `writer` - The destination of the plugin's notifications
+/// * `request` - The request to serve
+unsafe fn handle(plugin: &Plugin, writer: &Writer, request: Request) -> Reply {
+    let data = plugin.plugin_data;
+    let vtable = &plugin.vtable;
+
+    match request {
+        Request::Init => Reply::new((vtable.plugin_init)(data), Output::None),
+        Request::Deinit => Reply::new((vtable.plugin_deinit)(data), Output::None),
+        Request::SetNotifier => {
+            let notifier = Notifier {
+                callback: forward_notification,
+                context: writer as *const Writer as *mut c_void,
+            };
+            Reply::new((vtable.set_notifier)(data, notifier), Output::None)
+        }
+        Request::AttributeCount => {
+            let mut count: size_t = 0;
+            let code = (vtable.attribute_count)(data, &mut count);
+            Reply::new(code, Output::Count(count))
+        }
+        Request::AttributeIds(length) => {
+            let mut ids = vec![0; length];
+            let code = (vtable.attribute_ids)(data, ids.as_mut_ptr(), length);
+            Reply::new(code, Output::Ids(ids))
+        }
+        Request::AttributeName(id, length) => {
+            let mut buffer = vec![0; length];
+            let code = (vtable.attribute_name)(data, id, buffer.as_mut_ptr(), length);
+            Reply::new(code, Output::Buffer(buffer))
+        }
+        Request::AttributeAlias(id, length) => {
+            let mut buffer = vec![0; length];
+            let code = (vtable.attribute_alias)(data, id, buffer.as_mut_ptr(), length);
+            Reply::new(code, Output::Buffer(buffer))
+        }
+        Request::AttributePreInit(id) => {
+            let mut pre_init: c_char = 0;
+            let code = (vtable.attribute_pre_init)(data, id, &mut pre_init);
+            Reply::new(code, Output::Flag(pre_init))
+        }
+        Request::AttributeSettable(id, phase) => {
+            let mut settable: c_char = 0;
+            let code = (vtable.attribute_settable)(data, id, phase, &mut settable);
+            Reply::new(code, Output::Flag(settable))
+        }
+        Request::AttributeChoices(id, length) => {
+            let mut buffer = vec![0; length];
+            let code = (vtable.attribute_choices)(data, id, buffer.as_mut_ptr(), length);
+            Reply::new(code, Output::Buffer(buffer))
+        }
+        Request::AttributeMetadata(id) => {
+            let mut metadata = MetadataVal::default();
+            let code = (vtable.attribute_metadata)(data, id, &mut metadata);
+            if code != PLUGIN_OK {
+                return Reply::new(code, Output::None);
+            }
+            Reply::new(code, Output::Metadata(WireMetadata::from_val(&metadata)))
+        }
+        Request::AttributeValue(id, phase) => {
+            let mut value = Val::Int(0);
+            let code = (vtable.attribute_value)(data, id, &mut value, phase);
+            value_reply(code, &value)
+        }
+        Request::SetAttributeValue(id, value, phase) => {
+            let value = match value.into_value() {
+                Ok(value) => value,
+                Err(_) => return Reply::new(CONVERSION_ERR, Output::None),
+            };
+            let code = (vtable.set_attribute_value)(data, id, &value.as_val(), phase);
+            Reply::new(code, Output::None)
+        }
+        Request::ActionCount => {
+            let mut count: size_t = 0;
+            let code = (vtable.action_count)(data, &mut count);
+            Reply::new(code, Output::Count(count))
+        }
+        Request::ActionIds(length) => {
+            let mut ids = vec![0; length];
+            let code = (vtable.action_ids)(data, ids.as_mut_ptr(), length);
+            Reply::new(code, Output::Ids(ids))
+        }
+        Request::ActionName(id, length) => {
+            let mut buffer = vec![0; length];
+            let code = (vtable.action_name)(data, id, buffer.as_mut_ptr(), length);
+            Reply::new(code, Output::Buffer(buffer))
+        }
+        Request::ActionArgumentCount(id) => {
+            let mut count: size_t = 0;
+            let code = (vtable.action_argument_count)(data, id, &mut count);
+            Reply::new(code, Output::Count(count))
+        }
+        Request::ActionArgument(id, index, length) => {
+            let mut buffer: Vec<c_uchar> = vec![0; length];
+            let mut value = Val::Int(0);
+            let code =
+                (vtable.action_argument)(data, id, index, buffer.as_mut_ptr(), length, &mut value);
+            match value_reply(code, &value) {
+                Reply {
+                    code,
+                    output: Output::Value(value),
+                } => Reply::new(code, Output::Argument(buffer, value)),
+                reply => reply,
+            }
+        }
+        Request::InvokeAction(id, args) => {
+            let args = match args
+                .into_iter()
+                .map(WireValue::into_value)
+                .collect::<Result<Vec<Value>, _>>()
+            {
+                Ok(args) => args,
+                Err(_) => return Reply::new(CONVERSION_ERR, Output::None),
+            };
+            let vals: Vec<Val> = args.iter().map(Value::as_val).collect();
+            let mut result = Val::Int(0);
+            let code = (vtable.invoke_action)(data, id, vals.as_ptr(), vals.len(), &mut result);
+            value_reply(code, &result)
+        }
+    }
+}
+
+/// Returns a reply that contains a copy of a value that was written by the plugin.
+///
+/// The value is only copied if the plugin did not return an error.
+///
+/// # Arguments
+///
+/// * `code` - The status code that was returned by the plugin
+/// * `value` - The value that was written by the plugin
+fn value_reply(code: i32, value: &Val) -> Reply {
+    if code != PLUGIN_OK {
+        return Reply::new(code, Output::None);
+    }
+
+    match WireValue::from_val(value) {
+        Ok(value) => Reply::new(code, Output::Value(value)),
+        Err(_) => Reply::new(CONVERSION_ERR, Output::None),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn value_reply_copies_the_value() {
+        let value = Value::DoubleArray(vec![1.0, f64::NAN]);
+        let reply = value_reply(PLUGIN_OK, &value.as_val());
+
+        match reply.output {
+            Output::Value(WireValue::DoubleArray(values)) => {
+                assert_eq!(values[0], 1.0);
+                assert!(values[1].is_nan());
+            }
+            output => panic!("Unexpected output: {:?}", output),
+        }
+    }
+
+    #[test]
+    fn value_reply_omits_the_value_of_errors() {
+        let value = Value::Int(1);
+        let reply = value_reply(ATTRIBUTE_DOES_NOT_EXIST, &value.as_val());
+
+        assert_eq!(reply.code, ATTRIBUTE_DOES_NOT_EXIST);
+        assert!(matches!(reply.output, Output::None));
+    }
+
+    #[test]
+    fn strings_survive_a_round_trip() {
+        let value = Value::String(std::ffi::CString::new("foo").unwrap());
+        let wire = WireValue::from_val(&value.as_val()).unwrap();
+        let bytes = bincode::serialize(&wire).unwrap();
+        let wire: WireValue = bincode::deserialize(&bytes).unwrap();
+
+        assert_eq!(wire.into_value().unwrap(), value);
+    }
+}
@@ -1,13 +1,16 @@
 //! Messages and handlers for communications between peripheral threads and web server requests.
 
-use std::{fmt::Debug, sync::mpsc::Receiver as Recv, sync::mpsc::Sender};
+use std::{collections::BTreeMap, fmt::Debug, sync::mpsc::Receiver as Recv, sync::mpsc::Sender};
 
-use kpal_plugin::Val as PluginValue;
+use kpal_plugin::{Val as PluginValue, INIT_PHASE, RUN_PHASE};
 use log;
 
-use super::{Executor, PluginError};
+use super::{merge_attribute, Executor, MergeAttributesError, PluginError, Subscription};
 
-use crate::models::{Attribute, Model, Peripheral, Value};
+use crate::{
+    integrations::ErrorReason,
+    models::{Attribute, AttributeBuilder, Model, Peripheral, PeripheralState, Value},
+};
 
 /// Represents a single receiver that is owned by a peripheral.
 pub type Receiver = Recv<Message>;
@@ -15,12 +18,74 @@ pub type Receiver = Recv<Message>;
 /// Represents a single transmitter for communicating with a peripheral.
 pub type Transmitter = Sender<Message>;
 
-/// A message that is passed from a request handler to a peripheral.
+/// A message that is passed from a request handler or from the plugin itself to a peripheral.
 pub enum Message {
+    AttributeChanged(usize),
+    DeinitPeripheral(Sender<Result<Peripheral, PluginError>>),
     GetPeripheral(Sender<Result<Peripheral, PluginError>>),
     GetPeripheralAttribute(usize, Sender<Result<Attribute, PluginError>>),
     GetPeripheralAttributes(Sender<Result<Vec<Attribute>, PluginError>>),
+    InitPeripheral(Sender<Result<Peripheral, PluginError>>),
+    InvokeAction(usize, Vec<Value>, Sender<Result<Value, PluginError>>),
     PatchPeripheralAttribute(usize, Value, Sender<Result<Attribute, PluginError>>),
+    PatchPeripheralAttributes(
+        Vec<(usize, Value)>,
+        UpdateMode,
+        Sender<Result<Vec<AttributeUpdate<PluginError>>, PluginError>>,
+    ),
+    RestartPeripheral(
+        Vec<AttributeBuilder>,
+        Sender<Result<Peripheral, PluginError>>,
+    ),
+    Shutdown(Sender<Result<(), PluginError>>),
+    SubscribeEvents(Option<u64>, Sender<Result<Subscription, PluginError>>),
+}
+
+/// Determines what happens when one of several values that are set together cannot be set.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum UpdateMode {
+    /// The remaining values are set anyway.
+    Continue,
+
+    /// The remaining values are skipped.
+    StopOnError,
+
+    /// The remaining values are skipped and the values that were already set are restored.
+    Atomic,
+}
+
+/// The outcome of setting one of several values that are set together.
+#[derive(Debug)]
+pub enum AttributeUpdate<E> {
+    /// The value was set.
+    Applied(Attribute),
+
+    /// The value could not be set.
+    Failed(E),
+
+    /// The value was set and then restored to the attribute's previous value.
+    RolledBack(Attribute),
+
+    /// The value was set, but the attribute's previous value could not be restored.
+    RollbackFailed(E),
+
+    /// The value was not set because a preceding one could not be set.
+    Skipped,
+}
+
+impl<E> AttributeUpdate<E> {
+    /// Converts the error of a failed update or rollback, leaving all other outcomes untouched.
+    pub fn map_err<F, O: FnOnce(E) -> F>(self, op: O) -> AttributeUpdate<F> {
+        use AttributeUpdate::*;
+
+        match self {
+            Applied(attr) => Applied(attr),
+            Failed(e) => Failed(op(e)),
+            RolledBack(attr) => RolledBack(attr),
+            RollbackFailed(e) => RollbackFailed(op(e)),
+            Skipped => Skipped,
+        }
+    }
 }
 
 impl Message {
@@ -32,6 +97,30 @@ impl Message {
     /// * `periph` - A reference to the peripheral model that maintains the peripheral state
     pub fn handle(&self, ex: &mut Executor, periph: &mut Peripheral) {
         match self {
+            // Notifications are sent by the plugin itself, so there is nobody to respond to.
+            Message::AttributeChanged(id) => {
+                if !periph.attributes().contains_key(id) {
+                    log::error!(
+                        "Received notification for unknown attribute {} of peripheral {}",
+                        id,
+                        periph.id()
+                    );
+                } else if let Err(e) = attribute_value_wrapper(ex, periph, *id) {
+                    log::error!(
+                        "Could not update attribute {} of peripheral {} after a notification: {}",
+                        id,
+                        periph.id(),
+                        e
+                    );
+                }
+            }
+
+            Message::DeinitPeripheral(tx) => {
+                let result = deinit_wrapper(ex, periph);
+
+                log_and_send(tx.clone(), result, periph.id());
+            }
+
             Message::GetPeripheral(tx) => log_and_send(tx.clone(), Ok(periph.clone()), periph.id()),
 
             Message::GetPeripheralAttribute(id, tx) => {
@@ -58,12 +147,54 @@ impl Message {
                 log_and_send(tx.clone(), attrs.into_iter().collect(), periph.id());
             }
 
+            Message::InitPeripheral(tx) => {
+                let result = init_wrapper(ex, periph);
+
+                log_and_send(tx.clone(), result, periph.id());
+            }
+
+            Message::InvokeAction(id, args, tx) => {
+                let result = invoke_action_wrapper(ex, periph, *id, args);
+
+                log_and_send(tx.clone(), result, periph.id());
+            }
+
             Message::PatchPeripheralAttribute(id, value, tx) => {
-                let value: PluginValue = value.as_val();
                 let result = set_attribute_value_wrapper(ex, periph, *id, value);
 
                 log_and_send(tx.clone(), result, periph.id());
             }
+
+            Message::PatchPeripheralAttributes(values, mode, tx) => {
+                let result = set_attribute_values_wrapper(ex, periph, values, *mode);
+
+                log_and_send(tx.clone(), Ok(result), periph.id());
+            }
+
+            Message::RestartPeripheral(attr_builders, tx) => {
+                let result = restart_wrapper(ex, periph, attr_builders.clone());
+
+                log_and_send(tx.clone(), result, periph.id());
+            }
+
+            // Shutting down requires ownership of the executor, so this message is consumed by
+            // the executor's run loop before it ever reaches this method.
+            Message::Shutdown(tx) => log_and_send(
+                tx.clone(),
+                Err(PluginError::new(
+                    "Shutdown messages can only be handled by the executor's run loop".to_string(),
+                    ErrorReason::InternalError,
+                    None,
+                )),
+                periph.id(),
+            ),
+
+            Message::SubscribeEvents(last_event_id, tx) => {
+                let subscription = ex.events.subscribe(*last_event_id);
+                log::debug!("New event subscriber for peripheral {}", periph.id());
+
+                log_and_send(tx.clone(), Ok(subscription), periph.id());
+            }
         };
     }
 }
@@ -96,15 +227,14 @@ fn attribute_value_wrapper(
             PluginError::from(e)
         })?;
 
-    periph.set_attribute_from_value(id, value)?;
-    let attr = &periph.attributes()[&id];
-    Ok(attr.clone())
+    update_attribute(ex, periph, id, value)
 }
 
 /// Wraps the driver's set_attribute_value function.
 ///
 /// This function is provided for ergonomics. It keeps the `handle()` function DRY and easier to
-/// read.
+/// read. Values that are not one of the attribute's choices or that lie outside of its range are
+/// rejected before they reach the plugin.
 ///
 /// # Arguments
 ///
@@ -116,8 +246,49 @@ fn set_attribute_value_wrapper(
     ex: &mut Executor,
     periph: &mut Peripheral,
     id: usize,
-    value: PluginValue,
+    value: &Value,
 ) -> Result<Attribute, PluginError> {
+    if let Some(attr) = periph.attributes().get(&id) {
+        let settable = if ex.phase() == INIT_PHASE {
+            attr.settable().init
+        } else {
+            attr.settable().run
+        };
+        if !settable {
+            return Err(PluginError::new(
+                format!(
+                    "Attribute cannot be set in the current lifecycle phase: attribute id {}",
+                    id
+                ),
+                ErrorReason::UnprocessableRequest,
+                None,
+            ));
+        }
+
+        if !attr.allows(value) {
+            return Err(PluginError::new(
+                format!(
+                    "Provided value is not one of the attribute's choices: attribute id {}",
+                    id
+                ),
+                ErrorReason::UnprocessableRequest,
+                None,
+            ));
+        }
+
+        if !attr.in_range(value) {
+            return Err(PluginError::new(
+                format!(
+                    "Provided value is outside of the attribute's range: attribute id {}",
+                    id
+                ),
+                ErrorReason::UnprocessableRequest,
+                None,
+            ));
+        }
+    }
+
+    let value: PluginValue = value.as_val();
     ex.set_attribute_value(id, &value)
         .map(|_| {
             log::debug!("Set value {:?} on peripheral {}", value, periph.id(),);
@@ -127,8 +298,304 @@ fn set_attribute_value_wrapper(
             PluginError::from(e)
         })?;
 
+    update_attribute(ex, periph, id, value)
+}
+
+/// Sets the values of several attributes in order.
+///
+/// In the atomic mode, the previous value of each attribute is taken from the peripheral's cached
+/// state before it is set. If a value cannot be set, then the values that were already set are
+/// restored in reverse order.
+///
+/// # Arguments
+///
+/// * `ex` - A reference to the current executor instance
+/// * `periph` - A reference to the perhipheral model that maintains the peripheral's state
+/// * `values` - The ids of the attributes to set and their new values
+/// * `mode` - Determines what happens after a value cannot be set
+fn set_attribute_values_wrapper(
+    ex: &mut Executor,
+    periph: &mut Peripheral,
+    values: &[(usize, Value)],
+    mode: UpdateMode,
+) -> Vec<AttributeUpdate<PluginError>> {
+    let mut results = Vec::new();
+    let mut previous_values = Vec::new();
+    let mut failed = false;
+
+    for (id, value) in values {
+        if failed && mode != UpdateMode::Continue {
+            results.push(AttributeUpdate::Skipped);
+            continue;
+        }
+
+        let previous = periph.attributes().get(id).map(|attr| attr.value().clone());
+
+        match set_attribute_value_wrapper(ex, periph, *id, value) {
+            Ok(attr) => {
+                results.push(AttributeUpdate::Applied(attr));
+                previous_values.push(previous);
+            }
+            Err(e) => {
+                results.push(AttributeUpdate::Failed(e));
+                failed = true;
+            }
+        }
+    }
+
+    if failed && mode == UpdateMode::Atomic {
+        log::info!(
+            "Restoring the previous attribute values of peripheral {}",
+            periph.id()
+        );
+
+        // Only the applied values have a previous value, and they all precede the failure.
+        for (i, previous) in previous_values.into_iter().enumerate().rev() {
+            let (id, _) = values[i];
+            results[i] = match previous {
+                Some(previous) => match restore_attribute_value(ex, periph, id, &previous) {
+                    Ok(attr) => AttributeUpdate::RolledBack(attr),
+                    Err(e) => AttributeUpdate::RollbackFailed(e),
+                },
+                None => AttributeUpdate::RollbackFailed(PluginError::new(
+                    format!("Attribute has no previous value: attribute id {}", id),
+                    ErrorReason::InternalError,
+                    None,
+                )),
+            };
+        }
+    }
+
+    results
+}
+
+/// Sets an attribute back to a value that it had before.
+///
+/// Unlike `set_attribute_value_wrapper`, the value is not checked against the attribute's
+/// settability, choices, or range because the attribute already had it.
+///
+/// # Arguments
+///
+/// * `ex` - A reference to the current executor instance
+/// * `periph` - A reference to the perhipheral model that maintains the peripheral's state
+/// * `id` - The id of the attribute to restore
+/// * `previous` - The previous value of the attribute
+fn restore_attribute_value(
+    ex: &mut Executor,
+    periph: &mut Peripheral,
+    id: usize,
+    previous: &Value,
+) -> Result<Attribute, PluginError> {
+    let value: PluginValue = previous.as_val();
+    ex.set_attribute_value(id, &value)
+        .map(|_| {
+            log::debug!("Restored value {:?} on peripheral {}", value, periph.id());
+        })
+        .map_err(|e| {
+            log::error!("Could not restore attribute {}: {:?}", id, e);
+            PluginError::from(e)
+        })?;
+
+    update_attribute(ex, periph, id, value)
+}
+
+/// Wraps the executor's invoke_action function.
+///
+/// Actions may only be invoked after the peripheral has been initialized.
+///
+/// # Arguments
+///
+/// * `ex` - A reference to the current executor instance
+/// * `periph` - A reference to the perhipheral model that maintains the peripheral's state
+/// * `id` - The id of the action to invoke
+/// * `args` - The values of the action's arguments
+fn invoke_action_wrapper(
+    ex: &mut Executor,
+    periph: &Peripheral,
+    id: usize,
+    args: &[Value],
+) -> Result<Value, PluginError> {
+    if ex.phase() != RUN_PHASE {
+        return Err(PluginError::new(
+            "Peripheral is not initialized".to_string(),
+            ErrorReason::UnprocessableRequest,
+            None,
+        ));
+    }
+
+    let args: Vec<PluginValue> = args.iter().map(|arg| arg.as_val()).collect();
+    ex.invoke_action(id, &args)
+        .map(|result| {
+            log::debug!(
+                "Invoked action {} on peripheral {}: {:?}",
+                id,
+                periph.id(),
+                result
+            );
+            result
+        })
+        .map_err(|e| {
+            log::error!("Message handler error: {:?}", e);
+            PluginError::from(e)
+        })
+}
+
+/// Deinitializes the plugin and returns it to its init phase.
+///
+/// # Arguments
+///
+/// * `ex` - A reference to the current executor instance
+/// * `periph` - A reference to the perhipheral model that maintains the peripheral's state
+fn deinit_wrapper(ex: &mut Executor, periph: &mut Peripheral) -> Result<Peripheral, PluginError> {
+    if ex.phase() != RUN_PHASE {
+        return Err(PluginError::new(
+            "Peripheral is not initialized".to_string(),
+            ErrorReason::UnprocessableRequest,
+            None,
+        ));
+    }
+
+    ex.deinit()?;
+    ex.retreat()?;
+    periph.set_state(PeripheralState::Init);
+    log::info!("Deinitialized peripheral {}", periph.id());
+
+    Ok(periph.clone())
+}
+
+/// Initializes a plugin that is in its init phase and advances it to the run phase.
+///
+/// # Arguments
+///
+/// * `ex` - A reference to the current executor instance
+/// * `periph` - A reference to the perhipheral model that maintains the peripheral's state
+fn init_wrapper(ex: &mut Executor, periph: &mut Peripheral) -> Result<Peripheral, PluginError> {
+    if ex.phase() != INIT_PHASE {
+        return Err(PluginError::new(
+            "Peripheral is already initialized".to_string(),
+            ErrorReason::UnprocessableRequest,
+            None,
+        ));
+    }
+
+    ex.init()?;
+    ex.advance()?;
+    periph.set_state(PeripheralState::Run);
+    log::info!("Initialized peripheral {}", periph.id());
+
+    Ok(periph.clone())
+}
+
+/// Deinitializes the plugin if necessary, sets new pre-init values, and initializes it again.
+///
+/// # Arguments
+///
+/// * `ex` - A reference to the current executor instance
+/// * `periph` - A reference to the perhipheral model that maintains the peripheral's state
+/// * `attr_builders` - Builders containing the new values of pre-init attributes
+fn restart_wrapper(
+    ex: &mut Executor,
+    periph: &mut Peripheral,
+    attr_builders: Vec<AttributeBuilder>,
+) -> Result<Peripheral, PluginError> {
+    // Validate all the new values before touching the plugin.
+    let mut attrs = periph.attributes().clone();
+    for attr_builder in attr_builders {
+        let id = *attr_builder.id();
+        let default = attrs.get(&id).ok_or_else(|| {
+            MergeAttributesError::DoesNotExist(format!(
+                "Attribute does not exist: attribute id {}",
+                id
+            ))
+        })?;
+        let attr = merge_attribute(default, attr_builder)?;
+        attrs.insert(id, attr);
+    }
+
+    // The plugin may be left in its init phase, which is reported to the client and by later reads
+    // of the peripheral.
+    let result = restart_plugin(ex, periph, &attrs);
+    let (state, phase) = if ex.phase() == RUN_PHASE {
+        (PeripheralState::Run, "run")
+    } else {
+        (PeripheralState::Init, "init")
+    };
+    periph.set_state(state);
+
+    if let Err(e) = result {
+        return Err(PluginError::new(
+            format!(
+                "{}; the plugin of peripheral {} is in its {} phase",
+                e.message(),
+                periph.id(),
+                phase
+            ),
+            e.reason(),
+            Some(Box::new(e)),
+        ));
+    }
+    log::info!("Restarted peripheral {}", periph.id());
+
+    Ok(periph.clone())
+}
+
+/// Moves a plugin to its init phase, sets new values of its attributes, and initializes it again.
+///
+/// # Arguments
+///
+/// * `ex` - A reference to the current executor instance
+/// * `periph` - A reference to the perhipheral model that maintains the peripheral's state
+/// * `attrs` - The attributes of the peripheral with their new values
+fn restart_plugin(
+    ex: &mut Executor,
+    periph: &mut Peripheral,
+    attrs: &BTreeMap<usize, Attribute>,
+) -> Result<(), PluginError> {
+    // A plugin whose host process has exited is replaced by a new one in the init phase.
+    ex.revive()?;
+    if ex.phase() == RUN_PHASE {
+        ex.deinit()?;
+        ex.retreat()?;
+    }
+
+    ex.sync(attrs)?;
+    for (id, attr) in attrs {
+        update_attribute(ex, periph, *id, attr.value().as_val())?;
+    }
+
+    ex.init()?;
+    ex.advance()?;
+
+    Ok(())
+}
+
+/// Updates the cached value of a peripheral's attribute.
+///
+/// An event is published to the executor's subscribers if the attribute has changed.
+///
+/// # Arguments
+///
+/// * `ex` - A reference to the current executor instance
+/// * `periph` - A reference to the perhipheral model that maintains the peripheral's state
+/// * `id` - The id of the attribute to update
+/// * `value` - The new value of the attribute
+fn update_attribute(
+    ex: &mut Executor,
+    periph: &mut Peripheral,
+    id: usize,
+    value: PluginValue,
+) -> Result<Attribute, PluginError> {
+    let old = periph
+        .attributes()
+        .get(&id)
+        .map(|attr| attr.value().clone());
     periph.set_attribute_from_value(id, value)?;
+
     let attr = &periph.attributes()[&id];
+    if old.as_ref() != Some(attr.value()) {
+        ex.events.publish(attr.clone());
+    }
+
     Ok(attr.clone())
 }
 
@@ -139,7 +606,7 @@ fn set_attribute_value_wrapper(
 /// * `tx` - The sender used to return a response.
 /// * `result` - The result object to return
 /// * `peripheral_id` The ID of the peripheral from which the response originates
-fn log_and_send<T: Debug>(
+pub(super) fn log_and_send<T: Debug>(
     tx: Sender<Result<T, PluginError>>,
     result: Result<T, PluginError>,
     peripheral_id: usize,
@@ -6,7 +6,9 @@
 //! executor.
 
 mod errors;
+mod events;
 mod executor;
+pub mod host;
 mod messaging;
 
 use std::{
@@ -23,11 +25,12 @@ use kpal_plugin::{KpalPluginInit, Plugin};
 use crate::{
     init::{TSLibrary, Transmitters},
     integrations::ErrorReason,
-    models::{Library, Model, PeripheralBuilder},
+    models::{Attribute, AttributeBuilder, Library, Model, PeripheralBuilder, PeripheralState},
 };
 
 use errors::MergeAttributesError;
 pub use errors::PluginError;
+pub use events::{Event, Subscription};
 pub use executor::Executor;
 pub use messaging::*;
 
@@ -45,16 +48,25 @@ pub fn init(
 ) -> std::result::Result<(), PluginError> {
     let plugin: Plugin = {
         let lib = lib.lock()?;
-        unsafe { kpal_plugin_new(&lib)? }
+        if builder.isolated() {
+            host::spawn(lib.path())?
+        } else {
+            unsafe { kpal_plugin_new(&lib)? }
+        }
     };
 
     let mut executor = Executor::new(plugin);
+    executor.set_library(lib.clone());
+    executor.set_isolated(builder.isolated());
+
+    log::debug!("Passing the notifier to the plugin");
+    executor.set_notifier()?;
 
     log::debug!("Setting user-specified pre-init attributes");
     let builder = set_attributes(builder, lib)?;
 
     log::debug!("Synchronizing the plugin with daemon's peripheral data");
-    executor.sync(&builder)?;
+    executor.sync(builder.attributes())?;
 
     log::debug!("Running the plugin's initialization routine");
     executor.init()?;
@@ -62,13 +74,30 @@ pub fn init(
     log::debug!("Advancing the lifetime phase of the plugin");
     executor.advance()?;
 
-    let peripheral = builder.build()?;
+    let mut peripheral = builder.build()?;
+    peripheral.set_state(PeripheralState::Run);
 
     // Insert the transmitter into the collection of Transmitters only after we have initialized
     // everything successfully. Otherwise, we may insert a channel into the collection which will
     // be immediately closed when this function returns.
+    // The ID and the name are checked again here because another peripheral may have been
+    // created with them while this one was being initialized.
     let tx = Mutex::new(executor.tx.clone());
-    txs.write()?.insert(peripheral.id(), tx);
+    if txs
+        .write()?
+        .insert(peripheral.id(), peripheral.name(), tx)
+        .is_err()
+    {
+        return Err(PluginError::new(
+            format!(
+                "The ID {} or the name {} of the peripheral is already in use",
+                peripheral.id(),
+                peripheral.name()
+            ),
+            ErrorReason::UnprocessableRequest,
+            None,
+        ));
+    }
 
     log::debug!("Launching the plugin executor");
     executor.run(peripheral);
@@ -127,31 +156,8 @@ fn set_attributes(
 
     for (id, attr) in lib_attrs {
         // Build all attributes that were provided with initial values from the user.
-        let periph_attr = if let Some(mut attr_builder) = builder.attribute_builder(id) {
-            attr_builder = attr_builder
-                .set_name(attr.name().to_owned())
-                .set_pre_init(attr.pre_init());
-
-            Some(attr_builder.build()?)
-        } else {
-            None
-        };
-
-        if let Some(periph_attr) = periph_attr {
-            // Verify that user-provided values are valid.
-            if discriminant(attr.value()) != discriminant(periph_attr.value()) {
-                return Err(MergeAttributesError::VariantMismatch(format!(
-                    "Provided attribute variant does not match library's: attribute id {}",
-                    id
-                )));
-            };
-
-            if !periph_attr.pre_init() {
-                return Err(MergeAttributesError::IsNotPreInit(format!(
-                    "Attribute cannot be set before initialization: attribute id {}",
-                    id
-                )));
-            };
+        if let Some(attr_builder) = builder.attribute_builder(id) {
+            let periph_attr = merge_attribute(&attr, attr_builder)?;
 
             log::debug!(
                 "Setting attribute using value provided by the client: {:?}",
@@ -169,3 +175,54 @@ fn set_attributes(
 
     Ok(builder)
 }
+
+/// Builds an attribute from a user-provided value after verifying that the value is valid.
+///
+/// # Arguments
+///
+/// * `default` - The existing attribute whose value will be overridden
+/// * `attr_builder` - The builder containing the value provided by the user
+fn merge_attribute(
+    default: &Attribute,
+    attr_builder: AttributeBuilder,
+) -> Result<Attribute, MergeAttributesError> {
+    let id = default.id();
+    let attr = attr_builder
+        .set_name(default.name().to_owned())
+        .set_alias(default.alias().to_owned())
+        .set_pre_init(default.pre_init())
+        .set_choices(default.choices().to_vec())
+        .set_metadata(default.metadata().clone())
+        .set_settable(default.settable())
+        .build()?;
+
+    if discriminant(default.value()) != discriminant(attr.value()) {
+        return Err(MergeAttributesError::VariantMismatch(format!(
+            "Provided attribute variant does not match library's: attribute id {}",
+            id
+        )));
+    };
+
+    if !attr.pre_init() {
+        return Err(MergeAttributesError::IsNotPreInit(format!(
+            "Attribute cannot be set before initialization: attribute id {}",
+            id
+        )));
+    };
+
+    if !attr.allows(attr.value()) {
+        return Err(MergeAttributesError::NotAllowed(format!(
+            "Provided value is not one of the attribute's choices: attribute id {}",
+            id
+        )));
+    };
+
+    if !attr.in_range(attr.value()) {
+        return Err(MergeAttributesError::OutOfRange(format!(
+            "Provided value is outside of the attribute's range: attribute id {}",
+            id
+        )));
+    };
+
+    Ok(attr)
+}
@@ -0,0 +1,79 @@
+use std::{boxed::Box, error::Error, fmt, io, sync::PoisonError};
+
+use crate::{init::StoreError, integrations::IntegrationsError};
+
+/// An error that is raised while managing or evaluating a rule.
+#[derive(Debug)]
+pub struct RuleError {
+    /// A description of the error that is reported in the status of the rule.
+    message: String,
+
+    /// The lower-level instance of the Error that that caused this one, if any.
+    side: Option<Box<dyn Error + 'static>>,
+}
+
+impl RuleError {
+    pub fn new(message: String, side: Option<Box<dyn Error + 'static>>) -> RuleError {
+        RuleError { message, side }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Error for RuleError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.side.as_ref().map(|e| e.as_ref())
+    }
+}
+
+impl fmt::Display for RuleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "RuleError {{ message: {}, Cause: {:?} }}",
+            self.message, self.side
+        )
+    }
+}
+
+impl From<io::Error> for RuleError {
+    fn from(error: io::Error) -> RuleError {
+        RuleError::new(
+            "Could not access the rules file".to_string(),
+            Some(Box::new(error)),
+        )
+    }
+}
+
+impl From<IntegrationsError> for RuleError {
+    fn from(error: IntegrationsError) -> RuleError {
+        RuleError::new(error.message().to_owned(), Some(Box::new(error)))
+    }
+}
+
+impl<T> From<PoisonError<T>> for RuleError {
+    fn from(_: PoisonError<T>) -> RuleError {
+        // The PoisonError contains an item with a non-static lifetime.
+        RuleError::new("Mutex is poisoned".to_string(), None)
+    }
+}
+
+impl From<serde_json::Error> for RuleError {
+    fn from(error: serde_json::Error) -> RuleError {
+        RuleError::new(
+            "Could not encode or decode the rules".to_string(),
+            Some(Box::new(error)),
+        )
+    }
+}
+
+impl From<StoreError> for RuleError {
+    fn from(error: StoreError) -> RuleError {
+        RuleError::new(
+            "Could not convert a value of a rule".to_string(),
+            Some(Box::new(error)),
+        )
+    }
+}
@@ -0,0 +1,756 @@
+//! Automation rules that set the attributes of peripherals in response to the values of others.
+//!
+//! A rule consists of a condition on the value of one attribute and an action that sets the value
+//! of another attribute, possibly of a different peripheral. Each rule runs inside its own thread
+//! that subscribes to the events of the condition's peripheral and, optionally, also polls the
+//! attribute at a fixed interval.
+//!
+//! The action is issued once every time that the condition becomes true, not for every value
+//! for which it holds. A threshold condition with a hysteresis becomes false again only after the
+//! value has moved back past the threshold by more than the hysteresis, which prevents a noisy
+//! value from toggling the action.
+//!
+//! Rules are written to a JSON file every time that they change and are started again when the
+//! daemon restarts.
+mod errors;
+
+use std::{
+    collections::{BTreeMap, BTreeSet},
+    convert::TryFrom,
+    fs,
+    path::{Path, PathBuf},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        mpsc::{Receiver, RecvTimeoutError},
+        Arc, Mutex, RwLock,
+    },
+    time::{Duration, Instant},
+};
+
+use log;
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    constants::{RULE_RETRY_INTERVAL, RULE_WAKE_INTERVAL},
+    init::{Transmitters, ValueRecord},
+    integrations::{
+        read_peripheral_attribute, subscribe_peripheral_events, update_peripheral_attribute,
+    },
+    models::{Model, Value},
+    plugins::Event,
+    util::{self, write_json, StopSignal, TaskHandle},
+};
+
+pub use errors::RuleError;
+
+/// The Result type returned by the methods of the rules.
+type Result<T> = std::result::Result<T, RuleError>;
+
+/// A test of the value of an attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Comparison {
+    /// Holds once the value rises above the threshold until it falls below the threshold minus
+    /// the hysteresis.
+    Above { threshold: f64, hysteresis: f64 },
+
+    /// Holds once the value falls below the threshold until it rises above the threshold plus
+    /// the hysteresis.
+    Below { threshold: f64, hysteresis: f64 },
+
+    /// Holds while the value is equal to the given one.
+    Equal { value: Value },
+}
+
+impl Comparison {
+    /// Determines whether the comparison holds for a new value of the attribute.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The new value of the attribute
+    /// * `held` - Whether the comparison held for the previous value of the attribute
+    pub fn holds(&self, value: &Value, held: bool) -> Result<bool> {
+        let number = || {
+            value.as_f64().ok_or_else(|| {
+                RuleError::new(
+                    "The value of the attribute is not a number".to_string(),
+                    None,
+                )
+            })
+        };
+
+        Ok(match self {
+            Comparison::Above {
+                threshold,
+                hysteresis,
+            } => {
+                if held {
+                    number()? >= threshold - hysteresis
+                } else {
+                    number()? > *threshold
+                }
+            }
+            Comparison::Below {
+                threshold,
+                hysteresis,
+            } => {
+                if held {
+                    number()? <= threshold + hysteresis
+                } else {
+                    number()? < *threshold
+                }
+            }
+            Comparison::Equal { value: expected } => value == expected,
+        })
+    }
+}
+
+/// A condition on the value of an attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Condition {
+    /// The ID of the peripheral that owns the attribute.
+    pub peripheral_id: usize,
+
+    /// The ID of the attribute.
+    pub attribute_id: usize,
+
+    /// The test of the attribute's value.
+    pub comparison: Comparison,
+}
+
+/// Sets the value of an attribute.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SetAttribute {
+    /// The ID of the peripheral that owns the attribute.
+    pub peripheral_id: usize,
+
+    /// The ID of the attribute.
+    pub attribute_id: usize,
+
+    /// The new value of the attribute.
+    pub value: Value,
+}
+
+/// An automation rule.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Rule {
+    /// The action that is issued when the condition becomes true.
+    pub action: SetAttribute,
+
+    /// The condition that triggers the action.
+    pub condition: Condition,
+
+    /// The interval at which the attribute of the condition is read, if it is polled in addition
+    /// to being evaluated from the events of its peripheral.
+    pub poll_interval: Option<Duration>,
+}
+
+impl Rule {
+    /// Returns the IDs of the peripherals that are used by the rule.
+    pub fn peripheral_ids(&self) -> BTreeSet<usize> {
+        vec![self.condition.peripheral_id, self.action.peripheral_id]
+            .into_iter()
+            .collect()
+    }
+}
+
+/// The state of a rule.
+#[derive(Clone, Debug)]
+pub struct RuleStatus {
+    /// The ID of the rule.
+    pub id: usize,
+
+    /// The rule itself.
+    pub rule: Rule,
+
+    /// Whether the condition of the rule currently holds.
+    pub active: bool,
+
+    /// The number of times that the action was issued.
+    pub fired: u64,
+
+    /// The number of milliseconds since the Unix epoch at which the action was last issued, if
+    /// it has been.
+    pub last_fired: Option<u64>,
+
+    /// A description of the error that occurred the last time that the rule was evaluated, if
+    /// any.
+    pub error: Option<String>,
+}
+
+/// A rule whose thread is running.
+struct Entry {
+    /// The state of the rule.
+    status: Arc<Mutex<RuleStatus>>,
+
+    /// The handle whose drop stops the rule's thread.
+    _task: TaskHandle,
+}
+
+/// The collection of all rules, indexed by their IDs, that is backed by a file.
+pub struct Rules {
+    /// The ID of the next rule that is created.
+    next_id: AtomicUsize,
+
+    /// The file in which the rules are saved.
+    path: PathBuf,
+
+    /// The rules that are currently evaluated.
+    rules: Mutex<BTreeMap<usize, Entry>>,
+}
+
+impl Rules {
+    /// Opens the rules that are saved in the given file and starts evaluating them.
+    ///
+    /// No rules are started if the file does not yet exist. The file is not created until the
+    /// first rule is created.
+    ///
+    /// # Arguments
+    ///
+    /// * `path` - The file in which the rules are saved
+    /// * `timeout` - The maximum amount of time to wait for a response from a peripheral
+    /// * `txs` - The collection of transmitters for sending messages into executor threads
+    pub fn open(path: &Path, timeout: Duration, txs: Arc<RwLock<Transmitters>>) -> Result<Rules> {
+        let records: Vec<RuleRecord> = if path.exists() {
+            let contents = fs::read_to_string(path)?;
+            serde_json::from_str(&contents)?
+        } else {
+            log::debug!("No rules found at {:?}", path);
+            Vec::new()
+        };
+
+        let next_id = records.iter().map(|record| record.id + 1).max();
+        let mut rules = BTreeMap::new();
+        for record in records {
+            let rule = record.to_rule()?;
+            rules.insert(record.id, start(record.id, rule, timeout, txs.clone()));
+            log::info!("Restored rule {}", record.id);
+        }
+
+        Ok(Rules {
+            next_id: AtomicUsize::new(next_id.unwrap_or(0)),
+            path: path.to_owned(),
+            rules: Mutex::new(rules),
+        })
+    }
+
+    /// Starts evaluating a new rule, saves it, and returns its ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `rule` - The rule to start
+    /// * `timeout` - The maximum amount of time to wait for a response from a peripheral
+    /// * `txs` - The collection of transmitters for sending messages into executor threads
+    pub fn insert(
+        &self,
+        rule: Rule,
+        timeout: Duration,
+        txs: Arc<RwLock<Transmitters>>,
+    ) -> Result<usize> {
+        let mut rules = self.rules.lock()?;
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+
+        rules.insert(id, start(id, rule, timeout, txs));
+        if let Err(e) = self.save(&rules) {
+            rules.remove(&id);
+            return Err(e);
+        }
+
+        log::info!("Created rule {}", id);
+        Ok(id)
+    }
+
+    /// Stops evaluating a rule and removes it from the file.
+    ///
+    /// Returns false if the rule does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the rule
+    pub fn remove(&self, id: usize) -> Result<bool> {
+        let mut rules = self.rules.lock()?;
+
+        // Dropping the entry stops the rule's thread.
+        if rules.remove(&id).is_none() {
+            return Ok(false);
+        }
+        self.save(&rules)?;
+
+        log::info!("Removed rule {}", id);
+        Ok(true)
+    }
+
+    /// Returns the status of a rule, or None if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the rule
+    pub fn status(&self, id: usize) -> Result<Option<RuleStatus>> {
+        let status = match self.rules.lock()?.get(&id) {
+            Some(entry) => entry.status.clone(),
+            None => return Ok(None),
+        };
+
+        let status = status.lock()?.clone();
+        Ok(Some(status))
+    }
+
+    /// Returns the statuses of all the rules, ordered by ID.
+    pub fn statuses(&self) -> Result<Vec<RuleStatus>> {
+        let rules = self.rules.lock()?;
+
+        let mut statuses = Vec::new();
+        for entry in rules.values() {
+            statuses.push(entry.status.lock()?.clone());
+        }
+
+        Ok(statuses)
+    }
+
+    /// Writes the rules to the file.
+    fn save(&self, rules: &BTreeMap<usize, Entry>) -> Result<()> {
+        let mut records = Vec::new();
+        for (id, entry) in rules {
+            records.push(RuleRecord::new(*id, &entry.status.lock()?.rule)?);
+        }
+        write_json(&self.path, &records)?;
+
+        Ok(())
+    }
+}
+
+/// Starts the thread that evaluates a rule.
+///
+/// # Arguments
+///
+/// * `id` - The ID of the rule
+/// * `rule` - The rule to evaluate
+/// * `timeout` - The maximum amount of time to wait for a response from a peripheral
+/// * `txs` - The collection of transmitters for sending messages into executor threads
+fn start(id: usize, rule: Rule, timeout: Duration, txs: Arc<RwLock<Transmitters>>) -> Entry {
+    let status = Arc::new(Mutex::new(RuleStatus {
+        id,
+        rule: rule.clone(),
+        active: false,
+        fired: 0,
+        last_fired: None,
+        error: None,
+    }));
+
+    let task = Task {
+        id,
+        rule,
+        status: status.clone(),
+        timeout,
+        txs,
+    };
+
+    Entry {
+        status,
+        _task: util::spawn(move |stop| task.run(stop)),
+    }
+}
+
+/// The work that is performed by the thread of a single rule.
+struct Task {
+    id: usize,
+    rule: Rule,
+    status: Arc<Mutex<RuleStatus>>,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+}
+
+impl Task {
+    /// Evaluates the rule every time that its attribute changes or is polled until the rule is
+    /// removed.
+    ///
+    /// If the peripheral of the condition does not exist, the thread tries again to subscribe to
+    /// its events at a fixed interval so that the rule resumes if the peripheral is created.
+    ///
+    /// # Arguments
+    ///
+    /// * `stop` - The signal that is set when the rule is removed
+    fn run(self, stop: StopSignal) {
+        let mut events: Option<Receiver<Event>> = None;
+        let mut next_poll: Option<Instant> = None;
+
+        while !stop.is_set() {
+            let rx = match &events {
+                Some(rx) => rx,
+                None => {
+                    match self.subscribe() {
+                        Ok(rx) => {
+                            // The rule starts from the current value of the attribute.
+                            events = Some(rx);
+                            next_poll = Some(Instant::now());
+                        }
+                        Err(e) => {
+                            self.report(Err(e));
+                            if stop.wait(RULE_RETRY_INTERVAL) {
+                                break;
+                            }
+                        }
+                    }
+                    continue;
+                }
+            };
+
+            let now = Instant::now();
+            if next_poll.filter(|poll| *poll <= now).is_some() {
+                let result = self.poll();
+                self.report(result);
+                next_poll = self.rule.poll_interval.map(|interval| now + interval);
+            }
+
+            let wait = match next_poll {
+                Some(poll) => {
+                    RULE_WAKE_INTERVAL.min(poll.saturating_duration_since(Instant::now()))
+                }
+                None => RULE_WAKE_INTERVAL,
+            };
+            match rx.recv_timeout(wait) {
+                Ok(event) => {
+                    if event.attribute().id() == self.rule.condition.attribute_id {
+                        let result = self.evaluate(event.attribute().value());
+                        self.report(result);
+                    }
+                }
+                Err(RecvTimeoutError::Timeout) => (),
+                Err(RecvTimeoutError::Disconnected) => events = None,
+            }
+        }
+
+        log::debug!("Rule {} stopped", self.id);
+    }
+
+    /// Subscribes to the events of the peripheral of the condition.
+    fn subscribe(&self) -> Result<Receiver<Event>> {
+        let subscription = subscribe_peripheral_events(
+            self.rule.condition.peripheral_id,
+            None,
+            self.timeout,
+            self.txs.clone(),
+        )?;
+
+        Ok(subscription.rx)
+    }
+
+    /// Reads the attribute of the condition and evaluates the rule.
+    fn poll(&self) -> Result<()> {
+        let attribute_id = self.rule.condition.attribute_id;
+        let attr = read_peripheral_attribute(
+            self.rule.condition.peripheral_id,
+            attribute_id,
+            self.timeout,
+            self.txs.clone(),
+        )?;
+
+        self.evaluate(attr.value())
+    }
+
+    /// Evaluates the rule for a new value of its attribute and issues the action if the condition
+    /// has become true.
+    ///
+    /// # Arguments
+    ///
+    /// * `value` - The new value of the attribute of the condition
+    fn evaluate(&self, value: &Value) -> Result<()> {
+        let fire = {
+            let mut status = self.status.lock()?;
+            let held = status.active;
+            status.active = self.rule.condition.comparison.holds(value, held)?;
+            status.active && !held
+        };
+        if !fire {
+            return Ok(());
+        }
+
+        let action = &self.rule.action;
+        update_peripheral_attribute(
+            action.peripheral_id,
+            action.attribute_id,
+            action.value.clone(),
+            self.timeout,
+            self.txs.clone(),
+        )?;
+
+        let mut status = self.status.lock()?;
+        status.fired += 1;
+        status.last_fired = Some(util::timestamp());
+        log::info!(
+            "Rule {} set attribute {} of peripheral {}",
+            self.id,
+            action.attribute_id,
+            action.peripheral_id
+        );
+
+        Ok(())
+    }
+
+    /// Records the outcome of an evaluation of the rule in its status.
+    ///
+    /// An error is only logged when it differs from the previous one so that a missing
+    /// peripheral does not flood the log.
+    ///
+    /// # Arguments
+    ///
+    /// * `result` - The outcome of the evaluation
+    fn report(&self, result: Result<()>) {
+        let mut status = match self.status.lock() {
+            Ok(status) => status,
+            Err(_) => {
+                log::error!("Could not update the status of a rule: mutex is poisoned");
+                return;
+            }
+        };
+
+        match result {
+            Ok(_) => status.error = None,
+            Err(e) => {
+                if status.error.as_deref() != Some(e.message()) {
+                    log::error!("Could not evaluate rule {}: {}", self.id, e);
+                }
+                status.error = Some(e.message().to_owned());
+            }
+        }
+    }
+}
+
+/// The information that is saved to recreate a rule.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct RuleRecord {
+    action: SetAttributeRecord,
+    condition: ConditionRecord,
+    id: usize,
+    poll_interval_ms: Option<u64>,
+}
+
+impl RuleRecord {
+    /// Creates a new record from a rule.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the rule
+    /// * `rule` - The rule to record
+    fn new(id: usize, rule: &Rule) -> Result<RuleRecord> {
+        let comparison = match &rule.condition.comparison {
+            Comparison::Above {
+                threshold,
+                hysteresis,
+            } => ComparisonRecord::Above {
+                threshold: *threshold,
+                hysteresis: *hysteresis,
+            },
+            Comparison::Below {
+                threshold,
+                hysteresis,
+            } => ComparisonRecord::Below {
+                threshold: *threshold,
+                hysteresis: *hysteresis,
+            },
+            Comparison::Equal { value } => ComparisonRecord::Equal {
+                value: ValueRecord::try_from(value)?,
+            },
+        };
+
+        Ok(RuleRecord {
+            action: SetAttributeRecord {
+                peripheral_id: rule.action.peripheral_id,
+                attribute_id: rule.action.attribute_id,
+                value: ValueRecord::try_from(&rule.action.value)?,
+            },
+            condition: ConditionRecord {
+                peripheral_id: rule.condition.peripheral_id,
+                attribute_id: rule.condition.attribute_id,
+                comparison,
+            },
+            id,
+            poll_interval_ms: rule.poll_interval.map(|i| i.as_millis() as u64),
+        })
+    }
+
+    /// Returns the rule that is recorded.
+    fn to_rule(&self) -> Result<Rule> {
+        let comparison = match &self.condition.comparison {
+            ComparisonRecord::Above {
+                threshold,
+                hysteresis,
+            } => Comparison::Above {
+                threshold: *threshold,
+                hysteresis: *hysteresis,
+            },
+            ComparisonRecord::Below {
+                threshold,
+                hysteresis,
+            } => Comparison::Below {
+                threshold: *threshold,
+                hysteresis: *hysteresis,
+            },
+            ComparisonRecord::Equal { value } => Comparison::Equal {
+                value: Value::try_from(value)?,
+            },
+        };
+
+        Ok(Rule {
+            action: SetAttribute {
+                peripheral_id: self.action.peripheral_id,
+                attribute_id: self.action.attribute_id,
+                value: Value::try_from(&self.action.value)?,
+            },
+            condition: Condition {
+                peripheral_id: self.condition.peripheral_id,
+                attribute_id: self.condition.attribute_id,
+                comparison,
+            },
+            poll_interval: self.poll_interval_ms.map(Duration::from_millis),
+        })
+    }
+}
+
+/// The recorded comparison of a condition.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+#[serde(tag = "type", rename_all = "snake_case")]
+enum ComparisonRecord {
+    Above { threshold: f64, hysteresis: f64 },
+    Below { threshold: f64, hysteresis: f64 },
+    Equal { value: ValueRecord },
+}
+
+/// The recorded condition of a rule.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct ConditionRecord {
+    peripheral_id: usize,
+    attribute_id: usize,
+
+    #[serde(flatten)]
+    comparison: ComparisonRecord,
+}
+
+/// The recorded action of a rule.
+#[derive(Clone, Debug, Deserialize, PartialEq, Serialize)]
+struct SetAttributeRecord {
+    peripheral_id: usize,
+    attribute_id: usize,
+    value: ValueRecord,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_threshold_with_hysteresis() {
+        let above = Comparison::Above {
+            threshold: 40.0,
+            hysteresis: 2.0,
+        };
+        let value = |value| Value::Double { value };
+
+        assert!(!above.holds(&value(40.0), false).unwrap());
+        assert!(above.holds(&value(40.5), false).unwrap());
+        assert!(above.holds(&value(39.0), true).unwrap());
+        assert!(!above.holds(&value(37.5), true).unwrap());
+
+        let below = Comparison::Below {
+            threshold: 10.0,
+            hysteresis: 0.0,
+        };
+        assert!(below.holds(&Value::Int { value: 9 }, false).unwrap());
+        assert!(below.holds(&Value::Int { value: 10 }, true).unwrap());
+        assert!(!below.holds(&Value::Int { value: 11 }, true).unwrap());
+
+        assert!(above.holds(&Value::Bool { value: true }, false).is_err());
+    }
+
+    #[test]
+    fn test_equality() {
+        let equal = Comparison::Equal {
+            value: Value::Bool { value: true },
+        };
+
+        assert!(equal.holds(&Value::Bool { value: true }, false).unwrap());
+        assert!(!equal.holds(&Value::Bool { value: false }, true).unwrap());
+        assert!(!equal.holds(&Value::Int { value: 1 }, false).unwrap());
+    }
+
+    #[test]
+    fn test_rules_are_saved_and_restored() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("rules.json");
+        let txs = Arc::new(RwLock::new(Transmitters::default()));
+        let timeout = Duration::from_millis(10);
+        let rule = Rule {
+            action: SetAttribute {
+                peripheral_id: 1,
+                attribute_id: 0,
+                value: Value::Int { value: 1 },
+            },
+            condition: Condition {
+                peripheral_id: 0,
+                attribute_id: 2,
+                comparison: Comparison::Equal {
+                    value: Value::String {
+                        value: std::ffi::CString::new("on").unwrap(),
+                    },
+                },
+            },
+            poll_interval: Some(Duration::from_millis(100)),
+        };
+
+        let rules = Rules::open(&path, timeout, txs.clone()).unwrap();
+        assert!(rules.statuses().unwrap().is_empty());
+        assert!(!path.exists());
+
+        assert_eq!(0, rules.insert(rule.clone(), timeout, txs.clone()).unwrap());
+        assert_eq!(1, rules.insert(rule.clone(), timeout, txs.clone()).unwrap());
+        assert!(rules.remove(0).unwrap());
+        assert!(!rules.remove(0).unwrap());
+
+        let restored = Rules::open(&path, timeout, txs.clone()).unwrap();
+        let statuses = restored.statuses().unwrap();
+        assert_eq!(1, statuses.len());
+        assert_eq!(1, statuses[0].id);
+        assert_eq!(rule, statuses[0].rule);
+        assert_eq!(2, restored.insert(rule, timeout, txs).unwrap());
+    }
+
+    #[test]
+    fn test_missing_peripheral_is_reported() {
+        let dir = tempdir().unwrap();
+        let txs = Arc::new(RwLock::new(Transmitters::default()));
+        let timeout = Duration::from_millis(10);
+        let rule = Rule {
+            action: SetAttribute {
+                peripheral_id: 0,
+                attribute_id: 0,
+                value: Value::Int { value: 1 },
+            },
+            condition: Condition {
+                peripheral_id: 0,
+                attribute_id: 0,
+                comparison: Comparison::Above {
+                    threshold: 0.0,
+                    hysteresis: 0.0,
+                },
+            },
+            poll_interval: None,
+        };
+
+        let rules = Rules::open(&dir.path().join("rules.json"), timeout, txs.clone()).unwrap();
+        let id = rules.insert(rule, timeout, txs).unwrap();
+
+        for _ in 0..100 {
+            let status = rules.status(id).unwrap().unwrap();
+            if status.error.is_some() {
+                assert!(!status.active);
+                assert_eq!(0, status.fired);
+                return;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("Missing peripheral was not reported");
+    }
+}
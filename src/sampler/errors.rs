@@ -0,0 +1,45 @@
+use std::{boxed::Box, error::Error, fmt, io, sync::PoisonError};
+
+use crate::integrations::IntegrationsError;
+
+/// An error that is raised while sampling an attribute.
+#[derive(Debug)]
+pub struct SamplerError {
+    side: Option<Box<dyn Error + 'static>>,
+}
+
+impl SamplerError {
+    pub fn new(error: Option<Box<dyn Error + 'static>>) -> SamplerError {
+        SamplerError { side: error }
+    }
+}
+
+impl Error for SamplerError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.side.as_ref().map(|e| e.as_ref())
+    }
+}
+
+impl fmt::Display for SamplerError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "SamplerError {{ Cause: {:?} }}", self.side)
+    }
+}
+
+impl From<io::Error> for SamplerError {
+    fn from(error: io::Error) -> SamplerError {
+        SamplerError::new(Some(Box::new(error)))
+    }
+}
+
+impl From<IntegrationsError> for SamplerError {
+    fn from(error: IntegrationsError) -> SamplerError {
+        SamplerError::new(Some(Box::new(error)))
+    }
+}
+
+impl<T> From<PoisonError<T>> for SamplerError {
+    fn from(_: PoisonError<T>) -> SamplerError {
+        SamplerError::new(None) // The PoisonError contains an item with a non-static lifetime.
+    }
+}
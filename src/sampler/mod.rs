@@ -0,0 +1,488 @@
+//! Periodic sampling of the values of peripheral attributes.
+//!
+//! A sampler reads the value of a single attribute at a fixed interval and records each value
+//! together with the time at which it was read. The most recent samples are kept in memory so that
+//! clients can retrieve the history of an attribute. Samples may also be appended to a CSV file so
+//! that they are not lost when they are dropped from the history.
+//!
+//! Each sampler runs inside its own thread, which stops when its sampler is removed or when the
+//! peripheral no longer exists, in which case the sampler is removed as well.
+mod errors;
+
+use std::{
+    collections::{BTreeMap, VecDeque},
+    fs::{self, File, OpenOptions},
+    io::Write,
+    path::{Path, PathBuf},
+    sync::{Arc, Mutex, RwLock},
+    time::{Duration, Instant},
+};
+
+use log;
+
+use crate::{
+    constants::SAMPLE_HISTORY_LENGTH,
+    init::Transmitters,
+    integrations::read_peripheral_attribute,
+    models::Value,
+    util::{self, timestamp, StopSignal, TaskHandle},
+};
+
+pub use errors::SamplerError;
+
+/// The Result type returned by the methods of the samplers.
+type Result<T> = std::result::Result<T, SamplerError>;
+
+/// The recorded samples of a single attribute, ordered from oldest to newest.
+type History = Arc<Mutex<VecDeque<Sample>>>;
+
+/// A value of an attribute and the time at which it was read.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Sample {
+    /// The number of milliseconds since the Unix epoch at which the value was read.
+    pub timestamp: u64,
+
+    /// The value of the attribute.
+    pub value: Value,
+}
+
+/// The settings of a sampler.
+#[derive(Clone, Debug, PartialEq)]
+pub struct SamplerConfig {
+    /// The amount of time between two successive samples.
+    pub interval: Duration,
+
+    /// Whether the samples are also appended to a file.
+    pub record: bool,
+}
+
+/// A sampler of a single attribute.
+///
+/// The sampler's thread stops when the sampler is dropped.
+struct Sampler {
+    /// The settings of the sampler.
+    config: SamplerConfig,
+
+    /// The recorded samples.
+    history: History,
+
+    /// The handle whose drop stops the sampler's thread.
+    _task: TaskHandle,
+}
+
+/// The samplers that are currently running, indexed by peripheral and attribute ID.
+type Running = Arc<Mutex<BTreeMap<(usize, usize), Sampler>>>;
+
+/// The collection of all samplers, indexed by peripheral and attribute ID.
+pub struct Samplers {
+    /// The directory in which recorded samples are written.
+    dir: PathBuf,
+
+    /// The samplers that are currently running.
+    samplers: Running,
+}
+
+impl Samplers {
+    /// Returns an empty collection of samplers.
+    ///
+    /// # Arguments
+    ///
+    /// * `dir` - The directory in which recorded samples are written. It is created when the first
+    ///   sampler that records its samples is started.
+    pub fn new(dir: &Path) -> Samplers {
+        Samplers {
+            dir: dir.to_owned(),
+            samplers: Arc::new(Mutex::new(BTreeMap::new())),
+        }
+    }
+
+    /// Starts sampling an attribute, replacing any sampler of the same attribute.
+    ///
+    /// The history of a replaced sampler is kept.
+    ///
+    /// # Arguments
+    ///
+    /// * `peripheral_id` - The ID of the peripheral that owns the attribute
+    /// * `attribute_id` - The ID of the attribute to sample
+    /// * `config` - The settings of the sampler
+    /// * `timeout` - The maximum amount of time to wait for a response from the peripheral
+    /// * `txs` - The collection of transmitters for sending messages into executor threads
+    pub fn start(
+        &self,
+        peripheral_id: usize,
+        attribute_id: usize,
+        config: SamplerConfig,
+        timeout: Duration,
+        txs: Arc<RwLock<Transmitters>>,
+    ) -> Result<()> {
+        let file = if config.record {
+            Some(self.open_file(peripheral_id, attribute_id)?)
+        } else {
+            None
+        };
+
+        let mut samplers = self.samplers.lock()?;
+        let history = match samplers.remove(&(peripheral_id, attribute_id)) {
+            Some(sampler) => sampler.history,
+            None => Arc::new(Mutex::new(VecDeque::new())),
+        };
+
+        let task = Task {
+            peripheral_id,
+            attribute_id,
+            interval: config.interval,
+            timeout,
+            txs,
+            history: history.clone(),
+            file,
+            samplers: self.samplers.clone(),
+        };
+        let handle = util::spawn(move |stop| task.run(stop));
+
+        log::info!(
+            "Started sampling attribute {} of peripheral {} every {:?}",
+            attribute_id,
+            peripheral_id,
+            config.interval
+        );
+        samplers.insert(
+            (peripheral_id, attribute_id),
+            Sampler {
+                config,
+                history,
+                _task: handle,
+            },
+        );
+
+        Ok(())
+    }
+
+    /// Stops sampling an attribute and discards its history.
+    ///
+    /// Returns false if the attribute was not being sampled.
+    ///
+    /// # Arguments
+    ///
+    /// * `peripheral_id` - The ID of the peripheral that owns the attribute
+    /// * `attribute_id` - The ID of the sampled attribute
+    pub fn stop(&self, peripheral_id: usize, attribute_id: usize) -> Result<bool> {
+        let mut samplers = self.samplers.lock()?;
+        Ok(samplers.remove(&(peripheral_id, attribute_id)).is_some())
+    }
+
+    /// Stops sampling all the attributes of a peripheral and discards their histories.
+    ///
+    /// # Arguments
+    ///
+    /// * `peripheral_id` - The ID of the peripheral
+    pub fn stop_peripheral(&self, peripheral_id: usize) -> Result<()> {
+        let mut samplers = self.samplers.lock()?;
+        samplers.retain(|(id, _), _| *id != peripheral_id);
+        Ok(())
+    }
+
+    /// Returns the settings of the sampler of an attribute, if any.
+    ///
+    /// # Arguments
+    ///
+    /// * `peripheral_id` - The ID of the peripheral that owns the attribute
+    /// * `attribute_id` - The ID of the sampled attribute
+    pub fn config(
+        &self,
+        peripheral_id: usize,
+        attribute_id: usize,
+    ) -> Result<Option<SamplerConfig>> {
+        let samplers = self.samplers.lock()?;
+        Ok(samplers
+            .get(&(peripheral_id, attribute_id))
+            .map(|sampler| sampler.config.clone()))
+    }
+
+    /// Returns the recorded samples of an attribute, or None if the attribute is not sampled.
+    ///
+    /// # Arguments
+    ///
+    /// * `peripheral_id` - The ID of the peripheral that owns the attribute
+    /// * `attribute_id` - The ID of the sampled attribute
+    /// * `since` - If provided, only the samples taken after this time in milliseconds since the
+    ///   Unix epoch are returned
+    pub fn history(
+        &self,
+        peripheral_id: usize,
+        attribute_id: usize,
+        since: Option<u64>,
+    ) -> Result<Option<Vec<Sample>>> {
+        let history = match self.samplers.lock()?.get(&(peripheral_id, attribute_id)) {
+            Some(sampler) => sampler.history.clone(),
+            None => return Ok(None),
+        };
+
+        let since = since.unwrap_or(0);
+        let history = history.lock()?;
+        Ok(Some(
+            history
+                .iter()
+                .filter(|sample| sample.timestamp > since)
+                .cloned()
+                .collect(),
+        ))
+    }
+
+    /// Opens the file to which the samples of an attribute are appended.
+    ///
+    /// A header is written if the file is new.
+    ///
+    /// # Arguments
+    ///
+    /// * `peripheral_id` - The ID of the peripheral that owns the attribute
+    /// * `attribute_id` - The ID of the sampled attribute
+    fn open_file(&self, peripheral_id: usize, attribute_id: usize) -> Result<File> {
+        fs::create_dir_all(&self.dir)?;
+
+        let path = self.dir.join(format!(
+            "peripheral-{}-attribute-{}.csv",
+            peripheral_id, attribute_id
+        ));
+        let is_new = !path.exists();
+        let mut file = OpenOptions::new().create(true).append(true).open(&path)?;
+        if is_new {
+            writeln!(file, "timestamp,value")?;
+        }
+
+        log::debug!("Recording samples to {:?}", path);
+        Ok(file)
+    }
+}
+
+/// The work that is performed by the thread of a single sampler.
+struct Task {
+    peripheral_id: usize,
+    attribute_id: usize,
+    interval: Duration,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+    history: History,
+    file: Option<File>,
+    samplers: Running,
+}
+
+impl Task {
+    /// Samples the attribute until the sampler is dropped or the peripheral no longer exists.
+    ///
+    /// A sampler whose peripheral no longer exists is removed from the collection of samplers.
+    ///
+    /// # Arguments
+    ///
+    /// * `stop` - The signal that is set when the sampler is dropped
+    fn run(mut self, stop: StopSignal) {
+        let mut next = Instant::now();
+
+        loop {
+            match self.sample() {
+                Ok(true) => (),
+                Ok(false) => {
+                    log::info!(
+                        "Peripheral {} no longer exists; stopping its sampler",
+                        self.peripheral_id
+                    );
+                    self.remove(&stop);
+                    break;
+                }
+                Err(e) => log::error!(
+                    "Could not sample attribute {} of peripheral {}: {}",
+                    self.attribute_id,
+                    self.peripheral_id,
+                    e
+                ),
+            }
+
+            // Ticks that were missed because a reading was slow are skipped instead of being made
+            // up by a burst of readings.
+            let now = Instant::now();
+            next += self.interval;
+            if next < now {
+                next = now;
+            }
+            let wait = next - now;
+            if stop.wait(wait) {
+                break;
+            }
+        }
+
+        log::debug!(
+            "Sampler of attribute {} of peripheral {} stopped",
+            self.attribute_id,
+            self.peripheral_id
+        );
+    }
+
+    /// Removes the task's sampler from the collection of samplers.
+    ///
+    /// Nothing is removed if the sampler was already removed or replaced, which is the case when
+    /// its stop signal is set. The check is made while the collection is locked because samplers
+    /// are only dropped while it is locked.
+    ///
+    /// # Arguments
+    ///
+    /// * `stop` - The signal that is set when the sampler is dropped
+    fn remove(&self, stop: &StopSignal) {
+        let mut samplers = match self.samplers.lock() {
+            Ok(samplers) => samplers,
+            Err(e) => {
+                log::error!("Could not remove the sampler: {}", e);
+                return;
+            }
+        };
+
+        if !stop.is_set() {
+            samplers.remove(&(self.peripheral_id, self.attribute_id));
+        }
+    }
+
+    /// Reads and records a single sample of the attribute.
+    ///
+    /// Returns false if the peripheral no longer exists.
+    fn sample(&mut self) -> Result<bool> {
+        let attr = match read_peripheral_attribute(
+            self.peripheral_id,
+            self.attribute_id,
+            self.timeout,
+            self.txs.clone(),
+        ) {
+            Ok(attr) => attr,
+            Err(_) if !self.txs.read()?.contains_key(&self.peripheral_id) => return Ok(false),
+            Err(e) => return Err(e.into()),
+        };
+        let sample = Sample {
+            timestamp: timestamp(),
+            value: attr.value().clone(),
+        };
+
+        if let Some(file) = self.file.as_mut() {
+            writeln!(file, "{},{}", sample.timestamp, csv_value(&sample.value))?;
+        }
+
+        let mut history = self.history.lock()?;
+        if history.len() == SAMPLE_HISTORY_LENGTH {
+            history.pop_front();
+        }
+        history.push_back(sample);
+
+        Ok(true)
+    }
+}
+
+/// Formats a value as a field of a CSV file.
+///
+/// Strings and arrays are quoted. The elements of arrays are separated by spaces and bytes are
+/// encoded as base64.
+///
+/// # Arguments
+///
+/// * `value` - The value to format
+fn csv_value(value: &Value) -> String {
+    let quote = |field: String| format!("\"{}\"", field.replace('"', "\"\""));
+    let join = |items: Vec<String>| quote(items.join(" "));
+
+    match value {
+        Value::Int { value } => value.to_string(),
+        Value::Double { value } => value.to_string(),
+        Value::String { value } => quote(value.to_string_lossy().into_owned()),
+        Value::Uint { value } => value.to_string(),
+        Value::Bool { value } => value.to_string(),
+        Value::DoubleArray { value } => join(value.iter().map(|v| v.to_string()).collect()),
+        Value::IntArray { value } => join(value.iter().map(|v| v.to_string()).collect()),
+        Value::Bytes { value } => base64::encode(value),
+        Value::Int64 { value } => value.to_string(),
+        Value::Uint64 { value } => value.to_string(),
+        Value::Float { value } => value.to_string(),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::{ffi::CString, sync::mpsc::channel, thread};
+
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_csv_value() {
+        assert_eq!("2.5", csv_value(&Value::Double { value: 2.5 }));
+        assert_eq!("true", csv_value(&Value::Bool { value: true }));
+        assert_eq!(
+            "\"say \"\"hi\"\"\"",
+            csv_value(&Value::String {
+                value: CString::new("say \"hi\"").unwrap()
+            })
+        );
+        assert_eq!(
+            "\"1 2 3\"",
+            csv_value(&Value::IntArray {
+                value: vec![1, 2, 3]
+            })
+        );
+        assert_eq!(
+            "AAEC",
+            csv_value(&Value::Bytes {
+                value: vec![0, 1, 2]
+            })
+        );
+    }
+
+    #[test]
+    fn test_sampler_stops_without_peripheral() {
+        let dir = tempdir().unwrap();
+        let samplers = Samplers::new(dir.path());
+        let txs = Arc::new(RwLock::new(Transmitters::default()));
+        let config = SamplerConfig {
+            interval: Duration::from_millis(10),
+            record: true,
+        };
+
+        samplers
+            .start(0, 1, config.clone(), Duration::from_millis(10), txs)
+            .unwrap();
+
+        assert!(dir.path().join("peripheral-0-attribute-1.csv").exists());
+
+        // The sampler removes itself because the peripheral does not exist.
+        for _ in 0..100 {
+            if samplers.config(0, 1).unwrap().is_none() {
+                break;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        assert_eq!(None, samplers.config(0, 1).unwrap());
+        assert_eq!(None, samplers.history(0, 1, None).unwrap());
+        assert!(!samplers.stop(0, 1).unwrap());
+    }
+
+    #[test]
+    fn test_stop_sampler() {
+        let dir = tempdir().unwrap();
+        let samplers = Samplers::new(dir.path());
+        let (tx, _rx) = channel();
+        let txs = Arc::new(RwLock::new(Transmitters::default()));
+        txs.write()
+            .unwrap()
+            .insert(0, "foo", Mutex::new(tx))
+            .unwrap();
+        let config = SamplerConfig {
+            interval: Duration::from_millis(10),
+            record: false,
+        };
+
+        samplers
+            .start(0, 1, config.clone(), Duration::from_millis(10), txs)
+            .unwrap();
+
+        assert_eq!(Some(config), samplers.config(0, 1).unwrap());
+        assert_eq!(Some(Vec::new()), samplers.history(0, 1, None).unwrap());
+
+        assert!(samplers.stop(0, 1).unwrap());
+        assert!(!samplers.stop(0, 1).unwrap());
+        assert_eq!(None, samplers.history(0, 1, None).unwrap());
+    }
+}
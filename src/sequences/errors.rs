@@ -0,0 +1,52 @@
+use std::{boxed::Box, error::Error, fmt, sync::PoisonError};
+
+use crate::integrations::IntegrationsError;
+
+/// An error that is raised while running a sequence.
+#[derive(Debug)]
+pub struct SequenceError {
+    /// A description of the error that is reported in the status of the sequence.
+    message: String,
+
+    /// The lower-level instance of the Error that that caused this one, if any.
+    side: Option<Box<dyn Error + 'static>>,
+}
+
+impl SequenceError {
+    pub fn new(message: String, side: Option<Box<dyn Error + 'static>>) -> SequenceError {
+        SequenceError { message, side }
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+}
+
+impl Error for SequenceError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        self.side.as_ref().map(|e| e.as_ref())
+    }
+}
+
+impl fmt::Display for SequenceError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(
+            f,
+            "SequenceError {{ message: {}, Cause: {:?} }}",
+            self.message, self.side
+        )
+    }
+}
+
+impl From<IntegrationsError> for SequenceError {
+    fn from(error: IntegrationsError) -> SequenceError {
+        SequenceError::new(error.message().to_owned(), Some(Box::new(error)))
+    }
+}
+
+impl<T> From<PoisonError<T>> for SequenceError {
+    fn from(_: PoisonError<T>) -> SequenceError {
+        // The PoisonError contains an item with a non-static lifetime.
+        SequenceError::new("Mutex is poisoned".to_string(), None)
+    }
+}
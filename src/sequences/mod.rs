@@ -0,0 +1,527 @@
+//! Ordered procedures that are run by the daemon across several peripherals.
+//!
+//! A sequence is a list of steps that set and read attribute values, invoke actions, wait, and
+//! repeat other steps. Each sequence runs inside its own thread so that the steps are run one
+//! after the other without a round trip to the client in between. Waits are measured from the end
+//! of the preceding step.
+//!
+//! The status of a sequence, including the values that it read, is kept after it finishes until
+//! it is removed.
+mod errors;
+
+use std::{
+    collections::{BTreeMap, BTreeSet, VecDeque},
+    sync::{
+        atomic::{AtomicUsize, Ordering},
+        Arc, Mutex, RwLock,
+    },
+    time::Duration,
+};
+
+use log;
+
+use crate::{
+    constants::SEQUENCE_READINGS_LENGTH,
+    init::Transmitters,
+    integrations::{
+        invoke_peripheral_action, read_peripheral_attribute, update_peripheral_attribute,
+    },
+    models::Value,
+    util::{self, timestamp, StopSignal, TaskHandle},
+};
+
+pub use errors::SequenceError;
+
+/// The Result type returned by the methods of the sequences.
+type Result<T> = std::result::Result<T, SequenceError>;
+
+/// A single step of a sequence.
+#[derive(Clone, Debug, PartialEq)]
+pub enum Step {
+    /// Reads the value of an attribute and records it.
+    Get {
+        peripheral_id: usize,
+        attribute_id: usize,
+    },
+
+    /// Invokes an action. Its result is discarded.
+    Invoke {
+        peripheral_id: usize,
+        action_id: usize,
+        arguments: Vec<Value>,
+    },
+
+    /// Runs a list of steps a fixed number of times.
+    Loop { count: usize, steps: Vec<Step> },
+
+    /// Sets the value of an attribute.
+    Set {
+        peripheral_id: usize,
+        attribute_id: usize,
+        value: Value,
+    },
+
+    /// Does nothing for a fixed amount of time.
+    Wait { duration: Duration },
+}
+
+/// Returns the IDs of all the peripherals that are used by a list of steps.
+///
+/// # Arguments
+///
+/// * `steps` - The steps of a sequence
+pub fn peripheral_ids(steps: &[Step]) -> BTreeSet<usize> {
+    let mut ids = BTreeSet::new();
+    for step in steps {
+        match step {
+            Step::Get { peripheral_id, .. }
+            | Step::Invoke { peripheral_id, .. }
+            | Step::Set { peripheral_id, .. } => {
+                ids.insert(*peripheral_id);
+            }
+            Step::Loop { steps, .. } => ids.extend(peripheral_ids(steps)),
+            Step::Wait { .. } => (),
+        }
+    }
+    ids
+}
+
+/// The state of a sequence.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum State {
+    Running,
+    Completed,
+    Cancelled,
+    Failed,
+}
+
+/// A value of an attribute that was read by a sequence.
+#[derive(Clone, Debug, PartialEq)]
+pub struct Reading {
+    /// The number of milliseconds since the Unix epoch at which the value was read.
+    pub timestamp: u64,
+
+    /// The ID of the peripheral that owns the attribute.
+    pub peripheral_id: usize,
+
+    /// The ID of the attribute.
+    pub attribute_id: usize,
+
+    /// The value of the attribute.
+    pub value: Value,
+}
+
+/// The progress and results of a sequence.
+#[derive(Clone, Debug)]
+pub struct SequenceStatus {
+    /// The ID of the sequence.
+    pub id: usize,
+
+    /// The state of the sequence.
+    pub state: State,
+
+    /// The number of steps that were completed, not counting loops themselves.
+    pub steps_run: usize,
+
+    /// A description of the error that stopped the sequence, if any.
+    pub error: Option<String>,
+
+    /// The most recent values that were read by the sequence, ordered from oldest to newest.
+    pub readings: VecDeque<Reading>,
+
+    /// The number of milliseconds since the Unix epoch at which the sequence started.
+    pub started: u64,
+
+    /// The number of milliseconds since the Unix epoch at which the sequence finished, if it has.
+    pub finished: Option<u64>,
+}
+
+/// A sequence that is running or that has finished.
+struct Sequence {
+    /// The progress and results of the sequence.
+    status: Arc<Mutex<SequenceStatus>>,
+
+    /// The handle whose drop cancels the sequence's thread.
+    cancel: Option<TaskHandle>,
+}
+
+/// The collection of all sequences, indexed by their IDs.
+#[derive(Default)]
+pub struct Sequences {
+    /// The ID of the next sequence that is started.
+    next_id: AtomicUsize,
+
+    /// The sequences that are running or that have finished.
+    sequences: Mutex<BTreeMap<usize, Sequence>>,
+}
+
+impl Sequences {
+    /// Starts running a sequence and returns its ID.
+    ///
+    /// # Arguments
+    ///
+    /// * `steps` - The steps of the sequence
+    /// * `timeout` - The maximum amount of time to wait for a response from a peripheral
+    /// * `txs` - The collection of transmitters for sending messages into executor threads
+    pub fn start(
+        &self,
+        steps: Vec<Step>,
+        timeout: Duration,
+        txs: Arc<RwLock<Transmitters>>,
+    ) -> Result<usize> {
+        let mut sequences = self.sequences.lock()?;
+        let id = self.next_id.fetch_add(1, Ordering::SeqCst);
+
+        let status = Arc::new(Mutex::new(SequenceStatus {
+            id,
+            state: State::Running,
+            steps_run: 0,
+            error: None,
+            readings: VecDeque::new(),
+            started: timestamp(),
+            finished: None,
+        }));
+
+        let task = Task {
+            status: status.clone(),
+            timeout,
+            txs,
+        };
+        let handle = util::spawn(move |cancel| task.run(steps, cancel));
+
+        log::info!("Started sequence {}", id);
+        sequences.insert(
+            id,
+            Sequence {
+                status,
+                cancel: Some(handle),
+            },
+        );
+
+        Ok(id)
+    }
+
+    /// Returns the status of a sequence, or None if it does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the sequence
+    pub fn status(&self, id: usize) -> Result<Option<SequenceStatus>> {
+        let status = match self.sequences.lock()?.get(&id) {
+            Some(sequence) => sequence.status.clone(),
+            None => return Ok(None),
+        };
+
+        let status = status.lock()?.clone();
+        Ok(Some(status))
+    }
+
+    /// Returns the statuses of all the sequences, ordered by ID.
+    pub fn statuses(&self) -> Result<Vec<SequenceStatus>> {
+        let sequences = self.sequences.lock()?;
+
+        let mut statuses = Vec::new();
+        for sequence in sequences.values() {
+            statuses.push(sequence.status.lock()?.clone());
+        }
+
+        Ok(statuses)
+    }
+
+    /// Cancels a sequence and returns its status, or None if it does not exist.
+    ///
+    /// A step that is in progress is completed, but no further steps are run. Cancelling a
+    /// sequence that has already finished has no effect.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the sequence
+    pub fn cancel(&self, id: usize) -> Result<Option<SequenceStatus>> {
+        let mut sequences = self.sequences.lock()?;
+        let sequence = match sequences.get_mut(&id) {
+            Some(sequence) => sequence,
+            None => return Ok(None),
+        };
+
+        // Dropping the handle stops the sequence's thread before its next step.
+        sequence.cancel.take();
+
+        let mut status = sequence.status.lock()?;
+        if status.state == State::Running {
+            log::info!("Cancelled sequence {}", id);
+            status.state = State::Cancelled;
+            status.finished = Some(timestamp());
+        }
+
+        Ok(Some(status.clone()))
+    }
+
+    /// Cancels a sequence if it is running and discards its status.
+    ///
+    /// Returns false if the sequence does not exist.
+    ///
+    /// # Arguments
+    ///
+    /// * `id` - The ID of the sequence
+    pub fn remove(&self, id: usize) -> Result<bool> {
+        if self.cancel(id)?.is_none() {
+            return Ok(false);
+        }
+
+        let mut sequences = self.sequences.lock()?;
+        Ok(sequences.remove(&id).is_some())
+    }
+}
+
+/// The work that is performed by the thread of a single sequence.
+struct Task {
+    status: Arc<Mutex<SequenceStatus>>,
+    timeout: Duration,
+    txs: Arc<RwLock<Transmitters>>,
+}
+
+impl Task {
+    /// Runs the steps of a sequence until they are all done, one fails, or the sequence is
+    /// cancelled.
+    ///
+    /// # Arguments
+    ///
+    /// * `steps` - The steps of the sequence
+    /// * `cancel` - The signal that is set when the sequence is cancelled
+    fn run(self, steps: Vec<Step>, cancel: StopSignal) {
+        let result = self.run_steps(&steps, &cancel);
+
+        let mut status = match self.status.lock() {
+            Ok(status) => status,
+            Err(_) => {
+                log::error!("Could not update the status of a sequence: mutex is poisoned");
+                return;
+            }
+        };
+
+        // A sequence that was cancelled while it was running keeps its state.
+        if status.state != State::Running {
+            return;
+        }
+
+        match result {
+            Ok(true) => status.state = State::Completed,
+            Ok(false) => status.state = State::Cancelled,
+            Err(e) => {
+                log::error!("Sequence {} failed: {}", status.id, e);
+                status.state = State::Failed;
+                status.error = Some(e.message().to_owned());
+            }
+        }
+        status.finished = Some(timestamp());
+
+        log::info!("Sequence {} finished: {:?}", status.id, status.state);
+    }
+
+    /// Runs a list of steps in order.
+    ///
+    /// Returns false if the sequence was cancelled.
+    ///
+    /// # Arguments
+    ///
+    /// * `steps` - The steps to run
+    /// * `cancel` - The signal that is set when the sequence is cancelled
+    fn run_steps(&self, steps: &[Step], cancel: &StopSignal) -> Result<bool> {
+        for step in steps {
+            if cancel.is_set() {
+                return Ok(false);
+            }
+
+            match step {
+                Step::Get {
+                    peripheral_id,
+                    attribute_id,
+                } => {
+                    let attr = read_peripheral_attribute(
+                        *peripheral_id,
+                        *attribute_id,
+                        self.timeout,
+                        self.txs.clone(),
+                    )?;
+                    self.record(Reading {
+                        timestamp: timestamp(),
+                        peripheral_id: *peripheral_id,
+                        attribute_id: *attribute_id,
+                        value: attr.value().clone(),
+                    })?;
+                }
+
+                Step::Invoke {
+                    peripheral_id,
+                    action_id,
+                    arguments,
+                } => {
+                    invoke_peripheral_action(
+                        *peripheral_id,
+                        *action_id,
+                        arguments.clone(),
+                        self.timeout,
+                        self.txs.clone(),
+                    )?;
+                }
+
+                Step::Loop { count, steps } => {
+                    for _ in 0..*count {
+                        if cancel.is_set() || !self.run_steps(steps, cancel)? {
+                            return Ok(false);
+                        }
+                    }
+                    continue;
+                }
+
+                Step::Set {
+                    peripheral_id,
+                    attribute_id,
+                    value,
+                } => {
+                    update_peripheral_attribute(
+                        *peripheral_id,
+                        *attribute_id,
+                        value.clone(),
+                        self.timeout,
+                        self.txs.clone(),
+                    )?;
+                }
+
+                Step::Wait { duration } => {
+                    if cancel.wait(*duration) {
+                        return Ok(false);
+                    }
+                }
+            }
+
+            self.status.lock()?.steps_run += 1;
+        }
+
+        Ok(true)
+    }
+
+    /// Adds a reading to the status of the sequence, dropping the oldest one if necessary.
+    ///
+    /// # Arguments
+    ///
+    /// * `reading` - The value that was read
+    fn record(&self, reading: Reading) -> Result<()> {
+        let mut status = self.status.lock()?;
+        if status.readings.len() == SEQUENCE_READINGS_LENGTH {
+            status.readings.pop_front();
+        }
+        status.readings.push_back(reading);
+
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::thread;
+
+    #[test]
+    fn test_peripheral_ids() {
+        let steps = vec![
+            Step::Set {
+                peripheral_id: 1,
+                attribute_id: 0,
+                value: Value::Double { value: 1.0 },
+            },
+            Step::Loop {
+                count: 2,
+                steps: vec![
+                    Step::Wait {
+                        duration: Duration::from_millis(1),
+                    },
+                    Step::Get {
+                        peripheral_id: 3,
+                        attribute_id: 0,
+                    },
+                ],
+            },
+        ];
+
+        let expected: BTreeSet<usize> = vec![1, 3].into_iter().collect();
+        assert_eq!(expected, peripheral_ids(&steps));
+    }
+
+    #[test]
+    fn test_sequence_completes() {
+        let (sequences, txs) = set_up();
+        let steps = vec![Step::Loop {
+            count: 3,
+            steps: vec![Step::Wait {
+                duration: Duration::from_millis(5),
+            }],
+        }];
+
+        let id = sequences
+            .start(steps, Duration::from_millis(10), txs)
+            .unwrap();
+        let status = wait_until_finished(&sequences, id);
+
+        assert_eq!(State::Completed, status.state);
+        assert_eq!(3, status.steps_run);
+        assert!(status.finished.unwrap() >= status.started + 15);
+    }
+
+    #[test]
+    fn test_sequence_fails_without_peripheral() {
+        let (sequences, txs) = set_up();
+        let steps = vec![Step::Get {
+            peripheral_id: 0,
+            attribute_id: 0,
+        }];
+
+        let id = sequences
+            .start(steps, Duration::from_millis(10), txs)
+            .unwrap();
+        let status = wait_until_finished(&sequences, id);
+
+        assert_eq!(State::Failed, status.state);
+        assert_eq!(0, status.steps_run);
+        assert!(status.error.is_some());
+    }
+
+    #[test]
+    fn test_cancel_and_remove_sequence() {
+        let (sequences, txs) = set_up();
+        let steps = vec![Step::Wait {
+            duration: Duration::from_secs(60),
+        }];
+
+        let id = sequences
+            .start(steps, Duration::from_millis(10), txs)
+            .unwrap();
+        let status = sequences.cancel(id).unwrap().unwrap();
+        assert_eq!(State::Cancelled, status.state);
+        assert_eq!(State::Cancelled, wait_until_finished(&sequences, id).state);
+
+        assert!(sequences.remove(id).unwrap());
+        assert!(!sequences.remove(id).unwrap());
+        assert!(sequences.status(id).unwrap().is_none());
+        assert!(sequences.cancel(id).unwrap().is_none());
+    }
+
+    fn set_up() -> (Sequences, Arc<RwLock<Transmitters>>) {
+        (
+            Sequences::default(),
+            Arc::new(RwLock::new(Transmitters::default())),
+        )
+    }
+
+    fn wait_until_finished(sequences: &Sequences, id: usize) -> SequenceStatus {
+        for _ in 0..100 {
+            let status = sequences.status(id).unwrap().unwrap();
+            if status.finished.is_some() {
+                return status;
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+        panic!("Sequence {} did not finish", id);
+    }
+}
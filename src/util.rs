@@ -0,0 +1,134 @@
+//! Helpers that are shared by the daemon's background tasks and the files that it writes.
+//!
+//! Samplers, sequences and rules each run inside their own thread. They send their requests to
+//! the peripherals' executors through the functions of the `integrations` module, just like a
+//! request from a client, and they stop when the handle that was returned when they were spawned
+//! is dropped.
+use std::{
+    fs, io,
+    path::Path,
+    sync::mpsc::{channel, Receiver, RecvTimeoutError, Sender, TryRecvError},
+    thread,
+    time::{Duration, Instant, SystemTime, UNIX_EPOCH},
+};
+
+use serde::Serialize;
+
+/// The handle of a background task whose thread stops when the handle is dropped.
+pub(crate) struct TaskHandle {
+    /// The transmitter whose disconnection stops the task's thread.
+    _stop: Sender<()>,
+}
+
+/// The signal that tells the thread of a background task to stop.
+pub(crate) struct StopSignal {
+    /// The receiver that is disconnected when the task's handle is dropped.
+    rx: Receiver<()>,
+}
+
+impl StopSignal {
+    /// Returns true if the task's handle was dropped.
+    pub(crate) fn is_set(&self) -> bool {
+        self.rx.try_recv() == Err(TryRecvError::Disconnected)
+    }
+
+    /// Waits for a fixed amount of time.
+    ///
+    /// Returns true if the task's handle was dropped before the time elapsed.
+    ///
+    /// # Arguments
+    ///
+    /// * `duration` - The amount of time to wait
+    pub(crate) fn wait(&self, duration: Duration) -> bool {
+        let deadline = Instant::now() + duration;
+
+        loop {
+            let remaining = deadline.saturating_duration_since(Instant::now());
+            if remaining == Duration::from_secs(0) {
+                return false;
+            }
+
+            match self.rx.recv_timeout(remaining) {
+                Err(RecvTimeoutError::Timeout) => (),
+                _ => return true,
+            }
+        }
+    }
+}
+
+/// Runs a background task in a new thread and returns the handle that stops it.
+///
+/// # Arguments
+///
+/// * `task` - The work that is performed by the thread, which receives the signal to stop
+pub(crate) fn spawn<F>(task: F) -> TaskHandle
+where
+    F: FnOnce(StopSignal) + Send + 'static,
+{
+    let (tx, rx) = channel();
+    thread::spawn(move || task(StopSignal { rx }));
+
+    TaskHandle { _stop: tx }
+}
+
+/// Returns the current time in milliseconds since the Unix epoch.
+pub(crate) fn timestamp() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|time| time.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+/// Writes a value to a file in the JSON format.
+///
+/// The value is first written to a temporary file that then replaces the file so that a crash
+/// while writing does not corrupt it.
+///
+/// # Arguments
+///
+/// * `path` - The location of the file
+/// * `value` - The value to write
+pub(crate) fn write_json<T: Serialize + ?Sized>(path: &Path, value: &T) -> io::Result<()> {
+    if let Some(parent) = path.parent() {
+        fs::create_dir_all(parent)?;
+    }
+
+    let contents = serde_json::to_string_pretty(value)?;
+
+    let tmp = path.with_extension("tmp");
+    fs::write(&tmp, contents)?;
+    fs::rename(&tmp, path)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use tempfile::tempdir;
+
+    #[test]
+    fn test_task_stops_when_its_handle_is_dropped() {
+        let (tx, rx) = channel();
+        let handle = spawn(move |stop| {
+            while !stop.wait(Duration::from_millis(10)) {}
+            tx.send(stop.is_set()).unwrap();
+        });
+
+        assert!(rx.recv_timeout(Duration::from_millis(50)).is_err());
+        drop(handle);
+        assert!(rx.recv_timeout(Duration::from_secs(1)).unwrap());
+    }
+
+    #[test]
+    fn test_write_json() {
+        let dir = tempdir().unwrap();
+        let path = dir.path().join("kpal").join("values.json");
+
+        write_json(&path, &[1, 2]).unwrap();
+        write_json(&path, &[3]).unwrap();
+
+        let values: Vec<u8> = serde_json::from_str(&fs::read_to_string(&path).unwrap()).unwrap();
+        assert_eq!(values, vec![3]);
+        assert!(!path.with_extension("tmp").exists());
+    }
+}
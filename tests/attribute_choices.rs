@@ -0,0 +1,146 @@
+//! Integration test that verifies that attributes only accept values from their declared choices.
+pub mod common;
+
+use serde::{Deserialize, Serialize};
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Get, Patch, Post, Request,
+};
+
+// This matches the ID of the enumerated attribute in the BasicPlugin example.
+const URL: &str = "/api/v0/peripherals/0/attributes/9";
+
+#[test]
+fn test_attribute_choices() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Sets an enumerated attribute to values inside and outside of its choices.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data_bad_choice = PostData {
+        name: "foo",
+        library_id: 0,
+        attributes: vec![AttributeData {
+            id: 9,
+            r#type: "string",
+            value: "ultra",
+        }],
+    };
+    expect_status(
+        &Post::new(
+            &context.server_url,
+            "/api/v0/peripherals",
+            post_data_bad_choice,
+        ),
+        422,
+    )?;
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+        attributes: vec![AttributeData {
+            id: 9,
+            r#type: "string",
+            value: "high",
+        }],
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+    check(value(context, &client)? == "high", "Unexpected value")?;
+
+    let patch_data = PatchData {
+        r#type: "string",
+        value: "medium",
+    };
+    expect_status(&Patch::new(&context.server_url, URL, patch_data), 200)?;
+    check(value(context, &client)? == "medium", "Unexpected value")?;
+
+    let patch_data_bad_choice = PatchData {
+        r#type: "string",
+        value: "ultra",
+    };
+    expect_status(
+        &Patch::new(&context.server_url, URL, patch_data_bad_choice),
+        422,
+    )?;
+    check(
+        value(context, &client)? == "medium",
+        "A value outside of the choices was set",
+    )?;
+
+    Ok(())
+}
+
+/// Returns the value of the enumerated attribute after checking its choices.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+fn value(context: &Context, client: &reqwest::Client) -> Result<String, CommonError> {
+    let mut resp = Get::new(&context.server_url, URL).exec(client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the attribute",
+    )?;
+    let attr: Attribute = resp.json()?;
+    check(
+        attr.choices == ["low", "medium", "high"],
+        "Unexpected choices",
+    )?;
+
+    Ok(attr.value.value)
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+    attributes: Vec<AttributeData>,
+}
+
+/// Patch data to update an attribute value.
+#[derive(Debug, Serialize)]
+struct PatchData<T> {
+    r#type: &'static str,
+    value: T,
+}
+
+/// Initial value of a pre-init attribute.
+#[derive(Debug, Serialize)]
+struct AttributeData {
+    id: usize,
+    r#type: &'static str,
+    value: &'static str,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    choices: Vec<String>,
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: String,
+}
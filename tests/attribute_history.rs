@@ -0,0 +1,166 @@
+//! Integration test that verifies that attributes may be sampled periodically.
+pub mod common;
+
+use std::{fs, thread, time::Duration};
+
+use serde::{Deserialize, Serialize};
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Delete, Get, Post, Request,
+};
+
+const URL_HISTORY: &str = "/api/v0/peripherals/0/attributes/0/history";
+const URL_SAMPLER: &str = "/api/v0/peripherals/0/attributes/0/sampler";
+
+#[test]
+fn test_attribute_history() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Samples an attribute, reads its history, and then stops sampling it.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    // Attributes that are not sampled have no history.
+    expect_status(&Get::new(&context.server_url, URL_HISTORY), 404)?;
+    expect_status(&Get::new(&context.server_url, URL_SAMPLER), 404)?;
+
+    let sampler_data = SamplerData {
+        interval_ms: 50,
+        record: true,
+    };
+    let mut resp = Post::new(&context.server_url, URL_SAMPLER, sampler_data).exec(&client)?;
+    check(
+        resp.status().as_u16() == 201,
+        "Could not create the sampler",
+    )?;
+    let sampler: Sampler = resp.json()?;
+    check(sampler.interval_ms == 50, "Unexpected sampler interval")?;
+    check(sampler.record, "Sampler is not recording")?;
+
+    expect_status(&Get::new(&context.server_url, URL_SAMPLER), 200)?;
+
+    // Sampling intervals that are too short and missing attributes are rejected.
+    let too_fast = SamplerData {
+        interval_ms: 1,
+        record: false,
+    };
+    expect_status(&Post::new(&context.server_url, URL_SAMPLER, too_fast), 422)?;
+    let missing_attr = SamplerData {
+        interval_ms: 50,
+        record: false,
+    };
+    expect_status(
+        &Post::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/attributes/99/sampler",
+            missing_attr,
+        ),
+        404,
+    )?;
+
+    thread::sleep(Duration::from_millis(500));
+
+    let mut resp = Get::new(&context.server_url, URL_HISTORY).exec(&client)?;
+    check(resp.status().as_u16() == 200, "Could not read the history")?;
+    let samples: Vec<Sample> = resp.json()?;
+    check(samples.len() > 1, "Too few samples were taken")?;
+    check(
+        samples.windows(2).all(|s| s[0].timestamp <= s[1].timestamp),
+        "Samples are not ordered by time",
+    )?;
+    check(
+        samples
+            .iter()
+            .all(|s| (s.value.value - 0.0).abs() < f64::EPSILON),
+        "Unexpected sample value",
+    )?;
+
+    // Only the samples that were taken after the given time are returned.
+    let since = samples[0].timestamp;
+    let route = format!("{}?since={}", URL_HISTORY, since);
+    let mut resp = Get::new(&context.server_url, &route).exec(&client)?;
+    let recent: Vec<Sample> = resp.json()?;
+    check(
+        recent.iter().all(|s| s.timestamp > since),
+        "Samples were not filtered by time",
+    )?;
+
+    let route = format!("{}?since=yesterday", URL_HISTORY);
+    expect_status(&Get::new(&context.server_url, &route), 400)?;
+
+    // Recorded samples are written to a file.
+    let path = context
+        .store_dir
+        .path()
+        .join("samples")
+        .join("peripheral-0-attribute-0.csv");
+    let contents = fs::read_to_string(path)?;
+    check(
+        contents.starts_with("timestamp,value\n"),
+        "Unexpected file header",
+    )?;
+    check(contents.lines().count() > 1, "No samples were recorded")?;
+
+    expect_status(&Delete::new(&context.server_url, URL_SAMPLER), 204)?;
+    expect_status(&Get::new(&context.server_url, URL_HISTORY), 404)?;
+    expect_status(&Delete::new(&context.server_url, URL_SAMPLER), 404)?;
+
+    Ok(())
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Post data to create a new sampler.
+#[derive(Debug, Serialize)]
+struct SamplerData {
+    interval_ms: u64,
+    record: bool,
+}
+
+/// Represents a sampler returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Sampler {
+    interval_ms: u64,
+    record: bool,
+}
+
+/// Represents a sample returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Sample {
+    timestamp: u64,
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
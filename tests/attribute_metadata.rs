@@ -0,0 +1,167 @@
+//! Integration test that verifies that attribute metadata is returned and that ranges are enforced.
+pub mod common;
+
+use serde::{Deserialize, Serialize};
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Get, Patch, Post, Request,
+};
+
+// This matches the ID of the attribute with a range in the BasicPlugin example.
+const URL: &str = "/api/v0/peripherals/0/attributes/0";
+
+#[test]
+fn test_attribute_metadata() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Sets an attribute with a range to values inside and outside of the range.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data_out_of_range = PostData {
+        name: "foo",
+        library_id: 0,
+        attributes: vec![AttributeData {
+            id: 0,
+            r#type: "double",
+            value: -5000.0,
+        }],
+    };
+    expect_status(
+        &Post::new(
+            &context.server_url,
+            "/api/v0/peripherals",
+            post_data_out_of_range,
+        ),
+        422,
+    )?;
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+        attributes: vec![AttributeData {
+            id: 0,
+            r#type: "double",
+            value: 10.0,
+        }],
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+    check(
+        (value(context, &client)? - 10.0).abs() < f64::EPSILON,
+        "Unexpected value",
+    )?;
+
+    let patch_data = PatchData {
+        r#type: "double",
+        value: 1000.0,
+    };
+    expect_status(&Patch::new(&context.server_url, URL, patch_data), 200)?;
+    check(
+        (value(context, &client)? - 1000.0).abs() < f64::EPSILON,
+        "Unexpected value",
+    )?;
+
+    let patch_data_out_of_range = PatchData {
+        r#type: "double",
+        value: 1000.5,
+    };
+    expect_status(
+        &Patch::new(&context.server_url, URL, patch_data_out_of_range),
+        422,
+    )?;
+    check(
+        (value(context, &client)? - 1000.0).abs() < f64::EPSILON,
+        "A value outside of the range was set",
+    )?;
+
+    Ok(())
+}
+
+/// Returns the value of the attribute after checking its metadata.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+fn value(context: &Context, client: &reqwest::Client) -> Result<f64, CommonError> {
+    let mut resp = Get::new(&context.server_url, URL).exec(client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the attribute",
+    )?;
+    let attr: Attribute = resp.json()?;
+
+    let expected_metadata = Metadata {
+        description: Some("The position of the stage".to_string()),
+        max: Some(1000.0),
+        min: Some(-1000.0),
+        step: Some(0.01),
+        units: Some("mm".to_string()),
+    };
+    check(attr.metadata == expected_metadata, "Unexpected metadata")?;
+
+    Ok(attr.value.value)
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+    attributes: Vec<AttributeData>,
+}
+
+/// Patch data to update an attribute value.
+#[derive(Debug, Serialize)]
+struct PatchData<T> {
+    r#type: &'static str,
+    value: T,
+}
+
+/// Initial value of a pre-init attribute.
+#[derive(Debug, Serialize)]
+struct AttributeData {
+    id: usize,
+    r#type: &'static str,
+    value: f64,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    metadata: Metadata,
+    value: Value,
+}
+
+/// Represents the metadata of an attribute returned by the daemon.
+#[derive(Debug, Deserialize, PartialEq)]
+struct Metadata {
+    description: Option<String>,
+    max: Option<f64>,
+    min: Option<f64>,
+    step: Option<f64>,
+    units: Option<String>,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
@@ -0,0 +1,129 @@
+//! Integration test that verifies that read-only attributes are reported and cannot be set.
+pub mod common;
+
+use serde::{Deserialize, Serialize};
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Get, Patch, Post, Request,
+};
+
+// These match the IDs of a constant and a settable attribute in the BasicPlugin example.
+const URL_CONSTANT: &str = "/api/v0/peripherals/0/attributes/2";
+const URL_SETTABLE: &str = "/api/v0/peripherals/0/attributes/3";
+
+#[test]
+fn test_attribute_settable() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Checks the settable status of a constant and a settable attribute and tries to set them.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    check(
+        settable(context, &client, URL_CONSTANT)?
+            == Settable {
+                init: false,
+                run: false,
+            },
+        "The constant attribute is reported as settable",
+    )?;
+    let patch_data_constant = PatchData {
+        r#type: "integer",
+        value: 0,
+    };
+    expect_status(
+        &Patch::new(&context.server_url, URL_CONSTANT, patch_data_constant),
+        422,
+    )?;
+
+    check(
+        settable(context, &client, URL_SETTABLE)?
+            == Settable {
+                init: false,
+                run: true,
+            },
+        "The settable attribute is not reported as settable in the run phase",
+    )?;
+    let patch_data_settable = PatchData {
+        r#type: "string",
+        value: "bar",
+    };
+    expect_status(
+        &Patch::new(&context.server_url, URL_SETTABLE, patch_data_settable),
+        200,
+    )?;
+
+    Ok(())
+}
+
+/// Returns whether an attribute may be set in each lifecycle phase.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+/// * `url` - The route of the attribute
+fn settable(
+    context: &Context,
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<Settable, CommonError> {
+    let mut resp = Get::new(&context.server_url, url).exec(client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the attribute",
+    )?;
+    let attr: Attribute = resp.json()?;
+
+    Ok(attr.settable)
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Patch data to update an attribute value.
+#[derive(Debug, Serialize)]
+struct PatchData<T> {
+    r#type: &'static str,
+    value: T,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    settable: Settable,
+}
+
+/// Represents whether an attribute may be set in each lifecycle phase.
+#[derive(Debug, Deserialize, PartialEq)]
+struct Settable {
+    init: bool,
+    run: bool,
+}
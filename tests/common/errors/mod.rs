@@ -38,6 +38,12 @@ impl From<ReqwestError> for CommonError {
     }
 }
 
+impl From<StartDaemonError> for CommonError {
+    fn from(error: StartDaemonError) -> CommonError {
+        CommonError::new(Some(Box::new(error)))
+    }
+}
+
 /// Indicates that an error occured when starting the daemon.
 #[derive(Debug)]
 pub struct StartDaemonError {}
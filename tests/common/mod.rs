@@ -1,4 +1,7 @@
 //! Common code used by the integration tests.
+//!
+//! Each integration test compiles its own copy of this module and uses only part of it.
+#![allow(dead_code)]
 
 mod errors;
 mod requests;
@@ -24,18 +27,35 @@ pub use requests::*;
 
 const LIBRARY_FILENAME: &str = "libbasic-plugin.so";
 
+const STORE_FILENAME: &str = "peripherals.json";
+const RULES_FILENAME: &str = "rules.json";
+const SAMPLES_DIR: &str = "samples";
+
+const CONFIG_FILENAME: &str = "kpald.toml";
+
 /// Data that specifies the context within which the test is run.
 #[derive(Debug)]
 pub struct Context {
     pub bin_exe: PathBuf,
+    pub config_file: Option<PathBuf>,
     pub daemon: Child,
     pub library_dir: TempDir,
     pub server_addr: String,
     pub server_url: Url,
+    pub store_dir: TempDir,
 }
 
 /// Sets up a clean working directory and daemon before an integration test is run.
 pub fn set_up() -> Result<Context, CommonError> {
+    set_up_with_config(None)
+}
+
+/// Sets up a clean working directory and a daemon that reads the given configuration file.
+///
+/// # Arguments
+///
+/// * `config` - The contents of the daemon's TOML configuration file, if any.
+pub fn set_up_with_config(config: Option<&str>) -> Result<Context, CommonError> {
     let _ = env_logger::builder().is_test(true).try_init();
 
     // Set up the temporary directory to hold library files
@@ -49,6 +69,19 @@ pub fn set_up() -> Result<Context, CommonError> {
 
     fs::copy(library_file_src.as_path(), library_file_dest.as_path())?;
 
+    // Set up the temporary directory to hold the peripheral store
+    let store_dir = tempdir()?;
+
+    // Write the configuration file, if any, next to the peripheral store
+    let config_file = match config {
+        Some(contents) => {
+            let path = store_dir.path().join(CONFIG_FILENAME);
+            fs::write(&path, contents)?;
+            Some(path)
+        }
+        None => None,
+    };
+
     // Find the kpald binary
     let mut bin_exe = PathBuf::from(env!("CARGO_MANIFEST_DIR"));
     bin_exe.push(artifacts_dir());
@@ -65,7 +98,9 @@ pub fn set_up() -> Result<Context, CommonError> {
     // Start the server
     let daemon = start_daemon(
         bin_exe.as_path(),
+        config_file.as_deref(),
         library_dir.path(),
+        store_dir.path(),
         &server_addr,
         &server_url,
     )
@@ -73,13 +108,36 @@ pub fn set_up() -> Result<Context, CommonError> {
 
     Ok(Context {
         bin_exe,
+        config_file,
         daemon,
         library_dir,
         server_addr,
         server_url,
+        store_dir,
     })
 }
 
+/// Stops the daemon and starts it again within the same context.
+///
+/// # Arguments
+///
+/// * `context` - Values that define the context within which the tests are run.
+pub fn restart(context: &mut Context) -> Result<(), CommonError> {
+    let _ = context.daemon.kill();
+    let _ = context.daemon.wait();
+
+    context.daemon = start_daemon(
+        context.bin_exe.as_path(),
+        context.config_file.as_deref(),
+        context.library_dir.path(),
+        context.store_dir.path(),
+        &context.server_addr,
+        &context.server_url,
+    )?;
+
+    Ok(())
+}
+
 /// Cleans up any resoruces that were created for an integration test.
 ///
 /// # Arguments
@@ -89,6 +147,33 @@ pub fn tear_down(mut context: Context) {
     let _ = context.daemon.kill();
 }
 
+/// Makes a single request and checks its HTTP status code.
+///
+/// # Arguments
+///
+/// * `req` - The HTTP request to make
+/// * `expected` - The expected HTTP status code
+pub fn expect_status(req: &dyn Request, expected: u16) -> Result<(), CommonError> {
+    log::info!("Testing route: {}", req.url());
+    let client = reqwest::Client::new();
+
+    log::debug!("Making HTTP {:?} request to {}", req.verb(), req.url());
+    let resp = req.exec(&client)?;
+    check(
+        resp.status().as_u16() == expected,
+        &format!("Expected status code {} from {}", expected, req.url()),
+    )
+}
+
+/// Returns an error with the given message if a condition is false.
+pub fn check(condition: bool, message: &str) -> Result<(), CommonError> {
+    if condition {
+        Ok(())
+    } else {
+        Err(CommonError::new(Some(message.into())))
+    }
+}
+
 /// Starts the daemon for a test.
 ///
 /// This method must ensure that the daemon process is killed if any error occurs during the setup.
@@ -96,22 +181,35 @@ pub fn tear_down(mut context: Context) {
 /// # Arguments
 ///
 /// * `bin_exe` - The location of the daemon's binary file
+/// * `config_file` - The location of the daemon's configuration file, if any
 /// * `library_dir` - The location of the peripheral library files
+/// * `store_dir` - The location of the peripheral store, the rules and the recorded samples
 /// * `server_addr` - The address of the server in the form $ADDRESS:$PORT
 /// * `server_url` - The URL of the server in the form $SCHEME://$ADDRESS:$PORT
 fn start_daemon(
     bin_exe: &Path,
+    config_file: Option<&Path>,
     library_dir: &Path,
+    store_dir: &Path,
     server_addr: &str,
     server_url: &Url,
 ) -> Result<Child, StartDaemonError> {
-    let mut daemon = Command::new(bin_exe)
+    let mut command = Command::new(bin_exe);
+    command
         .arg("--library-dir")
         .arg(library_dir)
+        .arg("--store-file")
+        .arg(store_dir.join(STORE_FILENAME))
+        .arg("--rules-file")
+        .arg(store_dir.join(RULES_FILENAME))
+        .arg("--samples-dir")
+        .arg(store_dir.join(SAMPLES_DIR))
         .arg("--server-address")
-        .arg(server_addr)
-        .spawn()
-        .expect("daemon failed to start");
+        .arg(server_addr);
+    if let Some(config_file) = config_file {
+        command.arg("--config").arg(config_file);
+    }
+    let mut daemon = command.spawn().expect("daemon failed to start");
 
     let mut attempt = 0;
     let num_attempts = 3;
@@ -10,6 +10,7 @@ use {
 /// The set HTTP request verbs used by the tests.
 #[derive(Debug)]
 pub enum HttpVerb {
+    Delete,
     Get,
     Post,
     Patch,
@@ -20,6 +21,7 @@ pub trait Request {
 
     fn init(&self) -> Box<dyn Fn(&Client, &Url) -> RequestBuilder> {
         match self.verb() {
+            HttpVerb::Delete => Box::new(|client: &Client, url: &Url| client.delete(url.as_str())),
             HttpVerb::Get => Box::new(|client: &Client, url: &Url| client.get(url.as_str())),
             HttpVerb::Post => Box::new(|client: &Client, url: &Url| client.post(url.as_str())),
             HttpVerb::Patch => Box::new(|client: &Client, url: &Url| client.patch(url.as_str())),
@@ -31,6 +33,34 @@ pub trait Request {
     fn verb(&self) -> HttpVerb;
 }
 
+/// A DELETE request.
+pub struct Delete {
+    url: Url,
+}
+
+impl Delete {
+    pub fn new(domain: &Url, route: &str) -> Delete {
+        let url = domain
+            .join(route)
+            .expect("Could not produce full URL for the test");
+        Delete { url }
+    }
+}
+
+impl Request for Delete {
+    fn exec(&self, client: &Client) -> Result<Response, ReqwestError> {
+        self.init()(client, &self.url).send()
+    }
+
+    fn url(&self) -> &Url {
+        &self.url
+    }
+
+    fn verb(&self) -> HttpVerb {
+        HttpVerb::Delete
+    }
+}
+
 /// A GET request.
 pub struct Get {
     url: Url,
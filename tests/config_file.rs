@@ -0,0 +1,131 @@
+//! Integration test that verifies that the daemon creates the peripherals in its configuration.
+pub mod common;
+
+use std::fs;
+
+use serde::Deserialize;
+use serde_json::json;
+
+use common::{
+    check, expect_status, restart, set_up_with_config, tear_down, CommonError, Context, Get, Post,
+    Request,
+};
+
+const CONFIG: &str = r#"
+request_timeout_ms = 1000
+
+[[peripherals]]
+library_name = "libbasic-plugin.so"
+name = "foo"
+attributes = [{ id = 0, type = "double", value = 2.5 }]
+"#;
+
+/// The configuration after a peripheral was added to it and the original one was removed.
+const CONFIG_CHANGED: &str = r#"
+request_timeout_ms = 1000
+
+[[peripherals]]
+library_name = "libbasic-plugin.so"
+name = "baz"
+"#;
+
+#[test]
+fn test_config_file() {
+    let mut context = set_up_with_config(Some(CONFIG)).expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&mut context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Verifies that the configured peripherals are created and that the IDs of peripherals do not
+/// change when the configuration changes.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &mut Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    expect_status(&Get::new(&context.server_url, "/api/v0/peripherals/0"), 200)?;
+    let mut resp =
+        Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/0").exec(&client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the attribute",
+    )?;
+    let attr: Attribute = resp.json()?;
+    check(
+        (attr.value.value - 2.5).abs() < f64::EPSILON,
+        "Configured attribute value was not set",
+    )?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/peripherals/1"), 404)?;
+
+    let post_data = json!({"name": "bar", "library_id": 0});
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    restart(context)?;
+    check(
+        name(context, 0)? == "foo",
+        "Configured peripheral was moved",
+    )?;
+    check(name(context, 1)? == "bar", "Stored peripheral was moved")?;
+
+    let config_file = context.config_file.as_ref().expect("No configuration file");
+    fs::write(config_file, CONFIG_CHANGED)?;
+    restart(context)?;
+
+    expect_status(&Get::new(&context.server_url, "/api/v0/peripherals/0"), 404)?;
+    check(name(context, 1)? == "bar", "Stored peripheral was moved")?;
+    check(
+        name(context, 2)? == "baz",
+        "New peripheral has an unexpected ID",
+    )?;
+
+    Ok(())
+}
+
+/// Returns the name of a peripheral.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `id` - The ID of the peripheral
+fn name(context: &Context, id: usize) -> Result<String, CommonError> {
+    let client = reqwest::Client::new();
+    let route = format!("/api/v0/peripherals/{}", id);
+    let mut resp = Get::new(&context.server_url, &route).exec(&client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the peripheral",
+    )?;
+    let periph: Peripheral = resp.json()?;
+
+    Ok(periph.name)
+}
+
+/// Represents a peripheral returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Peripheral {
+    name: String,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
@@ -0,0 +1,60 @@
+//! Integration test that verifies that peripherals may be deleted.
+pub mod common;
+
+use serde::Serialize;
+
+use common::{expect_status, set_up, tear_down, CommonError, Context, Delete, Get, Post};
+
+#[test]
+fn test_delete_peripheral() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Creates and deletes a peripheral and checks that it can no longer be found.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/peripherals/0"), 200)?;
+
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/peripherals/0"),
+        204,
+    )?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/peripherals/0"), 404)?;
+    expect_status(
+        &Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/0"),
+        404,
+    )?;
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/peripherals/0"),
+        404,
+    )?;
+
+    Ok(())
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
@@ -0,0 +1,174 @@
+//! Integration test that verifies that an attribute may be read and set across a group.
+pub mod common;
+
+use std::collections::BTreeMap;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Delete, Get, Patch, Post,
+    Request,
+};
+
+const URL_GROUP_X: &str = "/api/v0/groups/0/attributes/0";
+
+#[test]
+fn test_groups() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Creates a group of peripherals, reads and sets one of their attributes, and deletes it.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    for name in &["foo", "bar", "baz"] {
+        let post_data = PostData {
+            name,
+            library_id: 0,
+        };
+        expect_status(
+            &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+            201,
+        )?;
+    }
+
+    let group = json!({"name": "bank", "peripheral_ids": [2, 0, 1]});
+    let resp = Post::new(&context.server_url, "/api/v0/groups", group).exec(&client)?;
+    check(resp.status().as_u16() == 201, "Could not create the group")?;
+    check(
+        resp.headers()["Location"] == "/api/v0/groups/0",
+        "Unexpected group location",
+    )?;
+
+    let mut resp = Get::new(&context.server_url, "/api/v0/groups/0").exec(&client)?;
+    let group: Group = resp.json()?;
+    check(group.name == "bank", "Unexpected group name")?;
+    check(group.peripheral_ids == vec![0, 1, 2], "Unexpected members")?;
+
+    // The same value is set on every member.
+    let value = json!({"type": "double", "value": 1.5});
+    let mut resp = Patch::new(&context.server_url, URL_GROUP_X, value).exec(&client)?;
+    check(resp.status().as_u16() == 200, "Could not set the attribute")?;
+    let results: BTreeMap<usize, MemberResult> = resp.json()?;
+    check_values(&results, 1.5)?;
+
+    let mut resp = Get::new(&context.server_url, URL_GROUP_X).exec(&client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the attribute",
+    )?;
+    let results: BTreeMap<usize, MemberResult> = resp.json()?;
+    check_values(&results, 1.5)?;
+
+    // Each member reports its own failure.
+    let value = json!({"type": "double", "value": 5000.0});
+    let mut resp = Patch::new(&context.server_url, URL_GROUP_X, value).exec(&client)?;
+    let results: BTreeMap<usize, MemberResult> = resp.json()?;
+    check(
+        results
+            .values()
+            .all(|r| r.status == "failed" && r.http_status_code == Some(422)),
+        "Invalid value was set",
+    )?;
+
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/peripherals/1"),
+        204,
+    )?;
+    let mut resp = Get::new(&context.server_url, URL_GROUP_X).exec(&client)?;
+    let results: BTreeMap<usize, MemberResult> = resp.json()?;
+    check(results[&0].status == "succeeded", "Member 0 failed")?;
+    check(
+        results[&1].http_status_code == Some(404),
+        "Deleted member was found",
+    )?;
+    check(results[&2].status == "succeeded", "Member 2 failed")?;
+
+    // Groups with missing peripherals are rejected.
+    let group = json!({"name": "missing", "peripheral_ids": [0, 99]});
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/groups", group),
+        422,
+    )?;
+
+    let mut resp = Get::new(&context.server_url, "/api/v0/groups").exec(&client)?;
+    let groups: Vec<Group> = resp.json()?;
+    check(groups.len() == 1, "Unexpected number of groups")?;
+
+    expect_status(&Delete::new(&context.server_url, "/api/v0/groups/0"), 204)?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/groups/0"), 404)?;
+    expect_status(&Get::new(&context.server_url, URL_GROUP_X), 404)?;
+
+    Ok(())
+}
+
+/// Checks that every member of a group succeeded and returned the given value.
+///
+/// # Arguments
+///
+/// * `results` - The results returned by the daemon, indexed by peripheral ID
+/// * `expected` - The expected value of the attribute
+fn check_values(results: &BTreeMap<usize, MemberResult>, expected: f64) -> Result<(), CommonError> {
+    check(
+        results.keys().cloned().collect::<Vec<usize>>() == vec![0, 1, 2],
+        "Unexpected members",
+    )?;
+    for result in results.values() {
+        check(result.status == "succeeded", "A member failed")?;
+        let value = result.attribute.as_ref().map(|a| a.value.value);
+        check(
+            value.is_some_and(|v| (v - expected).abs() < f64::EPSILON),
+            "Unexpected attribute value",
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Represents a group returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Group {
+    name: String,
+    peripheral_ids: Vec<usize>,
+}
+
+/// Represents the result of an operation on one member of a group.
+#[derive(Debug, Deserialize)]
+struct MemberResult {
+    status: String,
+    attribute: Option<Attribute>,
+    http_status_code: Option<u16>,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
@@ -0,0 +1,197 @@
+//! Integration test that verifies that plugins may be run in their own processes.
+pub mod common;
+
+use std::{fs, thread, time::Duration};
+
+use serde::Deserialize;
+use serde_json::json;
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Delete, Get, Patch, Post,
+    Request,
+};
+
+const URL_X: &str = "/api/v0/peripherals/0/attributes/0";
+
+#[test]
+fn test_isolated_plugins() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Creates an isolated peripheral, kills its host process, and restarts it.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = json!({"name": "foo", "library_id": 0, "isolated": true});
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    let mut resp = Get::new(&context.server_url, "/api/v0/peripherals/0").exec(&client)?;
+    let periph: Peripheral = resp.json()?;
+    check(periph.isolated, "Peripheral is not isolated")?;
+
+    // The plugin is used through its host process like any other plugin.
+    expect_status(
+        &Patch::new(
+            &context.server_url,
+            URL_X,
+            json!({"type": "double", "value": 1.5}),
+        ),
+        200,
+    )?;
+    check(
+        (x(context, &client)? - 1.5).abs() < f64::EPSILON,
+        "Unexpected value",
+    )?;
+
+    let move_by = json!({"arguments": [{"type": "double", "value": 2.5}]});
+    expect_status(
+        &Post::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/actions/1",
+            move_by,
+        ),
+        200,
+    )?;
+
+    // A crash of the host process is reported as a failure of the peripheral only.
+    let hosts = host_pids(context.daemon.id());
+    check(hosts.len() == 1, "Unexpected number of host processes")?;
+    unsafe { libc::kill(hosts[0], libc::SIGKILL) };
+    thread::sleep(Duration::from_millis(200));
+
+    expect_status(&Get::new(&context.server_url, URL_X), 500)?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/libraries/0"), 200)?;
+    check(
+        state(context, &client)? == "failed",
+        "Peripheral is not reported as failed",
+    )?;
+
+    let restart_data = json!({"attributes": [{"id": 0, "type": "double", "value": 7.0}]});
+    expect_status(
+        &Post::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/restart",
+            restart_data,
+        ),
+        200,
+    )?;
+    check(
+        (x(context, &client)? - 7.0).abs() < f64::EPSILON,
+        "Peripheral was not restarted",
+    )?;
+    check(
+        state(context, &client)? == "run",
+        "Peripheral is not running after its restart",
+    )?;
+
+    let hosts = host_pids(context.daemon.id());
+    check(hosts.len() == 1, "Host process was not restarted")?;
+
+    // The host process exits when its peripheral is deleted.
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/peripherals/0"),
+        204,
+    )?;
+    check(
+        host_pids(context.daemon.id()).is_empty(),
+        "Host process did not exit",
+    )?;
+
+    Ok(())
+}
+
+/// Returns the value of the attribute with ID 0.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+fn x(context: &Context, client: &reqwest::Client) -> Result<f64, CommonError> {
+    let mut resp = Get::new(&context.server_url, URL_X).exec(client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the attribute",
+    )?;
+    let attr: Attribute = resp.json()?;
+
+    Ok(attr.value.value)
+}
+
+/// Returns the state of the peripheral with ID 0.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+fn state(context: &Context, client: &reqwest::Client) -> Result<String, CommonError> {
+    let mut resp = Get::new(&context.server_url, "/api/v0/peripherals/0").exec(client)?;
+    let periph: Peripheral = resp.json()?;
+
+    Ok(periph.state)
+}
+
+/// Returns the IDs of the living plugin host processes that were started by the daemon.
+///
+/// # Arguments
+///
+/// * `daemon` - The process ID of the daemon
+fn host_pids(daemon: u32) -> Vec<i32> {
+    let entries = match fs::read_dir("/proc") {
+        Ok(entries) => entries,
+        Err(_) => return vec![],
+    };
+
+    entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| fs::read_to_string(entry.path().join("stat")).ok())
+        .filter_map(|stat| {
+            // The name of the process is enclosed in parentheses and may contain spaces.
+            let (pid, rest) = stat.split_once(" (")?;
+            let (name, rest) = rest.rsplit_once(") ")?;
+            let mut fields = rest.split_whitespace();
+            let state = fields.next()?;
+            let ppid: u32 = fields.next()?.parse().ok()?;
+
+            if ppid == daemon && state != "Z" && "kpal-plugin-host".starts_with(name) {
+                pid.parse().ok()
+            } else {
+                None
+            }
+        })
+        .collect()
+}
+
+/// Represents a peripheral returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Peripheral {
+    isolated: bool,
+    state: String,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
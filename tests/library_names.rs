@@ -0,0 +1,97 @@
+//! Integration test that verifies what libraries report and that they may be referred to by name.
+pub mod common;
+
+use kpal_plugin::ABI_VERSION;
+use serde::Deserialize;
+use serde_json::json;
+
+use common::{check, expect_status, set_up, tear_down, CommonError, Context, Get, Post, Request};
+
+#[test]
+fn test_library_names() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Reads the name and versions of a library and creates peripherals that refer to it by name.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let mut resp = Get::new(&context.server_url, "/api/v0/libraries/0").exec(&client)?;
+    let lib: Library = resp.json()?;
+    check(lib.name == "basic-plugin", "Unexpected library name")?;
+    check(
+        lib.version.is_some_and(|v| v.starts_with("0.3.")),
+        "Unexpected library version",
+    )?;
+    check(lib.abi_version == ABI_VERSION, "Unexpected ABI version")?;
+    check(
+        lib.path
+            .starts_with(&*context.library_dir.path().to_string_lossy()),
+        "Unexpected library path",
+    )?;
+
+    let post_data = json!({"name": "foo", "library": "basic-plugin@0.3"});
+    let resp = Post::new(&context.server_url, "/api/v0/peripherals", post_data).exec(&client)?;
+    check(
+        resp.status().as_u16() == 201,
+        "Could not create the peripheral",
+    )?;
+
+    let mut resp = Get::new(&context.server_url, "/api/v0/peripherals/0").exec(&client)?;
+    let periph: Peripheral = resp.json()?;
+    check(periph.library_id == 0, "Unexpected library ID")?;
+
+    let post_data = json!({"name": "bar", "library": "basic-plugin"});
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    // Libraries that do not exist and invalid requests are rejected.
+    let invalid = vec![
+        (json!({"name": "baz", "library": "basic-plugin@9"}), 404),
+        (json!({"name": "baz", "library": "missing"}), 404),
+        (json!({"name": "baz", "library": "basic-plugin@x.y"}), 422),
+        (
+            json!({"name": "baz", "library": "basic-plugin", "library_id": 0}),
+            422,
+        ),
+        (json!({"name": "baz"}), 422),
+    ];
+    for (post_data, status) in invalid {
+        expect_status(
+            &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+            status,
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Represents a library returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Library {
+    abi_version: u32,
+    name: String,
+    path: String,
+    version: Option<String>,
+}
+
+/// Represents a peripheral returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Peripheral {
+    library_id: usize,
+}
@@ -0,0 +1,134 @@
+//! Integration test that verifies that libraries may be loaded and unloaded at runtime.
+pub mod common;
+
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Delete, Get, Post, Request,
+};
+
+const URL_RESCAN: &str = "/api/v0/libraries/rescan";
+
+#[test]
+fn test_library_rescan() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Loads a new library file, creates a peripheral from it, and unloads it again.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    // Libraries that are already loaded are not loaded again.
+    let libs = rescan(context, &client)?;
+    check(libs.is_empty(), "Library was loaded twice")?;
+
+    let dir = context.library_dir.path();
+    fs::copy(
+        dir.join("libbasic-plugin.so"),
+        dir.join("libbasic-plugin-copy.so"),
+    )?;
+    let libs = rescan(context, &client)?;
+    check(
+        libs.iter().map(|lib| lib.id).collect::<Vec<usize>>() == vec![1],
+        "New library was not loaded",
+    )?;
+
+    let mut resp = Get::new(&context.server_url, "/api/v0/libraries").exec(&client)?;
+    let libs: Vec<Library> = resp.json()?;
+    check(libs.len() == 2, "Unexpected number of libraries")?;
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 1,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    // Libraries that are used by peripherals are not unloaded.
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/libraries/1"),
+        422,
+    )?;
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/peripherals/0"),
+        204,
+    )?;
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/libraries/1"),
+        204,
+    )?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/libraries/1"), 404)?;
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/libraries/1"),
+        404,
+    )?;
+
+    // Unloaded libraries are loaded again with new IDs.
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/libraries/0"),
+        204,
+    )?;
+    let libs = rescan(context, &client)?;
+    check(
+        libs.iter().map(|lib| lib.id).collect::<Vec<usize>>() == vec![2, 3],
+        "Libraries were not loaded again",
+    )?;
+
+    let post_data = PostData {
+        name: "bar",
+        library_id: 2,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    Ok(())
+}
+
+/// Rescans the library directories and returns the libraries that were loaded.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+fn rescan(context: &Context, client: &reqwest::Client) -> Result<Vec<Library>, CommonError> {
+    let mut resp = Post::new(&context.server_url, URL_RESCAN, json!(null)).exec(client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not rescan the libraries",
+    )?;
+
+    Ok(resp.json()?)
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Represents a library returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Library {
+    id: usize,
+}
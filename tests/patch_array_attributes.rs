@@ -0,0 +1,170 @@
+//! Integration test that verifies that array and byte buffer attributes are correctly updated.
+pub mod common;
+
+use {
+    serde::{Deserialize, Serialize},
+    serde_json::json,
+};
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Get, Patch, Post, Request,
+};
+
+// These match the IDs of the array and byte buffer attributes in the BasicPlugin example.
+const ARRAY_URL: &str = "/api/v0/peripherals/0/attributes/5";
+const BYTES_URL: &str = "/api/v0/peripherals/0/attributes/7";
+
+#[test]
+fn test_patch_array_attributes() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Reads and sets an array attribute and a byte buffer attribute.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    let expected_array_before_patch = Attribute {
+        id: 5,
+        name: "waveform".to_string(),
+        value: Value {
+            r#type: "double_array".to_string(),
+            value: json!([0.0, 0.5, 1.0, 0.5, 0.0]),
+        },
+    };
+    check(
+        attribute(context, &client, ARRAY_URL)? == expected_array_before_patch,
+        "Unexpected array attribute before the patch",
+    )?;
+
+    let patch_array = PatchData {
+        r#type: "double_array",
+        value: json!([1.5, -2.0]),
+    };
+    expect_status(
+        &Patch::new(&context.server_url, ARRAY_URL, patch_array),
+        200,
+    )?;
+
+    let expected_array_after_patch = Attribute {
+        id: 5,
+        name: "waveform".to_string(),
+        value: Value {
+            r#type: "double_array".to_string(),
+            value: json!([1.5, -2.0]),
+        },
+    };
+    check(
+        attribute(context, &client, ARRAY_URL)? == expected_array_after_patch,
+        "Unexpected array attribute after the patch",
+    )?;
+
+    let expected_bytes_before_patch = Attribute {
+        id: 7,
+        name: "frame".to_string(),
+        value: Value {
+            r#type: "bytes".to_string(),
+            value: json!("AAECAw=="),
+        },
+    };
+    check(
+        attribute(context, &client, BYTES_URL)? == expected_bytes_before_patch,
+        "Unexpected byte buffer attribute before the patch",
+    )?;
+
+    let patch_bytes = PatchData {
+        r#type: "bytes",
+        // The bytes [255, 0, 127] encoded in base64
+        value: json!("/wB/"),
+    };
+    expect_status(
+        &Patch::new(&context.server_url, BYTES_URL, patch_bytes),
+        200,
+    )?;
+
+    let expected_bytes_after_patch = Attribute {
+        id: 7,
+        name: "frame".to_string(),
+        value: Value {
+            r#type: "bytes".to_string(),
+            value: json!("/wB/"),
+        },
+    };
+    check(
+        attribute(context, &client, BYTES_URL)? == expected_bytes_after_patch,
+        "Unexpected byte buffer attribute after the patch",
+    )?;
+
+    Ok(())
+}
+
+/// Returns an attribute of the peripheral.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+/// * `url` - The route of the attribute
+fn attribute(
+    context: &Context,
+    client: &reqwest::Client,
+    url: &str,
+) -> Result<Attribute, CommonError> {
+    let mut resp = Get::new(&context.server_url, url).exec(client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the attribute",
+    )?;
+
+    Ok(resp.json()?)
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Patch data to update an attribute value.
+#[derive(Debug, Serialize)]
+struct PatchData {
+    r#type: &'static str,
+    value: serde_json::Value,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Attribute {
+    id: usize,
+    name: String,
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize, PartialEq, Serialize)]
+struct Value {
+    r#type: String,
+    value: serde_json::Value,
+}
@@ -0,0 +1,194 @@
+//! Integration test that verifies that several attributes may be updated in a single request.
+pub mod common;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Get, Patch, Post, Request,
+};
+
+const URL_ATTRIBUTES: &str = "/api/v0/peripherals/0/attributes";
+
+#[test]
+fn test_patch_multiple_attributes() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Updates several attributes at once with each of the ways of handling a failure.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    // A failure does not prevent the values that follow it from being set.
+    let patch_data = json!({
+        "attributes": [
+            {"id": 0, "type": "double", "value": 1.5},
+            {"id": 4, "type": "boolean", "value": true},
+            {"id": 2, "type": "integer", "value": 7},
+            {"id": 0, "type": "double", "value": 2.5},
+        ]
+    });
+    let mut resp = Patch::new(&context.server_url, URL_ATTRIBUTES, patch_data).exec(&client)?;
+    check(resp.status().as_u16() == 200, "Could not update attributes")?;
+    let results: Vec<UpdateResult> = resp.json()?;
+    check_results(
+        &results,
+        &[
+            (0, "applied", None),
+            (4, "applied", None),
+            (2, "failed", Some(422)),
+            (0, "applied", None),
+        ],
+    )?;
+    check_x(context, &client, 2.5)?;
+
+    // The values that follow the first failure are skipped if requested.
+    let patch_data = json!({
+        "attributes": [
+            {"id": 0, "type": "double", "value": 5000.0},
+            {"id": 0, "type": "double", "value": 1.0},
+        ],
+        "stop_on_error": true
+    });
+    let mut resp = Patch::new(&context.server_url, URL_ATTRIBUTES, patch_data).exec(&client)?;
+    check(resp.status().as_u16() == 200, "Could not update attributes")?;
+    let results: Vec<UpdateResult> = resp.json()?;
+    check_results(&results, &[(0, "failed", Some(422)), (0, "skipped", None)])?;
+    check_x(context, &client, 2.5)?;
+
+    // The values that were set before a failure are restored in an atomic update.
+    let patch_data = json!({
+        "attributes": [
+            {"id": 0, "type": "double", "value": 7.0},
+            {"id": 4, "type": "boolean", "value": false},
+            {"id": 0, "type": "double", "value": 5000.0},
+            {"id": 4, "type": "boolean", "value": false},
+        ],
+        "atomic": true
+    });
+    let mut resp = Patch::new(&context.server_url, URL_ATTRIBUTES, patch_data).exec(&client)?;
+    check(resp.status().as_u16() == 200, "Could not update attributes")?;
+    let results: Vec<UpdateResult> = resp.json()?;
+    check_results(
+        &results,
+        &[
+            (0, "rolled_back", None),
+            (4, "rolled_back", None),
+            (0, "failed", Some(422)),
+            (4, "skipped", None),
+        ],
+    )?;
+    check_x(context, &client, 2.5)?;
+
+    let route = format!("{}/4", URL_ATTRIBUTES);
+    let mut resp = Get::new(&context.server_url, &route).exec(&client)?;
+    let enabled: serde_json::Value = resp.json()?;
+    check(
+        enabled["value"]["value"] == true,
+        "Boolean attribute was not restored",
+    )?;
+
+    let patch_data = json!({"attributes": [{"id": 0, "type": "double", "value": 1.0}]});
+    expect_status(
+        &Patch::new(
+            &context.server_url,
+            "/api/v0/peripherals/99/attributes",
+            patch_data,
+        ),
+        404,
+    )?;
+
+    Ok(())
+}
+
+/// Checks the ID, status and HTTP status code of each result of an update.
+///
+/// # Arguments
+///
+/// * `results` - The results returned by the daemon
+/// * `expected` - The expected ID, status and HTTP status code of each result
+fn check_results(
+    results: &[UpdateResult],
+    expected: &[(usize, &str, Option<u16>)],
+) -> Result<(), CommonError> {
+    check(
+        results.len() == expected.len(),
+        "Unexpected number of results",
+    )?;
+    for (result, (id, status, http_status_code)) in results.iter().zip(expected) {
+        check(result.id == *id, "Unexpected attribute ID")?;
+        check(&result.status == status, "Unexpected result status")?;
+        check(
+            result.http_status_code == *http_status_code,
+            "Unexpected HTTP status code",
+        )?;
+    }
+
+    Ok(())
+}
+
+/// Checks the current value of the attribute with ID 0.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+/// * `expected` - The expected value of the attribute
+fn check_x(context: &Context, client: &reqwest::Client, expected: f64) -> Result<(), CommonError> {
+    let route = format!("{}/0", URL_ATTRIBUTES);
+    let mut resp = Get::new(&context.server_url, &route).exec(client)?;
+    let attr: Attribute = resp.json()?;
+    check(
+        (attr.value.value - expected).abs() < f64::EPSILON,
+        "Unexpected attribute value",
+    )
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Represents the result of updating one attribute.
+#[derive(Debug, Deserialize)]
+struct UpdateResult {
+    id: usize,
+    status: String,
+    http_status_code: Option<u16>,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
@@ -0,0 +1,152 @@
+//! Integration test that verifies that peripheral actions may be discovered and invoked.
+pub mod common;
+
+use serde::{Deserialize, Serialize};
+
+use common::{check, expect_status, set_up, tear_down, CommonError, Context, Get, Post, Request};
+
+// These match the IDs of the actions in the BasicPlugin example.
+const URL_HOME: &str = "/api/v0/peripherals/0/actions/0";
+const URL_MOVE_BY: &str = "/api/v0/peripherals/0/actions/1";
+const URL_DOES_NOT_EXIST: &str = "/api/v0/peripherals/0/actions/99";
+
+#[test]
+fn test_peripheral_actions() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Lists the actions of a library and invokes them with valid and invalid arguments.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    let mut resp = Get::new(&context.server_url, "/api/v0/libraries/0").exec(&client)?;
+    check(resp.status().as_u16() == 200, "Could not read the library")?;
+    let library: Library = resp.json()?;
+    let names: Vec<&str> = library.actions.iter().map(|a| &*a.name).collect();
+    check(names == ["home", "move_by"], "Unexpected actions")?;
+
+    let move_by_data = ActionData {
+        arguments: vec![ArgumentData {
+            r#type: "double",
+            value: 2.5,
+        }],
+    };
+    check(
+        (invoke(context, &client, URL_MOVE_BY, move_by_data)? - 2.5).abs() < f64::EPSILON,
+        "Unexpected result of move_by",
+    )?;
+
+    let move_by_data_wrong_type = ActionData {
+        arguments: vec![ArgumentData {
+            r#type: "integer",
+            value: 2,
+        }],
+    };
+    expect_status(
+        &Post::new(&context.server_url, URL_MOVE_BY, move_by_data_wrong_type),
+        422,
+    )?;
+
+    let no_arguments: ActionData<f64> = ActionData { arguments: vec![] };
+    expect_status(
+        &Post::new(&context.server_url, URL_MOVE_BY, &no_arguments),
+        422,
+    )?;
+    check(
+        invoke(context, &client, URL_HOME, &no_arguments)?.abs() < f64::EPSILON,
+        "Unexpected result of home",
+    )?;
+    expect_status(
+        &Post::new(&context.server_url, URL_DOES_NOT_EXIST, &no_arguments),
+        404,
+    )?;
+
+    Ok(())
+}
+
+/// Invokes an action and returns its result.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+/// * `url` - The route of the action
+/// * `data` - The arguments of the action
+fn invoke<T: Serialize>(
+    context: &Context,
+    client: &reqwest::Client,
+    url: &str,
+    data: T,
+) -> Result<f64, CommonError> {
+    let mut resp = Post::new(&context.server_url, url, data).exec(client)?;
+    check(resp.status().as_u16() == 200, "Could not invoke the action")?;
+    let result: ActionResult = resp.json()?;
+
+    Ok(result.result.value)
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Post data to invoke an action.
+#[derive(Debug, Serialize)]
+struct ActionData<T> {
+    arguments: Vec<ArgumentData<T>>,
+}
+
+/// The value of a single action argument.
+#[derive(Debug, Serialize)]
+struct ArgumentData<T> {
+    r#type: &'static str,
+    value: T,
+}
+
+/// Represents the result of an action returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct ActionResult {
+    result: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
+
+/// Represents a library returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Library {
+    actions: Vec<Action>,
+}
+
+/// Represents an action of a library returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Action {
+    name: String,
+}
@@ -0,0 +1,194 @@
+//! Integration test that verifies that changes to attributes are streamed as server-sent events.
+pub mod common;
+
+use std::{
+    io::{BufRead, BufReader},
+    time::Duration,
+};
+
+use serde::{Deserialize, Serialize};
+
+use common::{check, expect_status, set_up, tear_down, CommonError, Context, Patch, Post};
+
+#[test]
+fn test_peripheral_events() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Streams the events of a peripheral while its attributes are modified.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::builder()
+        .timeout(Duration::from_secs(10))
+        .build()?;
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    let events_url = context
+        .server_url
+        .join("/api/v0/peripherals/0/events")
+        .expect("Could not produce full URL for the test");
+    let resp = client.get(events_url.as_str()).send()?;
+    check(resp.status().as_u16() == 200, "Could not open event stream")?;
+    check(
+        resp.headers()["Content-Type"] == "text/event-stream",
+        "Unexpected Content-Type",
+    )?;
+    check(
+        resp.headers()
+            .get("Connection")
+            .is_none_or(|value| value != "upgrade"),
+        "Event stream upgraded the connection",
+    )?;
+    let mut stream = BufReader::new(resp);
+
+    // Setting the attribute produces the first event.
+    let patch_data = PatchData {
+        r#type: "double",
+        value: 2.5,
+    };
+    expect_status(
+        &Patch::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/attributes/0",
+            patch_data,
+        ),
+        200,
+    )?;
+
+    let (first_id, attr) = read_event(&mut stream)?;
+    check(attr.id == 0, "Unexpected attribute ID")?;
+    check(
+        (attr.value.value - 2.5).abs() < f64::EPSILON,
+        "Unexpected attribute value",
+    )?;
+
+    // A client that resumes the stream receives the events that it missed.
+    let resp = client
+        .get(events_url.as_str())
+        .header("Last-Event-ID", (first_id - 1).to_string())
+        .send()?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not resume event stream",
+    )?;
+    let mut stream = BufReader::new(resp);
+
+    let (id, _) = read_event(&mut stream)?;
+    check(id == first_id, "Missed event was not replayed")?;
+
+    // Changes that are reported by the plugin itself also produce events.
+    let action_data = ActionData {
+        arguments: vec![PatchData {
+            r#type: "double",
+            value: 1.0,
+        }],
+    };
+    expect_status(
+        &Post::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/actions/1",
+            action_data,
+        ),
+        200,
+    )?;
+
+    let (id, attr) = read_event(&mut stream)?;
+    check(id == first_id + 1, "Unexpected event ID")?;
+    check(attr.id == 0, "Unexpected attribute ID")?;
+    check(
+        (attr.value.value - 3.5).abs() < f64::EPSILON,
+        "Unexpected attribute value",
+    )?;
+
+    let missing_url = context
+        .server_url
+        .join("/api/v0/peripherals/99/events")
+        .expect("Could not produce full URL for the test");
+    check(
+        client.get(missing_url.as_str()).send()?.status().as_u16() == 404,
+        "Expected 404 for a missing peripheral",
+    )?;
+
+    Ok(())
+}
+
+/// Reads a single event from a stream, skipping any comments.
+///
+/// # Arguments
+///
+/// * `stream` - The event stream
+fn read_event<R: BufRead>(stream: &mut R) -> Result<(u64, Attribute), CommonError> {
+    let mut id: Option<u64> = None;
+    let mut data: Option<String> = None;
+
+    loop {
+        let mut line = String::new();
+        check(stream.read_line(&mut line)? > 0, "Event stream ended")?;
+        let line = line.trim_end();
+
+        if line.is_empty() {
+            if let (Some(id), Some(data)) = (id, data.take()) {
+                let attr: Attribute =
+                    serde_json::from_str(&data).map_err(|e| CommonError::new(Some(Box::new(e))))?;
+                return Ok((id, attr));
+            }
+        } else if let Some(value) = line.strip_prefix("id: ") {
+            id = value.parse().ok();
+        } else if let Some(value) = line.strip_prefix("data: ") {
+            data = Some(value.to_owned());
+        }
+    }
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Patch data to set the value of an attribute. It also represents the value of an argument.
+#[derive(Debug, Serialize)]
+struct PatchData {
+    r#type: &'static str,
+    value: f64,
+}
+
+/// Post data to invoke an action.
+#[derive(Debug, Serialize)]
+struct ActionData {
+    arguments: Vec<PatchData>,
+}
+
+/// Represents the attribute of an event returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    id: usize,
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
@@ -0,0 +1,112 @@
+//! Integration test that verifies that peripherals and attributes may be found by name.
+pub mod common;
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Delete, Get, Patch, Post,
+    Request,
+};
+
+const URL_BY_NAME: &str = "/api/v0/peripherals/by-name/foo/attributes/x";
+
+#[test]
+fn test_peripheral_names() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Reads and sets an attribute by name and checks that peripheral names are unique.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", &post_data),
+        201,
+    )?;
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", &post_data),
+        422,
+    )?;
+
+    let mut resp =
+        Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/0").exec(&client)?;
+    let attr: Attribute = resp.json()?;
+    check(attr.alias == "x", "Unexpected attribute alias")?;
+
+    let value = json!({"type": "double", "value": 2.5});
+    let mut resp = Patch::new(&context.server_url, URL_BY_NAME, value).exec(&client)?;
+    check(resp.status().as_u16() == 200, "Could not set the attribute")?;
+    let attr: Attribute = resp.json()?;
+    check(attr.id == 0, "Unexpected attribute ID")?;
+
+    let mut resp = Get::new(&context.server_url, URL_BY_NAME).exec(&client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the attribute",
+    )?;
+    let attr: Attribute = resp.json()?;
+    check(
+        (attr.value.value - 2.5).abs() < f64::EPSILON,
+        "Unexpected attribute value",
+    )?;
+
+    // Missing peripherals and attributes are not found.
+    for route in &[
+        "/api/v0/peripherals/by-name/bar/attributes/x",
+        "/api/v0/peripherals/by-name/foo/attributes/w",
+    ] {
+        expect_status(&Get::new(&context.server_url, route), 404)?;
+    }
+
+    // The name may be used again once the peripheral is deleted.
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/peripherals/0"),
+        204,
+    )?;
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", &post_data),
+        201,
+    )?;
+    expect_status(&Get::new(&context.server_url, URL_BY_NAME), 200)?;
+
+    Ok(())
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    alias: String,
+    id: usize,
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
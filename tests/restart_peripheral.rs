@@ -0,0 +1,188 @@
+//! Integration test that verifies that peripherals may be deinitialized and restarted.
+pub mod common;
+
+use serde::{Deserialize, Serialize};
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Get, Patch, Post, Request,
+};
+
+const URL_X: &str = "/api/v0/peripherals/0/attributes/0";
+
+#[test]
+fn test_restart_peripheral() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Moves a peripheral between its lifecycle phases and sets pre-init attributes on restarts.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    // A peripheral may only be initialized after it was deinitialized.
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals/0/init", ()),
+        422,
+    )?;
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals/0/deinit", ()),
+        200,
+    )?;
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals/0/deinit", ()),
+        422,
+    )?;
+
+    let patch_data = PatchData {
+        r#type: "double",
+        value: 5.0,
+    };
+    expect_status(&Patch::new(&context.server_url, URL_X, patch_data), 200)?;
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals/0/init", ()),
+        200,
+    )?;
+    check(
+        (x(context, &client)? - 5.0).abs() < f64::EPSILON,
+        "Pre-init value was not kept",
+    )?;
+
+    let restart_data = RestartData {
+        attributes: vec![AttributeData {
+            id: 0,
+            r#type: "double",
+            value: 7.0,
+        }],
+    };
+    expect_status(
+        &Post::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/restart",
+            restart_data,
+        ),
+        200,
+    )?;
+    check(
+        (x(context, &client)? - 7.0).abs() < f64::EPSILON,
+        "Restart value was not set",
+    )?;
+
+    let restart_data_bad_id = RestartData {
+        attributes: vec![AttributeData {
+            id: 99,
+            r#type: "double",
+            value: 1.0,
+        }],
+    };
+    expect_status(
+        &Post::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/restart",
+            restart_data_bad_id,
+        ),
+        422,
+    )?;
+
+    let restart_data_empty = RestartData { attributes: vec![] };
+    expect_status(
+        &Post::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/restart",
+            &restart_data_empty,
+        ),
+        200,
+    )?;
+    check(
+        (x(context, &client)? - 7.0).abs() < f64::EPSILON,
+        "Value was not kept by a restart without new values",
+    )?;
+
+    expect_status(
+        &Post::new(
+            &context.server_url,
+            "/api/v0/peripherals/1/restart",
+            &restart_data_empty,
+        ),
+        404,
+    )?;
+
+    Ok(())
+}
+
+/// Returns the value of the attribute with ID 0.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+fn x(context: &Context, client: &reqwest::Client) -> Result<f64, CommonError> {
+    let mut resp = Get::new(&context.server_url, URL_X).exec(client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the attribute",
+    )?;
+    let attr: Attribute = resp.json()?;
+
+    Ok(attr.value.value)
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Patch data to update an attribute value.
+#[derive(Debug, Serialize)]
+struct PatchData<T> {
+    r#type: &'static str,
+    value: T,
+}
+
+/// Post data to restart a peripheral.
+#[derive(Debug, Serialize)]
+struct RestartData {
+    attributes: Vec<AttributeData>,
+}
+
+/// New value of a pre-init attribute.
+#[derive(Debug, Serialize)]
+struct AttributeData {
+    id: usize,
+    r#type: &'static str,
+    value: f64,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
@@ -0,0 +1,139 @@
+//! Integration test that verifies that peripherals are restored when the daemon restarts.
+pub mod common;
+
+use std::fs;
+
+use serde::{Deserialize, Serialize};
+
+use common::{
+    check, expect_status, restart, set_up, tear_down, CommonError, Context, Delete, Get, Post,
+    Request,
+};
+
+const URL_X: &str = "/api/v0/peripherals/0/attributes/0";
+
+/// A stored peripheral whose library does not exist.
+const BROKEN_STORE: &str = r#"[
+  {"attributes": [], "id": 0, "library_name": "libmissing.so", "name": "broken"}
+]"#;
+
+#[test]
+fn test_restore_peripherals() {
+    let mut context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&mut context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Creates a peripheral, restarts the daemon, and checks that the peripheral and the IDs of
+/// stored peripherals are kept.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &mut Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+        attributes: vec![AttributeData {
+            id: 0,
+            r#type: "double",
+            value: 3.0,
+        }],
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+    check(
+        (x(context, &client)? - 3.0).abs() < f64::EPSILON,
+        "Unexpected value",
+    )?;
+
+    restart(context)?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/peripherals/0"), 200)?;
+    check(
+        (x(context, &client)? - 3.0).abs() < f64::EPSILON,
+        "Value was not restored",
+    )?;
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/peripherals/0"),
+        204,
+    )?;
+
+    restart(context)?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/peripherals/0"), 404)?;
+
+    // The ID of a peripheral that could not be restored is not given to a new peripheral.
+    let store_file = context.store_dir.path().join("peripherals.json");
+    fs::write(&store_file, BROKEN_STORE)?;
+    restart(context)?;
+
+    expect_status(&Get::new(&context.server_url, "/api/v0/peripherals/0"), 404)?;
+    let post_data = PostData {
+        name: "bar",
+        library_id: 0,
+        attributes: vec![],
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/peripherals/0"), 404)?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/peripherals/1"), 200)?;
+
+    Ok(())
+}
+
+/// Returns the value of the attribute with ID 0.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+fn x(context: &Context, client: &reqwest::Client) -> Result<f64, CommonError> {
+    let mut resp = Get::new(&context.server_url, URL_X).exec(client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not read the attribute",
+    )?;
+    let attr: Attribute = resp.json()?;
+
+    Ok(attr.value.value)
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+    attributes: Vec<AttributeData>,
+}
+
+/// Initial value of a pre-init attribute.
+#[derive(Debug, Serialize)]
+struct AttributeData {
+    id: usize,
+    r#type: &'static str,
+    value: f64,
+}
+
+/// Represents an attribute returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Attribute {
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
@@ -0,0 +1,212 @@
+//! Integration test that verifies that automation rules set attributes in response to others.
+pub mod common;
+
+use std::{thread, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use common::{
+    check, expect_status, restart, set_up, tear_down, CommonError, Context, Delete, Get, Patch,
+    Post, Request,
+};
+
+const URL_ENABLED: &str = "/api/v0/peripherals/0/attributes/4";
+const URL_X: &str = "/api/v0/peripherals/0/attributes/0";
+
+#[test]
+fn test_rules() {
+    let mut context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&mut context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Creates a rule, triggers it, restarts the daemon, and then deletes the rule.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &mut Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    // Enable the peripheral once x rises above 1.0.
+    let rule = json!({
+        "condition": {"peripheral_id": 0, "attribute_id": 0,
+                      "type": "above", "threshold": 1.0, "hysteresis": 0.5},
+        "action": {"peripheral_id": 0, "attribute_id": 4,
+                   "value": {"type": "boolean", "value": true}}
+    });
+    let resp = Post::new(&context.server_url, "/api/v0/rules", rule).exec(&client)?;
+    check(resp.status().as_u16() == 201, "Could not create the rule")?;
+    check(
+        resp.headers()["Location"] == "/api/v0/rules/0",
+        "Unexpected rule location",
+    )?;
+
+    set(context, URL_X, json!({"type": "double", "value": 2.0}))?;
+    wait_for_fired(context, &client, 1)?;
+    check(enabled(context, &client)?, "Rule did not set the attribute")?;
+
+    // The rule does not fire again until x falls below the threshold minus the hysteresis.
+    set(
+        context,
+        URL_ENABLED,
+        json!({"type": "boolean", "value": false}),
+    )?;
+    set(context, URL_X, json!({"type": "double", "value": 0.8}))?;
+    set(context, URL_X, json!({"type": "double", "value": 2.0}))?;
+    thread::sleep(Duration::from_millis(200));
+    check(
+        !enabled(context, &client)?,
+        "Rule fired within its hysteresis",
+    )?;
+
+    set(context, URL_X, json!({"type": "double", "value": 0.0}))?;
+    set(context, URL_X, json!({"type": "double", "value": 2.0}))?;
+    let rule = wait_for_fired(context, &client, 2)?;
+    check(rule.active, "Rule is not active")?;
+    check(rule.error.is_none(), "Rule reported an error")?;
+
+    // Rules that use missing peripherals or invalid settings are rejected.
+    let invalid = vec![
+        (
+            json!({
+                "condition": {"peripheral_id": 99, "attribute_id": 0,
+                              "type": "equal", "value": {"type": "integer", "value": 1}},
+                "action": {"peripheral_id": 0, "attribute_id": 4,
+                           "value": {"type": "boolean", "value": true}}
+            }),
+            422,
+        ),
+        (
+            json!({
+                "condition": {"peripheral_id": 0, "attribute_id": 0,
+                              "type": "below", "threshold": 1.0, "hysteresis": -1.0},
+                "action": {"peripheral_id": 0, "attribute_id": 4,
+                           "value": {"type": "boolean", "value": true}}
+            }),
+            422,
+        ),
+        (
+            json!({
+                "condition": {"peripheral_id": 0, "attribute_id": 0,
+                              "type": "below", "threshold": 1.0},
+                "action": {"peripheral_id": 0, "attribute_id": 4,
+                           "value": {"type": "boolean", "value": true}},
+                "poll_interval_ms": 1
+            }),
+            422,
+        ),
+        (
+            json!({
+                "condition": {"peripheral_id": 0, "attribute_id": 0, "type": "between"},
+                "action": {"peripheral_id": 0, "attribute_id": 4,
+                           "value": {"type": "boolean", "value": true}}
+            }),
+            400,
+        ),
+    ];
+    for (rule, status) in invalid {
+        expect_status(
+            &Post::new(&context.server_url, "/api/v0/rules", rule),
+            status,
+        )?;
+    }
+
+    // Rules are restored when the daemon restarts.
+    restart(context)?;
+    let mut resp = Get::new(&context.server_url, "/api/v0/rules").exec(&client)?;
+    let rules: Vec<Rule> = resp.json()?;
+    check(rules.len() == 1, "Rule was not restored")?;
+    check(rules[0].id == 0, "Unexpected rule ID")?;
+    check(
+        rules[0].poll_interval_ms.is_none(),
+        "Unexpected polling interval",
+    )?;
+
+    expect_status(&Delete::new(&context.server_url, "/api/v0/rules/0"), 204)?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/rules/0"), 404)?;
+    expect_status(&Delete::new(&context.server_url, "/api/v0/rules/0"), 404)?;
+
+    Ok(())
+}
+
+/// Sets the value of an attribute.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `route` - The route of the attribute
+/// * `value` - The new value of the attribute
+fn set(context: &Context, route: &str, value: serde_json::Value) -> Result<(), CommonError> {
+    expect_status(&Patch::new(&context.server_url, route, value), 200)
+}
+
+/// Returns the value of the attribute with ID 4.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+fn enabled(context: &Context, client: &reqwest::Client) -> Result<bool, CommonError> {
+    let mut resp = Get::new(&context.server_url, URL_ENABLED).exec(client)?;
+    let attr: serde_json::Value = resp.json()?;
+    Ok(attr["value"]["value"] == true)
+}
+
+/// Polls the rule with ID 0 until its action has been issued a given number of times.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+/// * `fired` - The expected number of times that the action was issued
+fn wait_for_fired(
+    context: &Context,
+    client: &reqwest::Client,
+    fired: u64,
+) -> Result<Rule, CommonError> {
+    for _ in 0..50 {
+        let mut resp = Get::new(&context.server_url, "/api/v0/rules/0").exec(client)?;
+        let rule: Rule = resp.json()?;
+        if rule.fired == fired {
+            return Ok(rule);
+        }
+        thread::sleep(Duration::from_millis(20));
+    }
+
+    Err(CommonError::new(Some("Rule did not fire".into())))
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Represents a rule returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Rule {
+    id: usize,
+    active: bool,
+    fired: u64,
+    error: Option<String>,
+    poll_interval_ms: Option<u64>,
+}
@@ -0,0 +1,175 @@
+//! Integration test that verifies that sequences of steps are run by the daemon.
+pub mod common;
+
+use std::{thread, time::Duration};
+
+use serde::{Deserialize, Serialize};
+use serde_json::json;
+
+use common::{
+    check, expect_status, set_up, tear_down, CommonError, Context, Delete, Get, Post, Request,
+};
+
+#[test]
+fn test_sequences() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Runs a sequence to completion, cancels another one, and checks that invalid ones are rejected.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), CommonError> {
+    let client = reqwest::Client::new();
+
+    let post_data = PostData {
+        name: "foo",
+        library_id: 0,
+    };
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/peripherals", post_data),
+        201,
+    )?;
+
+    let program = json!({
+        "steps": [
+            {"type": "set", "peripheral_id": 0, "attribute_id": 0,
+             "value": {"type": "double", "value": 1.5}},
+            {"type": "loop", "count": 3, "steps": [
+                {"type": "wait", "duration_ms": 20},
+                {"type": "get", "peripheral_id": 0, "attribute_id": 0},
+            ]},
+            {"type": "invoke", "peripheral_id": 0, "action_id": 1,
+             "arguments": [{"type": "double", "value": 1.0}]},
+            {"type": "get", "peripheral_id": 0, "attribute_id": 0},
+        ]
+    });
+    let resp = Post::new(&context.server_url, "/api/v0/sequences", program).exec(&client)?;
+    check(
+        resp.status().as_u16() == 201,
+        "Could not start the sequence",
+    )?;
+    check(
+        resp.headers()["Location"] == "/api/v0/sequences/0",
+        "Unexpected sequence location",
+    )?;
+
+    let status = wait_until_finished(context, &client, "/api/v0/sequences/0")?;
+    check(status.state == "completed", "Sequence did not complete")?;
+    check(status.steps_run == 9, "Unexpected number of steps run")?;
+    check(
+        status.finished.unwrap_or(0) >= status.started + 60,
+        "Sequence did not wait",
+    )?;
+    let values: Vec<f64> = status.readings.iter().map(|r| r.value.value).collect();
+    check(
+        values == vec![1.5, 1.5, 1.5, 2.5],
+        "Unexpected values were read",
+    )?;
+
+    // A running sequence may be cancelled.
+    let program = json!({"steps": [{"type": "wait", "duration_ms": 60000}]});
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/sequences", program),
+        201,
+    )?;
+    let mut resp = Post::new(
+        &context.server_url,
+        "/api/v0/sequences/1/cancel",
+        json!(null),
+    )
+    .exec(&client)?;
+    check(
+        resp.status().as_u16() == 200,
+        "Could not cancel the sequence",
+    )?;
+    let status: Sequence = resp.json()?;
+    check(status.state == "cancelled", "Sequence was not cancelled")?;
+
+    let mut resp = Get::new(&context.server_url, "/api/v0/sequences").exec(&client)?;
+    let statuses: Vec<Sequence> = resp.json()?;
+    check(statuses.len() == 2, "Unexpected number of sequences")?;
+
+    expect_status(
+        &Delete::new(&context.server_url, "/api/v0/sequences/1"),
+        204,
+    )?;
+    expect_status(&Get::new(&context.server_url, "/api/v0/sequences/1"), 404)?;
+
+    // Sequences that use missing peripherals or unknown steps are rejected.
+    let program = json!({"steps": [{"type": "get", "peripheral_id": 99, "attribute_id": 0}]});
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/sequences", program),
+        422,
+    )?;
+    let program = json!({"steps": [{"type": "jump"}]});
+    expect_status(
+        &Post::new(&context.server_url, "/api/v0/sequences", program),
+        400,
+    )?;
+
+    Ok(())
+}
+
+/// Polls the status of a sequence until it has finished.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+/// * `client` - The HTTP client
+/// * `route` - The route of the sequence
+fn wait_until_finished(
+    context: &Context,
+    client: &reqwest::Client,
+    route: &str,
+) -> Result<Sequence, CommonError> {
+    for _ in 0..50 {
+        let mut resp = Get::new(&context.server_url, route).exec(client)?;
+        let status: Sequence = resp.json()?;
+        if status.finished.is_some() {
+            return Ok(status);
+        }
+        thread::sleep(Duration::from_millis(50));
+    }
+
+    Err(CommonError::new(Some("Sequence did not finish".into())))
+}
+
+/// Post data to create a new peripheral.
+#[derive(Debug, Serialize)]
+struct PostData {
+    name: &'static str,
+    library_id: usize,
+}
+
+/// Represents the status of a sequence returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Sequence {
+    state: String,
+    steps_run: usize,
+    started: u64,
+    finished: Option<u64>,
+    readings: Vec<Reading>,
+}
+
+/// Represents a value that was read by a sequence.
+#[derive(Debug, Deserialize)]
+struct Reading {
+    value: Value,
+}
+
+/// Represents a value returned by the daemon.
+#[derive(Debug, Deserialize)]
+struct Value {
+    value: f64,
+}
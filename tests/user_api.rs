@@ -3,7 +3,7 @@ mod common;
 
 use {log, reqwest, serde::Serialize};
 
-use common::{set_up, tear_down, CommonError, Get, Patch, Post, Request};
+use common::{set_up, tear_down, CommonError, Delete, Get, Patch, Post, Request};
 
 #[test]
 fn test_user_api() {
@@ -22,6 +22,22 @@ fn test_user_api() {
         r#type: "string",
         value: "foobarbaz",
     };
+    let patch_attr_4 = PatchData {
+        r#type: "boolean",
+        value: true,
+    };
+    let patch_attr_5 = PatchData {
+        r#type: "double_array",
+        value: vec![1.0, 2.0, 3.0],
+    };
+    let patch_attr_7 = PatchData {
+        r#type: "bytes",
+        value: "AAEC",
+    };
+    let patch_attr_8 = PatchData {
+        r#type: "integer64",
+        value: "9007199254740993",
+    };
     #[rustfmt::skip]
     let cases: Vec<Case> = vec![
         Box::new(Get::new(&context.server_url, "/api/v0/libraries")),
@@ -34,6 +50,11 @@ fn test_user_api() {
         Box::new(Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/1")),
         Box::new(Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/2")),
         Box::new(Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/3")),
+        Box::new(Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/4")),
+        Box::new(Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/5")),
+        Box::new(Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/6")),
+        Box::new(Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/7")),
+        Box::new(Get::new(&context.server_url, "/api/v0/peripherals/0/attributes/8")),
         Box::new(Patch::new(&context.server_url,
             "/api/v0/peripherals/0/attributes/0",
             patch_attr_0,
@@ -43,6 +64,30 @@ fn test_user_api() {
             "/api/v0/peripherals/0/attributes/3",
             patch_attr_3,
         )),
+        Box::new(Patch::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/attributes/4",
+            patch_attr_4,
+        )),
+        Box::new(Patch::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/attributes/5",
+            patch_attr_5,
+        )),
+        Box::new(Patch::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/attributes/7",
+            patch_attr_7,
+        )),
+        Box::new(Patch::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/attributes/8",
+            patch_attr_8,
+        )),
+        Box::new(Post::new(&context.server_url, "/api/v0/peripherals/0/deinit", ())),
+        Box::new(Post::new(&context.server_url, "/api/v0/peripherals/0/init", ())),
+        Box::new(Post::new(&context.server_url, "/api/v0/peripherals/0/restart", RestartData {})),
+        Box::new(Delete::new(&context.server_url, "/api/v0/peripherals/0")),
     ];
 
     let result = {
@@ -107,3 +152,7 @@ struct PatchData<T> {
     r#type: &'static str,
     value: T,
 }
+
+/// Post data to restart a peripheral with its current attribute values.
+#[derive(Debug, Serialize)]
+struct RestartData {}
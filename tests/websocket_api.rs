@@ -0,0 +1,179 @@
+//! Integration test that verifies that the daemon may be controlled over a websocket.
+pub mod common;
+
+use std::net::TcpStream;
+
+use serde_json::{json, Value};
+use tungstenite::{connect, stream::MaybeTlsStream, Message, WebSocket};
+
+use common::{expect_status, set_up, tear_down, Context, Patch};
+
+/// The websocket address that the daemon listens on by default.
+const WEBSOCKET_URL: &str = "ws://127.0.0.1:8001";
+
+type Client = WebSocket<MaybeTlsStream<TcpStream>>;
+
+#[test]
+fn test_websocket_api() {
+    let context = set_up().expect("Setup failed");
+    log::debug!("{:?}", context);
+
+    let result = run_tests(&context);
+    tear_down(context);
+
+    if let Err(e) = &result {
+        log::error!("{}", e);
+    }
+    assert!(result.is_ok())
+}
+
+/// Sends requests over a single websocket connection and checks the responses.
+///
+/// # Arguments
+///
+/// * `context` - The context of the test
+fn run_tests(context: &Context) -> Result<(), String> {
+    let (mut client, _) = connect(WEBSOCKET_URL).map_err(|e| e.to_string())?;
+
+    let resp = request(
+        &mut client,
+        json!({"id": 1, "type": "create_peripheral", "data": {"name": "foo", "library_id": 0}}),
+    )?;
+    check(
+        resp["type"] == "result",
+        "Could not create peripheral",
+        &resp,
+    )?;
+    check(resp["id"] == 1, "Unexpected response ID", &resp)?;
+    check(resp["data"]["id"] == 0, "Unexpected peripheral ID", &resp)?;
+
+    let resp = request(
+        &mut client,
+        json!({"id": 2, "type": "read_attributes", "peripheral_id": 0}),
+    )?;
+    check(resp["type"] == "result", "Could not read attributes", &resp)?;
+    check(
+        resp["data"][0]["name"] == "x",
+        "Unexpected attribute",
+        &resp,
+    )?;
+
+    let resp = request(
+        &mut client,
+        json!({"id": 3, "type": "subscribe", "peripheral_id": 0}),
+    )?;
+    check(resp["type"] == "result", "Could not subscribe", &resp)?;
+
+    // The event may arrive before the response to the request that caused it.
+    let first = request(
+        &mut client,
+        json!({
+            "id": 4,
+            "type": "patch_attribute",
+            "peripheral_id": 0,
+            "attribute_id": 0,
+            "data": {"type": "double", "value": 2.5}
+        }),
+    )?;
+    let second = receive(&mut client)?;
+    let (resp, event) = if first["type"] == "event" {
+        (second, first)
+    } else {
+        (first, second)
+    };
+    check(resp["type"] == "result", "Could not patch attribute", &resp)?;
+    check(
+        resp["data"]["value"]["value"] == 2.5,
+        "Unexpected value",
+        &resp,
+    )?;
+    check(event["type"] == "event", "Expected an event", &event)?;
+    check(
+        event["data"]["value"]["value"] == 2.5,
+        "Unexpected value",
+        &event,
+    )?;
+
+    // Events are pushed while the client is waiting without sending any requests.
+    expect_status(
+        &Patch::new(
+            &context.server_url,
+            "/api/v0/peripherals/0/attributes/0",
+            json!({"type": "double", "value": 3.5}),
+        ),
+        200,
+    )
+    .map_err(|e| e.to_string())?;
+
+    let event = receive(&mut client)?;
+    check(event["type"] == "event", "Expected an event", &event)?;
+    check(
+        event["peripheral_id"] == 0,
+        "Unexpected peripheral ID",
+        &event,
+    )?;
+    check(event["event_id"].is_u64(), "Missing event ID", &event)?;
+    check(
+        event["data"]["value"]["value"] == 3.5,
+        "Unexpected value",
+        &event,
+    )?;
+
+    let resp = request(
+        &mut client,
+        json!({"id": 5, "type": "read_peripheral", "peripheral_id": 99}),
+    )?;
+    check(resp["type"] == "error", "Expected an error", &resp)?;
+    check(resp["id"] == 5, "Unexpected response ID", &resp)?;
+    check(
+        resp["reason"] == "resource_not_found",
+        "Unexpected reason",
+        &resp,
+    )?;
+
+    let resp = request(&mut client, json!({"id": 6, "type": "does_not_exist"}))?;
+    check(resp["type"] == "error", "Expected an error", &resp)?;
+    check(resp["reason"] == "bad_request", "Unexpected reason", &resp)?;
+
+    let _ = client.close(None);
+
+    Ok(())
+}
+
+/// Sends a single request and returns the next message from the daemon.
+///
+/// # Arguments
+///
+/// * `client` - The websocket connection to the daemon
+/// * `data` - The request to send
+fn request(client: &mut Client, data: Value) -> Result<Value, String> {
+    log::info!("Sending websocket request: {}", data);
+    client
+        .send(Message::Text(data.to_string()))
+        .map_err(|e| e.to_string())?;
+
+    receive(client)
+}
+
+/// Returns the next message from the daemon.
+///
+/// # Arguments
+///
+/// * `client` - The websocket connection to the daemon
+fn receive(client: &mut Client) -> Result<Value, String> {
+    loop {
+        if let Message::Text(text) = client.read().map_err(|e| e.to_string())? {
+            log::debug!("Received websocket message: {}", text);
+            return serde_json::from_str(&text).map_err(|e| e.to_string());
+        }
+    }
+}
+
+/// Returns an error with the given message if a condition is false.
+fn check(condition: bool, message: &str, data: &Value) -> Result<(), String> {
+    if condition {
+        Ok(())
+    } else {
+        Err(format!("{}: {}", message, data))
+    }
+}